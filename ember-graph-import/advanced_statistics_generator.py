@@ -78,6 +78,15 @@ class AttributeStats:
     """ The type of the attribute """
 
 
+@dataclass
+class DegreeStats:
+    """The edges of a label between a pair of vertex labels"""
+
+    src_label: str = ""
+    dst_label: str = ""
+    count: int = 0
+
+
 @dataclass
 class Statistics:
     """Statistics of the graph"""
@@ -93,6 +102,9 @@ class Statistics:
     e_attr_stats: Dict[str, Dict[str, AttributeStats]] = field(default_factory=dict)
     """ {e_label: {e_attr_name: stats}} """
 
+    e_degree_stats: Dict[str, List[DegreeStats]] = field(default_factory=dict)
+    """ {e_label: [(src_label, dst_label, count)]} """
+
 
 class StatisticsEncoder(json.JSONEncoder):
     def default(self, o: Any):
@@ -101,6 +113,7 @@ class StatisticsEncoder(json.JSONEncoder):
             or isinstance(o, AttributeStats)
             or isinstance(o, AttributeHistogram)
             or isinstance(o, OperatorSelectivity)
+            or isinstance(o, DegreeStats)
         ):
             return asdict(o)
         return super().default(o)
@@ -108,6 +121,21 @@ class StatisticsEncoder(json.JSONEncoder):
 
 statistics = Statistics()
 
+node_labels: Dict[str, pl.DataFrame] = {}
+""" {id_space: [id, :LABEL]}, to tell the labels of the endpoints of the edges """
+
+relationship_ends: List[pl.DataFrame] = []
+""" [[src_space, src_id, dst_space, dst_id, :TYPE]] """
+
+
+def column_with_prefix(column_names: List[str], prefix: str) -> Optional[str]:
+    """The column named like `:ID(Person)`, which the formatter names after the id space."""
+    return next((col for col in column_names if col.startswith(prefix)), None)
+
+
+def id_space_of(column: str) -> str:
+    return column[column.index("(") + 1 : column.index(")")]
+
 
 def collect_attribute_stats(df: pl.DataFrame, label: str) -> Dict[str, AttributeStats]:
     stats: Dict[str, AttributeStats] = {}
@@ -259,6 +287,17 @@ async def visit_node_csv(file_path: Path):
         df = await lf.collect_async()
         labels = df[":LABEL"].unique()
 
+        if id_col := column_with_prefix(column_names, ":ID("):
+            ids = df.select(
+                pl.col(id_col).cast(pl.Utf8).alias("id"), pl.col(":LABEL").cast(pl.Utf8)
+            )
+            id_space = id_space_of(id_col)
+            node_labels[id_space] = (
+                pl.concat([node_labels[id_space], ids])
+                if id_space in node_labels
+                else ids
+            )
+
         for label in labels:
             label_str = str(label)
             label_df = df.filter(pl.col(":LABEL") == label)
@@ -301,6 +340,19 @@ async def visit_relationship_csv(file_path: Path):
         df = await lf.collect_async()
         labels = df[":TYPE"].unique()
 
+        start_col = column_with_prefix(column_names, ":START_ID(")
+        end_col = column_with_prefix(column_names, ":END_ID(")
+        if start_col and end_col:
+            relationship_ends.append(
+                df.select(
+                    pl.lit(id_space_of(start_col)).alias("src_space"),
+                    pl.col(start_col).cast(pl.Utf8).alias("src_id"),
+                    pl.lit(id_space_of(end_col)).alias("dst_space"),
+                    pl.col(end_col).cast(pl.Utf8).alias("dst_id"),
+                    pl.col(":TYPE").cast(pl.Utf8),
+                )
+            )
+
         for label in labels:
             label_str = str(label)
             label_df = df.filter(pl.col(":TYPE") == label)
@@ -340,6 +392,45 @@ async def exec_async(
 
     await asyncio.gather(*tasks)
 
+    collect_degree_stats()
+
+
+def collect_degree_stats():
+    """Count the edges of each label between each pair of vertex labels."""
+    global statistics
+    if not node_labels or not relationship_ends:
+        return
+
+    labels = pl.concat(
+        [
+            ids.with_columns(pl.lit(id_space).alias("space"))
+            for id_space, ids in node_labels.items()
+        ]
+    )
+    ends = pl.concat(relationship_ends)
+    ends = ends.join(
+        labels.rename({"space": "src_space", "id": "src_id", ":LABEL": "src_label"}),
+        on=["src_space", "src_id"],
+    ).join(
+        labels.rename({"space": "dst_space", "id": "dst_id", ":LABEL": "dst_label"}),
+        on=["dst_space", "dst_id"],
+    )
+
+    counts = ends.group_by([":TYPE", "src_label", "dst_label"]).agg(
+        pl.len().alias("count")
+    )
+    for e_label, src_label, dst_label, count in counts.sort(
+        [":TYPE", "src_label", "dst_label"]
+    ).iter_rows():
+        statistics.e_degree_stats.setdefault(str(e_label), []).append(
+            DegreeStats(str(src_label), str(dst_label), int(count))
+        )
+
+    print(f"""\
+✅  Degree statistics => {Fore.YELLOW + str(len(statistics.e_degree_stats)) + Style.RESET_ALL} \
+edge labels\
+    """)
+
 
 def exec(nodes_dir: Optional[Path] = None, relationships_dir: Optional[Path] = None):
     return asyncio.run(exec_async(nodes_dir, relationships_dir))
@@ -7,6 +7,11 @@ class AttrType(StrEnum):
     Int = "int"
     Float = "float"
     String = "string"
+    Date = "date"
+    DateTime = "datetime"
+    Bool = "bool"
+    Null = "null"
+    List = "list"
 
 
 class Op(StrEnum):
@@ -1,22 +1,54 @@
+import json
+from datetime import date, datetime, timezone
 from typing import Optional
 
 from schema import AttrType
 from sqlmodel import Field, Session, SQLModel, create_engine
 from sqlmodel.ext.asyncio.session import AsyncSession
 
-type Attr = int | float | str
+type Attr = int | float | str | date | datetime | bool | None | list
 type AttrDict = dict[str, Attr]
 
 
 def extract_type(value: Attr) -> str:
-    if isinstance(value, int):
+    # `bool` is a subclass of `int`
+    if isinstance(value, bool):
+        return AttrType.Bool
+    elif value is None:
+        return AttrType.Null
+    elif isinstance(value, list):
+        return AttrType.List
+    elif isinstance(value, int):
         return AttrType.Int
     elif isinstance(value, float):
         return AttrType.Float
+    # `datetime` is a subclass of `date`
+    elif isinstance(value, datetime):
+        return AttrType.DateTime
+    elif isinstance(value, date):
+        return AttrType.Date
     else:
         return AttrType.String
 
 
+def to_storage_str(value: Attr) -> str:
+    """Datetimes are stored in UTC with millis, so that their text sorts chronologically."""
+    if isinstance(value, datetime):
+        if value.tzinfo is None:
+            value = value.replace(tzinfo=timezone.utc)
+        value = value.astimezone(timezone.utc)
+        return value.strftime("%Y-%m-%dT%H:%M:%S.") + f"{value.microsecond // 1000:03d}Z"
+    elif isinstance(value, date):
+        return value.isoformat()
+    elif isinstance(value, bool):
+        return "true" if value else "false"
+    elif value is None:
+        return ""
+    elif isinstance(value, list):
+        return json.dumps(value, default=str)
+    return str(value)
+
+
 class BaseAttribute(SQLModel):
     id: Optional[int] = Field(default=None, primary_key=True)
     key: str = Field(index=True)
@@ -31,6 +63,16 @@ class BaseAttribute(SQLModel):
             return int(self.value)
         elif self.type == "float":
             return float(self.value)
+        elif self.type == "date":
+            return date.fromisoformat(self.value)
+        elif self.type == "datetime":
+            return datetime.fromisoformat(self.value)
+        elif self.type == "bool":
+            return self.value == "true"
+        elif self.type == "null":
+            return None
+        elif self.type == "list":
+            return json.loads(self.value)
         else:
             return self.value
 
@@ -46,7 +88,7 @@ class DB_Vertex(SQLModel, table=True):
     def load_pending_attrs(self, session: AsyncSession | Session):
         for key, value in self._pending_attrs.items():
             attr = Vertex_Attribute(
-                vid=self.vid, key=key, value=str(value), type=extract_type(value)
+                vid=self.vid, key=key, value=to_storage_str(value), type=extract_type(value)
             )
             session.add(attr)
 
@@ -73,7 +115,7 @@ class DB_Edge(SQLModel, table=True):
     def load_pending_attrs(self, session: AsyncSession | Session):
         for key, value in self._pending_attrs.items():
             attr = Edge_Attribute(
-                eid=self.eid, key=key, value=str(value), type=extract_type(value)
+                eid=self.eid, key=key, value=to_storage_str(value), type=extract_type(value)
             )
             session.add(attr)
 
@@ -1,3 +1,4 @@
+from datetime import date, datetime
 from pathlib import Path
 from typing import Any, Optional
 
@@ -12,7 +13,7 @@ raw_eid_cnt: dict[str, int] = {}
 
 
 def to_typed_attrs(attrs: dict[str, Any]):
-    result: dict[str, str | int | float] = {}
+    result: dict[str, str | int | float | date | datetime | bool | list] = {}
     for key, value in attrs.items():
         if ":" not in key:
             result[key] = str(value)
@@ -24,6 +25,18 @@ def to_typed_attrs(attrs: dict[str, Any]):
                 result[name] = int(value)
             case "float":
                 result[name] = float(value)
+            case "date":
+                result[name] = date.fromisoformat(str(value))
+            case "datetime":
+                result[name] = datetime.fromisoformat(str(value).replace("Z", "+00:00"))
+            case "boolean" | "bool":
+                result[name] = str(value).lower() == "true"
+            # e.g. `email:string[]`, separated by `;`
+            case _ if type_.endswith("[]"):
+                result[name] = [
+                    to_typed_attrs({f"_:{type_[:-2]}": v})["_"]
+                    for v in str(value).split(";")
+                ]
             case _:
                 result[name] = str(value)
     return result
@@ -7,8 +7,6 @@ MATCH
 WHERE
     country1.name = 'Afghanistan' and
     country2.name = 'India'
-ORDER BY
-    country1.name ASC, country2.name ASC
 RETURN
     person1,
     person2,
@@ -9,5 +9,4 @@ MATCH
 WHERE
     country.name = 'Afghanistan' AND
     tagClass.name = 'Album'
-RETURN country, city, person, forum, post, comment, tag, tagClass, a, b, c, d, e, f, g
-ORDER BY country.name, tagClass.name
\ No newline at end of file
+RETURN country, city, person, forum, post, comment, tag, tagClass, a, b, c, d, e, f, g
\ No newline at end of file
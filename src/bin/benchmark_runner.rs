@@ -5,7 +5,7 @@ use dotenv::dotenv;
 use ember_graph::storage::CachedStorageAdapter;
 use ember_graph::{
   executor::ExecEngine,
-  schemas::PlanData,
+  schemas::{ParamError, PlanData},
   storage::{AsyncDefault, Neo4jStorageAdapter, SqliteStorageAdapter},
   utils::parallel,
 };
@@ -664,6 +664,7 @@ async fn execute_single_benchmark(
   let plan: PlanData =
     serde_json::from_str(&plan_json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
   let plan_arc = Arc::new(plan);
+  let unbound = |e: ParamError| io::Error::new(io::ErrorKind::InvalidInput, e);
 
   let mut durations_ms = Vec::with_capacity(args.runs);
 
@@ -684,13 +685,13 @@ async fn execute_single_benchmark(
       // Warm-up runs
       if args.warmup > 0 {
         for _ in 0..args.warmup {
-          executor.exec().await;
+          executor.exec().await.map_err(unbound)?;
         }
       }
       // Measurement runs
       for _ in 0..args.runs {
         let start_time = Instant::now();
-        executor.exec().await;
+        executor.exec().await.map_err(unbound)?;
         let duration = start_time.elapsed();
         durations_ms.push(duration.as_secs_f64() * 1000.0);
       }
@@ -709,13 +710,13 @@ async fn execute_single_benchmark(
       // Warm-up runs
       if args.warmup > 0 {
         for _ in 0..args.warmup {
-          executor.exec().await;
+          executor.exec().await.map_err(unbound)?;
         }
       }
       // Measurement runs
       for _ in 0..args.runs {
         let start_time = Instant::now();
-        executor.exec().await;
+        executor.exec().await.map_err(unbound)?;
         let duration = start_time.elapsed();
         durations_ms.push(duration.as_secs_f64() * 1000.0);
       }
@@ -23,6 +23,7 @@ pub async fn query<S: AdvancedStorageAdapter + 'static>(plan_filename: &str) ->
       .parallel_exec(),
   )
   .await;
+  let result = result.map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
 
   let len = result.len();
 
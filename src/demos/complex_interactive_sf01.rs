@@ -18,6 +18,7 @@ async fn exec(plan_filename: &str) -> io::Result<()> {
       .parallel_exec(),
   )
   .await;
+  let result = result.map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
 
   let len = result.len();
 
@@ -0,0 +1,130 @@
+use super::join_sub_pattern;
+use crate::{
+  matching_ctx::MatchingCtx, schemas::Instruction, storage::AdvancedStorageAdapter,
+  utils::sub_pattern::SubPatternKind,
+};
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct AntiJoinOperator<S: AdvancedStorageAdapter> {
+  pub(crate) storage_adapter: Arc<S>,
+  pub(crate) ctx: Arc<MatchingCtx>,
+}
+
+impl<S: AdvancedStorageAdapter + 'static> AntiJoinOperator<S> {
+  pub async fn execute(&mut self, instr: &Instruction) -> Option<()> {
+    #[cfg(not(feature = "benchmark"))]
+    println!("\t{instr}");
+
+    // core logic: drop the partial matches where the negated sub-pattern exists
+    join_sub_pattern(
+      SubPatternKind::Negated,
+      instr,
+      &self.storage_adapter,
+      &self.ctx,
+    )
+    .await;
+
+    Some(())
+  }
+}
+
+#[cfg(test)]
+mod test_anti_join {
+  use crate::{
+    executor::{
+      ExecEngine,
+      test_utils::{bound_vars, exec_cypher},
+    },
+    planner::{QueryFormat, generate_optimal_plan_from_query},
+    schemas::InstructionType,
+    storage::SqliteStorageAdapter,
+  };
+  use itertools::Itertools;
+  use std::sync::Arc;
+
+  // a -> b -> c -> a, c -> d; m1 (tagged `x`) by a, m2 (tagged `y`) by b
+  const SOCIAL_SQL: &str = r#"
+    INSERT INTO db_vertex VALUES
+      ('a', 'Person'), ('b', 'Person'), ('c', 'Person'), ('d', 'Person'),
+      ('m1', 'Message'), ('m2', 'Message'), ('t1', 'Tag'), ('t2', 'Tag');
+    INSERT INTO vertex_attribute (vid, key, value, type) VALUES
+      ('t1', 'name', 'x', 'string'), ('t2', 'name', 'y', 'string');
+    INSERT INTO db_edge VALUES
+      ('k1', 'knows', 'a', 'b'), ('k2', 'knows', 'b', 'c'), ('k3', 'knows', 'c', 'a'),
+      ('k4', 'knows', 'c', 'd'),
+      ('hc1', 'hasCreator', 'm1', 'a'), ('hc2', 'hasCreator', 'm2', 'b'),
+      ('ht1', 'hasTag', 'm1', 't1'), ('ht2', 'hasTag', 'm2', 't2');
+  "#;
+
+  /// the data vids bound to `vars` in each match
+  async fn match_vars(cypher: &str, vars: &[&str]) -> Vec<Vec<String>> {
+    let (plan_data, results) = exec_cypher(cypher, SOCIAL_SQL).await;
+    // right before `Report`
+    let types = plan_data
+      .instructions
+      .iter()
+      .map(|instr| instr.type_)
+      .collect_vec();
+    assert_eq!(types[types.len() - 2], InstructionType::AntiJoin);
+    bound_vars(&results, vars)
+  }
+
+  #[tokio::test]
+  async fn test_not_exists_sub_pattern() {
+    let matched = match_vars(
+      "MATCH (p: Person)
+       WHERE NOT EXISTS { (m: Message)-[:hasCreator]->(p), (m)-[:hasTag]->(t: Tag) WHERE t.name = 'x' }
+       RETURN p",
+      &["p"],
+    )
+    .await;
+    assert_eq!(matched, [["b"], ["c"], ["d"]]);
+  }
+
+  #[tokio::test]
+  async fn test_not_edge_between_matched() {
+    let matched = match_vars(
+      "MATCH (x: Person)-[:knows]->(y: Person)-[:knows]->(z: Person)
+       WHERE NOT (x)-[:knows]-(z)
+       RETURN x, z",
+      &["x", "z"],
+    )
+    .await;
+    assert_eq!(matched, [["b", "d"]]);
+  }
+
+  #[tokio::test]
+  async fn test_not_exists_injective() {
+    // from `b` / `c`, the only such path leads back to itself
+    let matched = match_vars(
+      "MATCH (p: Person)
+       WHERE NOT EXISTS { (p)-[:knows]->(q: Person)-[:knows]->(r: Person)-[:knows]->(s: Person) }
+       RETURN p",
+      &["p"],
+    )
+    .await;
+    assert_eq!(matched, [["b"], ["c"], ["d"]]);
+  }
+
+  #[tokio::test]
+  async fn test_disconnected_sub_pattern_in_plan() {
+    let mut plan_data = generate_optimal_plan_from_query(
+      "MATCH (p: Person)
+       WHERE NOT EXISTS { (m: Message)-[hc: hasCreator]->(p), (m)-[:hasTag]->(t: Tag) }
+       RETURN p",
+      QueryFormat::Cypher,
+    )
+    .unwrap();
+    // as if written by hand, `m` and `t` can't be reached from `p` without `hc`
+    let anti_join = (plan_data.instructions.iter_mut())
+      .find(|instr| instr.type_ == InstructionType::AntiJoin)
+      .unwrap();
+    anti_join.expand_eids.retain(|eid| eid != "hc");
+
+    let storage_adapter = Arc::new(SqliteStorageAdapter::in_memory_test_only(SOCIAL_SQL));
+    let mut engine = ExecEngine::new(Arc::new(plan_data), storage_adapter);
+    let results = engine.exec().await.unwrap();
+    assert_eq!(bound_vars(&results, &["p"]), [["a"], ["b"], ["c"], ["d"]]);
+  }
+}
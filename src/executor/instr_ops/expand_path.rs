@@ -0,0 +1,143 @@
+use super::resolve_var;
+use crate::{
+  matching_ctx::{MatchingCtx, buckets::ABucket},
+  schemas::{Instruction, PatternEdge},
+  storage::AdvancedStorageAdapter,
+};
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct ExpandPathOperator<S: AdvancedStorageAdapter> {
+  pub(crate) storage_adapter: Arc<S>,
+  pub(crate) ctx: Arc<MatchingCtx>,
+}
+
+impl<S: AdvancedStorageAdapter + 'static> ExpandPathOperator<S> {
+  pub async fn execute(&mut self, instr: &Instruction) -> Option<()> {
+    #[cfg(not(feature = "benchmark"))]
+    println!("\t{instr}");
+
+    // to resolve current `pattern_vid`
+    let (_, curr_pat_vid) = resolve_var(instr.single_op.as_ref().unwrap());
+
+    let f_bucket = self
+      .ctx
+      .pop_from_f_block(instr.single_op.as_ref().unwrap())?;
+
+    let (var_length_es, plain_es): (Vec<_>, Vec<_>) = self
+      .ctx
+      .fetch_pattern_e_batch(instr.expand_eids.iter().map(String::as_str))
+      .into_iter()
+      .partition(PatternEdge::is_var_length);
+
+    // the plain edges are loaded as `GetAdj` does, from the same frontiers
+    let plain_a_bucket = if plain_es.is_empty() {
+      None
+    } else {
+      let mut a_bucket = ABucket::from_f_bucket(f_bucket.clone(), curr_pat_vid);
+      let pattern_vs = self.ctx.pattern_vs().clone();
+      a_bucket
+        .batched_incremental_load_new_edges(
+          plain_es,
+          pattern_vs,
+          self.storage_adapter.clone(),
+          self.ctx.attr_cmp_mode(),
+        )
+        .await;
+      Some(a_bucket)
+    };
+
+    // core logic: expand the paths hop by hop
+    let mut a_bucket = ABucket::from_f_bucket(f_bucket, curr_pat_vid);
+    a_bucket
+      .incremental_expand_paths(
+        var_length_es,
+        self.storage_adapter.clone(),
+        self.ctx.attr_cmp_mode(),
+      )
+      .await;
+
+    if let Some(plain_a_bucket) = plain_a_bucket {
+      for (next_pat_vid, expanding_graphs) in plain_a_bucket.next_pat_grouped_expanding {
+        a_bucket
+          .next_pat_grouped_expanding
+          .entry(next_pat_vid)
+          .or_default()
+          .extend(expanding_graphs);
+      }
+    }
+
+    // update the `block` and `extended data vid set`
+    self.ctx.update_a_block(&instr.target_var, a_bucket);
+
+    Some(())
+  }
+}
+
+#[cfg(test)]
+mod test_expand_path {
+  use crate::{
+    executor::test_utils::exec_cypher,
+    schemas::{EBase, InstructionType, STR_PATH_SPLITTER, VBase},
+  };
+  use itertools::Itertools;
+
+  // a -> b -> c -> d -> a, and b -> d
+  const KNOWS_SQL: &str = r#"
+    INSERT INTO db_vertex VALUES ('a', 'Person'), ('b', 'Person'), ('c', 'Person'), ('d', 'Person');
+    INSERT INTO vertex_attribute (vid, key, value, type) VALUES
+      ('a', 'name', 'a', 'string'), ('b', 'name', 'b', 'string'),
+      ('c', 'name', 'c', 'string'), ('d', 'name', 'd', 'string');
+    INSERT INTO db_edge VALUES
+      ('k1', 'knows', 'a', 'b'), ('k2', 'knows', 'b', 'c'), ('k3', 'knows', 'c', 'd'),
+      ('k4', 'knows', 'd', 'a'), ('k5', 'knows', 'b', 'd');
+  "#;
+
+  /// `(y, path eid)` of all the matches
+  async fn match_paths(cypher: &str) -> Vec<(String, String)> {
+    let (plan_data, results) = exec_cypher(cypher, KNOWS_SQL).await;
+    assert!(
+      plan_data
+        .instructions
+        .iter()
+        .any(|instr| instr.type_ == InstructionType::ExpandPath)
+    );
+
+    (results.into_iter())
+      .map(|g| {
+        let y = g.pattern_2_vids["y"].iter().exactly_one().unwrap();
+        let e = g.pattern_2_eids["e"].iter().exactly_one().unwrap();
+        let path = g.view_e_from_eid(e).unwrap();
+        assert!(path.contains(g.view_v_from_vid(y).unwrap().vid()));
+        assert_eq!(path.hops().len(), e.split(STR_PATH_SPLITTER).count());
+        (y.clone(), e.clone())
+      })
+      .sorted()
+      .collect()
+  }
+
+  #[tokio::test]
+  async fn test_var_length_paths() {
+    let matched =
+      match_paths("MATCH (x: Person)-[e: knows*1..2]->(y: Person) WHERE x.name = 'a' RETURN x, y")
+        .await;
+    let expected = [("b", "k1"), ("c", "k1|k2"), ("d", "k1|k5")];
+    assert_eq!(matched, expected.map(|(y, e)| (y.into(), e.into())));
+
+    // the path back to `a` is dropped, as `a` is already matched as `x`
+    let matched =
+      match_paths("MATCH (x: Person)-[e: knows*3]->(y: Person) WHERE x.name = 'a' RETURN x").await;
+    assert_eq!(matched, [("d".into(), "k1|k2|k3".into())]);
+
+    // undirected, where the hops go from the pattern edge's `src_vid` (i.e. `y`)
+    let matched =
+      match_paths("MATCH (y: Person)-[e: knows*2]-(x: Person) WHERE x.name = 'a' RETURN x").await;
+    let expected = [
+      ("b", "k5|k4"),
+      ("c", "k2|k1"),
+      ("c", "k3|k4"),
+      ("d", "k5|k1"),
+    ];
+    assert_eq!(matched, expected.map(|(y, e)| (y.into(), e.into())));
+  }
+}
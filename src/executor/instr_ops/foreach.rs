@@ -18,10 +18,79 @@ impl ForeachOperator {
       .ctx
       .pop_from_c_block(instr.single_op.as_ref().unwrap())?;
 
-    let f_bucket = FBucket::from_c_bucket(c_bucket).await;
+    let mut f_bucket = FBucket::from_c_bucket(c_bucket).await;
+    f_bucket.retain_satisfying(&instr.predicates, &instr.vid, self.ctx.attr_cmp_mode());
 
     self.ctx.update_f_block(&instr.target_var, f_bucket);
 
     Some(())
   }
 }
+
+#[cfg(test)]
+mod test_foreach {
+  use crate::{
+    executor::test_utils::{bound_vars, exec_cypher},
+    schemas::{InstructionType, PlanData},
+    utils::dyn_graph::DynGraph,
+  };
+
+  // a (30) -> b (20) -> c (40) -> a, and a -> c
+  const KNOWS_SQL: &str = r#"
+    INSERT INTO db_vertex VALUES ('a', 'Person'), ('b', 'Person'), ('c', 'Person');
+    INSERT INTO vertex_attribute (vid, key, value, type) VALUES
+      ('a', 'age', '30', 'int'), ('b', 'age', '20', 'int'), ('c', 'age', '40', 'int');
+    INSERT INTO db_edge VALUES
+      ('k1', 'knows', 'a', 'b'), ('k2', 'knows', 'a', 'c'), ('k3', 'knows', 'b', 'c'),
+      ('k4', 'knows', 'c', 'a');
+    INSERT INTO edge_attribute (eid, key, value, type) VALUES
+      ('k1', 'since', '10', 'int'), ('k2', 'since', '35', 'int'),
+      ('k3', 'since', '25', 'int'), ('k4', 'since', '50', 'int');
+  "#;
+
+  async fn exec(cypher: &str) -> (PlanData, Vec<DynGraph>) {
+    exec_cypher(cypher, KNOWS_SQL).await
+  }
+
+  /// the types of the instructions that check cross predicates
+  fn checked_by(plan_data: &PlanData) -> Vec<InstructionType> {
+    plan_data
+      .instructions
+      .iter()
+      .filter(|instr| !instr.predicates.is_empty())
+      .map(|instr| instr.type_)
+      .collect()
+  }
+
+  #[tokio::test]
+  async fn test_cross_predicate_on_foreach() {
+    let (plan_data, results) =
+      exec("MATCH (p1: Person)-[:knows]->(p2: Person) WHERE p1.age < p2.age RETURN p1, p2").await;
+    assert_eq!(checked_by(&plan_data), [InstructionType::Foreach]);
+    assert_eq!(
+      bound_vars(&results, &["p1", "p2"]),
+      [["a", "c"], ["b", "c"]]
+    );
+
+    // the edge is bound along with its later end
+    let (plan_data, results) =
+      exec("MATCH (p1: Person)-[k:knows]->(p2: Person) WHERE k.since > p1.age RETURN p1, p2").await;
+    assert_eq!(checked_by(&plan_data), [InstructionType::Foreach]);
+    assert_eq!(
+      bound_vars(&results, &["p1", "p2"]),
+      [["a", "c"], ["b", "c"], ["c", "a"]]
+    );
+  }
+
+  #[tokio::test]
+  async fn test_cross_predicate_on_report() {
+    // not connected, so it's only checked once the components are merged
+    let (plan_data, results) =
+      exec("MATCH (x: Person), (y: Person) WHERE x.age > y.age RETURN x, y").await;
+    assert_eq!(checked_by(&plan_data), [InstructionType::Report]);
+    assert_eq!(
+      bound_vars(&results, &["x", "y"]),
+      [["a", "b"], ["c", "a"], ["c", "b"]]
+    );
+  }
+}
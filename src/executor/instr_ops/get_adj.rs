@@ -35,7 +35,12 @@ impl<S: AdvancedStorageAdapter + 'static> GetAdjOperator<S> {
 
     // core logic: incremental load new edges
     a_bucket
-      .batched_incremental_load_new_edges(pattern_es, pattern_vs, self.storage_adapter.clone())
+      .batched_incremental_load_new_edges(
+        pattern_es,
+        pattern_vs,
+        self.storage_adapter.clone(),
+        self.ctx.attr_cmp_mode(),
+      )
       .await;
 
     // update the `block` and `extended data vid set`
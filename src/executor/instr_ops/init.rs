@@ -21,10 +21,21 @@ impl<S: StorageAdapter> InitOperator<S> {
     let pattern_v = self.ctx.get_pattern_v(&instr.vid)?.clone();
 
     let label = pattern_v.label.as_str();
-    let attr = pattern_v.attr.as_ref();
+    let attrs = pattern_v.attrs.as_slice();
+    let mode = self.ctx.attr_cmp_mode();
 
     // load vertices
-    let matched_vs = self.storage_adapter.load_v(label, attr).await;
+    let mut matched_vs = self.storage_adapter.load_v(label, attrs, mode).await;
+    // the cross predicates on the attributes of this very vertex
+    if !instr.predicates.is_empty() {
+      matched_vs.retain(|data_v| {
+        let attrs_of = |var: &str| (var == instr.vid).then_some(&data_v.attrs);
+        instr
+          .predicates
+          .iter()
+          .all(|pred| pred.is_satisfied_by(&attrs_of, mode))
+      });
+    }
 
     #[cfg(feature = "trace_init")]
     {
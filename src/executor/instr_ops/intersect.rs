@@ -59,13 +59,14 @@ impl<S: StorageAdapter + 'static> IntersectOperator<S> {
       let pattern_v = self.ctx.get_pattern_v(&instr.vid)?.clone();
       let pattern_vid = pattern_v.vid.clone();
       let expected_label = pattern_v.label.clone();
-      let expected_attr = pattern_v.attr.clone();
+      let expected_attrs = pattern_v.attrs.clone();
       CBucket::build_from_a_group_lazy(
         a_group,
         pattern_vid.into(),
         expected_label.into(),
-        expected_attr.map(Arc::new),
+        expected_attrs.into(),
         self.storage_adapter.clone(),
+        self.ctx.attr_cmp_mode(),
       )
       .await
     };
@@ -96,7 +97,9 @@ impl<S: StorageAdapter + 'static> IntersectOperator<S> {
           }
           IntersectTarget => {
             // `Ai` ∩ `Tj` -> `Tx`
-            let rhs_t_group = self.ctx.pop_from_t_block(instr.multi_ops[1].as_str())?;
+            let rhs_t_group = self
+              .ctx
+              .pop_from_t_block(instr.multi_ops[1].as_str(), &instr.vid)?;
             TBucket::build_from_a_t(lhs_a_group, rhs_t_group).await
           }
           _ => panic!("❌  Invalid var_prefix: {rhs_pref}"),
@@ -104,7 +107,9 @@ impl<S: StorageAdapter + 'static> IntersectOperator<S> {
       }
       IntersectTarget => {
         // `Ti` ∩ `Aj / Tj` -> `Tx`
-        let lhs_t_group = self.ctx.pop_from_t_block(instr.multi_ops[0].as_str())?;
+        let lhs_t_group = self
+          .ctx
+          .pop_from_t_block(instr.multi_ops[0].as_str(), &instr.vid)?;
         match rhs_pref {
           DbQueryTarget => {
             // `Ti` ∩ `Aj` -> `Tx`
@@ -115,7 +120,9 @@ impl<S: StorageAdapter + 'static> IntersectOperator<S> {
           }
           IntersectTarget => {
             // `Ti` ∩ `Tj` -> `Tx`
-            let rhs_t_group = self.ctx.pop_from_t_block(instr.multi_ops[1].as_str())?;
+            let rhs_t_group = self
+              .ctx
+              .pop_from_t_block(instr.multi_ops[1].as_str(), &instr.vid)?;
             TBucket::build_from_t_t(lhs_t_group, rhs_t_group).await
           }
           _ => panic!("❌  Invalid var_prefix: {rhs_pref}"),
@@ -137,7 +144,9 @@ impl<S: StorageAdapter + 'static> IntersectOperator<S> {
             TBucket::build_from_t_a(t_bucket, next_a_group).await
           }
           IntersectTarget => {
-            let next_t_group = self.ctx.pop_from_t_block(instr.multi_ops[idx].as_str())?;
+            let next_t_group = self
+              .ctx
+              .pop_from_t_block(instr.multi_ops[idx].as_str(), &instr.vid)?;
             TBucket::build_from_t_t(t_bucket, next_t_group).await
           }
           _ => panic!("❌  Invalid var_prefix: {next_pref}"),
@@ -158,7 +167,7 @@ impl<S: StorageAdapter + 'static> IntersectOperator<S> {
   async fn with_temp_intersected(&mut self, instr: &Instruction) -> Option<()> {
     let t_bucket = self
       .ctx
-      .pop_from_t_block(instr.single_op.as_ref().unwrap())?;
+      .pop_from_t_block(instr.single_op.as_ref().unwrap(), &instr.vid)?;
 
     #[cfg(not(feature = "lazy_load_v"))]
     let c_bucket = {
@@ -178,13 +187,14 @@ impl<S: StorageAdapter + 'static> IntersectOperator<S> {
       let pattern_v = self.ctx.get_pattern_v(&instr.vid)?.clone();
       let pattern_vid = pattern_v.vid.clone();
       let expected_label = pattern_v.label.clone();
-      let expected_attr = pattern_v.attr.clone();
+      let expected_attrs = pattern_v.attrs.clone();
       CBucket::build_from_t_lazy(
         t_bucket,
         pattern_vid.into(),
         expected_label.into(),
-        expected_attr.map(Arc::new),
+        expected_attrs.into(),
         self.storage_adapter.clone(),
+        self.ctx.attr_cmp_mode(),
       )
       .await
     };
@@ -204,8 +214,10 @@ impl<S: StorageAdapter + 'static> IntersectOperator<S> {
     let pattern_vid = pattern_v.vid.clone();
 
     let label = pattern_v.label.as_str();
-    let attr = pattern_v.attr.as_ref();
-    let matched_vs = self.storage_adapter.load_v(label, attr).await;
+    let attrs = pattern_v.attrs.as_slice();
+    let matched_vs = (self.storage_adapter)
+      .load_v(label, attrs, self.ctx.attr_cmp_mode())
+      .await;
 
     let mut raw = matched_vs
       .into_iter()
@@ -1,21 +1,34 @@
 use crate::{
   matching_ctx::MatchingCtx,
-  schemas::{Instruction, InstructionType::*, STR_TUPLE_SPLITTER, VarPrefix},
+  schemas::{
+    EBase, Instruction, InstructionType::*, PatternEdge, PatternVertex, STR_TUPLE_SPLITTER,
+    VarPrefix, Vid,
+  },
   storage::AdvancedStorageAdapter,
+  utils::sub_pattern::SubPatternKind,
 };
+use anti_join::AntiJoinOperator;
+use expand_path::ExpandPathOperator;
 use foreach::ForeachOperator;
 use get_adj::GetAdjOperator;
+use hashbrown::HashMap;
 use init::InitOperator;
 use intersect::IntersectOperator;
 use itertools::Itertools;
+use optional_expand::OptionalExpandOperator;
 use report::ReportOperator;
 use std::{str::FromStr, sync::Arc};
+use t_cache::TCacheOperator;
 
+pub mod anti_join;
+pub mod expand_path;
 pub mod foreach;
 pub mod get_adj;
 pub mod init;
 pub mod intersect;
+pub mod optional_expand;
 pub mod report;
+pub mod t_cache;
 
 #[inline]
 pub(crate) fn resolve_var(target_var: &str) -> (VarPrefix, &str) {
@@ -25,12 +38,73 @@ pub(crate) fn resolve_var(target_var: &str) -> (VarPrefix, &str) {
   (VarPrefix::from_str(var_type).unwrap(), var_name)
 }
 
+/// The required vertices that the sub-pattern of `pattern_es` hangs on.
+pub(crate) fn sub_pattern_anchors(
+  pattern_es: &[PatternEdge],
+  pattern_vs: &HashMap<Vid, PatternVertex>,
+) -> Vec<Vid> {
+  pattern_es
+    .iter()
+    .flat_map(|e| [e.src_vid(), e.dst_vid()])
+    .filter(|vid| {
+      pattern_vs
+        .get(*vid)
+        .is_some_and(|v| SubPatternKind::of_v(v).is_none())
+    })
+    .unique()
+    .map(String::from)
+    .collect()
+}
+
+/// Join each f bucket of `instr.multi_ops` with the sub-pattern of `instr.expand_eids`,
+/// see `FBucket::join_sub_pattern`.
+pub(crate) async fn join_sub_pattern<S: AdvancedStorageAdapter + 'static>(
+  kind: SubPatternKind,
+  instr: &Instruction,
+  storage_adapter: &Arc<S>,
+  ctx: &MatchingCtx,
+) {
+  let pattern_vs = Arc::new(ctx.pattern_vs().clone());
+  let pattern_es = ctx.fetch_pattern_e_batch(instr.expand_eids.iter().map(String::as_str));
+
+  let anchors = sub_pattern_anchors(&pattern_es, &pattern_vs);
+
+  for f_var in instr.multi_ops.iter() {
+    // `GetAdj` may have consumed it already
+    let Some(mut f_bucket) = ctx.pop_from_f_block(f_var) else {
+      continue;
+    };
+
+    let joined = f_bucket
+      .join_sub_pattern(
+        kind,
+        pattern_es.clone(),
+        pattern_vs.clone(),
+        anchors.clone(),
+        storage_adapter.clone(),
+        ctx.attr_cmp_mode(),
+      )
+      .await;
+    // the planner only takes the sub-patterns connected to the rest (see `check_sub_patterns`),
+    // but a hand-written plan may not
+    if let Err(reason) = joined {
+      eprintln!("❌  Failed to join `{f_var}` by `{instr}`, left as it is: {reason}");
+    }
+
+    ctx.update_f_block(f_var, f_bucket);
+  }
+}
+
 pub enum InstrOperator<S: AdvancedStorageAdapter + 'static> {
   Init(InitOperator<S>),
   GetAdj(GetAdjOperator<S>),
   Foreach(ForeachOperator),
   Intersect(IntersectOperator<S>),
+  TCache(TCacheOperator),
   Report(ReportOperator),
+  ExpandPath(ExpandPathOperator<S>),
+  AntiJoin(AntiJoinOperator<S>),
+  OptionalExpand(OptionalExpandOperator<S>),
 }
 
 impl<S: AdvancedStorageAdapter + 'static> InstrOperator<S> {
@@ -40,7 +114,11 @@ impl<S: AdvancedStorageAdapter + 'static> InstrOperator<S> {
       InstrOperator::GetAdj(operator) => operator.execute(instr).await,
       InstrOperator::Foreach(operator) => operator.execute(instr).await,
       InstrOperator::Intersect(operator) => operator.execute(instr).await,
+      InstrOperator::TCache(operator) => operator.execute(instr).await,
       InstrOperator::Report(operator) => operator.execute(instr).await,
+      InstrOperator::ExpandPath(operator) => operator.execute(instr).await,
+      InstrOperator::AntiJoin(operator) => operator.execute(instr).await,
+      InstrOperator::OptionalExpand(operator) => operator.execute(instr).await,
     };
   }
 }
@@ -67,8 +145,20 @@ impl InstrOperatorFactory {
         storage_adapter,
         ctx,
       }),
+      TCache => InstrOperator::TCache(TCacheOperator { ctx }),
       Report => InstrOperator::Report(ReportOperator { ctx }),
-      TCache => unimplemented!("`TCache` operator is not implemented yet."),
+      ExpandPath => InstrOperator::ExpandPath(ExpandPathOperator {
+        storage_adapter,
+        ctx,
+      }),
+      AntiJoin => InstrOperator::AntiJoin(AntiJoinOperator {
+        storage_adapter,
+        ctx,
+      }),
+      OptionalExpand => InstrOperator::OptionalExpand(OptionalExpandOperator {
+        storage_adapter,
+        ctx,
+      }),
     }
   }
 }
@@ -0,0 +1,129 @@
+use super::join_sub_pattern;
+use crate::{
+  matching_ctx::MatchingCtx, schemas::Instruction, storage::AdvancedStorageAdapter,
+  utils::sub_pattern::SubPatternKind,
+};
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct OptionalExpandOperator<S: AdvancedStorageAdapter> {
+  pub(crate) storage_adapter: Arc<S>,
+  pub(crate) ctx: Arc<MatchingCtx>,
+}
+
+impl<S: AdvancedStorageAdapter + 'static> OptionalExpandOperator<S> {
+  pub async fn execute(&mut self, instr: &Instruction) -> Option<()> {
+    #[cfg(not(feature = "benchmark"))]
+    println!("\t{instr}");
+
+    // core logic: left-outer join the partial matches with the optional sub-pattern
+    join_sub_pattern(
+      SubPatternKind::Optional,
+      instr,
+      &self.storage_adapter,
+      &self.ctx,
+    )
+    .await;
+
+    Some(())
+  }
+}
+
+#[cfg(test)]
+mod test_optional_expand {
+  use crate::{
+    executor::{
+      ExecEngine,
+      test_utils::{bound_vars, exec_cypher},
+    },
+    planner::{QueryFormat, generate_optimal_plan_from_query},
+    result_dump::ResultDumper,
+    schemas::{InstructionType, PlanData},
+    storage::SqliteStorageAdapter,
+    utils::dyn_graph::DynGraph,
+  };
+  use itertools::Itertools;
+  use std::sync::Arc;
+
+  // a, b and c live in x; a studies at u1 and u2, b at u3 (which isn't in x), c nowhere
+  const CAMPUS_SQL: &str = r#"
+    INSERT INTO db_vertex VALUES
+      ('a', 'Person'), ('b', 'Person'), ('c', 'Person'), ('x', 'City'), ('y', 'City'),
+      ('u1', 'University'), ('u2', 'University'), ('u3', 'University');
+    INSERT INTO db_edge VALUES
+      ('l1', 'isLocatedIn', 'a', 'x'), ('l2', 'isLocatedIn', 'b', 'x'),
+      ('l3', 'isLocatedIn', 'c', 'x'),
+      ('ul1', 'isLocatedIn', 'u1', 'x'), ('ul2', 'isLocatedIn', 'u2', 'x'),
+      ('ul3', 'isLocatedIn', 'u3', 'y'),
+      ('s1', 'studyAt', 'a', 'u1'), ('s2', 'studyAt', 'a', 'u2'), ('s3', 'studyAt', 'b', 'u3');
+  "#;
+
+  async fn exec(cypher: &str) -> (PlanData, Vec<DynGraph>) {
+    let (plan_data, results) = exec_cypher(cypher, CAMPUS_SQL).await;
+    // right before `Report`
+    let types = plan_data
+      .instructions
+      .iter()
+      .map(|instr| instr.type_)
+      .collect_vec();
+    assert_eq!(types[types.len() - 2], InstructionType::OptionalExpand);
+    (plan_data, results)
+  }
+
+  #[tokio::test]
+  async fn test_optional_sub_pattern() {
+    let (_, results) = exec(
+      "MATCH (p: Person)-[:isLocatedIn]->(c: City)
+       OPTIONAL MATCH (p)-[:studyAt]->(u: University)-[:isLocatedIn]->(c)
+       RETURN p, u",
+    )
+    .await;
+    assert_eq!(
+      bound_vars(&results, &["p", "u"]),
+      [["a", "u1"], ["a", "u2"], ["b", "null"], ["c", "null"]]
+    );
+  }
+
+  #[tokio::test]
+  async fn test_unbound_as_null() {
+    let (plan_data, results) = exec(
+      "MATCH (p: Person)
+       OPTIONAL MATCH (p)-[:studyAt]->(u: University) WHERE u.name = 'none'
+       RETURN p, u",
+    )
+    .await;
+    assert_eq!(results.len(), 3);
+
+    let df = ResultDumper::new(results)
+      .with_plan_data(&plan_data)
+      .to_simplified_df(false)
+      .unwrap();
+    assert_eq!(df.height(), 3);
+    assert_eq!(df.column("p").unwrap().null_count(), 0);
+    assert_eq!(df.column("u").unwrap().null_count(), 3);
+  }
+
+  #[tokio::test]
+  async fn test_disconnected_sub_pattern_in_plan() {
+    let mut plan_data = generate_optimal_plan_from_query(
+      "MATCH (p: Person)
+       OPTIONAL MATCH (p)-[s: studyAt]->(u: University)-[:isLocatedIn]->(c: City)
+       RETURN p, u",
+      QueryFormat::Cypher,
+    )
+    .unwrap();
+    // as if written by hand, `u` and `c` can't be reached from `p` without `s`
+    let optional_expand = (plan_data.instructions.iter_mut())
+      .find(|instr| instr.type_ == InstructionType::OptionalExpand)
+      .unwrap();
+    optional_expand.expand_eids.retain(|eid| eid != "s");
+
+    let storage_adapter = Arc::new(SqliteStorageAdapter::in_memory_test_only(CAMPUS_SQL));
+    let mut engine = ExecEngine::new(Arc::new(plan_data), storage_adapter);
+    let results = engine.exec().await.unwrap();
+    assert_eq!(
+      bound_vars(&results, &["p", "u"]),
+      [["a", "null"], ["b", "null"], ["c", "null"]]
+    );
+  }
+}
@@ -0,0 +1,21 @@
+use crate::{matching_ctx::MatchingCtx, schemas::Instruction};
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct TCacheOperator {
+  pub(crate) ctx: Arc<MatchingCtx>,
+}
+
+impl TCacheOperator {
+  /// `Tx` -> `T#x`, shared by all the intersections on `T#x`
+  pub async fn execute(&mut self, instr: &Instruction) -> Option<()> {
+    #[cfg(not(feature = "benchmark"))]
+    println!("\t{instr}");
+
+    self.ctx.cache_t_bucket(
+      instr.single_op.as_ref().unwrap(),
+      &instr.target_var,
+      instr.consumers?,
+    )
+  }
+}
@@ -1,20 +1,23 @@
 use crate::{
   matching_ctx::MatchingCtx,
   schemas::*,
-  storage::{AdvancedStorageAdapter, TestOnlyStorageAdapter},
-  utils::{dyn_graph::DynGraph, parallel},
+  storage::{AdvancedStorageAdapter, ProfiledStorageAdapter, TestOnlyStorageAdapter},
+  utils::{dyn_graph::DynGraph, parallel, time_async},
 };
 #[cfg(not(feature = "benchmark"))]
 use colored::Colorize;
-use hashbrown::HashMap;
 use instr_ops::InstrOperatorFactory;
 use itertools::Itertools;
 #[cfg(not(feature = "benchmark"))]
 use polars::{frame::DataFrame, prelude::Column, series::Series};
+use profile::{BucketRows, Profile, ProfiledInstruction, ProfiledMerge};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use std::{collections::VecDeque, sync::Arc};
+use std::{sync::Arc, time::Instant};
 
 pub mod instr_ops;
+pub mod profile;
+#[cfg(test)]
+pub(crate) mod test_utils;
 
 #[derive(Clone)]
 pub struct ExecEngine<S: AdvancedStorageAdapter> {
@@ -73,7 +76,6 @@ impl<S: AdvancedStorageAdapter + 'static> ExecEngine<S> {
       .plan_data
       .instructions
       .iter()
-      .cloned()
       .map(|instr| instr.to_string_uncolored())
       .collect_vec();
     let height = instructions.len();
@@ -92,7 +94,10 @@ impl<S: AdvancedStorageAdapter + 'static> ExecEngine<S> {
     println!("{df}\n");
   }
 
-  pub async fn exec_without_final_merge(&mut self) -> Vec<Vec<DynGraph>> {
+  /// `Unbound` if the plan has `$param`s, which have to be bound by `exec_with_params` instead.
+  pub async fn exec_without_final_merge(&mut self) -> Result<Vec<Vec<DynGraph>>, ParamError> {
+    self.plan_data.check_params_bound()?;
+
     let mut operators = Vec::with_capacity(self.plan_data.instructions.len());
     for instr in self.plan_data.instructions.iter() {
       let operator = InstrOperatorFactory::create(
@@ -112,7 +117,7 @@ impl<S: AdvancedStorageAdapter + 'static> ExecEngine<S> {
       result.push(matched_graphs);
     }
 
-    result
+    Ok(result)
   }
 
   async fn exec_helper(&mut self, unmerged_results: Vec<Vec<DynGraph>>) -> Vec<DynGraph> {
@@ -132,6 +137,17 @@ impl<S: AdvancedStorageAdapter + 'static> ExecEngine<S> {
       return vec![];
     }
 
+    // the cross predicates that span several connected components of the pattern
+    let report_predicates = self
+      .plan_data
+      .instructions
+      .iter()
+      .filter(|instr| instr.type_ == InstructionType::Report)
+      .flat_map(|instr| instr.predicates.iter().cloned())
+      .collect_vec();
+    let symmetry = self.plan_data.symmetry.clone();
+    let mode = self.plan_data.attr_cmp_mode;
+
     parallel::spawn_blocking(move || {
       let mut results = vec![];
 
@@ -172,41 +188,155 @@ impl<S: AdvancedStorageAdapter + 'static> ExecEngine<S> {
         }
       }
 
+      if !report_predicates.is_empty() {
+        results = results
+          .into_par_iter()
+          .filter(|g| g.satisfies(&report_predicates, None, mode))
+          .collect();
+      }
+
+      // each subgraph is only matched once, so it's mapped through the automorphisms
+      if symmetry.mode() == SymmetryMode::AllEmbeddings && !symmetry.is_trivial() {
+        results = results
+          .into_par_iter()
+          .flat_map_iter(|g| {
+            let embeddings = (symmetry.automorphisms().iter())
+              .map(|mapping| g.with_patterns_renamed(mapping))
+              .collect_vec();
+            std::iter::once(g).chain(embeddings)
+          })
+          .collect();
+      }
+
       results
     })
     .await
   }
 
-  pub async fn exec(&mut self) -> Vec<DynGraph> {
-    let unmerged_results = self
-      .exec_without_final_merge()
-      .await
-      .into_iter()
+  /// `Unbound` if the plan has `$param`s, see `exec_without_final_merge`.
+  pub async fn exec(&mut self) -> Result<Vec<DynGraph>, ParamError> {
+    let unmerged_results = (self.exec_without_final_merge().await?.into_iter())
       .filter(|v| !v.is_empty())
       .collect_vec();
 
-    self.exec_helper(unmerged_results).await
+    Ok(self.exec_helper(unmerged_results).await)
   }
 
-  pub async fn parallel_exec(&mut self) -> Vec<DynGraph> {
-    let unmerged_results = self
-      .parallel_exec_without_final_merge()
+  /// Execute with the `$param`s bound to `params`.
+  ///
+  /// The plan itself is left unbound, so that it can be executed again with other bindings.
+  pub async fn exec_with_params(&self, params: &Params) -> Result<Vec<DynGraph>, ParamError> {
+    let plan_data = Arc::new(self.plan_data.bind_params(params)?);
+    Self::new(plan_data, self.storage_adapter.clone())
+      .exec()
       .await
-      .into_iter()
-      .filter(|v| !v.is_empty())
-      .collect_vec();
+  }
 
-    self.exec_helper(unmerged_results).await
+  /// The parallel counterpart of `exec_with_params`.
+  pub async fn parallel_exec_with_params(
+    &self,
+    params: &Params,
+  ) -> Result<Vec<DynGraph>, ParamError> {
+    let plan_data = Arc::new(self.plan_data.bind_params(params)?);
+    Self::new(plan_data, self.storage_adapter.clone())
+      .parallel_exec()
+      .await
   }
 
-  pub async fn parallel_exec_without_final_merge(&mut self) -> Vec<Vec<DynGraph>> {
-    let layers = self.build_dependency_layers();
+  /// Execute the instructions one after another (so that the loads of each one are told apart),
+  /// recording the time, the partial matches in / out and the storage loads of each one,
+  /// and those of the final merge.
+  pub async fn profile(&mut self) -> Result<(Vec<DynGraph>, Profile), ParamError> {
+    self.plan_data.check_params_bound()?;
+    let start = Instant::now();
+    let storage_adapter = Arc::new(ProfiledStorageAdapter::new(self.storage_adapter.clone()));
+
+    let mut instructions = Vec::with_capacity(self.plan_data.instructions.len());
+    for (id, instr) in self.plan_data.instructions.iter().enumerate() {
+      let mut operator =
+        InstrOperatorFactory::create(instr, storage_adapter.clone(), self.matching_ctx.clone());
+
+      // an `A` operand of `Intersect` only offers the group expanding to its vertex
+      let next_pat_vid = (instr.type_ == InstructionType::Intersect).then_some(instr.vid.as_str());
+      let buckets_in = (instr.single_op.iter().chain(&instr.multi_ops))
+        .map(|var| BucketRows {
+          var: var.clone(),
+          rows: self.matching_ctx.bucket_len(var, next_pat_vid),
+        })
+        .collect_vec();
+      let storage_before = storage_adapter.stats();
+
+      let (_, elapsed_ms) = time_async(operator.execute(instr)).await;
+
+      let rows_out = match instr.type_ {
+        // they update the buckets of their operands in place
+        InstructionType::AntiJoin | InstructionType::OptionalExpand => (instr.multi_ops.iter())
+          .map(|var| self.matching_ctx.bucket_len(var, None))
+          .sum(),
+        // left to the groups, once they're all reported
+        InstructionType::Report => 0,
+        _ => self.matching_ctx.bucket_len(&instr.target_var, None),
+      };
+      instructions.push(ProfiledInstruction {
+        id,
+        instruction: instr.to_string_uncolored(),
+        type_: instr.type_,
+        vid: instr.vid.clone(),
+        target_var: instr.target_var.clone(),
+        elapsed_ms,
+        buckets_in,
+        rows_out,
+        storage: storage_adapter.stats() - storage_before,
+        est_input_card: None,
+        est_output_card: None,
+      });
+    }
 
-    if layers.is_none() {
-      return self.exec_without_final_merge().await;
+    let mut unmerged_results = Vec::with_capacity(self.matching_ctx.grouped_partial_matches.len());
+    while let Some(matched_graphs) = self.matching_ctx.grouped_partial_matches.pop() {
+      if !matched_graphs.is_empty() {
+        unmerged_results.push(matched_graphs);
+      }
+    }
+    let group_rows = unmerged_results.iter().map(Vec::len).collect_vec();
+    for instr in instructions.iter_mut() {
+      if instr.type_ == InstructionType::Report {
+        instr.rows_out = group_rows.iter().sum();
+      }
     }
 
-    let layers = layers.unwrap();
+    let (results, elapsed_ms) = time_async(self.exec_helper(unmerged_results)).await;
+    let profile = Profile {
+      matching_order: self.plan_data.matching_order.clone(),
+      instructions,
+      final_merge: ProfiledMerge {
+        elapsed_ms,
+        group_rows,
+        rows_out: results.len(),
+      },
+      elapsed_ms: start.elapsed().as_millis_f64(),
+    };
+    Ok((results, profile))
+  }
+
+  /// `Unbound` if the plan has `$param`s, see `exec_without_final_merge`.
+  pub async fn parallel_exec(&mut self) -> Result<Vec<DynGraph>, ParamError> {
+    let unmerged_results = (self.parallel_exec_without_final_merge().await?.into_iter())
+      .filter(|v| !v.is_empty())
+      .collect_vec();
+
+    Ok(self.exec_helper(unmerged_results).await)
+  }
+
+  /// `Unbound` if the plan has `$param`s, see `exec_without_final_merge`.
+  pub async fn parallel_exec_without_final_merge(
+    &mut self,
+  ) -> Result<Vec<Vec<DynGraph>>, ParamError> {
+    self.plan_data.check_params_bound()?;
+    let Some(layers) = self.plan_data.dependency_layers() else {
+      eprintln!("⚠️  The plan contains a cycle. Fallback to sequential execution.");
+      return self.exec_without_final_merge().await;
+    };
 
     // execute the instructions in parallel (by layer)
     for (_layer_idx, layer) in layers.iter().enumerate() {
@@ -251,76 +381,251 @@ impl<S: AdvancedStorageAdapter + 'static> ExecEngine<S> {
       result.push(matched_graphs);
     }
 
-    result
+    Ok(result)
   }
+}
 
-  fn build_dependency_layers(&self) -> Option<Vec<Vec<usize>>> {
-    let instructions = &self.plan_data.instructions;
-    let n = instructions.len();
-
-    // build dependency graph
-    let mut incoming_edges = vec![0; n];
-    let mut graph = vec![vec![]; n];
-
-    // build `target_var -> index` map
-    let mut var_to_idx = HashMap::new();
-    for (idx, instr) in instructions.iter().enumerate() {
-      var_to_idx.insert(instr.target_var.clone(), idx);
-    }
-
-    // fill the dependency graph
-    for (idx, instr) in instructions.iter().enumerate() {
-      for dep_var in &instr.depend_on {
-        if let Some(&dep_idx) = var_to_idx.get(dep_var) {
-          graph[dep_idx].push(idx); // `dep_idx` is dependent on `idx`
-          incoming_edges[idx] += 1; // increase the incoming edge count for `idx`
-        }
-      }
-    }
-
-    // Kahn's algorithm for topological sorting
-    let mut layers = vec![];
-    let mut queue = VecDeque::new();
-
-    // init the queue with all nodes on which incoming edges = 0
-    for (i, &incoming_edge) in incoming_edges.iter().enumerate().take(n) {
-      if incoming_edge == 0 {
-        queue.push_back(i);
+#[cfg(test)]
+mod test_exec_engine {
+  use super::*;
+  use crate::{
+    executor::test_utils::{bound_vars, exec_cypher},
+    parser::CypherParser,
+    planner::{
+      QueryFormat, generate_optimal_plan_from_query, generate_plan_with_symmetry_from_query,
+      order_calc::OrderCalculator, plan_dump::PlanDumper, plan_gen::PlanGenerator,
+      plan_opt::PlanOptimizer,
+    },
+    storage::SqliteStorageAdapter,
+  };
+
+  const KNOWS_SQL: &str = r#"
+    INSERT INTO db_vertex VALUES ('a', 'Person'), ('b', 'Person'), ('c', 'Person');
+    INSERT INTO vertex_attribute (vid, key, value, type) VALUES
+      ('a', 'id', '1', 'int'), ('b', 'id', '2', 'int'), ('c', 'id', '3', 'int');
+    INSERT INTO db_edge VALUES
+      ('k1', 'knows', 'a', 'b'), ('k2', 'knows', 'a', 'c'), ('k3', 'knows', 'b', 'c');
+  "#;
+
+  #[tokio::test]
+  async fn test_exec_with_params() {
+    let plan_data = generate_optimal_plan_from_query(
+      "MATCH (p: Person)-[:knows]->(f: Person) WHERE p.id = $personId RETURN f",
+      QueryFormat::Cypher,
+    )
+    .unwrap();
+    assert_eq!(plan_data.params(), vec!["personId"]);
+
+    // the plan survives a round trip through JSON
+    let plan_json = serde_json::to_string(&plan_data).unwrap();
+    let plan_data: PlanData = serde_json::from_str(&plan_json).unwrap();
+
+    let storage_adapter = Arc::new(SqliteStorageAdapter::in_memory_test_only(KNOWS_SQL));
+    let engine = ExecEngine::new(Arc::new(plan_data), storage_adapter);
+    let friends_of = |person_id: i64| {
+      let params = Params::from([("personId".to_string(), AttrValue::Int(person_id))]);
+      let engine = engine.clone();
+      async move {
+        let results = engine.exec_with_params(&params).await.unwrap();
+        bound_vars(&results, &["f"])
       }
-    }
+    };
+    assert_eq!(friends_of(1).await, [["b"], ["c"]]);
+    assert_eq!(friends_of(2).await, [["c"]]);
+    assert!(friends_of(3).await.is_empty());
+
+    let err = engine.exec_with_params(&Params::new()).await.unwrap_err();
+    assert_eq!(err, ParamError::Unbound(vec!["personId".to_string()]));
+    // the plan itself is left unbound
+    let err = engine.clone().parallel_exec().await.unwrap_err();
+    assert_eq!(err, ParamError::Unbound(vec!["personId".to_string()]));
+  }
 
-    while !queue.is_empty() {
-      let mut current_layer = vec![];
-      let layer_size = queue.len();
+  #[tokio::test]
+  async fn test_exec_unlabeled_vertex() {
+    let sql = r#"
+      INSERT INTO db_vertex VALUES ('a', 'Person'), ('p', 'Post'), ('c', 'Comment'), ('t', 'Tag');
+      INSERT INTO vertex_attribute (vid, key, value, type) VALUES ('a', 'id', '1', 'int');
+      INSERT INTO db_edge VALUES
+        ('l1', 'likes', 'a', 'p'), ('l2', 'likes', 'a', 'c'), ('h1', 'hasTag', 'p', 't');
+    "#;
+    let (plan_data, results) = exec_cypher(
+      "MATCH (person: Person)-[:likes]->(m) WHERE person.id = 1 RETURN m",
+      sql,
+    )
+    .await;
+    assert_eq!(plan_data.pattern_vs["m"].label, "");
+    assert_eq!(bound_vars(&results, &["m"]), [["c"], ["p"]]);
+  }
 
-      // iterate over each node of current_layer
-      for _ in 0..layer_size {
-        let node = queue.pop_front().unwrap();
-        current_layer.push(node);
+  #[tokio::test]
+  async fn test_exec_connected_order() {
+    // both ends are as selective, but the middle one has to follow the first
+    let (plan_data, results) = exec_cypher(
+      "MATCH (p: Person)-[:knows]->(q: Person)-[:knows]->(r: Person) \
+       WHERE p.id = 1 AND r.id = 3 RETURN q",
+      KNOWS_SQL,
+    )
+    .await;
+    assert_eq!(plan_data.matching_order[1], "q");
+    let inits = (plan_data.instructions.iter())
+      .filter(|instr| instr.type_ == InstructionType::Init)
+      .count();
+    assert_eq!(inits, 1);
+    assert_eq!(bound_vars(&results, &["q"]), [["b"]]);
+  }
 
-        // decrease the incoming edge count for all dependent nodes
-        for &dependent in &graph[node] {
-          incoming_edges[dependent] -= 1;
+  #[tokio::test]
+  async fn test_exec_shared_t_bucket() {
+    // `a` and `d` both know `b`, `c` and `e`
+    let sql = r#"
+      INSERT INTO db_vertex VALUES
+        ('a', 'Person'), ('b', 'Person'), ('c', 'Person'), ('d', 'Person'), ('e', 'Person');
+      INSERT INTO vertex_attribute (vid, key, value, type) VALUES
+        ('a', 'id', '1', 'int'), ('d', 'id', '4', 'int');
+      INSERT INTO db_edge VALUES
+        ('k1', 'knows', 'a', 'b'), ('k2', 'knows', 'a', 'c'), ('k3', 'knows', 'a', 'e'),
+        ('k4', 'knows', 'd', 'b'), ('k5', 'knows', 'd', 'c'), ('k6', 'knows', 'd', 'e');
+    "#;
+    let mut parser = CypherParser::new(
+      "MATCH (w: Person)-[:knows]->(v1: Person)<-[:knows]-(x: Person), \
+       (w)-[:knows]->(v2: Person)<-[:knows]-(x), (w)-[:knows]->(v3: Person)<-[:knows]-(x) \
+       WHERE w.id = 1 AND x.id = 4 RETURN v1, v2, v3"
+        .to_string(),
+    );
+    parser.parse().unwrap();
+    let order_calc = OrderCalculator::new(parser.take_as_pattern_graph());
+    let plan_gen_input = order_calc.compute_optimal_order();
+    // the same plan (in the same order), but for whether the intersections may share a bucket
+    let plan = |share_t_buckets: bool| {
+      let mut plan_gen = PlanGenerator::from(plan_gen_input.clone());
+      plan_gen.generate_raw_plan();
+      let mut plan_optimizer = PlanOptimizer::from(plan_gen).with_shared_t_buckets(share_t_buckets);
+      plan_optimizer.apply_optimization();
+      PlanDumper::from(plan_optimizer).to_plan_data()
+    };
+    let t_caches = |plan_data: &PlanData| {
+      (plan_data.instructions.iter())
+        .filter(|instr| instr.type_ == InstructionType::TCache)
+        .cloned()
+        .collect_vec()
+    };
+    let (shared, unshared) = (plan(true), plan(false));
 
-          // if incoming_edges[dependent] == 0, then add to queue
-          if incoming_edges[dependent] == 0 {
-            queue.push_back(dependent);
-          }
-        }
+    // the two vertices intersected last share `A^w ∩ A^x`
+    let t_cache = t_caches(&shared).into_iter().exactly_one().unwrap();
+    let consumers = (shared.instructions.iter())
+      .filter(|instr| instr.single_op.as_ref() == Some(&t_cache.target_var))
+      .map(|instr| instr.vid.as_str())
+      .collect_vec();
+    assert_eq!(consumers.len(), 2);
+    assert_eq!(t_cache.consumers, Some(consumers.len()));
+    assert_ne!(consumers[0], consumers[1]);
+    assert!(t_caches(&unshared).is_empty());
+
+    // both of them are matched (the 1st one of the three apart from the others)
+    let storage_adapter = Arc::new(SqliteStorageAdapter::in_memory_test_only(sql));
+    let exec = |plan_data: PlanData| {
+      let mut engine = ExecEngine::new(Arc::new(plan_data), storage_adapter.clone());
+      async move {
+        let results = engine.exec().await.unwrap();
+        bound_vars(&results, &["v1", "v2", "v3"])
       }
+    };
+    let (shared_results, unshared_results) = (exec(shared).await, exec(unshared).await);
+    assert_eq!(shared_results.len(), 3 * 2 * 2);
+    // the shared bucket is mirrored onto its 2nd consumer without losing / adding any match
+    assert_eq!(shared_results, unshared_results);
+  }
 
-      // add current_layer to layers
-      if !current_layer.is_empty() {
-        layers.push(current_layer);
+  #[tokio::test]
+  async fn test_exec_with_symmetry() {
+    let storage_adapter = Arc::new(SqliteStorageAdapter::in_memory_test_only(KNOWS_SQL));
+    // the data vertices each match binds (to the pattern vertices in order)
+    let matches_of = |query: &str, mode: SymmetryMode| {
+      let plan_data = generate_plan_with_symmetry_from_query(query, QueryFormat::Cypher, mode);
+      let storage_adapter = storage_adapter.clone();
+      async move {
+        let results = ExecEngine::new(Arc::new(plan_data.unwrap()), storage_adapter)
+          .exec()
+          .await
+          .unwrap();
+        (results.iter())
+          .map(|g| {
+            (g.pattern_2_vids.iter())
+              .sorted_by_key(|(pattern, _)| *pattern)
+              .map(|(_, vids)| vids.iter().exactly_one().unwrap().as_str())
+              .collect::<String>()
+          })
+          .sorted()
+          .collect_vec()
       }
-    }
+    };
+    let subgraphs_of = |matches: &[String]| {
+      (matches.iter())
+        .map(|vids| vids.chars().sorted().collect::<String>())
+        .collect_vec()
+    };
 
-    // check for cycles
-    if layers.iter().map(|layer| layer.len()).sum::<usize>() < n {
-      eprintln!("⚠️  The plan contains a cycle. Fallback to sequential execution.");
-      return None;
-    }
+    // `a`, `b` and `c` know each other
+    let triangle =
+      "MATCH (p: Person)-[:knows]-(q: Person)-[:knows]-(r: Person)-[:knows]-(p) RETURN p";
+    let distinct_subgraphs = matches_of(triangle, SymmetryMode::DistinctSubgraphs).await;
+    assert_eq!(subgraphs_of(&distinct_subgraphs), ["abc"]);
+    let all_embeddings = matches_of(triangle, SymmetryMode::AllEmbeddings).await;
+    assert_eq!(all_embeddings, ["abc", "acb", "bac", "bca", "cab", "cba"]);
+
+    // a pair of persons who know each other, one way or the other
+    let pair = "MATCH (p: Person)-[k:knows]-(q: Person) RETURN p";
+    let distinct_subgraphs = matches_of(pair, SymmetryMode::DistinctSubgraphs).await;
+    assert_eq!(subgraphs_of(&distinct_subgraphs), ["ab", "ac", "bc"]);
+    let all_embeddings = matches_of(pair, SymmetryMode::AllEmbeddings).await;
+    assert_eq!(all_embeddings, ["ab", "ac", "ba", "bc", "ca", "cb"]);
+  }
 
-    Some(layers)
+  #[tokio::test]
+  async fn test_profile() {
+    let query = "MATCH (p: Person)-[:knows]->(q: Person) WHERE p.id = 1 RETURN q";
+    let plan_data = generate_optimal_plan_from_query(query, QueryFormat::Cypher).unwrap();
+    let storage_adapter = Arc::new(SqliteStorageAdapter::in_memory_test_only(KNOWS_SQL));
+    let (results, profile) = ExecEngine::new(Arc::new(plan_data), storage_adapter)
+      .profile()
+      .await
+      .unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(profile.final_merge().rows_out, 2);
+
+    let instrs = profile.instructions();
+    let rows_of = |type_| {
+      (instrs.iter())
+        .filter(|instr| instr.type_ == type_)
+        .map(|instr| (instr.rows_in(), instr.rows_out))
+        .collect_vec()
+    };
+    // `a` alone, then its two friends
+    assert_eq!(rows_of(InstructionType::Init), [(0, 1)]);
+    assert_eq!(rows_of(InstructionType::Foreach), [(2, 2)]);
+    assert_eq!(rows_of(InstructionType::Report), [(2, 2)]);
+    // the person is loaded by `Init`, and the friends along with the edges by `GetAdj`
+    let init = &instrs[0];
+    assert_eq!((init.storage.calls, init.storage.rows), (1, 1));
+    let get_adj = &instrs[1];
+    assert_eq!(get_adj.type_, InstructionType::GetAdj);
+    assert_eq!((get_adj.rows_in(), get_adj.rows_out), (1, 2));
+    assert_eq!(get_adj.storage.rows, 2);
+
+    let explain = crate::planner::explain_query(query, QueryFormat::Cypher).unwrap();
+    let profile = profile.with_estimates(&explain);
+    assert!((profile.instructions().iter()).all(|instr| instr.est_output_card.is_some()));
+
+    // nothing to attach from another query, but to `Report` (on no vertex)
+    let other_query = "MATCH (a: Person)-[:knows]->(b: Person) WHERE a.id = 1 RETURN b";
+    let explain = crate::planner::explain_query(other_query, QueryFormat::Cypher).unwrap();
+    let profile = profile.with_estimates(&explain);
+    let estimated = (profile.instructions().iter())
+      .filter(|instr| instr.est_output_card.is_some())
+      .map(|instr| instr.type_)
+      .collect_vec();
+    assert_eq!(estimated, [InstructionType::Report]);
   }
 }
@@ -0,0 +1,163 @@
+use crate::{
+  planner::explain::Explain,
+  schemas::{InstructionType, Vid},
+  storage::StorageStats,
+};
+use itertools::Itertools;
+use polars::{frame::DataFrame, prelude::Column};
+use serde::Serialize;
+use std::fmt::Display;
+
+/// The partial matches in the bucket of a var (`f`, `A`, `C` or `T`).
+#[derive(Debug, Clone, Serialize)]
+pub struct BucketRows {
+  pub(crate) var: String,
+  pub(crate) rows: usize,
+}
+
+/// What an instruction actually did, see [`super::ExecEngine::profile`].
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfiledInstruction {
+  pub(crate) id: usize,
+  pub(crate) instruction: String,
+  #[serde(rename = "type")]
+  pub(crate) type_: InstructionType,
+  pub(crate) vid: Vid,
+  pub(crate) target_var: String,
+  pub(crate) elapsed_ms: f64,
+  /// the buckets of its operands, before it runs
+  pub(crate) buckets_in: Vec<BucketRows>,
+  /// the partial matches of its target bucket (or of the ones it updates in place) once it's done,
+  /// and those of all the groups for `Report`
+  pub(crate) rows_out: usize,
+  /// the loads it issued to the storage
+  pub(crate) storage: StorageStats,
+  /// the estimated matches it consumes (see [`Explain`]), if they're attached
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub(crate) est_input_card: Option<f64>,
+  /// the estimated matches it yields (see [`Explain`]), if they're attached
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub(crate) est_output_card: Option<f64>,
+}
+
+impl ProfiledInstruction {
+  pub fn rows_in(&self) -> usize {
+    self.buckets_in.iter().map(|bucket| bucket.rows).sum()
+  }
+}
+
+/// The final merge of the groups of partial matches (one for each connected part of the pattern).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProfiledMerge {
+  pub(crate) elapsed_ms: f64,
+  /// the partial matches of each (non-empty) group
+  pub(crate) group_rows: Vec<usize>,
+  pub(crate) rows_out: usize,
+}
+
+/// The actual row counts, timings and storage loads of an execution, instruction by instruction.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Profile {
+  pub(crate) matching_order: Vec<Vid>,
+  pub(crate) instructions: Vec<ProfiledInstruction>,
+  pub(crate) final_merge: ProfiledMerge,
+  pub(crate) elapsed_ms: f64,
+}
+
+impl Profile {
+  /// Attach the estimates of the planner (for the same query), each to the instruction of the same
+  /// type, vertex and target var, where those left without one have no estimate.
+  pub fn with_estimates(mut self, explain: &Explain) -> Self {
+    let mut unmatched = explain.instructions().iter().collect_vec();
+    for profiled in self.instructions.iter_mut() {
+      let matched = unmatched.iter().position(|explained| {
+        explained.type_ == profiled.type_
+          && explained.target_var == profiled.target_var
+          && explained.vid == profiled.vid
+      });
+      let explained = matched.map(|pos| unmatched.remove(pos));
+      profiled.est_input_card = explained.and_then(|explained| explained.input_card);
+      profiled.est_output_card = explained.map(|explained| explained.output_card);
+    }
+    self
+  }
+
+  pub fn instructions(&self) -> &[ProfiledInstruction] {
+    &self.instructions
+  }
+
+  pub fn final_merge(&self) -> &ProfiledMerge {
+    &self.final_merge
+  }
+
+  pub fn to_json(&self) -> String {
+    serde_json::to_string_pretty(self).unwrap()
+  }
+
+  /// The instructions, with the rows in / out (and the estimates, if attached),
+  /// the time and the storage loads of each one.
+  pub fn to_df(&self) -> DataFrame {
+    let instrs = &self.instructions;
+    let mut columns = vec![
+      Column::new(
+        "Id".into(),
+        instrs.iter().map(|i| i.id as u64).collect_vec(),
+      ),
+      Column::new(
+        "Instructions".into(),
+        instrs.iter().map(|i| i.instruction.clone()).collect_vec(),
+      ),
+      Column::new(
+        "Time(ms)".into(),
+        instrs.iter().map(|i| i.elapsed_ms).collect_vec(),
+      ),
+      Column::new(
+        "Rows".into(),
+        (instrs.iter())
+          .map(|i| format!("{} -> {}", i.rows_in(), i.rows_out))
+          .collect_vec(),
+      ),
+    ];
+    if instrs.iter().any(|i| i.est_output_card.is_some()) {
+      let est_rows = instrs
+        .iter()
+        .map(|i| match (i.est_input_card, i.est_output_card) {
+          (Some(input), Some(output)) => Some(format!("{input:.1} -> {output:.1}")),
+          (None, Some(output)) => Some(format!("-> {output:.1}")),
+          _ => None,
+        });
+      columns.push(Column::new("EstRows".into(), est_rows.collect_vec()));
+    }
+    columns.extend([
+      Column::new(
+        "Calls".into(),
+        instrs.iter().map(|i| i.storage.calls as u64).collect_vec(),
+      ),
+      Column::new(
+        "LoadedRows".into(),
+        instrs.iter().map(|i| i.storage.rows as u64).collect_vec(),
+      ),
+      Column::new(
+        "CacheHits".into(),
+        instrs
+          .iter()
+          .map(|i| i.storage.cache_hits as u64)
+          .collect_vec(),
+      ),
+    ]);
+    DataFrame::new(columns).unwrap()
+  }
+}
+
+impl Display for Profile {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    writeln!(f, "🔍  Profile(Execution)")?;
+    writeln!(f, "{}", self.to_df())?;
+    writeln!(
+      f,
+      "✨  Final merge of {:?} -> {} results in {:.2} ms",
+      self.final_merge.group_rows, self.final_merge.rows_out, self.final_merge.elapsed_ms
+    )?;
+    writeln!(f, "✨  Total: {:.2} ms", self.elapsed_ms)
+  }
+}
@@ -0,0 +1,35 @@
+use super::ExecEngine;
+use crate::{
+  planner::{QueryFormat, generate_optimal_plan_from_query},
+  schemas::PlanData,
+  storage::SqliteStorageAdapter,
+  utils::dyn_graph::DynGraph,
+};
+use itertools::Itertools;
+use std::sync::Arc;
+
+/// The plan of `cypher` (as the planner's callers get it), and its matches on the data of
+/// `init_sql`.
+pub(crate) async fn exec_cypher(cypher: &str, init_sql: &str) -> (PlanData, Vec<DynGraph>) {
+  let plan_data = generate_optimal_plan_from_query(cypher, QueryFormat::Cypher).unwrap();
+  let storage_adapter = Arc::new(SqliteStorageAdapter::in_memory_test_only(init_sql));
+  let mut engine = ExecEngine::new(Arc::new(plan_data.clone()), storage_adapter);
+  let results = engine.exec().await.unwrap();
+  (plan_data, results)
+}
+
+/// The data vids bound to `vars` in each match (`"null"` for a var of an optional sub-pattern
+/// left unbound), sorted.
+pub(crate) fn bound_vars(results: &[DynGraph], vars: &[&str]) -> Vec<Vec<String>> {
+  (results.iter())
+    .map(|g| {
+      (vars.iter())
+        .map(|var| match g.pattern_2_vids.get(*var) {
+          Some(vids) => vids.iter().exactly_one().unwrap().clone(),
+          None => "null".to_string(),
+        })
+        .collect_vec()
+    })
+    .sorted()
+    .collect()
+}
@@ -1,10 +1,14 @@
 #![feature(duration_millis_float)]
 
-use crate::planner::generate_optimal_plan;
+use crate::planner::generate_optimal_plan_for;
 use colored::Colorize;
 use hashbrown::HashMap;
-use planner::generate_plan_with_given_order;
-use std::{path::PathBuf, sync::LazyLock};
+use planner::{QueryFormat, generate_plan_with_given_order_for, parse_query_file};
+use schemas::GraphSchema;
+use std::{
+  path::{Path, PathBuf},
+  sync::LazyLock,
+};
 use tokio::io;
 
 pub mod demos;
@@ -19,6 +23,7 @@ pub mod utils;
 
 pub use executor::ExecEngine;
 pub use parser::PatternParser;
+pub use planner::pattern::Pattern;
 pub use schemas::PlanData;
 
 #[cfg(feature = "enable_log")]
@@ -103,6 +108,19 @@ static PLANS: LazyLock<PathBuf> = LazyLock::new(|| {
   res
 });
 
+/// What the statistics know about the data graph.
+static SCHEMA: LazyLock<GraphSchema> = LazyLock::new(GraphSchema::from_default_statistics);
+
+/// Warn about whatever in the (parsed) query can't match the data graph, before planning it.
+fn warn_schema_issues(query_path: &Path, pattern: &Pattern) {
+  if let Err(e) = pattern.validate(&SCHEMA) {
+    eprintln!(
+      "⚠️  Query '{}' may match nothing, {e}",
+      query_path.to_str().unwrap().yellow()
+    );
+  }
+}
+
 pub async fn plan_gen_with_given_orders() -> io::Result<()> {
   let queries = QUERIES.clone();
   let plans = PLANS.clone().join("neo4j_ordered");
@@ -157,7 +175,8 @@ pub async fn plan_gen_with_given_orders() -> io::Result<()> {
       );
       continue;
     }
-    if path.extension().unwrap_or_default() != "txt" {
+    let extension = path.extension().and_then(|ext| ext.to_str());
+    if extension.and_then(QueryFormat::of_extension).is_none() {
       continue;
     }
 
@@ -176,7 +195,20 @@ pub async fn plan_gen_with_given_orders() -> io::Result<()> {
     );
 
     let handle = tokio::spawn(async move {
-      let plan_data = generate_plan_with_given_order(&path, &given_order);
+      let plan_data = parse_query_file(&path).and_then(|pattern| {
+        warn_schema_issues(&path, &pattern);
+        Ok(generate_plan_with_given_order_for(pattern, &given_order)?)
+      });
+      let plan_data = match plan_data {
+        Ok(plan_data) => plan_data,
+        Err(e) => {
+          eprintln!(
+            "❌  Failed to plan query '{}':\n{e}",
+            path.to_str().unwrap().red()
+          );
+          return;
+        }
+      };
       let plan_json = serde_json::to_string_pretty(&plan_data).unwrap();
       let filepath = plans.join(format!("{filename}.json"));
 
@@ -223,7 +255,8 @@ pub async fn plan_gen() -> io::Result<()> {
       );
       continue;
     }
-    if path.extension().unwrap_or_default() != "txt" {
+    let extension = path.extension().and_then(|ext| ext.to_str());
+    if extension.and_then(QueryFormat::of_extension).is_none() {
       continue;
     }
 
@@ -236,7 +269,20 @@ pub async fn plan_gen() -> io::Result<()> {
     );
 
     let handle = tokio::spawn(async move {
-      let plan_data = generate_optimal_plan(&path);
+      let plan_data = parse_query_file(&path).and_then(|pattern| {
+        warn_schema_issues(&path, &pattern);
+        Ok(generate_optimal_plan_for(pattern)?)
+      });
+      let plan_data = match plan_data {
+        Ok(plan_data) => plan_data,
+        Err(e) => {
+          eprintln!(
+            "❌  Failed to plan query '{}':\n{e}",
+            path.to_str().unwrap().red()
+          );
+          return;
+        }
+      };
       let plan_json = serde_json::to_string_pretty(&plan_data).unwrap();
       let filepath = plans.join(format!("{filename}.json"));
 
@@ -1,6 +1,24 @@
+use clap::{Parser, Subcommand};
 use colored::Colorize;
 use dotenv::dotenv;
-use ember_graph::utils::parallel;
+use ember_graph::{
+  planner::{
+    explain,
+    export::{GraphFormat, export_instructions, export_pattern},
+    generate_optimal_plan,
+    statistics::{STATISTICS_DIR, Statistics},
+  },
+  schemas::PlanData,
+  storage::{
+    AsyncDefault, Neo4jStorageAdapter, SchemaStorageAdapter, SqliteStorageAdapter,
+    StatisticsCollector,
+  },
+  utils::parallel,
+};
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
 use tokio::io;
 
 #[cfg(unix)]
@@ -11,6 +29,129 @@ static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
+/// Without a subcommand, plans are generated for the queries in `resources/queries`.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+  #[command(subcommand)]
+  command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+  /// Collect the statistics the planner reads, by scanning a storage.
+  Statistics {
+    /// Storage adapter type to scan ("sqlite" or "neo4j").
+    #[arg(long, default_value = "sqlite")]
+    storage: String,
+
+    /// Directory to write the statistics into (`resources/statistics` by default).
+    #[arg(short, long)]
+    output_dir: Option<PathBuf>,
+
+    /// Only scan the labels that are new to the existing statistics or whose counts changed
+    /// since (and those in `--labels`).
+    #[arg(long, default_value_t = false)]
+    refresh: bool,
+
+    /// Vertex / edge labels to scan again (comma-separated), which implies `--refresh`.
+    #[arg(long, value_delimiter = ',')]
+    labels: Vec<String>,
+  },
+  /// Explain the plan of a query without executing it, i.e. the estimated cardinality,
+  /// the pushdowns and the dependency layer of each instruction, and how the order is chosen.
+  Explain {
+    /// Query file (`*.cypher`, `*.json`, or the count-prefixed format otherwise).
+    query: PathBuf,
+
+    /// Print as JSON instead of tables.
+    #[arg(long, default_value_t = false)]
+    json: bool,
+  },
+  /// Export a plan to Graphviz (DOT) or Mermaid, either as its instructions
+  /// (grouped by dependency layer) or as its pattern graph (numbered by the matching order).
+  Export {
+    /// Plan file (`resources/plan/*.json`), or a query file along with `--query`.
+    plan: PathBuf,
+
+    /// Plan the query in the given file first.
+    #[arg(long, default_value_t = false)]
+    query: bool,
+
+    /// Graph to export ("instructions" or "pattern").
+    #[arg(long, default_value = "instructions")]
+    graph: String,
+
+    /// Output format ("dot" or "mermaid").
+    #[arg(long, default_value = "dot")]
+    format: String,
+
+    /// File to write into, instead of printing.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+  },
+}
+
+async fn collect_statistics(
+  storage: impl SchemaStorageAdapter,
+  output_dir: &Path,
+  refresh: bool,
+  labels: Vec<String>,
+) -> io::Result<()> {
+  let mut collector = StatisticsCollector::new(storage);
+  if refresh || !labels.is_empty() {
+    let previous = Statistics::from_dir(output_dir)?;
+    collector = collector.with_previous(previous).with_labels(labels);
+  }
+
+  let Some(statistics) = collector.collect().await else {
+    return Err(io::Error::other(
+      "❌  Failed to load the labels of the storage",
+    ));
+  };
+  statistics.dump_to_dir(output_dir)?;
+
+  println!(
+    "✅  Statistics saved to {}\n",
+    output_dir.display().to_string().green()
+  );
+  Ok(())
+}
+
+fn export_plan(
+  plan: &Path,
+  query: bool,
+  graph: &str,
+  format: &str,
+  output: Option<PathBuf>,
+) -> io::Result<()> {
+  let invalid_input = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+
+  let Some(format) = GraphFormat::of_name(format) else {
+    return Err(invalid_input(format!("Invalid format: {format}")));
+  };
+  let export = match graph.to_lowercase().as_str() {
+    "instructions" => export_instructions,
+    "pattern" => export_pattern,
+    _ => return Err(invalid_input(format!("Invalid graph: {graph}"))),
+  };
+  let plan_data = match query {
+    true => generate_optimal_plan(plan).map_err(|e| invalid_input(e.to_string()))?,
+    false => serde_json::from_str::<PlanData>(&fs::read_to_string(plan)?)
+      .map_err(|e| invalid_input(format!("Invalid plan: {e}")))?,
+  };
+
+  let exported = export(&plan_data, format);
+  match output {
+    Some(output) => {
+      fs::write(&output, exported)?;
+      println!("✅  Exported to {}\n", output.display().to_string().green());
+    }
+    None => print!("{exported}"),
+  }
+  Ok(())
+}
+
 async fn to_run() -> io::Result<()> {
   dotenv().ok();
 
@@ -20,6 +161,47 @@ async fn to_run() -> io::Result<()> {
   #[allow(unused_variables)]
   let guard = ember_graph::init_log::init_log().await?;
 
+  match Cli::parse().command {
+    Some(Command::Statistics {
+      storage,
+      output_dir,
+      refresh,
+      labels,
+    }) => {
+      let output_dir = output_dir.unwrap_or_else(|| STATISTICS_DIR.clone());
+      return match storage.to_lowercase().as_str() {
+        "sqlite" => {
+          let storage = SqliteStorageAdapter::async_default().await;
+          collect_statistics(storage, &output_dir, refresh, labels).await
+        }
+        "neo4j" => {
+          let storage = Neo4jStorageAdapter::async_default().await;
+          collect_statistics(storage, &output_dir, refresh, labels).await
+        }
+        _ => Err(io::Error::new(
+          io::ErrorKind::InvalidInput,
+          format!("Invalid storage type: {storage}"),
+        )),
+      };
+    }
+    Some(Command::Explain { query, json }) => {
+      let explain = explain(&query).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+      match json {
+        true => println!("{}", explain.to_json()),
+        false => println!("{explain}"),
+      }
+      return Ok(());
+    }
+    Some(Command::Export {
+      plan,
+      query,
+      graph,
+      format,
+      output,
+    }) => return export_plan(&plan, query, &graph, &format, output),
+    None => {}
+  }
+
   ember_graph::plan_gen().await?;
   ember_graph::plan_gen_with_given_orders().await?;
 
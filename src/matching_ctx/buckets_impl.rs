@@ -1,7 +1,8 @@
 use super::buckets::{ABucket, CBucket, FBucket, TBucket};
 use crate::{
   schemas::{
-    DataEdge, DataVertex, EBase, LabelRef, PatternAttr, PatternEdge, PatternVertex, Vid, VidRef,
+    AttrCmpMode, CrossPredicate, DataEdge, DataVertex, EBase, EdgeDirection, LabelRef, PatternEdge,
+    PatternPredicate, PatternVertex, Vid, VidRef,
   },
   storage::AdvancedStorageAdapter,
   utils::{
@@ -11,14 +12,19 @@ use crate::{
   },
 };
 use colored::Colorize;
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
 use std::sync::Arc;
 
 mod a_bucket_impl;
+mod a_bucket_path_impl;
 mod c_bucket_impl;
 mod f_bucket_impl;
+mod f_bucket_sub_pattern_impl;
 mod t_bucket_impl;
 
 #[allow(unused_imports)]
-pub use {a_bucket_impl::*, c_bucket_impl::*, f_bucket_impl::*, t_bucket_impl::*};
+pub use {
+  a_bucket_impl::*, a_bucket_path_impl::*, c_bucket_impl::*, f_bucket_impl::*,
+  f_bucket_sub_pattern_impl::*, t_bucket_impl::*,
+};
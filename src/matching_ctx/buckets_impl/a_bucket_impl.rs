@@ -20,6 +20,7 @@ impl ABucket {
     pattern_es: Vec<PatternEdge>,
     pattern_vs: HashMap<Vid, PatternVertex>,
     storage_adapter: Arc<impl AdvancedStorageAdapter + 'static>,
+    mode: AttrCmpMode,
   ) {
     let curr_pat_vid: Arc<str> = self.curr_pat_vid.as_str().into();
     let pattern_es = Arc::new(pattern_es);
@@ -69,7 +70,7 @@ impl ABucket {
             );
 
             let e_label = pat_e.label();
-            let e_attr = pat_e.attr.as_ref();
+            let e_attrs = pat_e.attrs.as_slice();
             let mut next_vid_grouped_conn_es = HashMap::new();
             let mut next_vid_grouped_conn_pat_strs = HashMap::new();
 
@@ -77,17 +78,19 @@ impl ABucket {
               if curr_pat_vid.as_ref() == pat_e.src_vid() {
                 let next_pat_vid = pat_e.dst_vid();
                 let next_v_label = pattern_vs.get(next_pat_vid).unwrap().label.as_str();
-                let next_v_attr = pattern_vs.get(next_pat_vid).unwrap().attr.as_ref();
+                let next_v_attrs = pattern_vs.get(next_pat_vid).unwrap().attrs.as_slice();
 
                 let matched_data_es = incremental_match_adj_e(LoadWithCondCtx {
                   storage_adapter: storage_adapter.as_ref(),
                   curr_matched_dg: &matched_dg,
                   frontier_vid: frontier_vid.as_str(),
                   e_label,
-                  e_attr,
+                  e_attrs,
                   next_v_label,
-                  next_v_attr,
+                  next_v_attrs,
                   is_src_curr_pat: true,
+                  direction: pat_e.direction,
+                  mode,
                 })
                 .await;
 
@@ -106,11 +109,11 @@ impl ABucket {
                 // group by: next data_vertex
                 for e in matched_data_es {
                   next_vid_grouped_conn_pat_strs
-                    .entry(e.dst_vid().to_string())
+                    .entry(e.opposite_vid(frontier_vid).to_string())
                     .or_insert_with(Vec::new)
                     .push(pat_e.eid().to_string());
                   next_vid_grouped_conn_es
-                    .entry(e.dst_vid().to_string())
+                    .entry(e.opposite_vid(frontier_vid).to_string())
                     .or_insert_with(Vec::new)
                     .push(e);
                 }
@@ -119,17 +122,19 @@ impl ABucket {
               } else {
                 let next_pat_vid = pat_e.src_vid();
                 let next_v_label = pattern_vs.get(next_pat_vid).unwrap().label.as_str();
-                let next_v_attr = pattern_vs.get(next_pat_vid).unwrap().attr.as_ref();
+                let next_v_attrs = pattern_vs.get(next_pat_vid).unwrap().attrs.as_slice();
 
                 let matched_data_es = incremental_match_adj_e(LoadWithCondCtx {
                   storage_adapter: storage_adapter.as_ref(),
                   curr_matched_dg: &matched_dg,
                   frontier_vid: frontier_vid.as_str(),
                   e_label,
-                  e_attr,
+                  e_attrs,
                   next_v_label,
-                  next_v_attr,
+                  next_v_attrs,
                   is_src_curr_pat: false,
+                  direction: pat_e.direction,
+                  mode,
                 })
                 .await;
 
@@ -148,11 +153,11 @@ impl ABucket {
                 // group by: next data_vertex
                 for e in matched_data_es {
                   next_vid_grouped_conn_pat_strs
-                    .entry(e.src_vid().to_string())
+                    .entry(e.opposite_vid(frontier_vid).to_string())
                     .or_insert_with(Vec::new)
                     .push(pat_e.eid().to_string());
                   next_vid_grouped_conn_es
-                    .entry(e.src_vid().to_string())
+                    .entry(e.opposite_vid(frontier_vid).to_string())
                     .or_insert_with(Vec::new)
                     .push(e);
                 }
@@ -244,6 +249,7 @@ impl ABucket {
     pattern_es: Vec<PatternEdge>,
     pattern_vs: HashMap<Vid, PatternVertex>,
     storage_adapter: Arc<impl AdvancedStorageAdapter + 'static>,
+    mode: AttrCmpMode,
   ) {
     let curr_pat_vid: Arc<str> = self.curr_pat_vid.as_str().into();
     let pattern_es = Arc::new(pattern_es);
@@ -304,7 +310,7 @@ impl ABucket {
               );
 
               let e_label = pat_e.label();
-              let e_attr = pat_e.attr.as_ref();
+              let e_attrs = pat_e.attrs.as_slice();
               let mut next_vid_grouped_conn_es = HashMap::new();
               let mut next_vid_grouped_conn_pat_strs = HashMap::new();
 
@@ -312,17 +318,19 @@ impl ABucket {
                 if curr_pat_vid.as_ref() == pat_e.src_vid() {
                   let next_pat_vid = pat_e.dst_vid();
                   let next_v_label = pattern_vs.get(next_pat_vid).unwrap().label.as_str();
-                  let next_v_attr = pattern_vs.get(next_pat_vid).unwrap().attr.as_ref();
+                  let next_v_attrs = pattern_vs.get(next_pat_vid).unwrap().attrs.as_slice();
 
                   let matched_data_es = incremental_match_adj_e(LoadWithCondCtx {
                     storage_adapter: storage_adapter.as_ref(),
                     curr_matched_dg: &matched_dg,
                     frontier_vid: frontier_vid.as_str(),
                     e_label,
-                    e_attr,
+                    e_attrs,
                     next_v_label,
-                    next_v_attr,
+                    next_v_attrs,
                     is_src_curr_pat: true,
+                    direction: pat_e.direction,
+                    mode,
                   })
                   .await;
 
@@ -341,11 +349,11 @@ impl ABucket {
                   // group by: next data_vertex
                   for e in matched_data_es {
                     next_vid_grouped_conn_pat_strs
-                      .entry(e.dst_vid().to_string())
+                      .entry(e.opposite_vid(frontier_vid).to_string())
                       .or_insert_with(Vec::new)
                       .push(pat_e.eid().to_string());
                     next_vid_grouped_conn_es
-                      .entry(e.dst_vid().to_string())
+                      .entry(e.opposite_vid(frontier_vid).to_string())
                       .or_insert_with(Vec::new)
                       .push(e);
                   }
@@ -354,17 +362,19 @@ impl ABucket {
                 } else {
                   let next_pat_vid = pat_e.src_vid();
                   let next_v_label = pattern_vs.get(next_pat_vid).unwrap().label.as_str();
-                  let next_v_attr = pattern_vs.get(next_pat_vid).unwrap().attr.as_ref();
+                  let next_v_attrs = pattern_vs.get(next_pat_vid).unwrap().attrs.as_slice();
 
                   let matched_data_es = incremental_match_adj_e(LoadWithCondCtx {
                     storage_adapter: storage_adapter.as_ref(),
                     curr_matched_dg: &matched_dg,
                     frontier_vid: frontier_vid.as_str(),
                     e_label,
-                    e_attr,
+                    e_attrs,
                     next_v_label,
-                    next_v_attr,
+                    next_v_attrs,
                     is_src_curr_pat: false,
+                    direction: pat_e.direction,
+                    mode,
                   })
                   .await;
 
@@ -383,11 +393,11 @@ impl ABucket {
                   // group by: next data_vertex
                   for e in matched_data_es {
                     next_vid_grouped_conn_pat_strs
-                      .entry(e.src_vid().to_string())
+                      .entry(e.opposite_vid(frontier_vid).to_string())
                       .or_insert_with(Vec::new)
                       .push(pat_e.eid().to_string());
                     next_vid_grouped_conn_es
-                      .entry(e.src_vid().to_string())
+                      .entry(e.opposite_vid(frontier_vid).to_string())
                       .or_insert_with(Vec::new)
                       .push(e);
                   }
@@ -481,6 +491,7 @@ impl ABucket {
     pattern_es: Vec<PatternEdge>,
     pattern_vs: HashMap<Vid, PatternVertex>,
     storage_adapter: Arc<impl AdvancedStorageAdapter + 'static>,
+    mode: AttrCmpMode,
   ) {
     let curr_pat_vid: Arc<str> = self.curr_pat_vid.as_str().into();
     let pattern_es = pattern_es.into_iter().map(Arc::new).collect_vec();
@@ -516,7 +527,7 @@ impl ABucket {
         let task_handle = tokio::spawn(async move {
           // extract the basic info of current pattern_edge (only extract once)
           let e_label = pat_e.label();
-          let e_attr = pat_e.attr.as_ref();
+          let e_attrs = pat_e.attrs.as_slice();
           let is_src_curr_pat = curr_pat_vid.as_ref() == pat_e.src_vid();
           let next_pat_vid = if is_src_curr_pat {
             pat_e.dst_vid()
@@ -524,7 +535,7 @@ impl ABucket {
             pat_e.src_vid()
           };
           let next_v_label = pattern_vs.get(next_pat_vid).unwrap().label.as_str();
-          let next_v_attr = pattern_vs.get(next_pat_vid).unwrap().attr.as_ref();
+          let next_v_attrs = pattern_vs.get(next_pat_vid).unwrap().attrs.as_slice();
 
           #[cfg(feature = "trace_get_adj")]
           println!(
@@ -541,10 +552,12 @@ impl ABucket {
                 curr_matched_dg: &matched_dg,
                 frontier_vid: frontier_vid.as_str(),
                 e_label,
-                e_attr,
+                e_attrs,
                 next_v_label,
-                next_v_attr,
+                next_v_attrs,
                 is_src_curr_pat: true,
+                direction: pat_e.direction,
+                mode,
               })
               .await
             } else {
@@ -553,10 +566,12 @@ impl ABucket {
                 curr_matched_dg: &matched_dg,
                 frontier_vid: frontier_vid.as_str(),
                 e_label,
-                e_attr,
+                e_attrs,
                 next_v_label,
-                next_v_attr,
+                next_v_attrs,
                 is_src_curr_pat: false,
+                direction: pat_e.direction,
+                mode,
               })
               .await
             };
@@ -570,25 +585,14 @@ impl ABucket {
             let mut next_vid_grouped_conn_pat_strs = HashMap::new();
 
             for e in matched_data_es {
-              if is_src_curr_pat {
-                next_vid_grouped_conn_pat_strs
-                  .entry(e.dst_vid().to_string())
-                  .or_insert_with(Vec::new)
-                  .push(pat_e.eid().to_string());
-                next_vid_grouped_conn_es
-                  .entry(e.dst_vid().to_string())
-                  .or_insert_with(Vec::new)
-                  .push(e);
-              } else {
-                next_vid_grouped_conn_pat_strs
-                  .entry(e.src_vid().to_string())
-                  .or_insert_with(Vec::new)
-                  .push(pat_e.eid().to_string());
-                next_vid_grouped_conn_es
-                  .entry(e.src_vid().to_string())
-                  .or_insert_with(Vec::new)
-                  .push(e);
-              }
+              next_vid_grouped_conn_pat_strs
+                .entry(e.opposite_vid(frontier_vid).to_string())
+                .or_insert_with(Vec::new)
+                .push(pat_e.eid().to_string());
+              next_vid_grouped_conn_es
+                .entry(e.opposite_vid(frontier_vid).to_string())
+                .or_insert_with(Vec::new)
+                .push(e);
             }
 
             // build expanding_graph and send it to channel
@@ -658,6 +662,7 @@ impl ABucket {
     pattern_es: Vec<PatternEdge>,
     pattern_vs: HashMap<Vid, PatternVertex>,
     storage_adapter: Arc<impl AdvancedStorageAdapter + 'static>,
+    mode: AttrCmpMode,
   ) {
     let curr_pat_vid: Arc<str> = self.curr_pat_vid.as_str().into();
     let pattern_es = pattern_es.into_iter().map(Arc::new).collect_vec();
@@ -700,7 +705,7 @@ impl ABucket {
         let task_handle = tokio::spawn(async move {
           // extract the basic info of current pattern_edge (only extract once)
           let e_label = pat_e.label();
-          let e_attr = pat_e.attr.as_ref();
+          let e_attrs = pat_e.attrs.as_slice();
           let is_src_curr_pat = curr_pat_vid.as_ref() == pat_e.src_vid();
           let next_pat_vid = if is_src_curr_pat {
             pat_e.dst_vid()
@@ -708,7 +713,7 @@ impl ABucket {
             pat_e.src_vid()
           };
           let next_v_label = pattern_vs.get(next_pat_vid).unwrap().label.as_str();
-          let next_v_attr = pattern_vs.get(next_pat_vid).unwrap().attr.as_ref();
+          let next_v_attrs = pattern_vs.get(next_pat_vid).unwrap().attrs.as_slice();
 
           #[cfg(feature = "trace_get_adj")]
           println!(
@@ -727,10 +732,12 @@ impl ABucket {
                   curr_matched_dg: matched_dg,
                   frontier_vid: frontier_vid.as_str(),
                   e_label,
-                  e_attr,
+                  e_attrs,
                   next_v_label,
-                  next_v_attr,
+                  next_v_attrs,
                   is_src_curr_pat: true,
+                  direction: pat_e.direction,
+                  mode,
                 })
                 .await
               } else {
@@ -739,10 +746,12 @@ impl ABucket {
                   curr_matched_dg: matched_dg,
                   frontier_vid: frontier_vid.as_str(),
                   e_label,
-                  e_attr,
+                  e_attrs,
                   next_v_label,
-                  next_v_attr,
+                  next_v_attrs,
                   is_src_curr_pat: false,
+                  direction: pat_e.direction,
+                  mode,
                 })
                 .await
               };
@@ -756,25 +765,14 @@ impl ABucket {
               let mut next_vid_grouped_conn_pat_strs = HashMap::new();
 
               for e in matched_data_es {
-                if is_src_curr_pat {
-                  next_vid_grouped_conn_pat_strs
-                    .entry(e.dst_vid().to_string())
-                    .or_insert_with(Vec::new)
-                    .push(pat_e.eid().to_string());
-                  next_vid_grouped_conn_es
-                    .entry(e.dst_vid().to_string())
-                    .or_insert_with(Vec::new)
-                    .push(e);
-                } else {
-                  next_vid_grouped_conn_pat_strs
-                    .entry(e.src_vid().to_string())
-                    .or_insert_with(Vec::new)
-                    .push(pat_e.eid().to_string());
-                  next_vid_grouped_conn_es
-                    .entry(e.src_vid().to_string())
-                    .or_insert_with(Vec::new)
-                    .push(e);
-                }
+                next_vid_grouped_conn_pat_strs
+                  .entry(e.opposite_vid(frontier_vid).to_string())
+                  .or_insert_with(Vec::new)
+                  .push(pat_e.eid().to_string());
+                next_vid_grouped_conn_es
+                  .entry(e.opposite_vid(frontier_vid).to_string())
+                  .or_insert_with(Vec::new)
+                  .push(e);
               }
 
               // build expanding_graph and send it to channel
@@ -847,39 +845,56 @@ struct LoadWithCondCtx<'a, S: AdvancedStorageAdapter> {
   curr_matched_dg: &'a DynGraph,
   frontier_vid: VidRef<'a>,
   e_label: LabelRef<'a>,
-  e_attr: Option<&'a PatternAttr>,
+  e_attrs: &'a [PatternPredicate],
   next_v_label: LabelRef<'a>,
-  next_v_attr: Option<&'a PatternAttr>,
+  next_v_attrs: &'a [PatternPredicate],
   is_src_curr_pat: bool,
+  direction: EdgeDirection,
+  mode: AttrCmpMode,
 }
 
 async fn incremental_match_adj_e<'a, S: AdvancedStorageAdapter>(
   ctx: LoadWithCondCtx<'a, S>,
 ) -> Vec<DataEdge> {
-  // load all edges first
-  let loaded_edges = if ctx.is_src_curr_pat {
-    ctx
-      .storage_adapter
-      .load_e_with_src_and_dst_filter(
-        ctx.frontier_vid.as_ref(),
-        ctx.e_label.as_ref(),
-        ctx.e_attr,
-        ctx.next_v_label.as_ref(),
-        ctx.next_v_attr,
-      )
-      .await
-  } else {
-    ctx
-      .storage_adapter
-      .load_e_with_dst_and_src_filter(
-        ctx.frontier_vid.as_ref(),
-        ctx.e_label.as_ref(),
-        ctx.e_attr,
-        ctx.next_v_label.as_ref(),
-        ctx.next_v_attr,
-      )
-      .await
-  };
+  let (load_with_src, load_with_dst) = ctx.direction.load_with_src_or_dst(ctx.is_src_curr_pat);
+
+  // load all edges first (an undirected edge is looked up on both sides)
+  let mut loaded_edges = vec![];
+  if load_with_src {
+    loaded_edges.extend(
+      ctx
+        .storage_adapter
+        .load_e_with_src_and_dst_filter(
+          ctx.frontier_vid.as_ref(),
+          ctx.e_label.as_ref(),
+          ctx.e_attrs,
+          ctx.next_v_label.as_ref(),
+          ctx.next_v_attrs,
+          ctx.mode,
+        )
+        .await,
+    );
+  }
+  if load_with_dst {
+    loaded_edges.extend(
+      ctx
+        .storage_adapter
+        .load_e_with_dst_and_src_filter(
+          ctx.frontier_vid.as_ref(),
+          ctx.e_label.as_ref(),
+          ctx.e_attrs,
+          ctx.next_v_label.as_ref(),
+          ctx.next_v_attrs,
+          ctx.mode,
+        )
+        .await,
+    );
+  }
+  // a self-loop shows up on both sides
+  if load_with_src && load_with_dst {
+    let mut seen_eids = HashSet::new();
+    loaded_edges.retain(|e| seen_eids.insert(e.eid().to_string()));
+  }
 
   // filter out the edges that are already matched
   // NOTE: DO NOT block the task
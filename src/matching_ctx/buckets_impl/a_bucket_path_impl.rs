@@ -0,0 +1,243 @@
+use super::*;
+use crate::schemas::{DataEdge, PathMode};
+use itertools::Itertools;
+
+impl ABucket {
+  /// Expand the variable-length `pattern_es` hop by hop from the frontiers.
+  ///
+  /// Each matched path is bound as a single dangling edge (see [`DataEdge::from_hops`]),
+  /// so that the later `Intersect` treats it just like a plain edge.
+  pub async fn incremental_expand_paths(
+    &mut self,
+    pattern_es: Vec<PatternEdge>,
+    storage_adapter: Arc<impl AdvancedStorageAdapter + 'static>,
+    mode: AttrCmpMode,
+  ) {
+    let curr_pat_vid: Arc<str> = self.curr_pat_vid.as_str().into();
+    let pattern_es = Arc::new(pattern_es);
+
+    let matched_with_frontiers = self.matched_with_frontiers.drain().collect_vec();
+    let mut all_matched_data = Vec::with_capacity(self.all_matched.len());
+    all_matched_data.append(&mut self.all_matched);
+
+    let mut matched_graph_handles = Vec::with_capacity(matched_with_frontiers.len());
+
+    // iter: each `matched` data_graph pair
+    for (idx, frontiers) in matched_with_frontiers {
+      let curr_pat_vid = curr_pat_vid.clone();
+      let pattern_es = pattern_es.clone();
+      let storage_adapter = storage_adapter.clone();
+
+      // get the current matched data_graph
+      let matched_dg = Arc::new(all_matched_data[idx].take().unwrap());
+
+      let matched_graph_handle = tokio::spawn(async move {
+        let mut next_pat_expanding = vec![];
+
+        // iter: `frontier_vid` on current data_graph
+        for frontier_vid in frontiers.iter() {
+          let mut is_frontier_formalized = false;
+
+          // iter: `pattern_edges`
+          for pat_e in pattern_es.iter() {
+            let is_src_curr_pat = curr_pat_vid.as_ref() == pat_e.src_vid();
+            let next_pat_vid = if is_src_curr_pat {
+              pat_e.dst_vid()
+            } else {
+              pat_e.src_vid()
+            };
+
+            let matched_paths = incremental_match_paths(ExpandPathCtx {
+              storage_adapter: storage_adapter.as_ref(),
+              curr_matched_dg: &matched_dg,
+              frontier_vid: frontier_vid.as_str(),
+              pat_e,
+              is_src_curr_pat,
+              mode,
+            })
+            .await;
+
+            #[cfg(feature = "trace_get_adj")]
+            println!(
+              "\t\t    ✨  Found {} paths that match: ({}: {})-[{}]-({})",
+              matched_paths.len().to_string().yellow(),
+              frontier_vid.to_string().green(),
+              curr_pat_vid.as_ref().cyan(),
+              pat_e.eid().purple(),
+              next_pat_vid.cyan()
+            );
+
+            if matched_paths.is_empty() {
+              // no matched paths, just skip current `frontier_vid`
+              break;
+            }
+
+            is_frontier_formalized = true;
+
+            // each path holds a `expanding_graph`, even if it ends at the same vertex
+            for path in matched_paths {
+              let mut expanding_graph = ExpandGraph::from(matched_dg.clone());
+              expanding_graph.update_valid_dangling_edges([(&path, pat_e.eid())]);
+
+              #[cfg(feature = "use_sort_merge_join")]
+              expanding_graph.sort_key_after_update_dangling_edges();
+
+              next_pat_expanding.push((next_pat_vid.to_string(), expanding_graph));
+            }
+          }
+
+          if !is_frontier_formalized {
+            // current matched_dg is invalid, skip it
+            break;
+          }
+        }
+
+        next_pat_expanding
+      });
+
+      matched_graph_handles.push(matched_graph_handle);
+    }
+
+    for handle in matched_graph_handles {
+      match handle.await {
+        Ok(next_pat_expanding) => {
+          for (next_pat_vid, expanding_graph) in next_pat_expanding {
+            self
+              .next_pat_grouped_expanding
+              .entry(next_pat_vid)
+              .or_default()
+              .push(expanding_graph);
+          }
+        }
+        Err(e) => eprintln!("❌  Task failed: {e}"),
+      }
+    }
+
+    self.all_matched.clear();
+  }
+}
+
+struct ExpandPathCtx<'a, S: AdvancedStorageAdapter> {
+  storage_adapter: &'a S,
+  curr_matched_dg: &'a DynGraph,
+  frontier_vid: VidRef<'a>,
+  pat_e: &'a PatternEdge,
+  is_src_curr_pat: bool,
+  mode: AttrCmpMode,
+}
+
+/// All the paths of `min_hops..=max_hops` hops from `frontier_vid`,
+/// each of which is bound as a single edge in the pattern edge's direction.
+async fn incremental_match_paths<'a, S: AdvancedStorageAdapter>(
+  ctx: ExpandPathCtx<'a, S>,
+) -> Vec<DataEdge> {
+  let var_length = ctx.pat_e.var_length.unwrap();
+  let (load_with_src, load_with_dst) = ctx
+    .pat_e
+    .direction
+    .load_with_src_or_dst(ctx.is_src_curr_pat);
+
+  // the adjacent edges of each reached vertex (loaded only once)
+  let mut loaded_adj_es: HashMap<Vid, Arc<[DataEdge]>> = HashMap::new();
+  // (hops, vids) of the paths that may go further, starting from the frontier
+  let mut partial_paths = vec![(vec![], vec![ctx.frontier_vid.to_string()])];
+  let mut matched_paths = vec![];
+
+  for hop in 1..=var_length.max_hops {
+    let mut next_partial_paths = vec![];
+
+    for (hops, vids) in partial_paths {
+      let last_vid: &Vid = vids.last().unwrap();
+      if !loaded_adj_es.contains_key(last_vid) {
+        let adj_es = load_adj_es(&ctx, last_vid, load_with_src, load_with_dst).await;
+        loaded_adj_es.insert(last_vid.clone(), adj_es.into());
+      }
+
+      for e in loaded_adj_es[last_vid].iter() {
+        // the edges that are already matched can't be reused
+        if ctx.curr_matched_dg.has_eid(e.eid()) {
+          continue;
+        }
+        let next_vid = e.opposite_vid(last_vid);
+        let is_repeated = match var_length.mode {
+          PathMode::Trail => hops.iter().any(|hop: &DataEdge| hop.eid == e.eid),
+          PathMode::Walk => false,
+          PathMode::Acyclic => vids.iter().any(|vid| vid == next_vid),
+        };
+        if is_repeated {
+          continue;
+        }
+
+        let mut next_hops = hops.clone();
+        next_hops.push(e.clone());
+        let mut next_vids = vids.clone();
+        next_vids.push(next_vid.to_string());
+
+        if hop >= var_length.min_hops {
+          matched_paths.push(bind_path(&ctx, next_hops.clone(), next_vid));
+        }
+        if hop < var_length.max_hops {
+          next_partial_paths.push((next_hops, next_vids));
+        }
+      }
+    }
+
+    if next_partial_paths.is_empty() {
+      break;
+    }
+    partial_paths = next_partial_paths;
+  }
+
+  matched_paths
+}
+
+/// Load the edges of a single hop, filtered by the pattern edge's label and attrs.
+async fn load_adj_es<S: AdvancedStorageAdapter>(
+  ctx: &ExpandPathCtx<'_, S>,
+  vid: VidRef<'_>,
+  load_with_src: bool,
+  load_with_dst: bool,
+) -> Vec<DataEdge> {
+  let e_label = ctx.pat_e.label();
+  let e_attrs = ctx.pat_e.attrs.as_slice();
+
+  let mut loaded_edges = vec![];
+  if load_with_src {
+    loaded_edges.extend(
+      ctx
+        .storage_adapter
+        .load_e_with_src(vid, e_label, e_attrs, ctx.mode)
+        .await,
+    );
+  }
+  if load_with_dst {
+    loaded_edges.extend(
+      ctx
+        .storage_adapter
+        .load_e_with_dst(vid, e_label, e_attrs, ctx.mode)
+        .await,
+    );
+  }
+  // a self-loop shows up on both sides
+  if load_with_src && load_with_dst {
+    let mut seen_eids = HashSet::new();
+    loaded_edges.retain(|e| seen_eids.insert(e.eid().to_string()));
+  }
+
+  loaded_edges
+}
+
+fn bind_path<S: AdvancedStorageAdapter>(
+  ctx: &ExpandPathCtx<'_, S>,
+  mut hops: Vec<DataEdge>,
+  end_vid: VidRef,
+) -> DataEdge {
+  let label = ctx.pat_e.label().to_string();
+  let frontier_vid = ctx.frontier_vid.to_string();
+  if ctx.is_src_curr_pat {
+    DataEdge::from_hops(label, frontier_vid, end_vid.to_string(), hops)
+  } else {
+    hops.reverse();
+    DataEdge::from_hops(label, end_vid.to_string(), frontier_vid, hops)
+  }
+}
@@ -8,8 +8,9 @@ impl CBucket {
     a_group: Vec<ExpandGraph>,
     pattern_str: Arc<str>,
     expected_label: Arc<str>,
-    expected_attr: Option<Arc<PatternAttr>>,
+    expected_attrs: Arc<[PatternPredicate]>,
     storage_adapter: Arc<impl StorageAdapter + 'static>,
+    mode: AttrCmpMode,
   ) -> Self {
     let mut all_expanded = vec![];
     let mut expanded_with_frontiers = HashMap::new();
@@ -25,15 +26,16 @@ impl CBucket {
       let pattern_str = pattern_str.clone();
       let storage_adapter = storage_adapter.clone();
       let expected_label = expected_label.clone();
-      let expected_attr = expected_attr.clone();
+      let expected_attrs = expected_attrs.clone();
 
       tokio::spawn(async move {
         let valid_targets = expanding
           .lazy_intersect_valid_target_vertices(
             pattern_str.clone(),
             expected_label,
-            expected_attr,
+            expected_attrs,
             storage_adapter.clone(),
+            mode,
           )
           .await;
 
@@ -123,8 +125,9 @@ impl CBucket {
     t_bucket: TBucket,
     pattern_str: Arc<str>,
     expected_label: Arc<str>,
-    expected_attr: Option<Arc<PatternAttr>>,
+    expected_attrs: Arc<[PatternPredicate]>,
     storage_adapter: Arc<impl StorageAdapter + 'static>,
+    mode: AttrCmpMode,
   ) -> Self {
     let mut all_expanded = vec![];
     let mut expanded_with_frontiers = HashMap::new();
@@ -140,15 +143,16 @@ impl CBucket {
       let pattern_str = pattern_str.clone();
       let storage_adapter = storage_adapter.clone();
       let expected_label = expected_label.clone();
-      let expected_attr = expected_attr.clone();
+      let expected_attrs = expected_attrs.clone();
 
       tokio::spawn(async move {
         let valid_targets = expanding
           .lazy_intersect_valid_target_vertices(
             pattern_str.clone(),
             expected_label,
-            expected_attr,
+            expected_attrs,
             storage_adapter.clone(),
+            mode,
           )
           .await;
 
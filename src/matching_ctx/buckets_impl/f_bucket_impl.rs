@@ -9,11 +9,13 @@ impl FBucket {
     let mut expanded_with_frontiers = c_bucket.expanded_with_frontiers;
 
     for (idx, matched) in all_expanded.into_iter().enumerate() {
+      let frontiers = expanded_with_frontiers.remove(&idx).unwrap_or_default();
+      // no valid target vertex, i.e. a dead end that must not be reported
+      if frontiers.is_empty() {
+        continue;
+      }
+      matched_with_frontiers.insert(all_matched.len(), frontiers);
       all_matched.push(matched.into());
-      matched_with_frontiers
-        .entry(idx)
-        .or_insert_with(Vec::new)
-        .extend(expanded_with_frontiers.remove(&idx).unwrap_or_default());
     }
 
     Self {
@@ -21,4 +23,32 @@ impl FBucket {
       matched_with_frontiers,
     }
   }
+
+  /// Keep the frontiers that satisfy `preds`, where each one is a candidate of `pat_vid`,
+  /// and drop the matched graphs without any of them left.
+  pub fn retain_satisfying(
+    &mut self,
+    preds: &[CrossPredicate],
+    pat_vid: VidRef,
+    mode: AttrCmpMode,
+  ) {
+    if preds.is_empty() {
+      return;
+    }
+
+    let all_matched = std::mem::take(&mut self.all_matched);
+    let mut matched_with_frontiers = std::mem::take(&mut self.matched_with_frontiers);
+
+    for (idx, matched) in all_matched.into_iter().enumerate() {
+      let mut frontiers = matched_with_frontiers.remove(&idx).unwrap_or_default();
+      frontiers.retain(|frontier| matched.satisfies(preds, Some((pat_vid, frontier)), mode));
+      if frontiers.is_empty() {
+        continue;
+      }
+      self
+        .matched_with_frontiers
+        .insert(self.all_matched.len(), frontiers);
+      self.all_matched.push(matched);
+    }
+  }
 }
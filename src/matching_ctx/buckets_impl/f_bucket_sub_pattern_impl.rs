@@ -0,0 +1,304 @@
+use super::*;
+use crate::{schemas::Eid, utils::sub_pattern::SubPatternKind};
+use itertools::Itertools;
+
+impl FBucket {
+  /// Join the matched graphs with the sub-pattern of `pattern_es`,
+  /// starting at the data vertices bound to `anchors`:
+  /// - a negated one drops the graphs from which it can be matched (i.e. anti-join);
+  /// - an optional one extends each graph with every match of it,
+  ///   and keeps the graphs without any of them as they are (i.e. left-outer join).
+  ///
+  /// The graphs that don't hold all the `anchors` can't be decided here, so they are kept.
+  ///
+  /// If the `pattern_es` can't be reached from the `anchors`, it's left as it is,
+  /// and the reason is returned.
+  pub async fn join_sub_pattern(
+    &mut self,
+    kind: SubPatternKind,
+    pattern_es: Vec<PatternEdge>,
+    pattern_vs: Arc<HashMap<Vid, PatternVertex>>,
+    anchors: Vec<Vid>,
+    storage_adapter: Arc<impl AdvancedStorageAdapter + 'static>,
+    mode: AttrCmpMode,
+  ) -> Result<(), String> {
+    let ctx = SubPatternCtx::new(kind, pattern_es, pattern_vs, anchors, mode)?;
+    self.join_with(ctx, storage_adapter).await;
+    Ok(())
+  }
+
+  async fn join_with(
+    &mut self,
+    ctx: SubPatternCtx,
+    storage_adapter: Arc<impl AdvancedStorageAdapter + 'static>,
+  ) {
+    let ctx = Arc::new(ctx);
+
+    let mut handles = Vec::with_capacity(self.all_matched.len());
+    for matched_dg in self.all_matched.drain(..) {
+      let ctx = ctx.clone();
+      let storage_adapter = storage_adapter.clone();
+
+      handles.push(tokio::spawn(async move {
+        let bound = ctx
+          .anchors
+          .iter()
+          .map(|anchor| {
+            let data_vid = matched_dg.pattern_2_vids.get(anchor)?.iter().next()?;
+            Some((anchor.clone(), data_vid.clone()))
+          })
+          .collect::<Option<HashMap<_, _>>>();
+        let Some(bound) = bound else {
+          return vec![matched_dg];
+        };
+
+        match ctx.kind {
+          SubPatternKind::Negated => {
+            let embeddings =
+              match_sub_pattern(storage_adapter.as_ref(), &ctx, bound, None, true).await;
+            if embeddings.is_empty() {
+              vec![matched_dg]
+            } else {
+              vec![]
+            }
+          }
+          SubPatternKind::Optional => {
+            let embeddings = match_sub_pattern(
+              storage_adapter.as_ref(),
+              &ctx,
+              bound,
+              Some(&matched_dg),
+              false,
+            )
+            .await;
+            if embeddings.is_empty() {
+              return vec![matched_dg];
+            }
+            let mut extended = Vec::with_capacity(embeddings.len());
+            for embedding in embeddings {
+              extended.push(
+                embedding
+                  .extend(&matched_dg, &ctx, storage_adapter.as_ref())
+                  .await,
+              );
+            }
+            extended
+          }
+        }
+      }));
+    }
+
+    let mut matched_with_frontiers = std::mem::take(&mut self.matched_with_frontiers);
+    for (idx, handle) in handles.into_iter().enumerate() {
+      match handle.await {
+        Ok(joined) => {
+          let frontiers = matched_with_frontiers.remove(&idx);
+          for matched_dg in joined {
+            if let Some(frontiers) = frontiers.as_ref() {
+              self
+                .matched_with_frontiers
+                .insert(self.all_matched.len(), frontiers.clone());
+            }
+            self.all_matched.push(matched_dg);
+          }
+        }
+        Err(e) => eprintln!("❌  Task failed: {e}"),
+      }
+    }
+  }
+}
+
+struct SubPatternCtx {
+  kind: SubPatternKind,
+  steps: Vec<SubPatternStep>,
+  pattern_vs: Arc<HashMap<Vid, PatternVertex>>,
+  anchors: Vec<Vid>,
+  mode: AttrCmpMode,
+}
+
+/// Match `pat_e` from the (already bound) `from_vid`, which binds / checks `to_vid`.
+struct SubPatternStep {
+  pat_e: PatternEdge,
+  from_vid: Vid,
+  to_vid: Vid,
+}
+
+impl SubPatternCtx {
+  /// Each step starts from a bound vertex, where the edges closing a cycle go first,
+  /// otherwise the edges that can't be reached from the `anchors`.
+  fn new(
+    kind: SubPatternKind,
+    mut pattern_es: Vec<PatternEdge>,
+    pattern_vs: Arc<HashMap<Vid, PatternVertex>>,
+    anchors: Vec<Vid>,
+    mode: AttrCmpMode,
+  ) -> Result<Self, String> {
+    let mut bound_vids = anchors.iter().cloned().collect::<HashSet<_>>();
+    let mut steps = Vec::with_capacity(pattern_es.len());
+
+    while !pattern_es.is_empty() {
+      let is_bound = |vid: VidRef| bound_vids.contains(vid);
+      let idx = pattern_es
+        .iter()
+        .position(|e| is_bound(e.src_vid()) && is_bound(e.dst_vid()))
+        .or_else(|| {
+          pattern_es
+            .iter()
+            .position(|e| is_bound(e.src_vid()) || is_bound(e.dst_vid()))
+        });
+      let Some(idx) = idx else {
+        let eids = pattern_es
+          .iter()
+          .map(|e| format!("`{}`", e.eid()))
+          .join(", ");
+        return Err(format!(
+          "{kind} edges {eids} are not connected to the anchors {anchors:?}"
+        ));
+      };
+
+      let pat_e = pattern_es.remove(idx);
+      let (from_vid, to_vid) = if is_bound(pat_e.src_vid()) {
+        (pat_e.src_vid().to_string(), pat_e.dst_vid().to_string())
+      } else {
+        (pat_e.dst_vid().to_string(), pat_e.src_vid().to_string())
+      };
+      bound_vids.insert(to_vid.clone());
+      steps.push(SubPatternStep {
+        pat_e,
+        from_vid,
+        to_vid,
+      });
+    }
+
+    Ok(Self {
+      kind,
+      steps,
+      pattern_vs,
+      anchors,
+      mode,
+    })
+  }
+}
+
+/// A match of the sub-pattern: pattern_vid -> data_vid, and the (pattern_eid, data_edge) pairs.
+struct Embedding {
+  bound: HashMap<Vid, Vid>,
+  edges: Vec<(Eid, DataEdge)>,
+}
+
+impl Embedding {
+  async fn extend<S: AdvancedStorageAdapter>(
+    self,
+    matched_dg: &DynGraph,
+    ctx: &SubPatternCtx,
+    storage_adapter: &S,
+  ) -> DynGraph {
+    let mut extended = matched_dg.clone();
+    for (pat_vid, data_vid) in self.bound {
+      if ctx.anchors.contains(&pat_vid) {
+        continue;
+      }
+      if let Some(data_v) = storage_adapter.get_v(&data_vid).await {
+        extended.update_v(data_v, pat_vid);
+      }
+    }
+    for (pat_eid, data_e) in self.edges {
+      extended.update_e(data_e, pat_eid);
+    }
+    extended
+  }
+}
+
+/// All the matches of the sub-pattern (depth-first), or just the first one if `first_only`.
+///
+/// The vertices / edges of `matched_dg` (if any) can't be bound again,
+/// and neither can a data vertex be bound to two pattern vertices of the sub-pattern
+/// (the anchors included).
+async fn match_sub_pattern<S: AdvancedStorageAdapter>(
+  storage_adapter: &S,
+  ctx: &SubPatternCtx,
+  bound: HashMap<Vid, Vid>,
+  matched_dg: Option<&DynGraph>,
+  first_only: bool,
+) -> Vec<Embedding> {
+  let mut embeddings = vec![];
+  // (next step, pattern_vid -> data_vid, matched edges)
+  let mut stack = vec![(0, bound, vec![])];
+
+  while let Some((step_idx, bound, edges)) = stack.pop() {
+    let Some(step) = ctx.steps.get(step_idx) else {
+      embeddings.push(Embedding { bound, edges });
+      if first_only {
+        break;
+      }
+      continue;
+    };
+
+    let pat_e = &step.pat_e;
+    let next_pat_v = &ctx.pattern_vs[&step.to_vid];
+    let from_data_vid = bound[&step.from_vid].as_str();
+    let (load_with_src, load_with_dst) = pat_e
+      .direction
+      .load_with_src_or_dst(step.from_vid == pat_e.src_vid());
+
+    let mut loaded_edges = vec![];
+    if load_with_src {
+      loaded_edges.extend(
+        storage_adapter
+          .load_e_with_src_and_dst_filter(
+            from_data_vid,
+            pat_e.label(),
+            &pat_e.attrs,
+            &next_pat_v.label,
+            &next_pat_v.attrs,
+            ctx.mode,
+          )
+          .await,
+      );
+    }
+    if load_with_dst {
+      loaded_edges.extend(
+        storage_adapter
+          .load_e_with_dst_and_src_filter(
+            from_data_vid,
+            pat_e.label(),
+            &pat_e.attrs,
+            &next_pat_v.label,
+            &next_pat_v.attrs,
+            ctx.mode,
+          )
+          .await,
+      );
+    }
+
+    for e in loaded_edges.into_iter().unique_by(|e| e.eid().to_string()) {
+      let is_used = edges
+        .iter()
+        .any(|(_, used): &(Eid, DataEdge)| used.eid == e.eid)
+        || matched_dg.is_some_and(|dg| dg.has_eid(e.eid()));
+      if is_used {
+        continue;
+      }
+      let to_data_vid = e.opposite_vid(from_data_vid).to_string();
+      match bound.get(&step.to_vid) {
+        Some(data_vid) if *data_vid != to_data_vid => continue,
+        // a new vertex should be a new data vertex as well
+        None
+          if bound.values().contains(&to_data_vid)
+            || matched_dg.is_some_and(|dg| dg.has_vid(&to_data_vid)) =>
+        {
+          continue;
+        }
+        _ => {}
+      }
+
+      let mut next_bound = bound.clone();
+      next_bound.insert(step.to_vid.clone(), to_data_vid);
+      let mut next_edges = edges.clone();
+      next_edges.push((pat_e.eid().to_string(), e));
+      stack.push((step_idx + 1, next_bound, next_edges));
+    }
+  }
+
+  embeddings
+}
@@ -1,4 +1,5 @@
 use super::*;
+use crate::schemas::Eid;
 use rayon::iter::ParallelIterator;
 use rayon::prelude::*;
 #[cfg(not(feature = "intersection_force_element_paralleled"))]
@@ -33,6 +34,44 @@ impl TBucket {
     }
   }
 
+  /// The same bucket, as if it were intersected for `target_pat_vid`,
+  /// whose dangling edges are mirrored by `mirrored_eids` (see `TCache`).
+  ///
+  /// `None` if `mirrored_eids` doesn't cover all of them.
+  pub fn retarget(
+    self,
+    target_pat_vid: VidRef<'_>,
+    mirrored_eids: &HashMap<Eid, Eid>,
+  ) -> Option<Self> {
+    let Self {
+      target_pat_vid: prev_pat_vid,
+      mut expanding_graphs,
+    } = self;
+
+    for expanding_graph in expanding_graphs.iter_mut() {
+      for pattern in expanding_graph.dangling_e_patterns.values_mut() {
+        *pattern = mirrored_eids.get(pattern)?.clone();
+      }
+      for pattern in expanding_graph.target_v_patterns.values_mut() {
+        if *pattern == prev_pat_vid {
+          *pattern = target_pat_vid.to_owned();
+        }
+      }
+    }
+
+    Some(Self {
+      target_pat_vid: target_pat_vid.to_owned(),
+      expanding_graphs,
+    })
+  }
+
+  /// The pattern edges left dangling in any of its graphs.
+  pub fn dangling_eids(&self) -> HashSet<&Eid> {
+    (self.expanding_graphs.iter())
+      .flat_map(|expanding_graph| expanding_graph.dangling_e_patterns.values())
+      .collect()
+  }
+
   pub async fn build_from_a_t(a_group: Vec<ExpandGraph>, t_bucket: TBucket) -> Self {
     let left_group = a_group;
     let right_group = t_bucket.expanding_graphs;
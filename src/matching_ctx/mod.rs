@@ -1,12 +1,15 @@
-use std::{ops::BitOr, sync::Arc};
+use std::{ops::BitOr, str::FromStr, sync::Arc};
 
 use crate::{
-  schemas::{PatternEdge, PatternVertex, PlanData, STR_TUPLE_SPLITTER, Vid, VidRef},
+  schemas::{
+    AttrCmpMode, Eid, PatternEdge, PatternVertex, PlanData, STR_TUPLE_SPLITTER, VarPrefix, Vid,
+    VidRef,
+  },
   utils::{dyn_graph::DynGraph, expand_graph::ExpandGraph},
 };
 use buckets::{ABucket, CBucket, FBucket, TBucket};
 use crossbeam_queue::SegQueue;
-use dashmap::DashMap;
+use dashmap::{DashMap, mapref::entry::Entry};
 use hashbrown::HashMap;
 
 pub mod buckets;
@@ -25,6 +28,8 @@ pub struct MatchingCtx {
   pub(crate) a_block: DashMap<Vid, ABucket>,
   pub(crate) c_block: DashMap<Vid, CBucket>,
   pub(crate) t_block: DashMap<Vid, TBucket>,
+  /// `TCache`: the shared `t_bucket`s, along with the consumers yet to take them
+  pub(crate) t_cache: DashMap<Vid, (TBucket, usize)>,
 
   pub(crate) grouped_partial_matches: SegQueue<Vec<DynGraph>>,
 }
@@ -37,6 +42,7 @@ impl Clone for MatchingCtx {
       a_block: self.a_block.clone(),
       c_block: self.c_block.clone(),
       t_block: self.t_block.clone(),
+      t_cache: self.t_cache.clone(),
       grouped_partial_matches: SegQueue::new(),
     }
   }
@@ -52,6 +58,7 @@ impl BitOr for MatchingCtx {
     merged.a_block.extend(rhs.a_block);
     merged.c_block.extend(rhs.c_block);
     merged.t_block.extend(rhs.t_block);
+    merged.t_cache.extend(rhs.t_cache);
 
     // Note that `grouped_partial_matches` is not merged here,
     // because it will be only modified in the last `Report` step.
@@ -77,6 +84,10 @@ impl MatchingCtx {
   pub fn pattern_es(&self) -> &HashMap<Vid, PatternEdge> {
     &self.plan_data.pattern_es
   }
+
+  pub fn attr_cmp_mode(&self) -> AttrCmpMode {
+    self.plan_data.attr_cmp_mode
+  }
 }
 
 impl MatchingCtx {
@@ -197,9 +208,118 @@ impl MatchingCtx {
     self.t_block.insert(key.to_string(), t_bucket);
   }
 
-  /// `Intersect(Tx)`: Get `t_block` with `single_op` (pop it out)
-  pub fn pop_from_t_block(&self, single_op: &str) -> Option<TBucket> {
+  /// `Intersect(Tx)`: Get `t_block` with `single_op` for `curr_pat_str` (pop it out)
+  ///
+  /// A shared one (see [`Self::cache_t_bucket`]) is cloned instead, unless it's the last consumer,
+  /// and mirrored onto `curr_pat_str` if it's intersected for another pattern vertex
+  /// (`None` if it can't be).
+  pub fn pop_from_t_block(&self, single_op: &str, curr_pat_str: &str) -> Option<TBucket> {
     let key = resolve_var_name(single_op);
-    self.t_block.remove(key).map(|t| t.1)
+    let t_bucket = match self.t_cache.entry(key.to_string()) {
+      Entry::Occupied(mut shared) => {
+        let (t_bucket, consumers) = shared.get_mut();
+        *consumers = consumers.saturating_sub(1);
+        match *consumers {
+          0 => shared.remove().0,
+          _ => t_bucket.clone(),
+        }
+      }
+      Entry::Vacant(_) => self.t_block.remove(key)?.1,
+    };
+
+    if t_bucket.target_pat_vid == curr_pat_str {
+      return Some(t_bucket);
+    }
+    let mirrored_eids = self.mirrored_eids(&t_bucket.target_pat_vid, curr_pat_str);
+    t_bucket.retarget(curr_pat_str, &mirrored_eids)
+  }
+
+  /// `TCache`: Move `t_block` with `single_op` to `t_cache` as `target_var`,
+  /// to be shared by its `consumers`
+  ///
+  /// `None` if it can't be mirrored onto one of them (see [`Self::pop_from_t_block`]),
+  /// e.g. in a hand-written plan that shares it between consumers of different shapes.
+  pub fn cache_t_bucket(&self, single_op: &str, target_var: &str, consumers: usize) -> Option<()> {
+    let t_bucket = self.t_block.remove(resolve_var_name(single_op))?.1;
+
+    let dangling_eids = t_bucket.dangling_eids();
+    let consumer_vids = (self.plan_data.instructions.iter())
+      .filter(|instr| {
+        instr.single_op.as_deref() == Some(target_var)
+          || instr.multi_ops.iter().any(|op| op == target_var)
+      })
+      .map(|instr| instr.vid.as_str())
+      .filter(|vid| *vid != t_bucket.target_pat_vid);
+    for vid in consumer_vids {
+      let mirrored_eids = self.mirrored_eids(&t_bucket.target_pat_vid, vid);
+      if let Some(eid) = dangling_eids
+        .iter()
+        .find(|eid| !mirrored_eids.contains_key(**eid))
+      {
+        eprintln!(
+          "❌  No edge of `{vid}` mirrors `{eid}` of `{}` shared as `{target_var}`",
+          t_bucket.target_pat_vid
+        );
+        return None;
+      }
+    }
+
+    let key = resolve_var_name(target_var);
+    self.t_cache.insert(key.to_string(), (t_bucket, consumers));
+    Some(())
+  }
+
+  /// The pattern edges of `from`, each mapped to the one of `to` of the same shape
+  /// on the same neighbor (see [`PatternEdge::shape_from`]).
+  fn mirrored_eids(&self, from: VidRef, to: VidRef) -> HashMap<Eid, Eid> {
+    let is_adj = |e: &PatternEdge, vid: VidRef| e.src_vid == vid || e.dst_vid == vid;
+    let mut unmirrored = (self.pattern_es().values())
+      .filter(|e| is_adj(e, to))
+      .collect::<Vec<_>>();
+
+    let mut mirrored_eids = HashMap::new();
+    for e in (self.pattern_es().values()).filter(|e| is_adj(e, from)) {
+      let anchor = if e.src_vid == from {
+        &e.dst_vid
+      } else {
+        &e.src_vid
+      };
+      if anchor == to {
+        continue;
+      }
+      let shape = e.shape_from(anchor);
+      let mirrored = unmirrored
+        .iter()
+        .position(|other| is_adj(other, anchor) && other.shape_from(anchor) == shape);
+      if let Some(pos) = mirrored {
+        mirrored_eids.insert(e.eid.clone(), unmirrored.swap_remove(pos).eid.clone());
+      }
+    }
+    mirrored_eids
+  }
+}
+
+impl MatchingCtx {
+  /// The partial matches in the bucket of `var` (`0` if there's none), where those of an `A`
+  /// bucket are the ones expanding to `next_pat_vid` if it's given, or to any pattern vertex.
+  pub fn bucket_len(&self, var: &str, next_pat_vid: Option<VidRef>) -> usize {
+    let Some((prefix, key)) = var.split_once(STR_TUPLE_SPLITTER) else {
+      return 0;
+    };
+    match VarPrefix::from_str(prefix) {
+      Ok(VarPrefix::EnumerateTarget) => self.f_block.get(key).map_or(0, |f| f.all_matched.len()),
+      Ok(VarPrefix::DbQueryTarget) => self.a_block.get(key).map_or(0, |a| match next_pat_vid {
+        Some(next_pat_vid) => (a.next_pat_grouped_expanding.get(next_pat_vid)).map_or(0, Vec::len),
+        None => a.next_pat_grouped_expanding.values().map(Vec::len).sum(),
+      }),
+      Ok(VarPrefix::IntersectCandidate) => {
+        (self.c_block.get(key)).map_or(0, |c| c.all_expanded.len())
+      }
+      Ok(VarPrefix::IntersectTarget) => match self.t_cache.get(key) {
+        Some(shared) => shared.0.expanding_graphs.len(),
+        None => (self.t_block.get(key)).map_or(0, |t| t.expanding_graphs.len()),
+      },
+      _ => 0,
+    }
   }
 }
@@ -0,0 +1,1792 @@
+use super::{ParseError, ParseErrorKind};
+use crate::{
+  schemas::{
+    AttrRef, AttrValue, CrossPredicate, EdgeDirection, Eid, Label, Op, PatternAttr, PatternEdge,
+    PatternPredicate, PatternVertex, VarLength, Vid, is_param_name,
+  },
+  utils::{dyn_graph::DynGraph, sub_pattern::SubPatternKind},
+};
+use hashbrown::HashMap;
+use itertools::Itertools;
+
+/// Keywords recognized by the cypher frontend (case-insensitive).
+const KEYWORDS: [&str; 26] = [
+  "MATCH",
+  "OPTIONAL",
+  "WHERE",
+  "AND",
+  "OR",
+  "NOT",
+  "EXISTS",
+  "IN",
+  "BETWEEN",
+  "STARTS",
+  "ENDS",
+  "WITH",
+  "CONTAINS",
+  "RETURN",
+  "DISTINCT",
+  "AS",
+  "ORDER",
+  "BY",
+  "ASC",
+  "DESC",
+  "SKIP",
+  "LIMIT",
+  "ASCENDING",
+  "TRUE",
+  "FALSE",
+  "NULL",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+  Ident(String),
+  Int(String),
+  Float(String),
+  Str(String),
+  /// `$name`
+  Param(String),
+  Sym(&'static str),
+  Eof,
+}
+
+impl Token {
+  /// The token as it (roughly) appears in the source.
+  fn text(&self) -> String {
+    match self {
+      Token::Ident(s) | Token::Int(s) | Token::Float(s) => s.clone(),
+      Token::Str(s) => format!("'{s}'"),
+      Token::Param(s) => format!("${s}"),
+      Token::Sym(s) => s.to_string(),
+      Token::Eof => String::new(),
+    }
+  }
+
+  fn describe(&self) -> String {
+    match self {
+      Token::Eof => "end of input".to_string(),
+      Token::Str(_) => self.text(),
+      _ => format!("`{}`", self.text()),
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+struct Spanned {
+  token: Token,
+  line: usize,
+  col: usize,
+}
+
+impl Spanned {
+  fn error(&self, kind: ParseErrorKind) -> ParseError {
+    ParseError::new(kind, self.line, self.col).with_token(self.token.text())
+  }
+}
+
+struct Lexer<'a> {
+  chars: std::iter::Peekable<std::str::Chars<'a>>,
+  line: usize,
+  col: usize,
+}
+
+impl<'a> Lexer<'a> {
+  fn new(src: &'a str) -> Self {
+    Self {
+      chars: src.chars().peekable(),
+      line: 1,
+      col: 1,
+    }
+  }
+
+  fn bump(&mut self) -> Option<char> {
+    let c = self.chars.next()?;
+    if c == '\n' {
+      self.line += 1;
+      self.col = 1;
+    } else {
+      self.col += 1;
+    }
+    Some(c)
+  }
+
+  fn tokenize(mut self) -> Result<Vec<Spanned>, ParseError> {
+    let mut tokens = vec![];
+
+    loop {
+      // whitespaces and `//` comments
+      while let Some(&c) = self.chars.peek() {
+        if c.is_whitespace() {
+          self.bump();
+        } else if c == '/' && self.chars.clone().nth(1) == Some('/') {
+          while self.chars.peek().is_some_and(|&c| c != '\n') {
+            self.bump();
+          }
+        } else {
+          break;
+        }
+      }
+
+      let (line, col) = (self.line, self.col);
+      let Some(&c) = self.chars.peek() else {
+        tokens.push(Spanned {
+          token: Token::Eof,
+          line,
+          col,
+        });
+        return Ok(tokens);
+      };
+
+      let token = match c {
+        c if c.is_alphabetic() || c == '_' => {
+          let mut ident = String::new();
+          while let Some(&c) = self.chars.peek() {
+            if !(c.is_alphanumeric() || c == '_') {
+              break;
+            }
+            ident.push(c);
+            self.bump();
+          }
+          Token::Ident(ident)
+        }
+        '`' => {
+          self.bump();
+          let mut ident = String::new();
+          loop {
+            match self.bump() {
+              Some('`') => break,
+              Some(c) => ident.push(c),
+              None => {
+                return Err(
+                  ParseError::new(ParseErrorKind::UnexpectedEnd, line, col)
+                    .with_token(format!("`{ident}"))
+                    .with_expected("closing backtick '`'"),
+                );
+              }
+            }
+          }
+          Token::Ident(ident)
+        }
+        c if c.is_ascii_digit() => {
+          let mut num = String::new();
+          let mut is_float = false;
+          while let Some(&c) = self.chars.peek() {
+            if c.is_ascii_digit() {
+              num.push(c);
+              self.bump();
+            } else if c == '.'
+              && !is_float
+              && self
+                .chars
+                .clone()
+                .nth(1)
+                .is_some_and(|next| next.is_ascii_digit())
+            {
+              is_float = true;
+              num.push(c);
+              self.bump();
+            } else {
+              break;
+            }
+          }
+          if is_float {
+            Token::Float(num)
+          } else {
+            Token::Int(num)
+          }
+        }
+        '$' => {
+          self.bump();
+          let mut param = String::new();
+          while let Some(&c) = self.chars.peek() {
+            if !(c.is_ascii_alphanumeric() || c == '_') {
+              break;
+            }
+            param.push(c);
+            self.bump();
+          }
+          if !is_param_name(&param) {
+            return Err(
+              ParseError::new(ParseErrorKind::MalformedLiteral, line, col)
+                .with_token(format!("${param}"))
+                .with_expected("a parameter name, e.g. `$personId`")
+                .with_found(format!("`${param}`")),
+            );
+          }
+          Token::Param(param)
+        }
+        '\'' | '"' => {
+          let quote = c;
+          self.bump();
+          let mut s = String::new();
+          loop {
+            match self.bump() {
+              Some(c) if c == quote => break,
+              Some('\\') => match self.bump() {
+                Some('n') => s.push('\n'),
+                Some('t') => s.push('\t'),
+                Some(c) => s.push(c),
+                None => break,
+              },
+              Some(c) => s.push(c),
+              None => {
+                return Err(
+                  ParseError::new(ParseErrorKind::UnexpectedEnd, line, col)
+                    .with_token(format!("{quote}{s}"))
+                    .with_expected(format!("closing quote `{quote}`")),
+                );
+              }
+            }
+          }
+          Token::Str(s)
+        }
+        _ => {
+          let mut lookahead = self.chars.clone();
+          let first = lookahead.next().unwrap();
+          let second = lookahead.next();
+          let double = match (first, second) {
+            ('<', Some('>')) => Some("<>"),
+            ('!', Some('=')) => Some("!="),
+            ('<', Some('=')) => Some("<="),
+            ('>', Some('=')) => Some(">="),
+            ('=', Some('~')) => Some("=~"),
+            ('.', Some('.')) => Some(".."),
+            _ => None,
+          };
+          if let Some(sym) = double {
+            self.bump();
+            self.bump();
+            Token::Sym(sym)
+          } else {
+            let sym = match first {
+              '(' => "(",
+              ')' => ")",
+              '[' => "[",
+              ']' => "]",
+              '{' => "{",
+              '}' => "}",
+              ':' => ":",
+              ',' => ",",
+              '.' => ".",
+              '-' => "-",
+              '<' => "<",
+              '>' => ">",
+              '=' => "=",
+              '*' => "*",
+              ';' => ";",
+              _ => {
+                return Err(
+                  ParseError::new(ParseErrorKind::UnexpectedToken, line, col)
+                    .with_token(first.to_string())
+                    .with_found(format!("unexpected character `{first}`")),
+                );
+              }
+            };
+            self.bump();
+            Token::Sym(sym)
+          }
+        }
+      };
+
+      tokens.push(Spanned { token, line, col });
+    }
+  }
+}
+
+struct Cursor {
+  tokens: Vec<Spanned>,
+  pos: usize,
+}
+
+impl Cursor {
+  fn peek(&self) -> &Spanned {
+    &self.tokens[self.pos.min(self.tokens.len() - 1)]
+  }
+
+  fn next(&mut self) -> Spanned {
+    let token = self.peek().clone();
+    if self.pos < self.tokens.len() - 1 {
+      self.pos += 1;
+    }
+    token
+  }
+
+  fn expected(&self, expected: &str) -> ParseError {
+    let at = self.peek();
+    let kind = if at.token == Token::Eof {
+      ParseErrorKind::UnexpectedEnd
+    } else {
+      ParseErrorKind::UnexpectedToken
+    };
+    at.error(kind)
+      .with_expected(expected)
+      .with_found(at.token.describe())
+  }
+
+  fn is_keyword(&self, keyword: &str) -> bool {
+    matches!(&self.peek().token, Token::Ident(s) if s.eq_ignore_ascii_case(keyword))
+  }
+
+  fn eat_keyword(&mut self, keyword: &str) -> bool {
+    let matched = self.is_keyword(keyword);
+    if matched {
+      self.next();
+    }
+    matched
+  }
+
+  fn expect_keyword(&mut self, keyword: &str) -> Result<(), ParseError> {
+    if !self.eat_keyword(keyword) {
+      return Err(self.expected(&format!("`{keyword}`")));
+    }
+    Ok(())
+  }
+
+  fn is_sym(&self, sym: &str) -> bool {
+    self.is_sym_at(0, sym)
+  }
+
+  /// Whether the `n`-th token from the current one is `sym`.
+  fn is_sym_at(&self, n: usize, sym: &str) -> bool {
+    let token = &self.tokens[(self.pos + n).min(self.tokens.len() - 1)];
+    matches!(&token.token, Token::Sym(s) if *s == sym)
+  }
+
+  fn eat_sym(&mut self, sym: &str) -> bool {
+    let matched = self.is_sym(sym);
+    if matched {
+      self.next();
+    }
+    matched
+  }
+
+  fn expect_sym(&mut self, sym: &str) -> Result<(), ParseError> {
+    if !self.eat_sym(sym) {
+      return Err(self.expected(&format!("`{sym}`")));
+    }
+    Ok(())
+  }
+
+  /// Identifiers, excluding the reserved keywords.
+  fn eat_ident(&mut self) -> Option<String> {
+    match &self.peek().token {
+      Token::Ident(s) if !KEYWORDS.iter().any(|k| s.eq_ignore_ascii_case(k)) => {
+        let s = s.clone();
+        self.next();
+        Some(s)
+      }
+      _ => None,
+    }
+  }
+
+  fn expect_ident(&mut self, what: &str) -> Result<String, ParseError> {
+    self.eat_ident().ok_or_else(|| self.expected(what))
+  }
+}
+
+/// Parses a subset of Cypher into the same pattern graph as [`super::PatternParser`].
+///
+/// Supported:
+/// - one or more `MATCH` clauses of comma-separated paths, e.g.
+///   `(a: Person)-[e: knows]->(b: Person)<-[:hasCreator]-(c)`,
+///   where an undirected relationship `(a)-[e: knows]-(b)` matches either way,
+///   and a bounded variable-length one `(a)-[e: knows*1..3]->(b)` matches a path;
+///   a vertex left unlabeled in all of its occurrences matches any label
+/// - a `WHERE` expression of `AND` / `OR` / `NOT` (and parentheses) over
+///   `var.prop <op> literal` (or `literal <op> var.prop`), `var.prop IN [literal, ...]`
+///   and `var.prop BETWEEN literal AND literal`, where `<op>` is one of `=, <>, !=, <, <=, >, >=`
+///   or (on strings only) `STARTS WITH, ENDS WITH, CONTAINS, =~`;
+///   `toLower(var.prop) = literal` (or `toUpper`) compares case-insensitively;
+///   literals are numbers, strings, `true` / `false`, `null`,
+///   `date('2010-03-24')` or `datetime('2010-03-24T12:00:00Z')`;
+///   a `$param` may stand for the literal of a comparison, which is bound at execution;
+///   comparisons follow cypher's three-valued logic, where `null` (or a missing property)
+///   never equals anything;
+///   `var.prop <op> var.prop` compares the properties of two (or the same) variables;
+///   the top-level conjuncts across several variables are checked on the partial matches
+///   once all of them are bound
+/// - top-level `NOT EXISTS { [MATCH] <path>, ... [WHERE ...] }` (or `NOT <path>`) conjuncts,
+///   which drop the matches where the negated paths exist; their new variables are local,
+///   and their `WHERE` may only refer to those
+/// - trailing `OPTIONAL MATCH <path>, ... [WHERE ...]` clauses, which keep the matches
+///   without the optional paths (where their new variables are null);
+///   their `WHERE` may only refer to their own new variables as well
+/// - a `RETURN` list of `var` / `var.prop` items (optionally `DISTINCT`, `AS alias`)
+#[derive(Debug, Clone)]
+pub struct CypherParser {
+  src: String,
+  anonymous_cnt: usize,
+
+  v_labels: HashMap<Vid, Option<Label>>,
+  v_attrs: HashMap<Vid, Vec<PatternPredicate>>,
+
+  e_2_vv: HashMap<Eid, (Vid, Vid)>,
+  e_labels: HashMap<Eid, Label>,
+  e_attrs: HashMap<Eid, Vec<PatternPredicate>>,
+  e_directions: HashMap<Eid, EdgeDirection>,
+  e_var_lengths: HashMap<Eid, VarLength>,
+
+  /// var -> index of the `NOT EXISTS` that binds it
+  negated_vars: HashMap<String, usize>,
+  /// (the `NOT` of) each `NOT EXISTS`
+  negated_spans: Vec<Spanned>,
+  /// the `NOT EXISTS` being parsed
+  curr_negated: Option<usize>,
+  /// var -> index of the `OPTIONAL MATCH` that binds it
+  optional_vars: HashMap<String, usize>,
+  /// (the `OPTIONAL` of) each `OPTIONAL MATCH`
+  optional_spans: Vec<Spanned>,
+  /// the `OPTIONAL MATCH` being parsed
+  curr_optional: Option<usize>,
+
+  /// the top-level conjuncts that can't be attached to a single vertex / edge
+  cross_predicates: Vec<CrossPredicate>,
+
+  return_items: Vec<String>,
+}
+
+impl CypherParser {
+  pub fn new(src: String) -> Self {
+    Self {
+      src,
+      anonymous_cnt: 0,
+      v_labels: HashMap::new(),
+      v_attrs: HashMap::new(),
+      e_2_vv: HashMap::new(),
+      e_labels: HashMap::new(),
+      e_attrs: HashMap::new(),
+      e_directions: HashMap::new(),
+      e_var_lengths: HashMap::new(),
+      negated_vars: HashMap::new(),
+      negated_spans: vec![],
+      curr_negated: None,
+      optional_vars: HashMap::new(),
+      optional_spans: vec![],
+      curr_optional: None,
+      cross_predicates: vec![],
+      return_items: vec![],
+    }
+  }
+
+  /// Items of the `RETURN` clause, in their original order.
+  pub fn return_items(&self) -> &[String] {
+    &self.return_items
+  }
+
+  /// The predicates across several vertices / edges (or two properties of the same one).
+  pub fn cross_predicates(&self) -> &[CrossPredicate] {
+    &self.cross_predicates
+  }
+
+  /// Should only be called after a successful [`CypherParser::parse`].
+  pub fn take_as_pattern_graph(mut self) -> DynGraph<PatternVertex, PatternEdge> {
+    let mut pattern_graph = DynGraph::default();
+
+    // vertices
+    for (vid, label) in self.v_labels.drain() {
+      // left empty if it's unlabeled in all of its occurrences, which matches any label
+      let label = label.unwrap_or_default();
+      let attrs = self.v_attrs.remove(&vid).unwrap_or_default();
+      let pattern_vertex = PatternVertex {
+        vid: vid.clone(),
+        label,
+        attrs,
+        optional: self.optional_vars.contains_key(&vid),
+        negated: self.negated_vars.contains_key(&vid),
+      };
+      pattern_graph.update_v(pattern_vertex, vid);
+    }
+
+    // edges
+    for (eid, (src_vid, dst_vid)) in self.e_2_vv.drain() {
+      // an edge without a label is rejected by `parse_path`
+      let label = self.e_labels.remove(&eid).unwrap();
+      let attrs = self.e_attrs.remove(&eid).unwrap_or_default();
+      let direction = self.e_directions.remove(&eid).unwrap_or_default();
+      let var_length = self.e_var_lengths.remove(&eid);
+      let pattern_edge = PatternEdge {
+        eid: eid.clone(),
+        src_vid,
+        dst_vid,
+        label,
+        attrs,
+        direction,
+        var_length,
+        optional: self.optional_vars.contains_key(&eid),
+        negated: self.negated_vars.contains_key(&eid),
+      };
+      pattern_graph.update_e(pattern_edge, eid);
+    }
+
+    pattern_graph
+  }
+
+  pub fn parse(&mut self) -> Result<(), ParseError> {
+    self.parse_inner().map_err(|e| e.with_source(&self.src))
+  }
+
+  fn parse_inner(&mut self) -> Result<(), ParseError> {
+    let tokens = Lexer::new(&self.src).tokenize()?;
+    let mut cursor = Cursor { tokens, pos: 0 };
+    // (first occurrence) of each vertex, for error reporting
+    let mut v_spans = HashMap::new();
+
+    // MATCH
+    cursor.expect_keyword("MATCH")?;
+    loop {
+      self.parse_path(&mut cursor, &mut v_spans)?;
+      if cursor.eat_sym(",") || cursor.eat_keyword("MATCH") {
+        continue;
+      }
+      break;
+    }
+
+    // WHERE
+    if cursor.eat_keyword("WHERE") {
+      for cond in self.parse_disjunction(&mut cursor)? {
+        self.attach_condition(cond)?;
+      }
+    }
+
+    // OPTIONAL MATCH
+    while cursor.is_keyword("OPTIONAL") {
+      self.parse_optional_match(&mut cursor, &mut v_spans)?;
+    }
+
+    // RETURN
+    if !cursor.eat_keyword("RETURN") {
+      return Err(cursor.expected("`RETURN`"));
+    }
+    self.parse_return(&mut cursor)?;
+
+    // the executor yields all the matches, unordered
+    for clause in ["ORDER", "SKIP", "LIMIT"] {
+      if cursor.is_keyword(clause) {
+        return Err(
+          cursor
+            .peek()
+            .error(ParseErrorKind::Unsupported)
+            .with_found(format!("`{clause}` clause")),
+        );
+      }
+    }
+
+    let _ = cursor.eat_sym(";");
+    if cursor.peek().token != Token::Eof {
+      return Err(cursor.expected("end of query"));
+    }
+
+    if !self.negated_spans.is_empty() || !self.optional_spans.is_empty() {
+      self.check_sub_patterns()?;
+    }
+
+    Ok(())
+  }
+
+  fn next_anonymous(&mut self, prefix: &str) -> String {
+    self.anonymous_cnt += 1;
+    format!("__anon_{prefix}{}", self.anonymous_cnt)
+  }
+
+  /// `(a: A)-[e: E]->(b: B)<-[f: F]-(c: C) ...`
+  fn parse_path(
+    &mut self,
+    cursor: &mut Cursor,
+    v_spans: &mut HashMap<Vid, Spanned>,
+  ) -> Result<(), ParseError> {
+    let mut left = self.parse_node(cursor, v_spans)?;
+
+    while cursor.is_sym("-") || cursor.is_sym("<") {
+      let arrow_span = cursor.peek().clone();
+
+      let is_incoming = cursor.eat_sym("<");
+      cursor.expect_sym("-")?;
+      let (eid, label, var_length) = if cursor.eat_sym("[") {
+        let eid_span = cursor.peek().clone();
+        let eid = cursor.eat_ident().map(|eid| (eid, eid_span));
+        let label = if cursor.eat_sym(":") {
+          Some(cursor.expect_ident("a label")?)
+        } else {
+          None
+        };
+        let var_length = if cursor.is_sym("*") {
+          Some(Self::parse_var_length(cursor)?)
+        } else {
+          None
+        };
+        cursor.expect_sym("]")?;
+        cursor.expect_sym("-")?;
+        (eid, label, var_length)
+      } else {
+        cursor.expect_sym("-")?;
+        (None, None, None)
+      };
+      let is_outgoing = cursor.eat_sym(">");
+
+      let right = self.parse_node(cursor, v_spans)?;
+
+      let (eid, eid_span) = match eid {
+        Some(eid) => eid,
+        None => (self.next_anonymous("e"), arrow_span.clone()),
+      };
+      let Some(label) = label else {
+        return Err(
+          eid_span
+            .error(ParseErrorKind::MissingLabel)
+            .with_found(format!("relationship `{eid}` has no label")),
+        );
+      };
+      let (src_vid, dst_vid, direction) = match (is_incoming, is_outgoing) {
+        (false, true) => (left.clone(), right.clone(), EdgeDirection::Out),
+        (true, false) => (right.clone(), left.clone(), EdgeDirection::Out),
+        (false, false) => (left.clone(), right.clone(), EdgeDirection::Both),
+        (true, true) => {
+          return Err(
+            arrow_span
+              .error(ParseErrorKind::Unsupported)
+              .with_found(format!("relationship `{eid}` points in both directions")),
+          );
+        }
+      };
+
+      if self.v_labels.contains_key(&eid) || self.e_2_vv.contains_key(&eid) {
+        return Err(
+          eid_span
+            .error(ParseErrorKind::DuplicateEid)
+            .with_found(format!("variable `{eid}` is already bound")),
+        );
+      }
+      if let Some(idx) = self.curr_negated {
+        self.negated_vars.insert(eid.clone(), idx);
+      }
+      if let Some(idx) = self.curr_optional {
+        self.optional_vars.insert(eid.clone(), idx);
+      }
+      self.e_2_vv.insert(eid.clone(), (src_vid, dst_vid));
+      self.e_directions.insert(eid.clone(), direction);
+      if let Some(var_length) = var_length {
+        self.e_var_lengths.insert(eid.clone(), var_length);
+      }
+      self.e_labels.insert(eid, label);
+
+      left = right;
+    }
+
+    Ok(())
+  }
+
+  /// `*<hops>`, `*<min>..<max>` or `*..<max>` (from 1 hop), matched as a trail
+  fn parse_var_length(cursor: &mut Cursor) -> Result<VarLength, ParseError> {
+    let star_span = cursor.next();
+    let parse_hops = |cursor: &mut Cursor| match &cursor.peek().token {
+      Token::Int(hops) => {
+        let hops = hops.parse::<usize>().ok();
+        cursor.next();
+        hops
+      }
+      _ => None,
+    };
+
+    let min_hops = parse_hops(cursor);
+    let max_hops = if cursor.eat_sym("..") {
+      parse_hops(cursor)
+    } else {
+      min_hops
+    };
+
+    let unbounded = || {
+      star_span
+        .error(ParseErrorKind::Unsupported)
+        .with_found("a variable-length relationship without `1 <= min <= max` hops")
+    };
+    VarLength::new(
+      min_hops.unwrap_or(1),
+      max_hops.ok_or_else(unbounded)?,
+      Default::default(),
+    )
+    .ok_or_else(unbounded)
+  }
+
+  /// `(a)`, `(a: A)`, `(: A)`, `()`
+  fn parse_node(
+    &mut self,
+    cursor: &mut Cursor,
+    v_spans: &mut HashMap<Vid, Spanned>,
+  ) -> Result<Vid, ParseError> {
+    cursor.expect_sym("(")?;
+    let vid_span = cursor.peek().clone();
+    let vid = cursor.eat_ident();
+    let label_span = cursor.peek().clone();
+    let label = if cursor.eat_sym(":") {
+      Some(cursor.expect_ident("a label")?)
+    } else {
+      None
+    };
+    if cursor.is_sym(":") {
+      return Err(
+        cursor
+          .peek()
+          .error(ParseErrorKind::Unsupported)
+          .with_found("multiple labels on a single node"),
+      );
+    }
+    cursor.expect_sym(")")?;
+
+    let vid = vid.unwrap_or_else(|| self.next_anonymous("v"));
+    if self.e_2_vv.contains_key(&vid) {
+      return Err(
+        vid_span
+          .error(ParseErrorKind::DuplicateVid)
+          .with_found(format!("`{vid}` is already bound to a relationship")),
+      );
+    }
+    match (self.curr_negated, self.negated_vars.get(&vid)) {
+      (Some(idx), None) if !self.v_labels.contains_key(&vid) => {
+        self.negated_vars.insert(vid.clone(), idx);
+      }
+      (curr, Some(&idx)) if curr != Some(idx) => {
+        return Err(
+          vid_span
+            .error(ParseErrorKind::Unsupported)
+            .with_found(format!("`{vid}` is local to another `NOT EXISTS`")),
+        );
+      }
+      _ => {}
+    }
+    match (self.curr_optional, self.optional_vars.get(&vid)) {
+      (Some(idx), None) if !self.v_labels.contains_key(&vid) => {
+        self.optional_vars.insert(vid.clone(), idx);
+      }
+      (curr, Some(&idx)) if curr != Some(idx) => {
+        return Err(
+          vid_span
+            .error(ParseErrorKind::Unsupported)
+            .with_found(format!("`{vid}` is local to another `OPTIONAL MATCH`")),
+        );
+      }
+      _ => {}
+    }
+    v_spans.entry(vid.clone()).or_insert(vid_span);
+
+    let entry = self.v_labels.entry(vid.clone()).or_insert(None);
+    match (entry.as_ref(), label) {
+      (Some(prev), Some(curr)) if *prev != curr => {
+        return Err(
+          label_span
+            .error(ParseErrorKind::ConflictingLabel)
+            .with_expected(format!("`{prev}`"))
+            .with_found(format!("`{curr}`")),
+        );
+      }
+      (_, Some(curr)) => *entry = Some(curr),
+      _ => {}
+    }
+
+    Ok(vid)
+  }
+
+  /// Each top-level conjunct is attached to the (only) variable it refers to,
+  /// or kept aside as a cross predicate.
+  fn attach_condition(&mut self, cond: Condition) -> Result<(), ParseError> {
+    let single = match cond.vars.as_slice() {
+      [var] => cond.pred.to_single().map(|pred| (var.clone(), pred)),
+      _ => None,
+    };
+    let Some((var, pred)) = single else {
+      // they would be checked along with the sub-pattern, which isn't supported yet
+      let clause = match (self.curr_negated, self.curr_optional) {
+        (Some(_), _) => Some("`NOT EXISTS`"),
+        (_, Some(_)) => Some("`OPTIONAL MATCH`"),
+        _ => None,
+      };
+      if let Some(clause) = clause {
+        let vars = cond.vars.iter().map(|var| format!("`{var}`")).join(", ");
+        return Err(
+          cond
+            .span
+            .error(ParseErrorKind::Unsupported)
+            .with_found(format!(
+              "a predicate across several properties ({vars}) inside {clause}"
+            )),
+        );
+      }
+      self.cross_predicates.push(cond.pred);
+      return Ok(());
+    };
+    let attrs = if self.v_labels.contains_key(&var) {
+      &mut self.v_attrs
+    } else {
+      &mut self.e_attrs
+    };
+    attrs.entry(var).or_default().push(pred);
+    Ok(())
+  }
+
+  /// `OR`-separated conjunctions, as the conjuncts of an equivalent top-level `AND`.
+  fn parse_disjunction(&mut self, cursor: &mut Cursor) -> Result<Vec<Condition>, ParseError> {
+    let negated_cnt = self.negated_spans.len();
+    let mut disjuncts = vec![self.parse_conjunction(cursor)?];
+    while cursor.eat_keyword("OR") {
+      disjuncts.push(self.parse_conjunction(cursor)?);
+    }
+    if disjuncts.len() == 1 {
+      return Ok(disjuncts.pop().unwrap());
+    }
+    self.check_no_negated_pattern_since(negated_cnt, "`OR`")?;
+    let disjuncts = disjuncts.into_iter().map(Condition::all).collect();
+    Ok(vec![Condition::combine(disjuncts, CrossPredicate::or)])
+  }
+
+  /// `AND`-separated (negated) comparisons.
+  fn parse_conjunction(&mut self, cursor: &mut Cursor) -> Result<Vec<Condition>, ParseError> {
+    let mut conjuncts = self.parse_negation(cursor)?;
+    while cursor.eat_keyword("AND") {
+      conjuncts.extend(self.parse_negation(cursor)?);
+    }
+    Ok(conjuncts)
+  }
+
+  /// `NOT <negation>`, `NOT EXISTS { ... }`, `NOT <path>`, `( <disjunction> )` or a comparison
+  ///
+  /// A negated pattern yields no condition, as it's kept aside for the anti-join.
+  fn parse_negation(&mut self, cursor: &mut Cursor) -> Result<Vec<Condition>, ParseError> {
+    let span = cursor.peek().clone();
+    if cursor.eat_keyword("NOT") {
+      if cursor.is_keyword("EXISTS") || Self::is_node_start(cursor) {
+        self.parse_negated_pattern(cursor, span)?;
+        return Ok(vec![]);
+      }
+      let negated_cnt = self.negated_spans.len();
+      let conds = self.parse_negation(cursor)?;
+      self.check_no_negated_pattern_since(negated_cnt, "`NOT`")?;
+      let Condition { pred, vars, .. } = Condition::all(conds);
+      let pred = CrossPredicate::not(pred);
+      return Ok(vec![Condition { pred, vars, span }]);
+    }
+    if cursor.eat_sym("(") {
+      let conds = self.parse_disjunction(cursor)?;
+      cursor.expect_sym(")")?;
+      return Ok(conds);
+    }
+    Ok(vec![self.parse_comparison(cursor)?])
+  }
+
+  /// `(`, followed by `)`, `:` or `var )` / `var :`, i.e. a node rather than a sub-expression
+  fn is_node_start(cursor: &Cursor) -> bool {
+    let is_ident_at = |n: usize| {
+      let token = &cursor.tokens[(cursor.pos + n).min(cursor.tokens.len() - 1)];
+      matches!(token.token, Token::Ident(_))
+    };
+    cursor.is_sym("(")
+      && (cursor.is_sym_at(1, ")")
+        || cursor.is_sym_at(1, ":")
+        || is_ident_at(1) && (cursor.is_sym_at(2, ")") || cursor.is_sym_at(2, ":")))
+  }
+
+  /// `EXISTS { [MATCH] <path>, ... [WHERE <disjunction>] }` or `<path>`, following `NOT`.
+  fn parse_negated_pattern(
+    &mut self,
+    cursor: &mut Cursor,
+    not_span: Spanned,
+  ) -> Result<(), ParseError> {
+    if self.curr_negated.is_some() {
+      return Err(
+        not_span
+          .error(ParseErrorKind::Unsupported)
+          .with_found("a nested `NOT EXISTS`"),
+      );
+    }
+    if self.curr_optional.is_some() {
+      return Err(
+        not_span
+          .error(ParseErrorKind::Unsupported)
+          .with_found("a `NOT EXISTS` inside `OPTIONAL MATCH`"),
+      );
+    }
+    let idx = self.negated_spans.len();
+    self.negated_spans.push(not_span.clone());
+    self.curr_negated = Some(idx);
+
+    let mut v_spans = HashMap::new();
+    if cursor.eat_keyword("EXISTS") {
+      cursor.expect_sym("{")?;
+      let _ = cursor.eat_keyword("MATCH");
+      loop {
+        self.parse_path(cursor, &mut v_spans)?;
+        if !cursor.eat_sym(",") {
+          break;
+        }
+      }
+      if cursor.eat_keyword("WHERE") {
+        for cond in self.parse_disjunction(cursor)? {
+          // a predicate on an outer variable would be negated along with the pattern
+          if let Some(var) = cond
+            .vars
+            .iter()
+            .find(|var| self.negated_vars.get(*var) != Some(&idx))
+          {
+            return Err(
+              cond
+                .span
+                .error(ParseErrorKind::Unsupported)
+                .with_found(format!(
+                  "a predicate on the outer variable `{var}` inside `NOT EXISTS`"
+                )),
+            );
+          }
+          self.attach_condition(cond)?;
+        }
+      }
+      cursor.expect_sym("}")?;
+    } else {
+      self.parse_path(cursor, &mut v_spans)?;
+    }
+    self.curr_negated = None;
+
+    Ok(())
+  }
+
+  /// `OPTIONAL MATCH <path>, ... [WHERE <disjunction>]`
+  fn parse_optional_match(
+    &mut self,
+    cursor: &mut Cursor,
+    v_spans: &mut HashMap<Vid, Spanned>,
+  ) -> Result<(), ParseError> {
+    let optional_span = cursor.next();
+    cursor.expect_keyword("MATCH")?;
+    let idx = self.optional_spans.len();
+    self.optional_spans.push(optional_span);
+    self.curr_optional = Some(idx);
+
+    loop {
+      self.parse_path(cursor, v_spans)?;
+      if !cursor.eat_sym(",") {
+        break;
+      }
+    }
+    if cursor.eat_keyword("WHERE") {
+      for cond in self.parse_disjunction(cursor)? {
+        // a predicate on a required variable would drop the whole match instead
+        if let Some(var) = cond
+          .vars
+          .iter()
+          .find(|var| self.optional_vars.get(*var) != Some(&idx))
+        {
+          return Err(
+            cond
+              .span
+              .error(ParseErrorKind::Unsupported)
+              .with_found(format!(
+                "a predicate on the outer variable `{var}` inside `OPTIONAL MATCH`"
+              )),
+          );
+        }
+        self.attach_condition(cond)?;
+      }
+    }
+    self.curr_optional = None;
+
+    Ok(())
+  }
+
+  fn check_no_negated_pattern_since(
+    &self,
+    negated_cnt: usize,
+    under: &str,
+  ) -> Result<(), ParseError> {
+    match self.negated_spans.get(negated_cnt) {
+      Some(span) => Err(
+        span
+          .error(ParseErrorKind::Unsupported)
+          .with_found(format!("a negated pattern under {under}")),
+      ),
+      None => Ok(()),
+    }
+  }
+
+  /// Each `NOT EXISTS` / `OPTIONAL MATCH` should be a single sub-pattern,
+  /// hanging on the (connected) required part.
+  fn check_sub_patterns(&self) -> Result<(), ParseError> {
+    let pattern_graph = self.clone().take_as_pattern_graph();
+    let clauses_of = |kind: SubPatternKind| match kind {
+      SubPatternKind::Negated => (&self.negated_vars, &self.negated_spans, "`NOT EXISTS`"),
+      SubPatternKind::Optional => (
+        &self.optional_vars,
+        &self.optional_spans,
+        "`OPTIONAL MATCH`",
+      ),
+    };
+
+    if let Err((var, reason)) = pattern_graph.check_sub_patterns() {
+      let span = [&self.negated_vars, &self.optional_vars]
+        .into_iter()
+        .zip([&self.negated_spans, &self.optional_spans])
+        .find_map(|(vars, spans)| vars.get(&var).map(|&idx| &spans[idx]))
+        .or(self.negated_spans.first())
+        .or(self.optional_spans.first())
+        .unwrap();
+      return Err(span.error(ParseErrorKind::Unsupported).with_found(reason));
+    }
+
+    for kind in [SubPatternKind::Negated, SubPatternKind::Optional] {
+      let (vars, spans, clause) = clauses_of(kind);
+      let mut seen = vec![false; spans.len()];
+      for sub_pattern in pattern_graph.sub_patterns(kind) {
+        let idx = vars[&sub_pattern.eids[0]];
+        if std::mem::replace(&mut seen[idx], true) {
+          return Err(
+            spans[idx]
+              .error(ParseErrorKind::Unsupported)
+              .with_found(format!(
+                "a {clause} whose relationships are not connected by its own nodes"
+              )),
+          );
+        }
+      }
+      if let Some(idx) = seen.iter().position(|seen| !seen) {
+        return Err(
+          spans[idx]
+            .error(ParseErrorKind::Unsupported)
+            .with_found(format!("a {clause} without any relationship")),
+        );
+      }
+    }
+
+    Ok(())
+  }
+
+  /// `var.prop <op> literal`, `literal <op> var.prop`, `toLower(var.prop) = literal`,
+  /// `var.prop IN [literal, ...]` or `var.prop BETWEEN literal AND literal`
+  fn parse_comparison(&self, cursor: &mut Cursor) -> Result<Condition, ParseError> {
+    let span = cursor.peek().clone();
+    let case_fn = ["toLower", "toUpper"]
+      .into_iter()
+      .find(|f| cursor.is_keyword(f) && cursor.is_sym_at(1, "("));
+    let (var, key, op, value) = if let Some(case_fn) = case_fn {
+      cursor.next();
+      cursor.next();
+      let (var, key) = self.parse_property(cursor)?;
+      cursor.expect_sym(")")?;
+      let op_span = cursor.peek().clone();
+      if Self::parse_op(cursor)? != Op::Eq {
+        return Err(
+          op_span
+            .error(ParseErrorKind::Unsupported)
+            .with_found(format!("only `=` is supported after `{case_fn}(...)`")),
+        );
+      }
+      (var, key, Op::EqIgnoreCase, Self::parse_operand(cursor)?)
+    } else if matches!(cursor.peek().token, Token::Ident(_))
+      && !Self::is_temporal_call(cursor)
+      && !Self::is_literal_keyword(cursor)
+    {
+      let (var, key) = self.parse_property(cursor)?;
+
+      if cursor.eat_keyword("IN") {
+        cursor.expect_sym("[")?;
+        let mut values = vec![];
+        while !cursor.is_sym("]") {
+          values.push(Self::parse_literal(cursor)?);
+          if !cursor.eat_sym(",") {
+            break;
+          }
+        }
+        cursor.expect_sym("]")?;
+        let pred = PatternPredicate::In { key, values };
+        return Ok(Condition::new(pred, var, span));
+      }
+
+      if cursor.eat_keyword("BETWEEN") {
+        let low = Self::parse_literal(cursor)?;
+        cursor.expect_keyword("AND")?;
+        let high = Self::parse_literal(cursor)?;
+        if matches!(low, AttrValue::String(_)) || matches!(high, AttrValue::String(_)) {
+          return Err(
+            span
+              .error(ParseErrorKind::InvalidOperator)
+              .with_expected("`=` or `<>` for string literals")
+              .with_found("`BETWEEN`"),
+          );
+        }
+        let pred = PatternPredicate::Between {
+          key,
+          bounds: (low, high),
+        };
+        return Ok(Condition::new(pred, var, span));
+      }
+
+      let op = Self::parse_op(cursor)?;
+
+      // `var.prop <op> var.prop`
+      if matches!(cursor.peek().token, Token::Ident(_)) && cursor.is_sym_at(1, ".") {
+        let (right_var, right_key) = self.parse_property(cursor)?;
+        let pred = CrossPredicate::Cmp {
+          left: AttrRef::new(var.clone(), key),
+          op,
+          right: AttrRef::new(right_var.clone(), right_key),
+        };
+        return Ok(Condition::cross(pred, [var, right_var], span));
+      }
+
+      let value = Self::parse_operand(cursor)?;
+      (var, key, op, value)
+    } else {
+      let value = Self::parse_operand(cursor)?;
+      let op_span = cursor.peek().clone();
+      let op = match Self::parse_op(cursor)? {
+        Op::Gt => Op::Lt,
+        Op::Ge => Op::Le,
+        Op::Lt => Op::Gt,
+        Op::Le => Op::Ge,
+        op if op.is_string_only() => {
+          return Err(
+            op_span
+              .error(ParseErrorKind::Unsupported)
+              .with_found(format!("a literal on the left of `{op}`")),
+          );
+        }
+        op => op,
+      };
+      let (var, key) = self.parse_property(cursor)?;
+      (var, key, op, value)
+    };
+
+    // checked once it's bound
+    let value = match value {
+      Operand::Literal(value) => value,
+      Operand::Param(param) => {
+        return Ok(Condition::new(
+          PatternPredicate::Param { key, op, param },
+          var,
+          span,
+        ));
+      }
+    };
+    (value.check_operand_of(op)).map_err(|(kind, found)| span.error(kind).with_found(found))?;
+
+    let _type = value.to_type();
+    let pattern_attr = PatternAttr {
+      key,
+      op,
+      value,
+      _type,
+    };
+    Ok(Condition::new(pattern_attr.into(), var, span))
+  }
+
+  fn expect_bound_var(&self, cursor: &mut Cursor) -> Result<String, ParseError> {
+    let var_span = cursor.peek().clone();
+    let var = cursor.expect_ident("a variable")?;
+    let is_local_elsewhere = self
+      .negated_vars
+      .get(&var)
+      .is_some_and(|&idx| self.curr_negated != Some(idx));
+    if is_local_elsewhere {
+      return Err(
+        var_span
+          .error(ParseErrorKind::UndeclaredVertex)
+          .with_found(format!("variable `{var}` is local to its `NOT EXISTS`")),
+      );
+    }
+    if !self.v_labels.contains_key(&var) && !self.e_2_vv.contains_key(&var) {
+      let kind = ParseErrorKind::UndeclaredVertex;
+      return Err(
+        var_span
+          .error(kind)
+          .with_found(format!("variable `{var}` is not defined in `MATCH`")),
+      );
+    }
+    Ok(var)
+  }
+
+  /// `var.prop`, where `var` must be bound in `MATCH`
+  fn parse_property(&self, cursor: &mut Cursor) -> Result<(String, String), ParseError> {
+    let var = self.expect_bound_var(cursor)?;
+    cursor.expect_sym(".")?;
+    let key = cursor.expect_ident("a property key")?;
+    Ok((var, key))
+  }
+
+  fn parse_op(cursor: &mut Cursor) -> Result<Op, ParseError> {
+    let op = match &cursor.peek().token {
+      Token::Sym("=") => Op::Eq,
+      Token::Sym("<>") | Token::Sym("!=") => Op::Ne,
+      Token::Sym(">") => Op::Gt,
+      Token::Sym(">=") => Op::Ge,
+      Token::Sym("<") => Op::Lt,
+      Token::Sym("<=") => Op::Le,
+      Token::Sym("=~") => Op::Regex,
+      _ if cursor.is_keyword("CONTAINS") => Op::Contains,
+      _ if cursor.is_keyword("STARTS") || cursor.is_keyword("ENDS") => {
+        let op = if cursor.eat_keyword("STARTS") {
+          Op::StartsWith
+        } else {
+          cursor.next();
+          Op::EndsWith
+        };
+        cursor.expect_keyword("WITH")?;
+        return Ok(op);
+      }
+      _ => return Err(cursor.expected("a comparison operator")),
+    };
+    cursor.next();
+    Ok(op)
+  }
+
+  /// `true`, `false` or `null`
+  fn is_literal_keyword(cursor: &Cursor) -> bool {
+    ["true", "false", "null"]
+      .iter()
+      .any(|keyword| cursor.is_keyword(keyword))
+  }
+
+  /// `date(...)` or `datetime(...)`
+  fn is_temporal_call(cursor: &Cursor) -> bool {
+    (cursor.is_keyword("date") || cursor.is_keyword("datetime")) && cursor.is_sym_at(1, "(")
+  }
+
+  /// `date('2010-03-24')` or `datetime('2010-03-24T12:00:00Z')`
+  fn parse_temporal(cursor: &mut Cursor) -> Result<AttrValue, ParseError> {
+    let is_date = cursor.is_keyword("date");
+    let func = if is_date { "date" } else { "datetime" };
+    cursor.next();
+    cursor.expect_sym("(")?;
+    let at = cursor.peek().clone();
+    let Token::Str(iso) = &at.token else {
+      return Err(cursor.expected("a quoted ISO-8601 string"));
+    };
+    let value = AttrValue::parse_temporal_of(iso, is_date).ok_or_else(|| {
+      at.error(ParseErrorKind::MalformedLiteral)
+        .with_expected(format!("an ISO-8601 {func}"))
+        .with_found(at.token.describe())
+    })?;
+    cursor.next();
+    cursor.expect_sym(")")?;
+    Ok(value)
+  }
+
+  /// A literal or a `$param`
+  fn parse_operand(cursor: &mut Cursor) -> Result<Operand, ParseError> {
+    if let Token::Param(param) = &cursor.peek().token {
+      let param = param.clone();
+      cursor.next();
+      return Ok(Operand::Param(param));
+    }
+    Self::parse_literal(cursor).map(Operand::Literal)
+  }
+
+  fn parse_literal(cursor: &mut Cursor) -> Result<AttrValue, ParseError> {
+    if Self::is_temporal_call(cursor) {
+      return Self::parse_temporal(cursor);
+    }
+    for (keyword, value) in [
+      ("true", AttrValue::Bool(true)),
+      ("false", AttrValue::Bool(false)),
+      ("null", AttrValue::Null),
+    ] {
+      if cursor.eat_keyword(keyword) {
+        return Ok(value);
+      }
+    }
+    let negative = cursor.eat_sym("-");
+    let sign = if negative { "-" } else { "" };
+    let at = cursor.peek().clone();
+    let malformed = || {
+      at.error(ParseErrorKind::MalformedLiteral)
+        .with_found(at.token.describe())
+    };
+    let value = match &at.token {
+      Token::Int(raw) => format!("{sign}{raw}")
+        .parse::<i64>()
+        .map(AttrValue::Int)
+        .map_err(|_| malformed())?,
+      Token::Float(raw) => format!("{sign}{raw}")
+        .parse::<f64>()
+        .map(AttrValue::Float)
+        .map_err(|_| malformed())?,
+      Token::Str(s) if !negative => AttrValue::String(s.clone()),
+      _ => return Err(cursor.expected("a literal")),
+    };
+    cursor.next();
+    Ok(value)
+  }
+
+  /// `var` or `var.prop`
+  fn parse_projection(&self, cursor: &mut Cursor) -> Result<String, ParseError> {
+    let var = self.expect_bound_var(cursor)?;
+    if cursor.eat_sym(".") {
+      let key = cursor.expect_ident("a property key")?;
+      Ok(format!("{var}.{key}"))
+    } else {
+      Ok(var)
+    }
+  }
+
+  fn parse_return(&mut self, cursor: &mut Cursor) -> Result<(), ParseError> {
+    let _ = cursor.eat_keyword("DISTINCT");
+    if cursor.eat_sym("*") {
+      self.return_items = self
+        .v_labels
+        .keys()
+        .chain(self.e_2_vv.keys())
+        .filter(|var| !var.starts_with("__anon_") && !self.negated_vars.contains_key(*var))
+        .cloned()
+        .collect();
+      self.return_items.sort();
+      return Ok(());
+    }
+    loop {
+      let item = self.parse_projection(cursor)?;
+      if cursor.eat_keyword("AS") {
+        cursor.expect_ident("an alias")?;
+      }
+      self.return_items.push(item);
+      if !cursor.eat_sym(",") {
+        break;
+      }
+    }
+    Ok(())
+  }
+}
+/// The right-hand side of a comparison.
+enum Operand {
+  Literal(AttrValue),
+  Param(String),
+}
+
+/// A (sub-)expression of `WHERE`, with the variables it refers to.
+struct Condition {
+  pred: CrossPredicate,
+  vars: Vec<String>,
+  span: Spanned,
+}
+
+impl Condition {
+  fn new(pred: PatternPredicate, var: String, span: Spanned) -> Self {
+    Self {
+      pred: CrossPredicate::On {
+        var: var.clone(),
+        pred,
+      },
+      vars: vec![var],
+      span,
+    }
+  }
+
+  fn cross(pred: CrossPredicate, vars: [String; 2], span: Spanned) -> Self {
+    Self {
+      pred,
+      vars: vars.into_iter().unique().collect(),
+      span,
+    }
+  }
+
+  fn combine(conds: Vec<Condition>, op: fn(Vec<CrossPredicate>) -> CrossPredicate) -> Self {
+    let span = conds[0].span.clone();
+    let mut vars = vec![];
+    let mut preds = vec![];
+    for cond in conds {
+      for var in cond.vars {
+        if !vars.contains(&var) {
+          vars.push(var);
+        }
+      }
+      preds.push(cond.pred);
+    }
+    Self {
+      pred: op(preds),
+      vars,
+      span,
+    }
+  }
+
+  /// The conjunction of (non-empty) `conds`.
+  fn all(mut conds: Vec<Condition>) -> Self {
+    if conds.len() == 1 {
+      return conds.pop().unwrap();
+    }
+    Self::combine(conds, CrossPredicate::and)
+  }
+}
+
+#[cfg(test)]
+mod test_cypher_parser {
+  use super::*;
+  use crate::parser::PatternParser;
+  use crate::schemas::{EBase, VBase};
+  use hashbrown::HashSet;
+
+  fn summarize(
+    graph: &DynGraph<PatternVertex, PatternEdge>,
+  ) -> (HashSet<PatternVertex>, HashSet<PatternEdge>) {
+    (
+      graph.view_v_entities().into_iter().cloned().collect(),
+      graph.view_e_entities().into_iter().cloned().collect(),
+    )
+  }
+
+  #[test]
+  fn test_cypher_matches_hand_written_queries() {
+    let root = project_root::get_project_root().unwrap().join("resources");
+
+    let mut names = (std::fs::read_dir(root.join("cypher")).unwrap())
+      .map(|entry| entry.unwrap().path())
+      .filter(|path| path.extension().is_some_and(|ext| ext == "cypher"))
+      .map(|path| path.file_stem().unwrap().to_string_lossy().into_owned())
+      .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names.len(), 20);
+
+    for name in names {
+      let cypher = std::fs::read_to_string(root.join("cypher").join(format!("{name}.cypher")));
+      let txt = std::fs::read_to_string(root.join("queries").join(format!("ldbc-{name}.txt")));
+
+      let mut cypher_parser = CypherParser::new(cypher.unwrap());
+      cypher_parser.parse().unwrap();
+      let mut txt_parser = PatternParser::new(txt.unwrap());
+      txt_parser.parse().unwrap();
+
+      let txt_graph = txt_parser.take_as_pattern_graph();
+      let cypher_graph = cypher_parser.take_as_pattern_graph();
+      let (mut cypher_vs, mut cypher_es) = summarize(&cypher_graph);
+      // an unlabeled vertex matches the label given by hand
+      for v in cypher_graph.view_v_entities() {
+        if !v.label.is_empty() {
+          continue;
+        }
+        let labeled = txt_graph.view_v_from_vid(&v.vid).unwrap();
+        cypher_vs.remove(v);
+        cypher_vs.insert(PatternVertex {
+          label: labeled.label.clone(),
+          ..v.clone()
+        });
+      }
+      // the undirected edges are directed by hand, either way
+      let mut undirected = vec![];
+      for e in cypher_graph.view_e_entities() {
+        if e.direction != EdgeDirection::Both {
+          continue;
+        }
+        let directed = txt_graph.view_e_from_eid(&e.eid).unwrap();
+        assert!(
+          (e.src_vid(), e.dst_vid()) == (directed.src_vid(), directed.dst_vid())
+            || (e.src_vid(), e.dst_vid()) == (directed.dst_vid(), directed.src_vid())
+        );
+        undirected.push(e.eid.as_str());
+        cypher_es.remove(e);
+        cypher_es.insert(directed.clone());
+      }
+      undirected.sort();
+      let expected_undirected: &[&str] = if name == "bi-3" { &["c", "g"] } else { &[] };
+      assert_eq!(
+        undirected, expected_undirected,
+        "undirected edges of `{name}`"
+      );
+
+      assert_eq!(
+        (cypher_vs, cypher_es),
+        summarize(&txt_graph),
+        "pattern mismatch on `{name}`"
+      );
+    }
+  }
+
+  #[test]
+  fn test_cypher_undirected() {
+    let root = project_root::get_project_root().unwrap().join("resources");
+    let cypher = std::fs::read_to_string(root.join("cypher").join("bi-3.cypher")).unwrap();
+    let mut parser = CypherParser::new(cypher);
+    parser.parse().unwrap();
+    let graph = parser.take_as_pattern_graph();
+
+    for (eid, direction) in [
+      ("a", EdgeDirection::Out),
+      ("c", EdgeDirection::Both),
+      ("g", EdgeDirection::Both),
+    ] {
+      assert_eq!(graph.view_e_from_eid(eid).unwrap().direction, direction);
+    }
+    let c = graph.view_e_from_eid("c").unwrap();
+    assert_eq!((c.src_vid(), c.dst_vid()), ("person", "forum"));
+  }
+
+  #[test]
+  fn test_cypher_var_length() {
+    let mut parser = CypherParser::new(
+      "MATCH (a: Person)-[e: knows*1..3]-(b: Person)<-[:likes*2]-(c: Person),
+             (c)-[f: knows*..2]->(a)
+      RETURN a"
+        .to_string(),
+    );
+    parser.parse().unwrap();
+    let graph = parser.take_as_pattern_graph();
+    let e = graph.view_e_from_eid("e").unwrap();
+    assert_eq!(e.var_length, VarLength::new(1, 3, Default::default()));
+    assert_eq!(e.direction, EdgeDirection::Both);
+    assert_eq!(
+      graph.view_e_from_eid("f").unwrap().var_length,
+      VarLength::new(1, 2, Default::default())
+    );
+    let likes = graph
+      .e_entities()
+      .values()
+      .find(|e| e.label == "likes")
+      .unwrap();
+    assert_eq!(likes.var_length, VarLength::new(2, 2, Default::default()));
+    assert_eq!((likes.src_vid(), likes.dst_vid()), ("c", "b"));
+
+    for unbounded in ["*", "*2..", "*0..2", "*3..1"] {
+      let err = CypherParser::new(format!("MATCH (a: A)-[e: E{unbounded}]->(b: B) RETURN a"))
+        .parse()
+        .unwrap_err();
+      assert_eq!(err.kind(), ParseErrorKind::Unsupported);
+      assert_eq!((err.line(), err.col()), (1, 19));
+    }
+  }
+
+  #[test]
+  fn test_cypher_unsupported_clauses() {
+    // they'd change the matches reported, which are all of them, unordered
+    for clause in ["ORDER BY a.id DESC", "SKIP 10", "LIMIT 20"] {
+      let err = CypherParser::new(format!("MATCH (a: A) RETURN a {clause}"))
+        .parse()
+        .unwrap_err();
+      assert_eq!(err.kind(), ParseErrorKind::Unsupported);
+      assert_eq!((err.line(), err.col()), (1, 23));
+    }
+  }
+
+  #[test]
+  fn test_cypher_anonymous_and_flipped() {
+    let mut parser = CypherParser::new(
+      "MATCH (p: Person)<-[:hasCreator]-(: Post) WHERE 42 > p.id RETURN p.id AS id".to_string(),
+    );
+    parser.parse().unwrap();
+    assert_eq!(parser.return_items(), ["p.id"]);
+
+    let graph = parser.take_as_pattern_graph();
+    assert_eq!(graph.get_v_count(), 2);
+    let edge = graph.view_e_entities()[0];
+    assert_eq!(edge.label(), "hasCreator");
+    assert_eq!(edge.dst_vid(), "p");
+
+    let person = graph.view_v_from_vid("p").unwrap();
+    assert_eq!(person.label(), "Person");
+    let attr = person.attrs[0].as_cmp().unwrap();
+    assert_eq!(attr.op, Op::Lt);
+    assert_eq!(attr.value, AttrValue::Int(42));
+  }
+
+  #[test]
+  fn test_cypher_conjunction_on_same_variable() {
+    let mut parser = CypherParser::new(
+      "MATCH (m: Message) WHERE m.creationDate >= 10 AND m.creationDate < 20 RETURN m".to_string(),
+    );
+    parser.parse().unwrap();
+    let graph = parser.take_as_pattern_graph();
+    let attrs = &graph.view_v_from_vid("m").unwrap().attrs;
+    assert_eq!(attrs.len(), 2);
+    let ops = (attrs[0].as_cmp().unwrap().op, attrs[1].as_cmp().unwrap().op);
+    assert_eq!(ops, (Op::Ge, Op::Lt));
+  }
+
+  #[test]
+  fn test_cypher_boolean_expression() {
+    let mut parser = CypherParser::new(
+      "MATCH (a: A)-[e: E]->(b: B)
+       WHERE (a.id = 1 OR NOT a.name IN ['x', 'y']) AND b.id BETWEEN 1 AND 3 AND e.w > 0
+       RETURN a"
+        .to_string(),
+    );
+    parser.parse().unwrap();
+    let graph = parser.take_as_pattern_graph();
+
+    let a_attrs = &graph.view_v_from_vid("a").unwrap().attrs;
+    assert_eq!(a_attrs.len(), 1);
+    assert!(matches!(&a_attrs[0], PatternPredicate::Or { or } if or.len() == 2));
+    let b_attrs = &graph.view_v_from_vid("b").unwrap().attrs;
+    assert!(matches!(&b_attrs[..], [PatternPredicate::Between { .. }]));
+    assert_eq!(graph.view_e_from_eid("e").unwrap().attrs.len(), 1);
+
+    // a disjunction across variables is checked on the partial matches
+    let mut parser = CypherParser::new(
+      "MATCH (a: A)-[e: E]->(b: B)\nWHERE a.id = 1 OR b.id = 2\nRETURN a".to_string(),
+    );
+    parser.parse().unwrap();
+    assert_eq!(parser.cross_predicates()[0].vars(), vec!["a", "b"]);
+    let graph = parser.take_as_pattern_graph();
+    assert!(graph.view_v_from_vid("a").unwrap().attrs.is_empty());
+  }
+
+  #[test]
+  fn test_cypher_string_predicates() {
+    let mut parser = CypherParser::new(
+      "MATCH (a: A) WHERE a.name STARTS WITH 'Ch' AND a.name =~ 'C.*' AND toLower(a.name) = 'china'
+       AND a.name < 'D' RETURN a"
+        .to_string(),
+    );
+    parser.parse().unwrap();
+    let graph = parser.take_as_pattern_graph();
+    let ops = graph.view_v_from_vid("a").unwrap().attrs.iter();
+    let ops = ops
+      .map(|pred| pred.as_cmp().unwrap().op)
+      .collect::<Vec<_>>();
+    assert_eq!(ops, [Op::StartsWith, Op::Regex, Op::EqIgnoreCase, Op::Lt]);
+
+    let mut parser =
+      CypherParser::new("MATCH (a: A) WHERE 'Ch' CONTAINS a.name RETURN a".to_string());
+    let err = parser.parse().unwrap_err();
+    assert_eq!(err.kind(), ParseErrorKind::Unsupported);
+    assert_eq!(err.col(), 25);
+
+    let mut parser = CypherParser::new("MATCH (a: A) WHERE a.id ENDS WITH 1 RETURN a".to_string());
+    let err = parser.parse().unwrap_err();
+    assert_eq!(err.kind(), ParseErrorKind::InvalidOperator);
+    assert_eq!(err.col(), 20);
+  }
+
+  #[test]
+  fn test_cypher_cross_predicates() {
+    let mut parser = CypherParser::new(
+      "MATCH (p1: Person)-[k: knows]->(p2: Person)
+       WHERE p1.id <> p2.id AND (p1.age < p2.age OR p1.age = 1) AND p2.id = p2.parentId
+       RETURN *"
+        .to_string(),
+    );
+    parser.parse().unwrap();
+    let preds = parser.cross_predicates();
+    assert_eq!(
+      preds.iter().map(ToString::to_string).collect::<Vec<_>>(),
+      [
+        "p1.id <> p2.id",
+        "(p1.age < p2.age OR p1.age = 1)",
+        "p2.id = p2.parentId"
+      ]
+    );
+    let graph = parser.take_as_pattern_graph();
+    assert!(graph.view_v_from_vid("p1").unwrap().attrs.is_empty());
+
+    let parse_err = |src: &str| CypherParser::new(src.to_string()).parse().unwrap_err();
+    let err = parse_err(
+      "MATCH (a: A) OPTIONAL MATCH (a)-[:E]->(b: B)-[:E]->(c: C) WHERE b.id < c.id RETURN a",
+    );
+    assert_eq!(err.kind(), ParseErrorKind::Unsupported);
+    let err = parse_err("MATCH (a: A) WHERE a.id = b.id RETURN a");
+    assert_eq!(err.kind(), ParseErrorKind::UndeclaredVertex);
+    assert_eq!(err.token(), Some("b"));
+  }
+
+  #[test]
+  fn test_cypher_params() {
+    let mut parser = CypherParser::new(
+      "MATCH (p: Person)-[:knows]->(f: Person)
+       WHERE p.id = $personId AND $minAge <= f.age AND toLower(f.name) = $name
+       RETURN f"
+        .to_string(),
+    );
+    parser.parse().unwrap();
+    let graph = parser.take_as_pattern_graph();
+    let p_attrs = &graph.view_v_from_vid("p").unwrap().attrs;
+    assert_eq!(p_attrs[0].params(), vec!["personId"]);
+    let f_attrs = &graph.view_v_from_vid("f").unwrap().attrs;
+    assert!(matches!(
+      &f_attrs[..],
+      [
+        PatternPredicate::Param { op: Op::Ge, .. },
+        PatternPredicate::Param {
+          op: Op::EqIgnoreCase,
+          ..
+        }
+      ]
+    ));
+
+    let err = CypherParser::new("MATCH (a: A) WHERE a.id = $ RETURN a".to_string())
+      .parse()
+      .unwrap_err();
+    assert_eq!(err.kind(), ParseErrorKind::MalformedLiteral);
+    assert_eq!(err.col(), 27);
+  }
+
+  #[test]
+  fn test_cypher_undefined_variable() {
+    let mut parser = CypherParser::new("MATCH (a: A)\nWHERE b.id = 1\nRETURN a".to_string());
+    let err = parser.parse().unwrap_err();
+    assert_eq!(err.kind(), ParseErrorKind::UndeclaredVertex);
+    assert_eq!((err.line(), err.col()), (2, 7));
+    assert_eq!(err.token(), Some("b"));
+  }
+
+  #[test]
+  fn test_cypher_not_exists() {
+    let mut parser = CypherParser::new(
+      "MATCH (p: Person)-[:knows]->(q: Person)
+       WHERE p.id = 1 AND NOT EXISTS { MATCH (m: Message)-[h: hasCreator]->(p), (m)-[:hasTag]->(t: Tag) WHERE t.name = 'x' }
+         AND NOT (p)-[:likes]->(q)
+       RETURN *"
+        .to_string(),
+    );
+    parser.parse().unwrap();
+    assert_eq!(parser.return_items(), ["p", "q"]);
+    let graph = parser.take_as_pattern_graph();
+    assert!(graph.view_v_from_vid("m").unwrap().negated);
+    assert!(!graph.view_v_from_vid("p").unwrap().negated);
+    assert_eq!(graph.view_v_from_vid("t").unwrap().attrs.len(), 1);
+    assert_eq!(graph.view_v_from_vid("p").unwrap().attrs.len(), 1);
+    let sub_patterns = graph.sub_patterns(SubPatternKind::Negated);
+    assert_eq!(sub_patterns.len(), 2);
+    assert_eq!(graph.required_part().get_e_count(), 1);
+
+    let parse_err = |src: &str| CypherParser::new(src.to_string()).parse().unwrap_err();
+    let err = parse_err("MATCH (a: A) WHERE a.id = 1 OR NOT (a)-[:E]->(:B) RETURN a");
+    assert_eq!(err.kind(), ParseErrorKind::Unsupported);
+    assert_eq!(err.col(), 32);
+    let err =
+      parse_err("MATCH (a: A) WHERE NOT EXISTS { (a)-[:E]->(b: B) WHERE a.id = 1 } RETURN a");
+    assert_eq!(err.kind(), ParseErrorKind::Unsupported);
+    let err = parse_err("MATCH (a: A) WHERE NOT EXISTS { (a)-[:E]->(b: B) } RETURN b");
+    assert_eq!(err.kind(), ParseErrorKind::UndeclaredVertex);
+    let err = parse_err("MATCH (a: A), (b: B) WHERE NOT (a)-[:E]->(b) RETURN a");
+    assert_eq!(err.kind(), ParseErrorKind::Unsupported);
+    assert_eq!(err.col(), 28);
+  }
+
+  #[test]
+  fn test_cypher_optional_match() {
+    let mut parser = CypherParser::new(
+      "MATCH (p: Person)-[:isLocatedIn]->(c: City)
+       WHERE p.id = 1
+       OPTIONAL MATCH (p)-[s: studyAt]->(u: University) WHERE s.classYear > 2000
+       OPTIONAL MATCH (p)-[:knows]->(f: Person)
+       RETURN p, u, f"
+        .to_string(),
+    );
+    parser.parse().unwrap();
+    let graph = parser.take_as_pattern_graph();
+    assert!(graph.view_v_from_vid("u").unwrap().optional);
+    assert!(!graph.view_v_from_vid("p").unwrap().optional);
+    assert_eq!(graph.view_e_from_eid("s").unwrap().attrs.len(), 1);
+    let sub_patterns = graph.sub_patterns(SubPatternKind::Optional);
+    assert_eq!(sub_patterns.len(), 2);
+    assert!(
+      sub_patterns
+        .iter()
+        .all(|sub_pattern| sub_pattern.anchors == ["p"])
+    );
+    assert_eq!(graph.required_part().get_v_count(), 2);
+
+    let parse_err = |src: &str| CypherParser::new(src.to_string()).parse().unwrap_err();
+    let err = parse_err("MATCH (a: A) OPTIONAL MATCH (a)-[:E]->(b: B) WHERE a.id = 1 RETURN a");
+    assert_eq!(err.kind(), ParseErrorKind::Unsupported);
+    let err = parse_err("MATCH (a: A) OPTIONAL MATCH (b: B) RETURN a, b");
+    assert_eq!(err.kind(), ParseErrorKind::Unsupported);
+    assert_eq!(err.col(), 14);
+    let err = parse_err(
+      "MATCH (a: A) OPTIONAL MATCH (a)-[:E]->(b: B) OPTIONAL MATCH (b)-[:F]->(:C) RETURN a",
+    );
+    assert_eq!(err.kind(), ParseErrorKind::Unsupported);
+    let err =
+      parse_err("MATCH (a: A) OPTIONAL MATCH (a)-[:E]->(b: B) WHERE NOT (b)-[:F]->(:C) RETURN a");
+    assert_eq!(err.kind(), ParseErrorKind::Unsupported);
+  }
+}
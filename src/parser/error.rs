@@ -0,0 +1,166 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::Display)]
+pub enum ParseErrorKind {
+  #[strum(serialize = "unexpected token")]
+  UnexpectedToken,
+  #[strum(serialize = "unexpected end of input")]
+  UnexpectedEnd,
+  #[strum(serialize = "invalid operator")]
+  InvalidOperator,
+  #[strum(serialize = "malformed literal")]
+  MalformedLiteral,
+  #[strum(serialize = "count mismatch")]
+  CountMismatch,
+  #[strum(serialize = "duplicate vid")]
+  DuplicateVid,
+  #[strum(serialize = "duplicate eid")]
+  DuplicateEid,
+  #[strum(serialize = "undeclared vertex")]
+  UndeclaredVertex,
+  #[strum(serialize = "undeclared edge")]
+  UndeclaredEdge,
+  #[strum(serialize = "missing label")]
+  MissingLabel,
+  #[strum(serialize = "conflicting label")]
+  ConflictingLabel,
+  #[strum(serialize = "unsupported")]
+  Unsupported,
+  #[strum(serialize = "invalid matching order")]
+  InvalidOrder,
+}
+
+/// A position-aware parse error.
+///
+/// `line` and `col` are both 1-based, `col` counts chars (not bytes).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+  pub(crate) kind: ParseErrorKind,
+  pub(crate) line: usize,
+  pub(crate) col: usize,
+  pub(crate) token: Option<String>,
+  pub(crate) expected: Option<String>,
+  pub(crate) found: Option<String>,
+  pub(crate) source_line: Option<String>,
+}
+
+impl ParseError {
+  pub fn new(kind: ParseErrorKind, line: usize, col: usize) -> Self {
+    Self {
+      kind,
+      line,
+      col,
+      token: None,
+      expected: None,
+      found: None,
+      source_line: None,
+    }
+  }
+
+  pub fn with_token(mut self, token: impl Into<String>) -> Self {
+    self.token = Some(token.into());
+    self
+  }
+
+  pub fn with_expected(mut self, expected: impl Into<String>) -> Self {
+    self.expected = Some(expected.into());
+    self
+  }
+
+  pub fn with_found(mut self, found: impl Into<String>) -> Self {
+    self.found = Some(found.into());
+    self
+  }
+
+  /// Attach the offending source line (picked from `src`) for snippet rendering.
+  pub fn with_source(mut self, src: &str) -> Self {
+    if self.source_line.is_none() {
+      self.source_line = src
+        .lines()
+        .nth(self.line.saturating_sub(1))
+        .map(String::from);
+    }
+    self
+  }
+
+  /// Move an error reported against a sub-slice to its real position.
+  pub(crate) fn relocate(mut self, line: usize, col_offset: usize) -> Self {
+    self.line = line;
+    self.col += col_offset;
+    self
+  }
+}
+
+impl ParseError {
+  pub fn kind(&self) -> ParseErrorKind {
+    self.kind
+  }
+  pub fn line(&self) -> usize {
+    self.line
+  }
+  pub fn col(&self) -> usize {
+    self.col
+  }
+  pub fn token(&self) -> Option<&str> {
+    self.token.as_deref()
+  }
+  pub fn expected(&self) -> Option<&str> {
+    self.expected.as_deref()
+  }
+  pub fn found(&self) -> Option<&str> {
+    self.found.as_deref()
+  }
+
+  /// One-line summary, e.g. "unexpected token: expected `<vid> <label>`, found `a b c`".
+  pub fn message(&self) -> String {
+    let mut message = self.kind.to_string();
+    match (&self.expected, &self.found) {
+      (Some(expected), Some(found)) => message += &format!(": expected {expected}, found {found}"),
+      (Some(expected), None) => message += &format!(": expected {expected}"),
+      (None, Some(found)) => message += &format!(": {found}"),
+      (None, None) => {
+        if let Some(token) = &self.token {
+          message += &format!(": `{token}`");
+        }
+      }
+    }
+    message
+  }
+
+  /// The offending source line, with the offending token underlined.
+  pub fn snippet(&self) -> Option<String> {
+    let source_line = self.source_line.as_ref()?;
+    let line_no = self.line.to_string();
+    let gutter = " ".repeat(line_no.len());
+    let underline_len = self
+      .token
+      .as_ref()
+      .map(|token| token.chars().count())
+      .unwrap_or(1)
+      .max(1);
+
+    Some(format!(
+      "{gutter} |\n{line_no} | {source_line}\n{gutter} | {}{}",
+      " ".repeat(self.col.saturating_sub(1)),
+      "^".repeat(underline_len)
+    ))
+  }
+}
+
+impl Display for ParseError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "{}\n --> line {}, column {}",
+      self.message(),
+      self.line,
+      self.col
+    )?;
+    if let Some(snippet) = self.snippet() {
+      write!(f, "\n{snippet}")?;
+    }
+    Ok(())
+  }
+}
+
+impl std::error::Error for ParseError {}
@@ -0,0 +1,270 @@
+use super::{ParseError, ParseErrorKind};
+use crate::{
+  planner::pattern::Pattern,
+  schemas::{CrossPredicate, PatternEdge, PatternPredicate, PatternVertex},
+  utils::dyn_graph::DynGraph,
+};
+use hashbrown::HashSet;
+use serde::{Deserialize, Serialize};
+
+/// The JSON pattern format, where vertices, edges and predicates are laid out as in the plans:
+///
+/// ```json
+/// {
+///   "vertices": [
+///     {"vid": "c", "label": "City"},
+///     {"vid": "p", "label": "Person", "attrs": [{"attr": "id", "op": "=", "value": 42, "type": "int"}]},
+///     {"vid": "u", "label": "University", "optional": true}
+///   ],
+///   "edges": [
+///     {"eid": "e", "src_vid": "p", "dst_vid": "c", "label": "isLocatedIn"},
+///     {"eid": "k", "src_vid": "p", "dst_vid": "p", "label": "knows", "direction": "both",
+///      "var_length": {"min_hops": 1, "max_hops": 3, "mode": "walk"}},
+///     {"eid": "s", "src_vid": "p", "dst_vid": "u", "label": "studyAt", "optional": true}
+///   ],
+///   "cross_predicates": [
+///     {"left": {"var": "p", "attr": "firstName"}, "op": "!=", "right": {"var": "c", "attr": "name"}}
+///   ]
+/// }
+/// ```
+///
+/// - `vertices`: `vid`, `label`, and optionally `attrs` (a conjunction of [`PatternPredicate`]s),
+///   `optional` (`OPTIONAL MATCH`) and `negated` (`NOT EXISTS`)
+/// - `edges` (optional): `eid`, `src_vid`, `dst_vid`, `label`, and optionally `attrs`,
+///   `direction` (`out` by default, `in` or `both`), `var_length` (the `mode` is `trail` by
+///   default, `walk` or `acyclic`), `optional` and `negated`
+/// - `cross_predicates` (optional): a conjunction of [`CrossPredicate`]s
+///
+/// It's validated as the [`Pattern`] builder does, and [`Pattern::to_json`] writes it back.
+#[derive(Debug, Clone)]
+pub struct JsonPatternParser {
+  src: String,
+
+  pattern_graph: DynGraph<PatternVertex, PatternEdge>,
+  cross_predicates: Vec<CrossPredicate>,
+}
+
+/// The document of the JSON pattern format.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct JsonPattern {
+  pub(crate) vertices: Vec<PatternVertex>,
+  #[serde(default)]
+  pub(crate) edges: Vec<PatternEdge>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub(crate) cross_predicates: Vec<CrossPredicate>,
+}
+
+impl JsonPatternParser {
+  pub fn new(src: String) -> Self {
+    Self {
+      src,
+      pattern_graph: DynGraph::default(),
+      cross_predicates: vec![],
+    }
+  }
+
+  pub fn cross_predicates(&self) -> &[CrossPredicate] {
+    &self.cross_predicates
+  }
+
+  pub fn take_as_pattern_graph(self) -> DynGraph<PatternVertex, PatternEdge> {
+    self.pattern_graph
+  }
+
+  pub fn parse(&mut self) -> Result<(), ParseError> {
+    self.parse_inner().map_err(|e| e.with_source(&self.src))
+  }
+
+  fn parse_inner(&mut self) -> Result<(), ParseError> {
+    let json_pattern =
+      serde_json::from_str::<JsonPattern>(&self.src).map_err(|e| serde_error(&self.src, e))?;
+
+    let (pattern, step_vars) = json_pattern.replay();
+    let (pattern_graph, cross_predicates) = pattern.build().map_err(|e| {
+      let var = &step_vars[e.step() - 1];
+      // the second declaration is the offending one
+      let nth = match e.kind() {
+        ParseErrorKind::DuplicateVid | ParseErrorKind::DuplicateEid => 1,
+        _ => 0,
+      };
+      let (line, col) = locate(&self.src, var, nth);
+      ParseError::new(e.kind(), line, col)
+        .with_token(var.as_str())
+        .with_found(e.message())
+    })?;
+
+    self.pattern_graph = pattern_graph;
+    self.cross_predicates = cross_predicates;
+    Ok(())
+  }
+}
+
+impl JsonPattern {
+  /// Take the builder steps that declare the pattern, along with the var each step is about.
+  fn replay<'a>(&'a self) -> (Pattern, Vec<String>) {
+    let declared = (self.vertices.iter().map(|v| v.vid.as_str()))
+      .chain(self.edges.iter().map(|e| e.eid.as_str()))
+      .collect::<HashSet<_>>();
+    // the first undeclared one, if any
+    let offending = |vars: &[&'a str]| {
+      (vars.iter())
+        .find(|var| !declared.contains(*var))
+        .or(vars.first())
+        .copied()
+        .unwrap_or_default()
+    };
+
+    let mut pattern = Pattern::new();
+    let mut step_vars = vec![];
+    let mut take = |var: &str, step: &dyn Fn(Pattern) -> Pattern| {
+      pattern = step(std::mem::take(&mut pattern));
+      step_vars.push(var.to_string());
+    };
+
+    for v in &self.vertices {
+      take(&v.vid, &|p| p.vertex(&v.vid, &v.label));
+      for pred in &v.attrs {
+        take(&v.vid, &|p| match pred {
+          // so that the operand is checked, and the type follows the value
+          PatternPredicate::Cmp(attr) => p.where_v(&v.vid, &attr.key, attr.op, attr.value.clone()),
+          pred => p.where_v_pred(&v.vid, pred.clone()),
+        });
+      }
+    }
+    for e in &self.edges {
+      let var = match offending(&[&e.src_vid, &e.dst_vid]) {
+        vid if declared.contains(vid) => &e.eid,
+        vid => vid,
+      };
+      take(var, &|p| p.edge(&e.eid, &e.src_vid, &e.dst_vid, &e.label));
+      if !e.direction.is_out() {
+        take(&e.eid, &|p| p.direction(&e.eid, e.direction));
+      }
+      if let Some(var_length) = e.var_length {
+        take(&e.eid, &|p| {
+          p.var_length(
+            &e.eid,
+            var_length.min_hops,
+            var_length.max_hops,
+            var_length.mode,
+          )
+        });
+      }
+      for pred in &e.attrs {
+        take(&e.eid, &|p| match pred {
+          PatternPredicate::Cmp(attr) => p.where_e(&e.eid, &attr.key, attr.op, attr.value.clone()),
+          pred => p.where_e_pred(&e.eid, pred.clone()),
+        });
+      }
+    }
+
+    // flagged once all are declared, as the sub-patterns are checked as a whole
+    for (var, optional, negated) in (self.vertices.iter())
+      .map(|v| (&v.vid, v.optional, v.negated))
+      .chain(self.edges.iter().map(|e| (&e.eid, e.optional, e.negated)))
+    {
+      if optional {
+        take(var, &|p| p.optional(var));
+      }
+      if negated {
+        take(var, &|p| p.negated(var));
+      }
+    }
+
+    for pred in &self.cross_predicates {
+      take(offending(&pred.vars()), &|p| p.where_cross(pred.clone()));
+    }
+
+    (pattern, step_vars)
+  }
+}
+
+/// Where serde gave up, with the message but without its position suffix.
+fn serde_error(src: &str, e: serde_json::Error) -> ParseError {
+  let kind = match e.classify() {
+    serde_json::error::Category::Eof => ParseErrorKind::UnexpectedEnd,
+    _ => ParseErrorKind::UnexpectedToken,
+  };
+  let message = e.to_string();
+  let message = match message.rsplit_once(" at line ") {
+    Some((message, _)) => message.to_string(),
+    None => message,
+  };
+  // serde counts bytes, while `ParseError` counts chars
+  let line = src
+    .lines()
+    .nth(e.line().saturating_sub(1))
+    .unwrap_or_default();
+  let col = line
+    .char_indices()
+    .take_while(|(byte_idx, _)| *byte_idx < e.column().saturating_sub(1))
+    .count()
+    + 1;
+  ParseError::new(kind, e.line().max(1), col).with_found(message)
+}
+
+/// The (line, col) of the `nth` (0-based) `"<var>"` string in `src`,
+/// or else of the first one (or the very start).
+fn locate(src: &str, var: &str, nth: usize) -> (usize, usize) {
+  let quoted = serde_json::to_string(var).unwrap();
+  let mut matches = src.match_indices(&quoted).map(|(byte_idx, _)| byte_idx);
+  let first = matches.next();
+  let nth = match nth {
+    0 => first,
+    nth => matches.nth(nth - 1).or(first),
+  };
+  let Some(byte_idx) = nth else {
+    return (1, 1);
+  };
+  let before = &src[..byte_idx];
+  let line = before.matches('\n').count() + 1;
+  let line_start = before.rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+  // right after the opening quote
+  (line, before[line_start..].chars().count() + 2)
+}
+
+#[cfg(test)]
+mod test_json_parser {
+  use super::*;
+
+  fn parse_err(src: &str) -> ParseError {
+    JsonPatternParser::new(src.to_string()).parse().unwrap_err()
+  }
+
+  #[test]
+  fn test_json_errors() {
+    let err = parse_err("{\"vertices\": [\n  {\"vid\": \"a\", \"label\": \"A\"},\n]}");
+    assert_eq!(err.kind(), ParseErrorKind::UnexpectedToken);
+    assert_eq!(err.line(), 3);
+    let err = parse_err("{\"vertices\": [");
+    assert_eq!(err.kind(), ParseErrorKind::UnexpectedEnd);
+    let err = parse_err("{\"vertices\": [], \"edge\": []}");
+    assert!(err.found().unwrap().contains("unknown field `edge`"));
+
+    let src = r#"{
+  "vertices": [{"vid": "a", "label": "A"}],
+  "edges": [{"eid": "e", "src_vid": "a", "dst_vid": "b", "label": "E"}]
+}"#;
+    let err = parse_err(src);
+    assert_eq!(err.kind(), ParseErrorKind::UndeclaredVertex);
+    assert_eq!((err.line(), err.col(), err.token()), (3, 54, Some("b")));
+
+    let src = r#"{"vertices": [
+  {"vid": "a", "label": "A"},
+  {"vid": "a", "label": "B"}
+]}"#;
+    let err = parse_err(src);
+    assert_eq!(err.kind(), ParseErrorKind::DuplicateVid);
+    assert_eq!((err.line(), err.col()), (3, 12));
+
+    let src = r#"{"vertices": [
+  {"vid": "a", "label": "A", "attrs": [{"attr": "id", "op": "CONTAINS", "value": 1, "type": "int"}]}
+]}"#;
+    assert_eq!(parse_err(src).kind(), ParseErrorKind::InvalidOperator);
+    let src = r#"{"vertices": [{"vid": "a", "label": "A"}], "edges": [
+  {"eid": "e", "src_vid": "a", "dst_vid": "a", "label": "E", "var_length": {"min_hops": 3, "max_hops": 1}}
+]}"#;
+    assert_eq!(parse_err(src).kind(), ParseErrorKind::Unsupported);
+  }
+}
@@ -1,46 +1,76 @@
 use crate::{
-  schemas::{Eid, Label, PatternAttr, PatternEdge, PatternVertex, Vid},
-  utils::dyn_graph::DynGraph,
+  schemas::{
+    AttrRef, CrossPredicate, EdgeDirection, Eid, Label, Op, PathMode, PatternEdge,
+    PatternPredicate, PatternVertex, VarLength, Vid,
+  },
+  utils::{dyn_graph::DynGraph, sub_pattern::SubPatternKind},
 };
 use hashbrown::HashMap;
-use itertools::Itertools;
+use std::str::FromStr;
+
+pub mod cypher;
+pub mod error;
+pub mod json;
+pub mod serializer;
+
+pub use cypher::CypherParser;
+pub use error::{ParseError, ParseErrorKind};
+pub use json::JsonPatternParser;
+pub use serializer::TxtFormatError;
 
 #[derive(Debug, Clone)]
 pub struct PatternParser {
   src: String,
-  line: usize,
 
   v_labels: HashMap<Vid, Label>,
-  v_attrs: HashMap<Vid, PatternAttr>,
+  v_attrs: HashMap<Vid, Vec<PatternPredicate>>,
 
   e_2_vv: HashMap<Eid, (Vid, Vid)>,
   e_labels: HashMap<Eid, Label>,
-  e_attrs: HashMap<Eid, PatternAttr>,
+  e_attrs: HashMap<Eid, Vec<PatternPredicate>>,
+  e_directions: HashMap<Eid, EdgeDirection>,
+  e_var_lengths: HashMap<Eid, VarLength>,
+
+  v_sub_pattern_kinds: HashMap<Vid, SubPatternKind>,
+  e_sub_pattern_kinds: HashMap<Eid, SubPatternKind>,
+
+  cross_predicates: Vec<CrossPredicate>,
 }
 
 impl PatternParser {
   pub fn new(src: String) -> Self {
     Self {
       src,
-      line: 1,
       v_labels: HashMap::new(),
       v_attrs: HashMap::new(),
       e_2_vv: HashMap::new(),
       e_labels: HashMap::new(),
       e_attrs: HashMap::new(),
+      e_directions: HashMap::new(),
+      e_var_lengths: HashMap::new(),
+      v_sub_pattern_kinds: HashMap::new(),
+      e_sub_pattern_kinds: HashMap::new(),
+      cross_predicates: vec![],
     }
   }
 
+  /// The `<var>.<key> <op> <var>.<key>` lines.
+  pub fn cross_predicates(&self) -> &[CrossPredicate] {
+    &self.cross_predicates
+  }
+
   pub fn take_as_pattern_graph(mut self) -> DynGraph<PatternVertex, PatternEdge> {
     let mut pattern_graph = DynGraph::default();
 
     // vertices
     for (vid, label) in self.v_labels.drain() {
-      let attr = self.v_attrs.remove(&vid);
+      let attrs = self.v_attrs.remove(&vid).unwrap_or_default();
       let pattern_vertex = PatternVertex {
         vid: vid.clone(),
         label,
-        attr,
+        attrs,
+        optional: self.v_sub_pattern_kinds.get(&vid) == Some(&SubPatternKind::Optional),
+        negated: self.v_sub_pattern_kinds.get(&vid) == Some(&SubPatternKind::Negated),
       };
       pattern_graph.update_v(pattern_vertex, vid);
     }
@@ -48,13 +78,19 @@ impl PatternParser {
     // edges
     for (eid, (src_vid, dst_vid)) in self.e_2_vv.drain() {
       let label = self.e_labels.remove(&eid).unwrap();
-      let attr = self.e_attrs.remove(&eid);
+      let attrs = self.e_attrs.remove(&eid).unwrap_or_default();
+      let direction = self.e_directions.remove(&eid).unwrap_or_default();
+      let var_length = self.e_var_lengths.remove(&eid);
       let pattern_edge = PatternEdge {
         eid: eid.clone(),
         src_vid,
         dst_vid,
         label,
-        attr,
+        attrs,
+        direction,
+        var_length,
+        optional: self.e_sub_pattern_kinds.get(&eid) == Some(&SubPatternKind::Optional),
+        negated: self.e_sub_pattern_kinds.get(&eid) == Some(&SubPatternKind::Negated),
       };
       pattern_graph.update_e(pattern_edge, eid);
     }
@@ -62,101 +98,714 @@ impl PatternParser {
     pattern_graph
   }
 
-  pub fn parse(&mut self) {
-    let mut lines = self.src.lines().filter(|line| !line.trim().is_empty());
-    let cnt_args = lines
-      .next()
-      .expect("❌  Missing `count_args` line => Count(v, e, v_attr, e_attr).");
-
-    // Count(v, e, v_attr, e_attr)
-    let mut cnt_args = cnt_args.split_whitespace();
-    let v_cnt = cnt_args
-      .next()
-      .expect("❌  Missing 'vertex' count.")
-      .parse::<usize>()
-      .expect("❌  Invalid 'vertex' count.");
-    let e_cnt = cnt_args
-      .next()
-      .expect("❌  Missing 'edge' count.")
-      .parse::<usize>()
-      .expect("❌  Invalid 'edge' count.");
-    let v_attr_cnt = cnt_args
-      .next()
-      .expect("❌  Missing 'vertex attribute' count.")
-      .parse::<usize>()
-      .expect("❌  Invalid 'vertex attribute' count.");
-    let e_attr_cnt = cnt_args
-      .next()
-      .expect("❌  Missing 'edge attribute' count.")
-      .parse::<usize>()
-      .expect("❌  Invalid 'edge attribute' count.");
-    self.line += 1;
+  pub fn parse(&mut self) -> Result<(), ParseError> {
+    self.parse_inner().map_err(|e| e.with_source(&self.src))
+  }
+
+  fn parse_inner(&mut self) -> Result<(), ParseError> {
+    let mut lines = self
+      .src
+      .lines()
+      .enumerate()
+      .map(|(idx, line)| (idx + 1, line))
+      .filter(|(_, line)| !line.trim().is_empty());
+    // where to point at, once the input is exhausted
+    let eof = self
+      .src
+      .lines()
+      .enumerate()
+      .filter(|(_, line)| !line.trim().is_empty())
+      .last()
+      .map(|(idx, line)| (idx + 1, line.chars().count() + 1))
+      .unwrap_or((1, 1));
+
+    // Count(v, e, v_attr, e_attr, [cross])
+    let Some((line_no, cnt_args)) = lines.next() else {
+      return Err(
+        ParseError::new(ParseErrorKind::UnexpectedEnd, eof.0, eof.1)
+          .with_expected("`count_args` line => Count(v, e, v_attr, e_attr, [cross])"),
+      );
+    };
+    let cnt_line_no = line_no;
+    let cnt_args = split_with_cols(cnt_args);
+    let mut cnts = [0usize; 5];
+    for (idx, section) in SECTIONS.iter().enumerate() {
+      let Some(&(col, token)) = cnt_args.get(idx) else {
+        // the cross predicates are optional
+        if idx == SECTIONS.len() - 1 {
+          break;
+        }
+        return Err(end_of_line(line_no, &cnt_args).with_expected(format!("'{section}' count")));
+      };
+      cnts[idx] = token.parse::<usize>().map_err(|_| {
+        ParseError::new(ParseErrorKind::UnexpectedToken, line_no, col)
+          .with_token(token)
+          .with_expected(format!("'{section}' count"))
+          .with_found(format!("`{token}`"))
+      })?;
+    }
+    if let Some(&(col, token)) = cnt_args.get(SECTIONS.len()) {
+      return Err(
+        ParseError::new(ParseErrorKind::UnexpectedToken, line_no, col)
+          .with_token(token)
+          .with_expected("end of line")
+          .with_found(format!("`{token}`")),
+      );
+    }
+    let [v_cnt, e_cnt, v_attr_cnt, e_attr_cnt, cross_cnt] = cnts;
+
+    // declared `n` lines of a section, but only `found` are provided
+    let count_mismatch = |section: &str, declared: usize, found: usize, at: (usize, usize)| {
+      ParseError::new(ParseErrorKind::CountMismatch, at.0, at.1)
+        .with_expected(format!(
+          "{declared} '{section}' line(s), as declared on line {cnt_line_no}"
+        ))
+        .with_found(found.to_string())
+    };
+
+    // (line, col) of each vertex / edge declaration
+    let mut decl_lines = HashMap::new();
 
     // vertices
-    for _ in 0..v_cnt {
-      let line = lines.next().expect("❌  Missing 'vertex' line.");
-      let mut args = line.split_whitespace();
+    for idx in 0..v_cnt {
+      let Some((line_no, line)) = lines.next() else {
+        return Err(count_mismatch("vertex", v_cnt, idx, eof));
+      };
+      let args = split_with_cols(line);
 
-      let vid = args.next().expect("❌  Missing 'vid'.").to_string();
-      let label = args.next().expect("❌  Missing 'v_label'.").to_string();
+      // looks like the first edge line, so fewer vertices are provided than declared
+      if args.len() == 4
+        && self.v_labels.contains_key(args[1].1)
+        && self.v_labels.contains_key(args[2].1)
+      {
+        return Err(count_mismatch("vertex", v_cnt, idx, (line_no, 1)).with_token(line.trim()));
+      }
+      // `<vid> <v_label> [not | optional]`
+      let sub_pattern_kind = match args.as_slice() {
+        [_, _, (_, token)] => parse_sub_pattern_kind(token),
+        _ => None,
+      };
+      check_arity(
+        line_no,
+        &args[..args.len() - sub_pattern_kind.is_some() as usize],
+        &["vid", "v_label"],
+      )?;
 
-      self.v_labels.insert(vid, label);
+      let (col, vid) = args[0];
+      if self.v_labels.contains_key(vid) {
+        return Err(
+          ParseError::new(ParseErrorKind::DuplicateVid, line_no, col)
+            .with_token(vid)
+            .with_found(format!("vertex `{vid}` is declared more than once")),
+        );
+      }
+      self.v_labels.insert(vid.to_string(), args[1].1.to_string());
+      if let Some(kind) = sub_pattern_kind {
+        self.v_sub_pattern_kinds.insert(vid.to_string(), kind);
+      }
+      decl_lines.insert(vid.to_string(), (line_no, col));
     }
-    self.line += v_cnt;
 
     // edges
-    for _ in 0..e_cnt {
-      let line = lines.next().expect("❌  Missing 'edge' line.");
-      let mut args = line.split_whitespace();
+    for idx in 0..e_cnt {
+      let Some((line_no, line)) = lines.next() else {
+        return Err(count_mismatch("edge", e_cnt, idx, eof));
+      };
+      let args = split_with_cols(line);
 
-      let eid = args.next().expect("❌  Missing 'eid'.").to_string();
-      let src_vid = args.next().expect("❌  Missing 'src_vid'.").to_string();
-      let dst_vid = args.next().expect("❌  Missing 'dst_vid'.").to_string();
-      let label = args.next().expect("❌  Missing 'e_label'.").to_string();
+      // looks like the first attribute line, so fewer edges are provided than declared
+      let has_options = args.len() > 4 && parse_edge_options(line_no, &args[4..]).is_ok();
+      if args.len() != 4 && !has_options && args.len() >= 3 && self.v_labels.contains_key(args[0].1)
+      {
+        return Err(count_mismatch("edge", e_cnt, idx, (line_no, 1)).with_token(line.trim()));
+      }
+      // `<eid> <src_vid> <dst_vid> <e_label> [out | in | both] [*<min>..<max> [trail | walk | acyclic]] [not | optional]`
+      check_min_arity(line_no, &args, &["eid", "src_vid", "dst_vid", "e_label"])?;
+      let (direction, var_length, sub_pattern_kind) = parse_edge_options(line_no, &args[4..])?;
+
+      let (col, eid) = args[0];
+      if self.e_2_vv.contains_key(eid) || self.v_labels.contains_key(eid) {
+        return Err(
+          ParseError::new(ParseErrorKind::DuplicateEid, line_no, col)
+            .with_token(eid)
+            .with_found(format!("`{eid}` is declared more than once")),
+        );
+      }
+      for &(col, vid) in &args[1..3] {
+        if !self.v_labels.contains_key(vid) {
+          return Err(
+            ParseError::new(ParseErrorKind::UndeclaredVertex, line_no, col)
+              .with_token(vid)
+              .with_found(format!("edge `{eid}` references undeclared vertex `{vid}`")),
+          );
+        }
+      }
 
-      self.e_2_vv.insert(eid.clone(), (src_vid, dst_vid));
-      self.e_labels.insert(eid, label);
+      self.e_2_vv.insert(
+        eid.to_string(),
+        (args[1].1.to_string(), args[2].1.to_string()),
+      );
+      self.e_labels.insert(eid.to_string(), args[3].1.to_string());
+      self.e_directions.insert(eid.to_string(), direction);
+      if let Some(var_length) = var_length {
+        self.e_var_lengths.insert(eid.to_string(), var_length);
+      }
+      if let Some(kind) = sub_pattern_kind {
+        self.e_sub_pattern_kinds.insert(eid.to_string(), kind);
+      }
+      decl_lines.insert(eid.to_string(), (line_no, col));
     }
-    self.line += e_cnt;
 
     // vertex attributes
-    for _ in 0..v_attr_cnt {
-      let line = lines.next().expect("❌  Missing 'vertex attribute' line.");
-      let mut args = line.split_whitespace();
-
-      let vid = args.next().expect("❌  Missing 'vid'.").to_string();
-      let key = args
-        .next()
-        .expect("❌  Missing 'key' for `v_attr`.")
-        .to_string();
-      let raw_pred = args.collect_vec().join("");
-      if raw_pred.is_empty() {
-        panic!("❌  Missing 'predicate' for `v_attr`.");
+    for idx in 0..v_attr_cnt {
+      let Some((line_no, line)) = lines.next() else {
+        return Err(count_mismatch("vertex attribute", v_attr_cnt, idx, eof));
+      };
+      let args = split_with_cols(line);
+      check_min_arity(line_no, &args, &["vid", "key", "predicate"])?;
+
+      let (col, vid) = args[0];
+      if !self.v_labels.contains_key(vid) {
+        // looks like the first edge attribute line
+        if self.e_2_vv.contains_key(vid) {
+          let at = (line_no, 1);
+          return Err(
+            count_mismatch("vertex attribute", v_attr_cnt, idx, at).with_token(line.trim()),
+          );
+        }
+        return Err(
+          ParseError::new(ParseErrorKind::UndeclaredVertex, line_no, col)
+            .with_token(vid)
+            .with_found(format!("attribute on undeclared vertex `{vid}`")),
+        );
       }
 
-      let pattern_attr = PatternAttr::parse_from_raw(key, raw_pred);
-      self.v_attrs.insert(vid.clone(), pattern_attr);
+      let pattern_attr = parse_attr_line(line_no, line, &args)?;
+      self
+        .v_attrs
+        .entry(vid.to_string())
+        .or_default()
+        .push(pattern_attr);
     }
-    self.line += v_attr_cnt;
 
     // edge attributes
-    for _ in 0..e_attr_cnt {
-      let line = lines.next().expect("❌  Missing 'edge attribute' line.");
-      let mut args = line.split_whitespace();
-
-      let eid = args.next().expect("❌  Missing 'eid'.").to_string();
-      let key = args
-        .next()
-        .expect("❌  Missing 'key' for `e_attr`.")
-        .to_string();
-      let raw_pred = args.collect_vec().join("");
-      if raw_pred.is_empty() {
-        panic!("❌  Missing 'predicate' for `e_attr`.");
+    for idx in 0..e_attr_cnt {
+      let Some((line_no, line)) = lines.next() else {
+        return Err(count_mismatch("edge attribute", e_attr_cnt, idx, eof));
+      };
+      let args = split_with_cols(line);
+      check_min_arity(line_no, &args, &["eid", "key", "predicate"])?;
+
+      let (col, eid) = args[0];
+      if !self.e_2_vv.contains_key(eid) {
+        return Err(
+          ParseError::new(ParseErrorKind::UndeclaredEdge, line_no, col)
+            .with_token(eid)
+            .with_found(format!("attribute on undeclared edge `{eid}`")),
+        );
       }
 
-      let pattern_attr = PatternAttr::parse_from_raw(key, raw_pred);
-      self.e_attrs.insert(eid.clone(), pattern_attr);
+      let pattern_attr = parse_attr_line(line_no, line, &args)?;
+      self
+        .e_attrs
+        .entry(eid.to_string())
+        .or_default()
+        .push(pattern_attr);
+    }
+
+    // cross predicates
+    for idx in 0..cross_cnt {
+      let Some((line_no, line)) = lines.next() else {
+        return Err(count_mismatch("cross predicate", cross_cnt, idx, eof));
+      };
+      let args = split_with_cols(line);
+      let cross_predicate = self.parse_cross_line(line_no, &args)?;
+      self.cross_predicates.push(cross_predicate);
+    }
+
+    // nothing should be left
+    if let Some((line_no, line)) = lines.next() {
+      let total = v_cnt + e_cnt + v_attr_cnt + e_attr_cnt + cross_cnt;
+      return Err(
+        ParseError::new(ParseErrorKind::CountMismatch, line_no, 1)
+          .with_token(line.trim())
+          .with_expected(format!(
+            "end of input after {total} line(s), as declared on line {cnt_line_no}"
+          ))
+          .with_found("more lines"),
+      );
+    }
+
+    // the optional / negated sub-patterns should hang on the (connected) required part
+    if !self.v_sub_pattern_kinds.is_empty() || !self.e_sub_pattern_kinds.is_empty() {
+      let pattern_graph = self.clone().take_as_pattern_graph();
+      if let Err((var, reason)) = pattern_graph.check_sub_patterns() {
+        let (line_no, col) = decl_lines[&var];
+        return Err(
+          ParseError::new(ParseErrorKind::Unsupported, line_no, col)
+            .with_token(var)
+            .with_found(reason),
+        );
+      }
+    }
+
+    Ok(())
+  }
+}
+
+impl PatternParser {
+  /// `<var>.<key> <op> <var>.<key>`, where `<var>` is a declared vertex / edge.
+  fn parse_cross_line(
+    &self,
+    line_no: usize,
+    args: &[(usize, &str)],
+  ) -> Result<CrossPredicate, ParseError> {
+    check_min_arity(line_no, args, &["<var>.<key>", "op", "<var>.<key>"])?;
+
+    let parse_attr_ref = |&(col, token): &(usize, &str)| {
+      let Some((var, key)) = token
+        .split_once('.')
+        .filter(|(v, k)| !v.is_empty() && !k.is_empty())
+      else {
+        return Err(
+          ParseError::new(ParseErrorKind::UnexpectedToken, line_no, col)
+            .with_token(token)
+            .with_expected("`<var>.<key>`")
+            .with_found(format!("`{token}`")),
+        );
+      };
+      if !self.v_labels.contains_key(var) && !self.e_2_vv.contains_key(var) {
+        return Err(
+          ParseError::new(ParseErrorKind::UndeclaredVertex, line_no, col)
+            .with_token(var)
+            .with_found(format!("predicate on undeclared `{var}`")),
+        );
+      }
+      Ok(AttrRef::new(var, key))
+    };
+    let left = parse_attr_ref(&args[0])?;
+    let right = parse_attr_ref(args.last().unwrap())?;
+
+    let op_args = &args[1..args.len() - 1];
+    let raw_op = op_args.iter().map(|(_, token)| *token).collect::<Vec<_>>();
+    let op = Op::from_str(&raw_op.join(" ").to_uppercase()).map_err(|_| {
+      let (col, token) = op_args.first().copied().unwrap_or(args[1]);
+      ParseError::new(ParseErrorKind::InvalidOperator, line_no, col)
+        .with_token(token)
+        .with_expected(
+          "one of `=`, `!=`, `>`, `>=`, `<`, `<=`, `~=`, `=~`, `STARTS WITH`, `ENDS WITH`, `CONTAINS`",
+        )
+        .with_found(format!("`{}`", raw_op.join(" ")))
+    })?;
+
+    Ok(CrossPredicate::Cmp { left, op, right })
+  }
+}
+
+/// Marks a vertex / edge line as part of the negated sub-patterns.
+const STR_NEGATED: &str = "not";
+/// Marks a vertex / edge line as part of the optional sub-patterns.
+const STR_OPTIONAL: &str = "optional";
+
+fn parse_sub_pattern_kind(token: &str) -> Option<SubPatternKind> {
+  match token {
+    STR_NEGATED => Some(SubPatternKind::Negated),
+    STR_OPTIONAL => Some(SubPatternKind::Optional),
+    _ => None,
+  }
+}
+
+const SECTIONS: [&str; 5] = [
+  "vertex",
+  "edge",
+  "vertex attribute",
+  "edge attribute",
+  "cross predicate",
+];
+
+/// Split a line by whitespaces, keeping the (1-based, char-wise) column of each token.
+fn split_with_cols(line: &str) -> Vec<(usize, &str)> {
+  let mut res = vec![];
+  let mut start = None;
+  let mut col = 0;
+  for (byte_idx, c) in line.char_indices() {
+    col += 1;
+    match (c.is_whitespace(), start) {
+      (false, None) => start = Some((col, byte_idx)),
+      (true, Some((start_col, start_idx))) => {
+        res.push((start_col, &line[start_idx..byte_idx]));
+        start = None;
+      }
+      _ => {}
+    }
+  }
+  if let Some((start_col, start_idx)) = start {
+    res.push((start_col, &line[start_idx..]));
+  }
+  res
+}
+
+fn end_of_line(line_no: usize, args: &[(usize, &str)]) -> ParseError {
+  let col = args
+    .last()
+    .map(|(col, token)| col + token.chars().count() + 1)
+    .unwrap_or(1);
+  ParseError::new(ParseErrorKind::UnexpectedEnd, line_no, col).with_found("end of line")
+}
+
+fn check_min_arity(
+  line_no: usize,
+  args: &[(usize, &str)],
+  names: &[&str],
+) -> Result<(), ParseError> {
+  if let Some(name) = names.get(args.len()) {
+    return Err(end_of_line(line_no, args).with_expected(format!("'{name}'")));
+  }
+  Ok(())
+}
+
+fn check_arity(line_no: usize, args: &[(usize, &str)], names: &[&str]) -> Result<(), ParseError> {
+  check_min_arity(line_no, args, names)?;
+  if let Some(&(col, token)) = args.get(names.len()) {
+    return Err(
+      ParseError::new(ParseErrorKind::UnexpectedToken, line_no, col)
+        .with_token(token)
+        .with_expected("end of line")
+        .with_found(format!("`{token}`")),
+    );
+  }
+  Ok(())
+}
+
+/// `[out | in | both] [*<min>..<max> [trail | walk | acyclic]] [not]`, following the `e_label`.
+///
+/// The hops may also be `*<hops>` or `*..<max>` (from 1 hop).
+fn parse_edge_options(
+  line_no: usize,
+  args: &[(usize, &str)],
+) -> Result<(EdgeDirection, Option<VarLength>, Option<SubPatternKind>), ParseError> {
+  let mut args = args.iter().copied().peekable();
+
+  let direction = args
+    .next_if(|&(_, token)| EdgeDirection::from_str(token).is_ok())
+    .map(|(_, token)| EdgeDirection::from_str(token).unwrap());
+
+  let mut var_length = None;
+  let mut has_mode = false;
+  if let Some((col, token)) = args.next_if(|&(_, token)| token.starts_with('*')) {
+    let (min_hops, max_hops) = parse_hops(&token[1..]).ok_or_else(|| {
+      ParseError::new(ParseErrorKind::MalformedLiteral, line_no, col)
+        .with_token(token)
+        .with_expected("`*<min>..<max>`, `*<hops>` or `*..<max>`")
+        .with_found(format!("`{token}`"))
+    })?;
+    let mode = args
+      .next_if(|&(_, token)| PathMode::from_str(token).is_ok())
+      .map(|(_, token)| PathMode::from_str(token).unwrap());
+    has_mode = mode.is_some();
+    let mode = mode.unwrap_or_default();
+    let bounded = VarLength::new(min_hops.unwrap_or(1), max_hops.unwrap_or(0), mode);
+    var_length = Some(bounded.ok_or_else(|| {
+      ParseError::new(ParseErrorKind::Unsupported, line_no, col)
+        .with_token(token)
+        .with_found(format!("`{token}`, the hops should be `1 <= min <= max`"))
+    })?);
+  }
+
+  let sub_pattern_kind = args
+    .next_if(|&(_, token)| parse_sub_pattern_kind(token).is_some())
+    .and_then(|(_, token)| parse_sub_pattern_kind(token));
+
+  if let Some((col, token)) = args.next() {
+    let expected = match (direction, var_length) {
+      _ if sub_pattern_kind.is_some() => "end of line",
+      _ if has_mode => "`not`, `optional` or end of line",
+      (_, Some(_)) => "`trail`, `walk`, `acyclic`, `not`, `optional` or end of line",
+      (Some(_), None) => "`*<min>..<max>`, `not`, `optional` or end of line",
+      (None, None) => "`out`, `in`, `both`, `*<min>..<max>`, `not`, `optional` or end of line",
+    };
+    return Err(
+      ParseError::new(ParseErrorKind::UnexpectedToken, line_no, col)
+        .with_token(token)
+        .with_expected(expected)
+        .with_found(format!("`{token}`")),
+    );
+  }
+
+  Ok((direction.unwrap_or_default(), var_length, sub_pattern_kind))
+}
+
+/// `<hops>`, `<min>..<max>`, `..<max>` or `<min>..`, where a missing bound is `None`.
+fn parse_hops(bounds: &str) -> Option<(Option<usize>, Option<usize>)> {
+  let parse_bound = |s: &str| match s {
+    "" => Ok(None),
+    s => s.parse::<usize>().map(Some),
+  };
+  match bounds.split_once("..") {
+    Some((min_hops, max_hops)) => Some((parse_bound(min_hops).ok()?, parse_bound(max_hops).ok()?)),
+    None if bounds.is_empty() => Some((None, None)),
+    None => {
+      let hops = bounds.parse::<usize>().ok()?;
+      Some((Some(hops), Some(hops)))
+    }
+  }
+}
+
+/// `<id> <key> <predicate...>`, where the predicate keeps its inner whitespaces.
+fn parse_attr_line(
+  line_no: usize,
+  line: &str,
+  args: &[(usize, &str)],
+) -> Result<PatternPredicate, ParseError> {
+  let (pred_col, _) = args[2];
+  let raw_pred = line
+    .chars()
+    .skip(pred_col - 1)
+    .collect::<String>()
+    .trim_end()
+    .to_string();
+  PatternPredicate::parse_from_raw(args[1].1.to_string(), raw_pred)
+    .map_err(|e| e.relocate(line_no, pred_col - 1))
+}
+
+#[cfg(test)]
+mod test_pattern_parser {
+  use super::*;
+  use crate::schemas::{AttrValue, Op};
+
+  fn parse_err(src: &str) -> ParseError {
+    PatternParser::new(src.to_string()).parse().unwrap_err()
+  }
+
+  #[test]
+  fn test_count_mismatch() {
+    let err = parse_err("3 1 0 0\n\na A\nb B\n\ne a b E\n");
+    assert_eq!(err.kind(), ParseErrorKind::CountMismatch);
+    assert_eq!((err.line(), err.col()), (6, 1));
+    assert!(err.to_string().contains("6 | e a b E"));
+
+    let err = parse_err("1 0 0 0\na A\nb B\n");
+    assert_eq!(err.kind(), ParseErrorKind::CountMismatch);
+    assert_eq!(err.line(), 3);
+  }
+
+  #[test]
+  fn test_edge_direction() {
+    let mut parser =
+      PatternParser::new("2 3 0 0\na A\nb B\ne1 a b E\ne2 a b E in\ne3 a b E BOTH\n".into());
+    parser.parse().unwrap();
+    let graph = parser.take_as_pattern_graph();
+    for (eid, direction) in [
+      ("e1", EdgeDirection::Out),
+      ("e2", EdgeDirection::In),
+      ("e3", EdgeDirection::Both),
+    ] {
+      assert_eq!(graph.view_e_from_eid(eid).unwrap().direction, direction);
     }
-    self.line += e_attr_cnt;
+
+    let err = parse_err("2 1 0 0\na A\nb B\ne a b E sideways\n");
+    assert_eq!(err.kind(), ParseErrorKind::UnexpectedToken);
+    assert_eq!((err.line(), err.col()), (4, 9));
+    let err = parse_err("2 1 0 0\na A\nb B\ne a b E in x\n");
+    assert_eq!((err.line(), err.col()), (4, 12));
+  }
+
+  #[test]
+  fn test_var_length_edge() {
+    let src =
+      "2 4 0 0\na A\nb B\ne1 a b E *1..3\ne2 a b E both *2 walk\ne3 a b E *..2 ACYCLIC\ne4 a b E\n";
+    let mut parser = PatternParser::new(src.into());
+    parser.parse().unwrap();
+    let graph = parser.take_as_pattern_graph();
+    let var_length = |eid| graph.view_e_from_eid(eid).unwrap().var_length;
+    assert_eq!(var_length("e1"), VarLength::new(1, 3, PathMode::Trail));
+    assert_eq!(var_length("e2"), VarLength::new(2, 2, PathMode::Walk));
+    assert_eq!(var_length("e3"), VarLength::new(1, 2, PathMode::Acyclic));
+    assert_eq!(var_length("e4"), None);
+    assert_eq!(
+      graph.view_e_from_eid("e2").unwrap().direction,
+      EdgeDirection::Both
+    );
+
+    let err = parse_err("2 1 0 0\na A\nb B\ne a b E *1..x\n");
+    assert_eq!(err.kind(), ParseErrorKind::MalformedLiteral);
+    assert_eq!((err.line(), err.col()), (4, 9));
+    for unbounded in ["*", "*2..", "*0..2", "*3..2"] {
+      let err = parse_err(&format!("2 1 0 0\na A\nb B\ne a b E {unbounded}\n"));
+      assert_eq!(err.kind(), ParseErrorKind::Unsupported);
+    }
+    let err = parse_err("2 1 0 0\na A\nb B\ne a b E *2 walk in\n");
+    assert_eq!(err.kind(), ParseErrorKind::UnexpectedToken);
+    assert_eq!((err.line(), err.col()), (4, 17));
+  }
+
+  #[test]
+  fn test_negated_lines() {
+    let src = "3 3 0 0\na A\nb B\nc C not\ne1 a b E\ne2 a b F both not\ne3 b c G not\n";
+    let mut parser = PatternParser::new(src.into());
+    parser.parse().unwrap();
+    let graph = parser.take_as_pattern_graph();
+    assert!(graph.view_v_from_vid("c").unwrap().negated);
+    assert!(!graph.view_e_from_eid("e1").unwrap().negated);
+    let sub_patterns = graph.sub_patterns(SubPatternKind::Negated);
+    assert_eq!(sub_patterns.len(), 2);
+    assert_eq!(sub_patterns[1].eids, ["e3"]);
+    assert_eq!(sub_patterns[1].anchors, ["b"]);
+
+    // a positive edge on a negated vertex
+    let err = parse_err("2 1 0 0\na A\nb B not\ne a b E\n");
+    assert_eq!(err.kind(), ParseErrorKind::Unsupported);
+    assert_eq!((err.line(), err.col()), (4, 1));
+    // not anchored at the positive part
+    let err = parse_err("3 1 0 0\na A\nb B not\nc C not\ne b c E not\n");
+    assert_eq!(err.kind(), ParseErrorKind::Unsupported);
+    assert_eq!(err.line(), 5);
+  }
+
+  #[test]
+  fn test_optional_lines() {
+    let src =
+      "3 2 0 0\np Person\nu University optional\nc City\ne1 p c LivesIn\ne2 p u StudyAt optional\n";
+    let mut parser = PatternParser::new(src.into());
+    parser.parse().unwrap();
+    let graph = parser.take_as_pattern_graph();
+    assert!(graph.view_v_from_vid("u").unwrap().optional);
+    assert!(!graph.view_v_from_vid("u").unwrap().negated);
+    assert!(graph.view_e_from_eid("e2").unwrap().optional);
+    assert!(graph.sub_patterns(SubPatternKind::Negated).is_empty());
+    let sub_patterns = graph.sub_patterns(SubPatternKind::Optional);
+    assert_eq!(sub_patterns.len(), 1);
+    assert_eq!(sub_patterns[0].eids, ["e2"]);
+    assert_eq!(sub_patterns[0].anchors, ["p"]);
+
+    // a negated edge on an optional vertex
+    let err = parse_err("2 1 0 0\na A\nb B optional\ne a b E not\n");
+    assert_eq!(err.kind(), ParseErrorKind::Unsupported);
+    assert_eq!((err.line(), err.col()), (4, 1));
+    let err = parse_err("2 1 0 0\na A\nb B\ne a b E optional not\n");
+    assert_eq!(err.kind(), ParseErrorKind::UnexpectedToken);
+    assert_eq!((err.line(), err.col()), (4, 18));
+  }
+
+  #[test]
+  fn test_cross_lines() {
+    let src =
+      "2 1 0 0 2\na Person\nb Person\ne a b Knows\na.age < b.age\ne.since STARTS WITH a.name\n";
+    let mut parser = PatternParser::new(src.into());
+    parser.parse().unwrap();
+    let preds = parser.cross_predicates();
+    assert_eq!(preds.len(), 2);
+    assert_eq!(preds[0].to_string(), "a.age < b.age");
+    assert_eq!(preds[1].vars(), vec!["a", "e"]);
+
+    let err = parse_err("1 0 0 0 1\na A\na.id ! a.id\n");
+    assert_eq!(err.kind(), ParseErrorKind::InvalidOperator);
+    assert_eq!((err.line(), err.col()), (3, 6));
+    let err = parse_err("1 0 0 0 1\na A\na.id = c.id\n");
+    assert_eq!(err.kind(), ParseErrorKind::UndeclaredVertex);
+    assert_eq!(err.token(), Some("c"));
+    let err = parse_err("1 0 0 0 1\na A\na.id = id\n");
+    assert_eq!(err.kind(), ParseErrorKind::UnexpectedToken);
+    assert_eq!((err.line(), err.col()), (3, 8));
+  }
+
+  #[test]
+  fn test_duplicate_and_undeclared() {
+    let err = parse_err("2 0 0 0\na A\na B\n");
+    assert_eq!(err.kind(), ParseErrorKind::DuplicateVid);
+    assert_eq!((err.line(), err.col()), (3, 1));
+
+    let err = parse_err("2 1 0 0\na A\nb B\ne a  c E\n");
+    assert_eq!(err.kind(), ParseErrorKind::UndeclaredVertex);
+    assert_eq!((err.line(), err.col()), (4, 6));
+    assert_eq!(err.token(), Some("c"));
+  }
+
+  #[test]
+  fn test_attr_predicate() {
+    let err = parse_err("1 0 1 0\na A\na id CONTAINS 12\n");
+    assert_eq!(err.kind(), ParseErrorKind::InvalidOperator);
+    assert_eq!((err.line(), err.col()), (3, 6));
+    assert_eq!(err.token(), Some("CONTAINS"));
+
+    let err = parse_err("1 0 1 0\na A\na name =~ '(x'\n");
+    assert_eq!(err.kind(), ParseErrorKind::MalformedLiteral);
+    assert_eq!((err.line(), err.col()), (3, 11));
+
+    let err = parse_err("1 0 1 0\na A\na id =12x\n");
+    assert_eq!(err.kind(), ParseErrorKind::MalformedLiteral);
+    assert_eq!((err.line(), err.col()), (3, 7));
+
+    let mut parser = PatternParser::new("1 0 1 0\na A\na name = 'Hong Kong'\n".to_string());
+    parser.parse().unwrap();
+    let graph = parser.take_as_pattern_graph();
+    let attr = graph.view_v_from_vid("a").unwrap().attrs[0].clone();
+    let attr = attr.as_cmp().unwrap();
+    assert_eq!(attr.value, "Hong Kong".into());
+
+    let mut parser = PatternParser::new("1 0 1 0\na A\na name starts  with 'Hong'\n".to_string());
+    parser.parse().unwrap();
+    let graph = parser.take_as_pattern_graph();
+    let attr = graph.view_v_from_vid("a").unwrap().attrs[0].clone();
+    assert_eq!(attr.as_cmp().unwrap().op, Op::StartsWith);
+  }
+
+  #[test]
+  fn test_attr_conjunction() {
+    let src = "2 1 3 1\na A\nb B\ne a b E\na id > 1\na id <= 9\nb name = 'x'\ne w != 0\n";
+    let mut parser = PatternParser::new(src.to_string());
+    parser.parse().unwrap();
+    let graph = parser.take_as_pattern_graph();
+
+    let a_attrs = &graph.view_v_from_vid("a").unwrap().attrs;
+    assert_eq!(a_attrs.len(), 2);
+    let ops = (
+      a_attrs[0].as_cmp().unwrap().op,
+      a_attrs[1].as_cmp().unwrap().op,
+    );
+    assert_eq!(ops, (Op::Gt, Op::Le));
+    assert_eq!(graph.view_v_from_vid("b").unwrap().attrs.len(), 1);
+    assert_eq!(graph.view_e_from_eid("e").unwrap().attrs.len(), 1);
+  }
+
+  #[test]
+  fn test_attr_in_and_between() {
+    let src = "1 0 2 0\na A\na name in ['x, y', \"z\"]\na id BETWEEN -1 and 9\n";
+    let mut parser = PatternParser::new(src.to_string());
+    parser.parse().unwrap();
+    let graph = parser.take_as_pattern_graph();
+
+    let a_attrs = &graph.view_v_from_vid("a").unwrap().attrs;
+    let PatternPredicate::In { values, .. } = &a_attrs[0] else {
+      panic!("expected `IN`, got {:?}", a_attrs[0]);
+    };
+    assert_eq!(values, &["x, y".into(), "z".into()]);
+    let PatternPredicate::Between { bounds, .. } = &a_attrs[1] else {
+      panic!("expected `BETWEEN`, got {:?}", a_attrs[1]);
+    };
+    assert_eq!(bounds, &(AttrValue::Int(-1), AttrValue::Int(9)));
+
+    let err = parse_err("1 0 1 0\na A\na id IN [1, 2x]\n");
+    assert_eq!(err.kind(), ParseErrorKind::MalformedLiteral);
+    assert_eq!((err.line(), err.col()), (3, 13));
+  }
+
+  #[test]
+  fn test_attr_param() {
+    let src = "1 0 2 0\na A\na id =$personId\na name STARTS WITH $prefix\n";
+    let mut parser = PatternParser::new(src.to_string());
+    parser.parse().unwrap();
+    let graph = parser.take_as_pattern_graph();
+
+    let a_attrs = &graph.view_v_from_vid("a").unwrap().attrs;
+    assert!(matches!(
+      &a_attrs[0],
+      PatternPredicate::Param { op: Op::Eq, param, .. } if param == "personId"
+    ));
+    assert_eq!(a_attrs[1].params(), vec!["prefix"]);
+
+    let err = parse_err("1 0 1 0\na A\na id = $1st\n");
+    assert_eq!(err.kind(), ParseErrorKind::MalformedLiteral);
+    assert_eq!((err.line(), err.col()), (3, 8));
   }
 }
@@ -0,0 +1,516 @@
+use super::json::JsonPattern;
+use crate::{
+  planner::pattern::Pattern,
+  schemas::{
+    AttrCmpMode, AttrRef, AttrValue, CrossPredicate, Op, PatternEdge, PatternPredicate,
+    PatternVertex,
+  },
+};
+use itertools::Itertools;
+use serde::Serialize;
+use std::fmt::Display;
+
+/// A part of a pattern that the count-prefixed format can't express, e.g. an `OR` across vertices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxtFormatError {
+  pub(crate) message: String,
+}
+
+impl TxtFormatError {
+  pub fn message(&self) -> &str {
+    &self.message
+  }
+}
+
+impl Display for TxtFormatError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "unsupported in the count-prefixed format: {}",
+      self.message
+    )
+  }
+}
+
+impl std::error::Error for TxtFormatError {}
+
+fn unsupported(message: impl Into<String>) -> TxtFormatError {
+  TxtFormatError {
+    message: message.into(),
+  }
+}
+
+/// Both formats list the vertices / edges by their vids / eids, so that what's written
+/// doesn't depend on how the pattern is built.
+impl Pattern {
+  /// The count-prefixed format, which [`super::PatternParser`] reads back.
+  ///
+  /// Only conjunctions of comparisons (`IN`, `BETWEEN` and `$param`s included) have a form in it,
+  /// so does a comparison of two attributes, but `OR` and `NOT` don't.
+  pub fn to_txt(&self) -> Result<String, TxtFormatError> {
+    let (mut v_lines, mut e_lines) = (vec![], vec![]);
+    let (mut v_attr_lines, mut e_attr_lines, mut cross_lines) = (vec![], vec![], vec![]);
+
+    for v in self.sorted_vs() {
+      check_word("vid", &v.vid)?;
+      check_word("label", &v.label)?;
+      let mut line = format!("{} {}", v.vid, v.label);
+      push_sub_pattern_kind(&mut line, &v.vid, v.optional, v.negated)?;
+      v_lines.push(line);
+      for pred in &v.attrs {
+        push_attr_lines(&v.vid, pred, &mut v_attr_lines)?;
+      }
+    }
+
+    for e in self.sorted_es() {
+      check_word("eid", &e.eid)?;
+      check_word("label", &e.label)?;
+      let mut line = format!("{} {} {} {}", e.eid, e.src_vid, e.dst_vid, e.label);
+      if !e.direction.is_out() {
+        line += &format!(" {}", e.direction);
+      }
+      if let Some(var_length) = e.var_length {
+        line += &format!(" {var_length}");
+      }
+      push_sub_pattern_kind(&mut line, &e.eid, e.optional, e.negated)?;
+      e_lines.push(line);
+      for pred in &e.attrs {
+        push_attr_lines(&e.eid, pred, &mut e_attr_lines)?;
+      }
+    }
+
+    for pred in conjuncts(self.cross_predicates()) {
+      match pred {
+        CrossPredicate::Cmp { left, op, right } => {
+          for attr_ref in [left, right] {
+            check_word("var", &attr_ref.var)?;
+            check_word("key", &attr_ref.key)?;
+            if attr_ref.var.contains('.') {
+              return Err(unsupported(format!("`{attr_ref}`, whose var has a `.`")));
+            }
+          }
+          cross_lines.push(format!("{left} {op} {right}"));
+        }
+        // a predicate on a single vertex / edge is written along with its attributes
+        pred => match (&pred.vars()[..], pred.to_single()) {
+          (&[var], Some(single)) if self.graph().has_vid(var) => {
+            push_attr_lines(var, &single, &mut v_attr_lines)?
+          }
+          (&[var], Some(single)) => push_attr_lines(var, &single, &mut e_attr_lines)?,
+          _ => return Err(unsupported(format!("`{pred}`, only comparisons are"))),
+        },
+      }
+    }
+
+    let mut counts = [&v_lines, &e_lines, &v_attr_lines, &e_attr_lines]
+      .iter()
+      .map(|lines| lines.len())
+      .join(" ");
+    if !cross_lines.is_empty() {
+      counts += &format!(" {}", cross_lines.len());
+    }
+    let sections = [v_lines, e_lines, v_attr_lines, e_attr_lines, cross_lines]
+      .into_iter()
+      .filter(|lines| !lines.is_empty())
+      .map(|lines| lines.join("\n"));
+    Ok(format!(
+      "{}\n",
+      std::iter::once(counts).chain(sections).join("\n\n")
+    ))
+  }
+
+  /// The JSON pattern format, which [`super::JsonPatternParser`] reads back.
+  pub fn to_json(&self) -> String {
+    let json_pattern = JsonPattern {
+      vertices: self.sorted_vs().into_iter().cloned().collect(),
+      edges: self.sorted_es().into_iter().cloned().collect(),
+      cross_predicates: self.cross_predicates().to_vec(),
+    };
+    serde_json::to_string_pretty(&json_pattern).expect("❌  Failed to serialize the pattern")
+  }
+
+  /// The equivalent pattern, where equivalent predicates are written the same way,
+  /// so that `pattern.canonical().to_json()` can be diffed, hashed or stored.
+  ///
+  /// - predicates on a single vertex / edge are attached to it
+  /// - conjunctions are flattened, then both `AND` and `OR` operands are sorted and deduplicated,
+  ///   so are the `IN` values
+  /// - double negations are dropped
+  /// - `a.x > b.y` is turned into `b.y < a.x` (by the vars, then the keys)
+  pub fn canonical(&self) -> Self {
+    let mut graph = self.graph().clone();
+    let mut cross_predicates = vec![];
+    for pred in conjuncts(self.cross_predicates()).map(canonical_cross) {
+      if let [var] = pred.vars()[..]
+        && let Some(single) = pred.to_single()
+      {
+        let attrs = match graph.v_entities.get_mut(var) {
+          Some(v) => Some(&mut v.attrs),
+          None => graph.e_entities.get_mut(var).map(|e| &mut e.attrs),
+        };
+        if let Some(attrs) = attrs {
+          attrs.push(single);
+          continue;
+        }
+      }
+      cross_predicates.push(pred);
+    }
+
+    let canonical_attrs = |attrs: &mut Vec<PatternPredicate>| {
+      let flattened =
+        std::mem::take(attrs)
+          .into_iter()
+          .flat_map(|pred| match canonical_pred(&pred) {
+            PatternPredicate::And { and: preds } => preds,
+            pred => vec![pred],
+          });
+      *attrs = sorted_unique(flattened.collect());
+    };
+    graph
+      .v_entities
+      .values_mut()
+      .for_each(|v| canonical_attrs(&mut v.attrs));
+    graph
+      .e_entities
+      .values_mut()
+      .for_each(|e| canonical_attrs(&mut e.attrs));
+
+    Self::from_parsed(graph, sorted_unique(cross_predicates))
+  }
+}
+
+impl Pattern {
+  fn sorted_vs(&self) -> Vec<&PatternVertex> {
+    let mut vs = self.graph().view_v_entities();
+    vs.sort_unstable_by(|a, b| a.vid.cmp(&b.vid));
+    vs
+  }
+
+  fn sorted_es(&self) -> Vec<&PatternEdge> {
+    let mut es = self.graph().view_e_entities();
+    es.sort_unstable_by(|a, b| a.eid.cmp(&b.eid));
+    es
+  }
+}
+
+/// The operands of the top-level conjunctions.
+fn conjuncts(preds: &[CrossPredicate]) -> impl Iterator<Item = &CrossPredicate> {
+  preds.iter().flat_map(|pred| match pred {
+    CrossPredicate::And { and: preds } => conjuncts(preds).collect_vec(),
+    pred => vec![pred],
+  })
+}
+
+/// Names are written as whitespace-separated words.
+fn check_word(what: &str, word: &str) -> Result<(), TxtFormatError> {
+  if word.is_empty() || word.contains(char::is_whitespace) {
+    return Err(unsupported(format!(
+      "{what} `{word}`, which isn't a single word"
+    )));
+  }
+  Ok(())
+}
+
+/// `[not | optional]`, at the end of a vertex / edge line.
+fn push_sub_pattern_kind(
+  line: &mut String,
+  var: &str,
+  optional: bool,
+  negated: bool,
+) -> Result<(), TxtFormatError> {
+  match (optional, negated) {
+    (true, true) => return Err(unsupported(format!("`{var}`, both optional and negated"))),
+    (true, false) => *line += " optional",
+    (false, true) => *line += " not",
+    (false, false) => {}
+  }
+  Ok(())
+}
+
+/// `<var> <key> <predicate>`, with a line per operand of a conjunction.
+fn push_attr_lines(
+  var: &str,
+  pred: &PatternPredicate,
+  lines: &mut Vec<String>,
+) -> Result<(), TxtFormatError> {
+  let line = match pred {
+    PatternPredicate::And { and: preds } => {
+      for pred in preds {
+        push_attr_lines(var, pred, lines)?;
+      }
+      return Ok(());
+    }
+    PatternPredicate::Cmp(attr) => {
+      check_word("key", &attr.key)?;
+      format!(
+        "{var} {} {} {}",
+        attr.key,
+        attr.op,
+        txt_literal(&attr.value)?
+      )
+    }
+    PatternPredicate::Param { key, op, param } => {
+      check_word("key", key)?;
+      format!("{var} {key} {op} ${param}")
+    }
+    PatternPredicate::In { key, values } => {
+      check_word("key", key)?;
+      let values = values
+        .iter()
+        .map(txt_literal)
+        .collect::<Result<Vec<_>, _>>()?;
+      format!("{var} {key} IN [{}]", values.join(", "))
+    }
+    PatternPredicate::Between {
+      key,
+      bounds: (low, high),
+    } => {
+      check_word("key", key)?;
+      format!(
+        "{var} {key} BETWEEN {} AND {}",
+        txt_literal(low)?,
+        txt_literal(high)?
+      )
+    }
+    PatternPredicate::Or { .. } | PatternPredicate::Not { .. } => {
+      return Err(unsupported(format!(
+        "`{}`, only conjunctions are",
+        pred.to_neo4j_constraint(var, AttrCmpMode::default())
+      )));
+    }
+  };
+  lines.push(line);
+  Ok(())
+}
+
+/// The literal as the count-prefixed format reads it back.
+fn txt_literal(value: &AttrValue) -> Result<String, TxtFormatError> {
+  match value {
+    AttrValue::String(s) if s.contains(['\n', '\r']) => {
+      Err(unsupported(format!("`{s:?}`, which spans several lines")))
+    }
+    // there's no escaping, so it's quoted with the quote it doesn't contain
+    AttrValue::String(s) if !s.contains('\'') => Ok(format!("'{s}'")),
+    AttrValue::String(s) if !s.contains('"') => Ok(format!("\"{s}\"")),
+    AttrValue::String(s) => Err(unsupported(format!("`{s}`, which has both quotes"))),
+    AttrValue::Float(f) if !f.is_finite() => Err(unsupported(format!("`{f}`"))),
+    // keeps the `.0`, so that it's read back as a float
+    AttrValue::Float(f) => Ok(format!("{f:?}")),
+    AttrValue::List(_) => Err(unsupported(format!("`{}`, a list", value.to_literal()))),
+    _ => Ok(value.to_literal()),
+  }
+}
+
+/// Sorted by their json, which is also how duplicates are found.
+fn sorted_unique<T: Serialize>(items: Vec<T>) -> Vec<T> {
+  let mut keyed = items
+    .into_iter()
+    .map(|item| (serde_json::to_string(&item).unwrap(), item))
+    .collect_vec();
+  keyed.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+  keyed.dedup_by(|(a, _), (b, _)| a == b);
+  keyed.into_iter().map(|(_, item)| item).collect()
+}
+
+fn canonical_pred(pred: &PatternPredicate) -> PatternPredicate {
+  match pred {
+    PatternPredicate::Cmp(attr) => {
+      let mut attr = attr.clone();
+      attr._type = attr.value.to_type();
+      PatternPredicate::Cmp(attr)
+    }
+    PatternPredicate::In { key, values } => PatternPredicate::In {
+      key: key.clone(),
+      values: sorted_unique(values.clone()),
+    },
+    PatternPredicate::And { and: preds } => {
+      let preds = preds
+        .iter()
+        .map(canonical_pred)
+        .flat_map(|pred| match pred {
+          PatternPredicate::And { and: preds } => preds,
+          pred => vec![pred],
+        });
+      single_or(sorted_unique(preds.collect()), PatternPredicate::and)
+    }
+    PatternPredicate::Or { or: preds } => {
+      let preds = preds
+        .iter()
+        .map(canonical_pred)
+        .flat_map(|pred| match pred {
+          PatternPredicate::Or { or: preds } => preds,
+          pred => vec![pred],
+        });
+      single_or(sorted_unique(preds.collect()), PatternPredicate::or)
+    }
+    PatternPredicate::Not { not } => match canonical_pred(not) {
+      PatternPredicate::Not { not } => *not,
+      pred => PatternPredicate::not(pred),
+    },
+    pred => pred.clone(),
+  }
+}
+
+fn canonical_cross(pred: &CrossPredicate) -> CrossPredicate {
+  match pred {
+    CrossPredicate::Cmp { left, op, right } => {
+      let key = |attr_ref: &AttrRef| (attr_ref.var.clone(), attr_ref.key.clone());
+      match mirrored(*op) {
+        Some(op) if key(right) < key(left) => CrossPredicate::Cmp {
+          left: right.clone(),
+          op,
+          right: left.clone(),
+        },
+        _ => pred.clone(),
+      }
+    }
+    CrossPredicate::On { var, pred } => CrossPredicate::On {
+      var: var.clone(),
+      pred: canonical_pred(pred),
+    },
+    CrossPredicate::And { and: preds } => {
+      let preds = preds
+        .iter()
+        .map(canonical_cross)
+        .flat_map(|pred| match pred {
+          CrossPredicate::And { and: preds } => preds,
+          pred => vec![pred],
+        });
+      single_or(sorted_unique(preds.collect()), CrossPredicate::and)
+    }
+    CrossPredicate::Or { or: preds } => {
+      let preds = preds
+        .iter()
+        .map(canonical_cross)
+        .flat_map(|pred| match pred {
+          CrossPredicate::Or { or: preds } => preds,
+          pred => vec![pred],
+        });
+      single_or(sorted_unique(preds.collect()), CrossPredicate::or)
+    }
+    CrossPredicate::Not { not } => match canonical_cross(not) {
+      CrossPredicate::Not { not } => *not,
+      pred => CrossPredicate::not(pred),
+    },
+    CrossPredicate::VidLt { .. } => pred.clone(),
+  }
+}
+
+/// The only operand itself, or else `join` of all.
+fn single_or<T>(mut preds: Vec<T>, join: fn(Vec<T>) -> T) -> T {
+  if preds.len() == 1 {
+    return preds.pop().unwrap();
+  }
+  join(preds)
+}
+
+/// `op`, with its operands swapped (string-only ops except `~=` have no such form).
+fn mirrored(op: Op) -> Option<Op> {
+  match op {
+    Op::Eq | Op::Ne | Op::EqIgnoreCase => Some(op),
+    Op::Gt => Some(Op::Lt),
+    Op::Ge => Some(Op::Le),
+    Op::Lt => Some(Op::Gt),
+    Op::Le => Some(Op::Ge),
+    Op::StartsWith | Op::EndsWith | Op::Contains | Op::Regex => None,
+  }
+}
+
+#[cfg(test)]
+mod test_serializer {
+  use super::*;
+  use crate::planner::{QueryFormat, parse_query};
+
+  const TXT_SRC: &str = "4 4 3 1 1\n\
+    p Person\nc City\nu University optional\nq Person not\n\
+    e p c isLocatedIn\nf c p likes in *1..3 walk\ns p u studyAt both optional\nk p q knows not\n\
+    p firstName IN ['Chen', \"O'Neil\"]\np id >= $minId\nc name = 'Hong Kong'\n\
+    e since BETWEEN 1.0 AND date('2010-03-24')\n\
+    p.firstName STARTS WITH c.name\n";
+
+  fn round_trip(pattern: &Pattern, format: QueryFormat) -> Pattern {
+    let src = match format {
+      QueryFormat::Txt => pattern.to_txt().unwrap(),
+      _ => pattern.to_json(),
+    };
+    parse_query(&src, format).unwrap_or_else(|e| panic!("{e}\n{src}"))
+  }
+
+  #[test]
+  fn test_round_trip() {
+    let pattern = parse_query(TXT_SRC, QueryFormat::Txt).unwrap();
+    for format in [QueryFormat::Txt, QueryFormat::Json] {
+      let read_back = round_trip(&pattern, format);
+      assert_eq!(read_back.to_json(), pattern.to_json());
+    }
+    // written in the same way, no matter how it's declared
+    let txt = pattern.to_txt().unwrap();
+    assert_eq!(
+      round_trip(&pattern, QueryFormat::Txt).to_txt().unwrap(),
+      txt
+    );
+    assert!(txt.starts_with("4 4 3 1 1\n\nc City\np Person\nq Person not\n"));
+
+    let cypher = parse_query(
+      "MATCH (a: Person)-[k: knows]-(b: Person) \
+       WHERE a.id < 9 AND (b.name = 'x' OR b.name = 'y') AND a.age > b.age RETURN a",
+      QueryFormat::Cypher,
+    )
+    .unwrap();
+    let read_back = round_trip(&cypher, QueryFormat::Json);
+    assert_eq!(read_back.to_json(), cypher.to_json());
+    // an `OR` has no count-prefixed form
+    assert!(cypher.to_txt().unwrap_err().message().contains("OR"));
+  }
+
+  #[test]
+  fn test_canonical() {
+    let lt = |left: (&str, &str), right: (&str, &str)| CrossPredicate::Cmp {
+      left: AttrRef::new(left.0, left.1),
+      op: Op::Lt,
+      right: AttrRef::new(right.0, right.1),
+    };
+    let on = |var: &str, pred: PatternPredicate| CrossPredicate::On {
+      var: var.to_string(),
+      pred,
+    };
+    let is = |name: &str| PatternPredicate::In {
+      key: "name".to_string(),
+      values: vec![name.into()],
+    };
+
+    let a = Pattern::new()
+      .vertex("a", "Person")
+      .vertex("b", "Person")
+      .edge("k", "a", "b", "knows")
+      .where_v("a", "id", Op::Gt, 1)
+      .where_v_pred("a", PatternPredicate::or(vec![is("x"), is("y")]))
+      .where_cross(lt(("a", "age"), ("b", "age")));
+    let b = Pattern::new()
+      .vertex("b", "Person")
+      .vertex("a", "Person")
+      .edge("k", "a", "b", "knows")
+      .where_cross(CrossPredicate::and(vec![
+        CrossPredicate::not(CrossPredicate::not(on(
+          "a",
+          PatternPredicate::or(vec![is("y"), is("x"), is("y")]),
+        ))),
+        CrossPredicate::Cmp {
+          left: AttrRef::new("b", "age"),
+          op: Op::Gt,
+          right: AttrRef::new("a", "age"),
+        },
+      ]))
+      .where_v("a", "id", Op::Gt, 1)
+      .where_v("a", "id", Op::Gt, 1);
+    assert_ne!(a.to_json(), b.to_json());
+    assert_eq!(a.canonical().to_json(), b.canonical().to_json());
+    assert_eq!(
+      b.canonical().cross_predicates(),
+      [lt(("a", "age"), ("b", "age"))]
+    );
+    // stable
+    let canonical = a.canonical();
+    assert_eq!(canonical.canonical().to_json(), canonical.to_json());
+  }
+}
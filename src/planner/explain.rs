@@ -0,0 +1,329 @@
+use super::order_calc::{AttrGroup, OrderStrategy};
+use crate::schemas::{
+  Instruction, InstructionType, Label, PatternPredicate, PlanData, VarPrefix, Vid,
+};
+use hashbrown::HashMap;
+use itertools::Itertools;
+use polars::{frame::DataFrame, prelude::Column};
+use serde::Serialize;
+use std::fmt::Display;
+
+/// The terms of the cost model, by which a vertex is placed in the matching order.
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderTerm {
+  pub(crate) vid: Vid,
+  pub(crate) label: Label,
+  /// the connected part of the pattern it's in, by the order they're matched in
+  pub(crate) part: usize,
+  /// the data vertices of its label
+  pub(crate) label_cnt: usize,
+  /// of its predicates, by the attribute statistics
+  pub(crate) selectivity: f64,
+  /// `ceil(label_cnt * selectivity)`
+  pub(crate) candidates: usize,
+  /// by its most selective predicate
+  pub(crate) attr_group: AttrGroup,
+  /// by its edges and neighbors (rule-based only)
+  pub(crate) price: Option<f64>,
+  /// its candidates and the edges from them in the worst case (rule-based only)
+  pub(crate) cost: Option<usize>,
+  /// the matches of its connected part before it's matched (`1` for the first one of a part)
+  pub(crate) prev_matches: f64,
+  /// how many times the matches grow once it's matched
+  pub(crate) growth: f64,
+  /// `prev_matches * growth`
+  pub(crate) matches: f64,
+}
+
+/// An instruction of the plan, along with what the planner expects of it.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExplainedInstruction {
+  pub(crate) id: usize,
+  pub(crate) instruction: String,
+  #[serde(rename = "type")]
+  pub(crate) type_: InstructionType,
+  pub(crate) vid: Vid,
+  pub(crate) target_var: String,
+  /// the dependency layer it runs in (by `parallel_exec`), `None` if the plan contains a cycle
+  pub(crate) layer: Option<usize>,
+  /// the estimated matches it consumes, `None` for `Init`
+  pub(crate) input_card: Option<f64>,
+  /// the estimated matches it yields
+  pub(crate) output_card: f64,
+  /// the predicates evaluated by the storage while loading the vertices / edges
+  pub(crate) pushdowns: Vec<String>,
+  /// the cross predicates checked on the matches it yields
+  pub(crate) predicates: Vec<String>,
+}
+
+/// The plan of a query, with the estimated cardinality of each instruction
+/// and the terms of the cost model that produced the matching order, without executing it.
+///
+/// Cardinalities are the partial matches of the connected part an instruction works on
+/// (see `CardinalityModel`), where the cross predicates, the negated and the optional
+/// sub-patterns are assumed not to change them.
+#[derive(Debug, Clone, Serialize)]
+pub struct Explain {
+  pub(crate) strategy: OrderStrategy,
+  pub(crate) matching_order: Vec<Vid>,
+  pub(crate) order_terms: Vec<OrderTerm>,
+  pub(crate) instructions: Vec<ExplainedInstruction>,
+}
+
+impl Explain {
+  pub(crate) fn new(
+    plan_data: &PlanData,
+    strategy: OrderStrategy,
+    order_terms: Vec<OrderTerm>,
+  ) -> Self {
+    let layer_of = (plan_data
+      .dependency_layers()
+      .unwrap_or_default()
+      .into_iter())
+    .enumerate()
+    .flat_map(|(layer, idxs)| idxs.into_iter().map(move |idx| (idx, layer)))
+    .collect::<HashMap<_, _>>();
+    // the connected parts are merged by the end, where each one has its last matches
+    let full_matches = (order_terms.iter())
+      .map(|term| (term.part, term.matches))
+      .collect::<HashMap<_, _>>()
+      .into_values()
+      .product::<f64>();
+
+    let instructions = (plan_data.instructions.iter().enumerate())
+      .map(|(id, instr)| {
+        let term = (order_terms.iter()).find(|term| term.vid == instr.vid);
+        let (input_card, output_card) = match term {
+          Some(term) => Self::cards_of(instr, term),
+          None => (Some(full_matches), full_matches),
+        };
+        ExplainedInstruction {
+          id,
+          instruction: instr.to_string_uncolored(),
+          type_: instr.type_,
+          vid: instr.vid.clone(),
+          target_var: instr.target_var.clone(),
+          layer: layer_of.get(&id).copied(),
+          input_card,
+          output_card,
+          pushdowns: Self::pushdowns_of(instr, plan_data),
+          predicates: instr
+            .predicates
+            .iter()
+            .map(|pred| pred.to_string())
+            .collect(),
+        }
+      })
+      .collect();
+
+    Self {
+      strategy,
+      matching_order: plan_data.matching_order.clone(),
+      order_terms,
+      instructions,
+    }
+  }
+
+  /// (input, output) of an instruction on the vertex of `term`
+  fn cards_of(instr: &Instruction, term: &OrderTerm) -> (Option<f64>, f64) {
+    match instr.type_ {
+      InstructionType::Init => (None, term.matches),
+      // unlike `Intersect(Tx) -> Cx`, which works on the candidates intersected already
+      InstructionType::Intersect
+        if !(instr.single_op.as_ref())
+          .is_some_and(|op| op.starts_with(&VarPrefix::IntersectTarget.with(""))) =>
+      {
+        (Some(term.prev_matches), term.matches)
+      }
+      _ => (Some(term.matches), term.matches),
+    }
+  }
+
+  /// The predicates loaded along with the vertex / edges of an instruction.
+  fn pushdowns_of(instr: &Instruction, plan_data: &PlanData) -> Vec<String> {
+    let constraints_of = |attrs: &[PatternPredicate], var: &str| {
+      (attrs.iter())
+        .map(|pred| pred.to_neo4j_constraint(var, plan_data.attr_cmp_mode))
+        .collect_vec()
+    };
+    let v_constraints_of = |vid: &str| match plan_data.pattern_vs.get(vid) {
+      Some(v) => constraints_of(&v.attrs, vid),
+      None => vec![],
+    };
+
+    match instr.type_ {
+      InstructionType::Init | InstructionType::Intersect => v_constraints_of(&instr.vid),
+      InstructionType::GetAdj
+      | InstructionType::ExpandPath
+      | InstructionType::AntiJoin
+      | InstructionType::OptionalExpand => {
+        let is_loaded = |vid: &str| match instr.type_.is_expand() {
+          // the other end of an edge from `vid`
+          true => vid != instr.vid,
+          // the ends of the sub-pattern
+          false => !plan_data.matching_order.iter().any(|v| v == vid),
+        };
+        (instr.expand_eids.iter())
+          .filter_map(|eid| plan_data.pattern_es.get(eid))
+          .flat_map(|e| {
+            let mut constraints = constraints_of(&e.attrs, &e.eid);
+            for vid in [&e.src_vid, &e.dst_vid] {
+              if is_loaded(vid) {
+                constraints.extend(v_constraints_of(vid));
+              }
+            }
+            constraints
+          })
+          .unique()
+          .collect()
+      }
+      InstructionType::Foreach | InstructionType::TCache | InstructionType::Report => vec![],
+    }
+  }
+
+  pub fn order_terms(&self) -> &[OrderTerm] {
+    &self.order_terms
+  }
+
+  pub fn instructions(&self) -> &[ExplainedInstruction] {
+    &self.instructions
+  }
+
+  pub fn to_json(&self) -> String {
+    serde_json::to_string_pretty(self).unwrap()
+  }
+
+  /// The terms of the vertices, in the matching order
+  /// (the labels and the growths are left to [`Self::to_json`], for the sake of width).
+  pub fn order_df(&self) -> DataFrame {
+    let terms = &self.order_terms;
+    DataFrame::new(vec![
+      Column::new(
+        "Vid".into(),
+        terms.iter().map(|t| t.vid.clone()).collect_vec(),
+      ),
+      Column::new(
+        "LabelCnt".into(),
+        terms.iter().map(|t| t.label_cnt as u64).collect_vec(),
+      ),
+      Column::new(
+        "Selectivity".into(),
+        terms.iter().map(|t| t.selectivity).collect_vec(),
+      ),
+      Column::new(
+        "Candidates".into(),
+        terms.iter().map(|t| t.candidates as u64).collect_vec(),
+      ),
+      Column::new(
+        "AttrGroup".into(),
+        terms.iter().map(|t| t.attr_group.to_string()).collect_vec(),
+      ),
+      Column::new("Price".into(), terms.iter().map(|t| t.price).collect_vec()),
+      Column::new(
+        "Cost".into(),
+        terms.iter().map(|t| t.cost.map(|c| c as u64)).collect_vec(),
+      ),
+      Column::new(
+        "Matches".into(),
+        terms.iter().map(|t| t.matches).collect_vec(),
+      ),
+    ])
+    .unwrap()
+  }
+
+  /// The instructions, with their layers, cardinalities and pushdowns.
+  pub fn instructions_df(&self) -> DataFrame {
+    let instrs = &self.instructions;
+    DataFrame::new(vec![
+      Column::new(
+        "Id".into(),
+        instrs.iter().map(|i| i.id as u64).collect_vec(),
+      ),
+      Column::new(
+        "Layer".into(),
+        instrs
+          .iter()
+          .map(|i| i.layer.map(|l| l as u64))
+          .collect_vec(),
+      ),
+      Column::new(
+        "Instructions".into(),
+        instrs.iter().map(|i| i.instruction.clone()).collect_vec(),
+      ),
+      Column::new(
+        "InputCard".into(),
+        instrs.iter().map(|i| i.input_card).collect_vec(),
+      ),
+      Column::new(
+        "OutputCard".into(),
+        instrs.iter().map(|i| i.output_card).collect_vec(),
+      ),
+      Column::new(
+        "Pushdowns".into(),
+        instrs
+          .iter()
+          .map(|i| i.pushdowns.join(" AND "))
+          .collect_vec(),
+      ),
+    ])
+    .unwrap()
+  }
+}
+
+impl Display for Explain {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    writeln!(f, "🔍  Explain(Order: {})", self.strategy)?;
+    writeln!(f, "{}\n", self.order_df())?;
+    writeln!(f, "🔍  Explain(Instructions)")?;
+    writeln!(f, "{}", self.instructions_df())
+  }
+}
+
+#[cfg(test)]
+mod test_explain {
+  use crate::planner::{QueryFormat, explain_query};
+
+  #[test]
+  fn test_explain() {
+    let explain = explain_query(
+      "MATCH (t:Tag), (p:Person)-[k:knows]->(q:Person) WHERE q.id = 42 AND p.id <> q.id RETURN p",
+      QueryFormat::Cypher,
+    )
+    .unwrap();
+    let terms = explain.order_terms();
+    let instrs = explain.instructions();
+
+    // `q` first, then `p` next to it, and `t` on its own
+    let vids = terms
+      .iter()
+      .map(|term| term.vid.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(vids, ["q", "p", "t"]);
+    assert_eq!(
+      terms.iter().map(|term| term.part).collect::<Vec<_>>(),
+      [0, 0, 1]
+    );
+    assert!((terms.iter()).all(|term| term.price.is_some() && term.cost.is_some()));
+
+    // the id of `q` is loaded along with it, and `p <> q` is checked once `p` is bound
+    let init_q = &instrs[0];
+    assert_eq!(init_q.input_card, None);
+    assert_eq!(init_q.output_card, terms[0].matches);
+    assert_eq!(init_q.pushdowns, ["q.id = 42"]);
+    let foreach_p = (instrs.iter())
+      .find(|instr| instr.instruction.starts_with("Foreach (C^p)"))
+      .unwrap();
+    assert_eq!(foreach_p.predicates, ["p.id <> q.id"]);
+    assert_eq!(foreach_p.input_card, Some(terms[1].matches));
+
+    // the parts are only merged by `Report`, at the last layer
+    let report = instrs.last().unwrap();
+    assert_eq!(report.output_card, terms[1].matches * terms[2].matches);
+    let layers = (instrs.iter()).map(|instr| instr.layer.unwrap());
+    assert_eq!(layers.max(), report.layer);
+
+    let json: serde_json::Value = serde_json::from_str(&explain.to_json()).unwrap();
+    assert_eq!(json["instructions"][0]["type"], "init");
+    assert_eq!(json["order_terms"][0]["attr_group"], "Eq");
+  }
+}
@@ -0,0 +1,380 @@
+use crate::{
+  schemas::{EdgeDirection, PlanData},
+  utils::sub_pattern::SubPatternKind,
+};
+use hashbrown::HashMap;
+use itertools::Itertools;
+
+/// The text formats a plan is exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+  /// Graphviz
+  Dot,
+  Mermaid,
+}
+
+impl GraphFormat {
+  /// `dot` (or `graphviz`) / `mermaid`, case-insensitively.
+  pub fn of_name(name: &str) -> Option<Self> {
+    match name.to_lowercase().as_str() {
+      "dot" | "graphviz" => Some(Self::Dot),
+      "mermaid" => Some(Self::Mermaid),
+      _ => None,
+    }
+  }
+}
+
+/// The pattern graph of a plan, where each vertex is numbered by its place in the matching order,
+/// along with its predicates, and the optional / negated parts are dashed.
+pub fn export_pattern(plan_data: &PlanData, format: GraphFormat) -> String {
+  let pos = |vid: &str| plan_data.matching_order.iter().position(|v| v == vid);
+  let vs = (plan_data.pattern_vs.values())
+    .sorted_by_key(|v| (pos(&v.vid).unwrap_or(usize::MAX), &v.vid))
+    .collect_vec();
+  let node_ids = (vs.iter().enumerate())
+    .map(|(idx, v)| (v.vid.as_str(), format!("v{idx}")))
+    .collect::<HashMap<_, _>>();
+
+  let nodes = (vs.iter())
+    .map(|v| {
+      let mut head = match v.label.is_empty() {
+        true => v.vid.clone(),
+        false => format!("{}:{}", v.vid, v.label),
+      };
+      if let Some(pos) = pos(&v.vid) {
+        head = format!("#{} {head}", pos + 1);
+      }
+      let kind = SubPatternKind::of_v(v);
+      if let Some(kind) = kind {
+        head += &format!(" ({kind})");
+      }
+      let lines = std::iter::once(head)
+        .chain(
+          v.attrs
+            .iter()
+            .map(|pred| pred.to_neo4j_constraint(&v.vid, plan_data.attr_cmp_mode)),
+        )
+        .collect();
+      Node {
+        id: node_ids[v.vid.as_str()].clone(),
+        lines,
+        dashed: kind.is_some(),
+      }
+    })
+    .collect();
+
+  let edges = (plan_data.pattern_es.values())
+    .sorted_by_key(|e| &e.eid)
+    .map(|e| {
+      let mut head = format!("{}:{}", e.eid, e.label);
+      if let Some(var_length) = e.var_length {
+        head += &var_length.to_string();
+      }
+      let (from, to) = match e.direction {
+        EdgeDirection::In => (&e.dst_vid, &e.src_vid),
+        _ => (&e.src_vid, &e.dst_vid),
+      };
+      Edge {
+        from: node_ids[from.as_str()].clone(),
+        to: node_ids[to.as_str()].clone(),
+        lines: std::iter::once(head)
+          .chain(
+            e.attrs
+              .iter()
+              .map(|pred| pred.to_neo4j_constraint(&e.eid, plan_data.attr_cmp_mode)),
+          )
+          .collect(),
+        directed: e.direction != EdgeDirection::Both,
+        dashed: SubPatternKind::of_e(e).is_some(),
+      }
+    })
+    .collect();
+
+  let graph = ExportedGraph {
+    name: "pattern",
+    top_down: false,
+    rounded: true,
+    nodes,
+    edges,
+    clusters: vec![],
+  };
+  graph.render(format)
+}
+
+/// The instructions of a plan, each one pointing to those that depend on it (by the vars it yields),
+/// grouped by their layers (see [`PlanData::dependency_layers`]), unless there's a cycle.
+pub fn export_instructions(plan_data: &PlanData, format: GraphFormat) -> String {
+  let instructions = &plan_data.instructions;
+  let producers = (instructions.iter().enumerate())
+    .map(|(idx, instr)| (instr.target_var.as_str(), idx))
+    .collect::<HashMap<_, _>>();
+
+  let nodes = (instructions.iter().enumerate())
+    .map(|(idx, instr)| {
+      let instr = instr.to_string_uncolored();
+      let lines = match instr.split_once(" WHERE ") {
+        Some((instr, predicates)) => vec![instr.to_string(), format!("WHERE {predicates}")],
+        None => vec![instr],
+      };
+      Node {
+        id: format!("i{idx}"),
+        lines,
+        dashed: false,
+      }
+    })
+    .collect();
+
+  let edges = (instructions.iter().enumerate())
+    .flat_map(|(idx, instr)| {
+      (instr.depend_on.iter())
+        .filter_map(|var| producers.get(var.as_str()).map(|&producer| (producer, var)))
+        .map(move |(producer, var)| Edge {
+          from: format!("i{producer}"),
+          to: format!("i{idx}"),
+          lines: vec![var.clone()],
+          directed: true,
+          dashed: false,
+        })
+    })
+    .collect();
+
+  let layers = plan_data.dependency_layers().unwrap_or_default();
+  let clusters = (layers.into_iter().enumerate())
+    .map(|(layer_idx, layer)| (format!("layer {layer_idx}"), layer))
+    .collect();
+
+  let graph = ExportedGraph {
+    name: "instructions",
+    top_down: true,
+    rounded: false,
+    nodes,
+    edges,
+    clusters,
+  };
+  graph.render(format)
+}
+
+struct Node {
+  id: String,
+  lines: Vec<String>,
+  dashed: bool,
+}
+
+struct Edge {
+  from: String,
+  to: String,
+  lines: Vec<String>,
+  directed: bool,
+  dashed: bool,
+}
+
+/// What both formats are rendered from.
+struct ExportedGraph {
+  name: &'static str,
+  /// or else left to right
+  top_down: bool,
+  /// the shape of the nodes, rounded or boxed
+  rounded: bool,
+  nodes: Vec<Node>,
+  edges: Vec<Edge>,
+  /// (label, the indices of the nodes in it)
+  clusters: Vec<(String, Vec<usize>)>,
+}
+
+impl ExportedGraph {
+  fn render(&self, format: GraphFormat) -> String {
+    match format {
+      GraphFormat::Dot => self.to_dot(),
+      GraphFormat::Mermaid => self.to_mermaid(),
+    }
+  }
+
+  /// The nodes of each cluster (by index), where those in none are in the last one.
+  fn nodes_by_cluster(&self) -> Vec<Vec<&Node>> {
+    let mut clustered = vec![false; self.nodes.len()];
+    let mut by_cluster = (self.clusters.iter())
+      .map(|(_, idxs)| {
+        (idxs.iter())
+          .map(|&idx| {
+            clustered[idx] = true;
+            &self.nodes[idx]
+          })
+          .collect_vec()
+      })
+      .collect_vec();
+    by_cluster.push(
+      (self.nodes.iter().zip(clustered))
+        .filter(|(_, clustered)| !clustered)
+        .map(|(node, _)| node)
+        .collect(),
+    );
+    by_cluster
+  }
+
+  fn to_dot(&self) -> String {
+    let label = |lines: &[String]| {
+      (lines.iter())
+        .map(|line| line.replace('\\', "\\\\").replace('"', "\\\""))
+        .join("\\n")
+    };
+    let node_line = |node: &Node| {
+      let style = if node.dashed { ", style=dashed" } else { "" };
+      format!("{} [label=\"{}\"{style}];", node.id, label(&node.lines))
+    };
+
+    let mut lines = vec![
+      format!("digraph {} {{", self.name),
+      format!("  rankdir={};", if self.top_down { "TB" } else { "LR" }),
+      format!(
+        "  node [shape={}, fontname=\"monospace\"];",
+        if self.rounded { "ellipse" } else { "box" }
+      ),
+      "  edge [fontname=\"monospace\"];".to_string(),
+    ];
+    let mut by_cluster = self.nodes_by_cluster();
+    let unclustered = by_cluster.pop().unwrap_or_default();
+    for (idx, ((cluster_label, _), nodes)) in self.clusters.iter().zip(by_cluster).enumerate() {
+      lines.push(format!("  subgraph cluster_{idx} {{"));
+      lines.push(format!(
+        "    label=\"{}\";",
+        label(std::slice::from_ref(cluster_label))
+      ));
+      lines.extend(
+        nodes
+          .into_iter()
+          .map(|node| format!("    {}", node_line(node))),
+      );
+      lines.push("  }".to_string());
+    }
+    lines.extend(
+      unclustered
+        .into_iter()
+        .map(|node| format!("  {}", node_line(node))),
+    );
+    for edge in &self.edges {
+      let mut attrs = vec![format!("label=\"{}\"", label(&edge.lines))];
+      if !edge.directed {
+        attrs.push("dir=none".to_string());
+      }
+      if edge.dashed {
+        attrs.push("style=dashed".to_string());
+      }
+      lines.push(format!(
+        "  {} -> {} [{}];",
+        edge.from,
+        edge.to,
+        attrs.join(", ")
+      ));
+    }
+    lines.push("}".to_string());
+    lines.join("\n") + "\n"
+  }
+
+  fn to_mermaid(&self) -> String {
+    // `#` starts an entity code, and `<br/>` breaks the lines
+    let label = |lines: &[String]| {
+      (lines.iter())
+        .map(|line| {
+          (line.replace('#', "#35;"))
+            .replace('"', "#quot;")
+            .replace('<', "#lt;")
+            .replace('>', "#gt;")
+        })
+        .join("<br/>")
+    };
+    let node_line = |node: &Node| match self.rounded {
+      true => format!("{}([\"{}\"])", node.id, label(&node.lines)),
+      false => format!("{}[\"{}\"]", node.id, label(&node.lines)),
+    };
+
+    let direction = if self.top_down { "TB" } else { "LR" };
+    let mut lines = vec![format!("flowchart {direction}")];
+    let mut by_cluster = self.nodes_by_cluster();
+    let unclustered = by_cluster.pop().unwrap_or_default();
+    for (idx, ((cluster_label, _), nodes)) in self.clusters.iter().zip(by_cluster).enumerate() {
+      lines.push(format!(
+        "  subgraph cluster_{idx} [\"{}\"]",
+        label(std::slice::from_ref(cluster_label))
+      ));
+      lines.extend(
+        nodes
+          .into_iter()
+          .map(|node| format!("    {}", node_line(node))),
+      );
+      lines.push("  end".to_string());
+    }
+    lines.extend(
+      unclustered
+        .into_iter()
+        .map(|node| format!("  {}", node_line(node))),
+    );
+    for edge in &self.edges {
+      let arrow = match (edge.dashed, edge.directed) {
+        (false, true) => "-->",
+        (false, false) => "---",
+        (true, true) => "-.->",
+        (true, false) => "-.-",
+      };
+      lines.push(format!(
+        "  {} {arrow}|\"{}\"| {}",
+        edge.from,
+        label(&edge.lines),
+        edge.to
+      ));
+    }
+    let dashed = (self.nodes.iter()).filter(|node| node.dashed);
+    lines.extend(dashed.map(|node| format!("  style {} stroke-dasharray: 5 5", node.id)));
+    lines.join("\n") + "\n"
+  }
+}
+
+#[cfg(test)]
+mod test_export {
+  use super::*;
+  use crate::planner::{QueryFormat, generate_optimal_plan_from_query};
+
+  #[test]
+  fn test_export() {
+    let plan_data = generate_optimal_plan_from_query(
+      "MATCH (p:Person)-[k:knows]->(q:Person) WHERE p.id = 1 \
+       OPTIONAL MATCH (q)-[l:likes]-(m:Post) RETURN q",
+      QueryFormat::Cypher,
+    )
+    .unwrap();
+
+    // `p` comes first, as the selective one, and the optional part is dashed
+    let dot = export_pattern(&plan_data, GraphFormat::Dot);
+    assert!(dot.starts_with("digraph pattern {\n"));
+    assert!(dot.contains("v0 [label=\"#1 p:Person\\np.id = 1\"];"));
+    assert!(dot.contains("v2 [label=\"m:Post (optional)\", style=dashed];"));
+    assert!(dot.contains("v0 -> v1 [label=\"k:knows\"];"));
+    assert!(dot.contains("v1 -> v2 [label=\"l:likes\", dir=none, style=dashed];"));
+    let mermaid = export_pattern(&plan_data, GraphFormat::Mermaid);
+    assert!(mermaid.contains("v0([\"#35;1 p:Person<br/>p.id = 1\"])"));
+    assert!(mermaid.contains("v1 -.-|\"l:likes\"| v2"));
+
+    // each instruction is in a layer, pointed to by those it depends on
+    let layers = plan_data.dependency_layers().unwrap();
+    let dot = export_instructions(&plan_data, GraphFormat::Dot);
+    for idx in 0..layers.len() {
+      assert!(dot.contains(&format!(
+        "subgraph cluster_{idx} {{\n    label=\"layer {idx}\";"
+      )));
+    }
+    let init = &plan_data.instructions[0];
+    let depends_on_init = (plan_data.instructions.iter())
+      .positions(|instr| instr.depend_on.contains(&init.target_var))
+      .collect_vec();
+    assert!(!depends_on_init.is_empty());
+    for idx in depends_on_init {
+      assert!(dot.contains(&format!("i0 -> i{idx} [label=\"{}\"];", init.target_var)));
+    }
+    let mermaid = export_instructions(&plan_data, GraphFormat::Mermaid);
+    assert_eq!(mermaid.matches("subgraph").count(), layers.len());
+    let dependencies = (plan_data.instructions.iter())
+      .flat_map(|instr| &instr.depend_on)
+      .filter(|var| (plan_data.instructions.iter()).any(|instr| &instr.target_var == *var))
+      .count();
+    assert_eq!(mermaid.matches(" -->|").count(), dependencies);
+  }
+}
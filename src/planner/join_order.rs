@@ -0,0 +1,384 @@
+use super::statistics::Statistics;
+use crate::{
+  schemas::{PatternEdge, PatternVertex, Vid},
+  utils::dyn_graph::DynGraph,
+};
+use itertools::Itertools;
+
+/// Above this many vertices (in a connected part of the pattern), the order is picked greedily,
+/// since the connected sub-patterns to enumerate grow exponentially.
+pub const DP_MAX_VERTICES: usize = 14;
+/// What [`JoinOrderEnumerator::with_dp_max_vertices`] is clamped to,
+/// since the sub-patterns are bit masks and their table takes `2^n` entries.
+pub const DP_MAX_VERTICES_LIMIT: usize = 20;
+
+/// Estimates the matches of sub-patterns of a (required) pattern graph,
+/// assuming that the labels, the predicates and the edges are independent of each other.
+#[derive(Debug, Clone)]
+pub(crate) struct CardinalityModel<'a> {
+  /// sorted, so that ties are broken the same way every time
+  vids: Vec<&'a Vid>,
+  /// the matches of each vertex on its own
+  v_cards: Vec<f64>,
+  /// (idx_a, idx_b, the chance that a pair of data vertices matching them is connected as required)
+  e_probs: Vec<(usize, usize, f64)>,
+  /// idx -> the indices of its neighbors
+  adj_idxs: Vec<Vec<usize>>,
+}
+
+impl<'a> CardinalityModel<'a> {
+  pub(crate) fn new(
+    graph: &'a DynGraph<PatternVertex, PatternEdge>,
+    statistics: &Statistics,
+  ) -> Self {
+    let vids = graph.v_entities().keys().sorted().collect_vec();
+    let idx_of = |vid: &str| vids.iter().position(|v| *v == vid).unwrap();
+    let label_of = |vid: &str| graph.v_entities()[vid].label.as_str();
+    let label_cnt = |vid: &str| statistics.v_cnt_of(label_of(vid)) as f64;
+
+    let v_cards = (vids.iter())
+      .map(|vid| {
+        label_cnt(vid) * statistics.v_selectivity(label_of(vid), &graph.v_entities()[*vid].attrs)
+      })
+      .collect_vec();
+
+    let mut adj_idxs = vec![vec![]; vids.len()];
+    let mut e_probs = vec![];
+    for e in graph.e_entities().values().sorted_by_key(|e| &e.eid) {
+      let (a, b) = (idx_of(&e.src_vid), idx_of(&e.dst_vid));
+      let (a_cnt, b_cnt) = (
+        label_cnt(&e.src_vid).max(1.0),
+        label_cnt(&e.dst_vid).max(1.0),
+      );
+      // between the labels of the endpoints (in both directions, if it's undirected)
+      let e_cnt = statistics.e_matches(e, label_of(&e.src_vid), label_of(&e.dst_vid));
+
+      let prob = match e.var_length {
+        None => e_cnt / (a_cnt * b_cnt),
+        // the paths from a data vertex, each hop fanning out by the average degree
+        Some(var_length) => {
+          let fan_out = e_cnt / a_cnt;
+          let paths = (var_length.min_hops..=var_length.max_hops)
+            .map(|hops| fan_out.powi(hops as i32))
+            .sum::<f64>();
+          paths / b_cnt
+        }
+      };
+      e_probs.push((a, b, prob.min(1.0)));
+
+      if a != b {
+        adj_idxs[a].push(b);
+        adj_idxs[b].push(a);
+      }
+    }
+    adj_idxs.iter_mut().for_each(|idxs| {
+      idxs.sort_unstable();
+      idxs.dedup();
+    });
+
+    Self {
+      vids,
+      v_cards,
+      e_probs,
+      adj_idxs,
+    }
+  }
+
+  /// How many times the matches grow, when `idx` is matched after `is_matched` ones.
+  fn growth(&self, idx: usize, is_matched: impl Fn(usize) -> bool) -> f64 {
+    let probs = (self.e_probs.iter())
+      .filter(|&&(a, b, _)| {
+        (a == idx && (b == idx || is_matched(b))) || (b == idx && is_matched(a))
+      })
+      .map(|(_, _, prob)| prob)
+      .product::<f64>();
+    self.v_cards[idx] * probs
+  }
+
+  /// For each vertex of `order`: the connected part it's in, the matches of the part before it's
+  /// matched, and how many times they grow, where a vertex adjacent to none of the matched ones
+  /// starts another part (from a single empty match).
+  pub(crate) fn growths_along(&self, order: &[Vid]) -> Vec<(usize, f64, f64)> {
+    let mut is_matched = vec![false; self.vids.len()];
+    let mut part = 0;
+    let mut matches = 1.0;
+    let mut result = Vec::with_capacity(order.len());
+
+    for (i, vid) in order.iter().enumerate() {
+      let idx = self.vids.iter().position(|v| *v == vid).unwrap();
+      if i > 0 && !self.adj_idxs[idx].iter().any(|&adj| is_matched[adj]) {
+        part += 1;
+        matches = 1.0;
+      }
+      let growth = self.growth(idx, |adj| is_matched[adj]);
+      result.push((part, matches, growth));
+      matches *= growth;
+      is_matched[idx] = true;
+    }
+    result
+  }
+
+  /// The connected parts, each with its indices sorted.
+  fn components(&self) -> Vec<Vec<usize>> {
+    let mut visited = vec![false; self.vids.len()];
+    let mut components = vec![];
+    for start in 0..self.vids.len() {
+      if visited[start] {
+        continue;
+      }
+      visited[start] = true;
+      let mut component = vec![start];
+      let mut cursor = 0;
+      while let Some(&idx) = component.get(cursor) {
+        cursor += 1;
+        for &adj in &self.adj_idxs[idx] {
+          if !visited[adj] {
+            visited[adj] = true;
+            component.push(adj);
+          }
+        }
+      }
+      component.sort_unstable();
+      components.push(component);
+    }
+    components
+  }
+}
+
+/// Searches for the matching order with the least total size of the partial matches (`C_out`),
+/// where each vertex (but the first of a connected part) is adjacent to the matched ones.
+///
+/// Connected parts of at most [`DP_MAX_VERTICES`] vertices are enumerated by dynamic programming
+/// over their connected sub-patterns, from the smaller ones to the larger ones,
+/// and the larger parts are extended greedily by the vertex that grows the matches the least.
+/// Disconnected parts are matched one after another, from the one with the fewest matches.
+#[derive(Debug, Clone)]
+pub struct JoinOrderEnumerator<'a> {
+  model: CardinalityModel<'a>,
+  dp_max_vertices: usize,
+}
+
+impl<'a> JoinOrderEnumerator<'a> {
+  pub(crate) fn new(
+    required_graph: &'a DynGraph<PatternVertex, PatternEdge>,
+    statistics: &Statistics,
+  ) -> Self {
+    Self {
+      model: CardinalityModel::new(required_graph, statistics),
+      dp_max_vertices: DP_MAX_VERTICES,
+    }
+  }
+
+  /// Use dynamic programming for connected parts of at most `dp_max_vertices` vertices,
+  /// up to [`DP_MAX_VERTICES_LIMIT`].
+  pub fn with_dp_max_vertices(mut self, dp_max_vertices: usize) -> Self {
+    self.dp_max_vertices = dp_max_vertices.min(DP_MAX_VERTICES_LIMIT);
+    self
+  }
+
+  pub fn enumerate(&self) -> Vec<Vid> {
+    let mut orders = (self.model.components().into_iter())
+      .map(|component| match component.len() <= self.dp_max_vertices {
+        true => self.dp_order(&component),
+        false => self.greedy_order(&component),
+      })
+      .collect_vec();
+    // a cartesian product grows the least with the smaller parts first
+    orders.sort_by(|(_, l_card), (_, r_card)| l_card.total_cmp(r_card));
+
+    (orders.into_iter())
+      .flat_map(|(order, _)| order)
+      .map(|idx| self.model.vids[idx].clone())
+      .collect()
+  }
+
+  /// The optimal order of a connected part, with its estimated matches.
+  fn dp_order(&self, component: &[usize]) -> (Vec<usize>, f64) {
+    let n = component.len();
+    let full = (1usize << n) - 1;
+    // bit -> index in the model
+    let bit_of = |idx: usize| component.binary_search(&idx).unwrap();
+    let adj_masks = (component.iter())
+      .map(|&idx| {
+        (self.model.adj_idxs[idx].iter()).fold(0usize, |mask, &adj| mask | 1 << bit_of(adj))
+      })
+      .collect_vec();
+
+    // sub-pattern (as a bit mask) -> its matches / the cost of its best order / its last vertex
+    let mut cards = vec![0.0; full + 1];
+    let mut costs = vec![f64::INFINITY; full + 1];
+    let mut lasts = vec![usize::MAX; full + 1];
+    cards[0] = 1.0;
+
+    // a sub-pattern is always visited after all of its own sub-patterns
+    for mask in 1..=full {
+      // the matches don't depend on the order
+      let lowest = mask.trailing_zeros() as usize;
+      let rest = mask & !(1 << lowest);
+      cards[mask] = cards[rest]
+        * self
+          .model
+          .growth(component[lowest], |idx| rest & 1 << bit_of(idx) != 0);
+
+      if rest == 0 {
+        costs[mask] = cards[mask];
+        lasts[mask] = lowest;
+        continue;
+      }
+      for bit in (0..n).filter(|bit| mask & 1 << bit != 0) {
+        let prev = mask & !(1 << bit);
+        // the previous sub-pattern is connected, and so is the vertex to it
+        if costs[prev].is_infinite() || adj_masks[bit] & prev == 0 {
+          continue;
+        }
+        let cost = costs[prev] + cards[mask];
+        if cost < costs[mask] {
+          costs[mask] = cost;
+          lasts[mask] = bit;
+        }
+      }
+    }
+
+    let mut order = Vec::with_capacity(n);
+    let mut mask = full;
+    while mask != 0 {
+      let bit = lasts[mask];
+      order.push(component[bit]);
+      mask &= !(1 << bit);
+    }
+    order.reverse();
+    (order, cards[full])
+  }
+
+  /// A good enough order of a connected part, with its estimated matches.
+  fn greedy_order(&self, component: &[usize]) -> (Vec<usize>, f64) {
+    let mut is_matched = vec![false; self.model.vids.len()];
+    let mut order = Vec::with_capacity(component.len());
+    let mut card = 1.0;
+
+    while order.len() < component.len() {
+      let candidates = (component.iter())
+        .copied()
+        .filter(|&idx| !is_matched[idx])
+        .filter(|&idx| {
+          order.is_empty() || self.model.adj_idxs[idx].iter().any(|&adj| is_matched[adj])
+        });
+      // the first one of the fewest matches
+      let (idx, growth) = candidates
+        .map(|idx| (idx, self.model.growth(idx, |adj| is_matched[adj])))
+        .min_by(|(_, l), (_, r)| l.total_cmp(r))
+        .unwrap();
+      is_matched[idx] = true;
+      order.push(idx);
+      card *= growth;
+    }
+    (order, card)
+  }
+}
+
+#[cfg(test)]
+mod test_join_order {
+  use super::*;
+  use crate::planner::{
+    QueryFormat, generate_plan_with_strategy, order_calc::OrderStrategy, parse_query,
+    parse_query_file,
+  };
+  use hashbrown::HashMap;
+
+  fn statistics() -> Statistics {
+    Statistics {
+      v_cnt: 1_111_100,
+      e_cnt: 0,
+      v_label_cnt: HashMap::from_iter([
+        ("Country".to_string(), 100),
+        ("City".to_string(), 10_000),
+        ("Person".to_string(), 1_000_000),
+        ("Forum".to_string(), 100_000),
+        ("Tag".to_string(), 1_000),
+      ]),
+      e_label_cnt: HashMap::from_iter([
+        ("isPartOf".to_string(), 10_000),
+        ("isLocatedIn".to_string(), 1_000_000),
+        ("hasModerator".to_string(), 100_000),
+        ("hasInterest".to_string(), 10_000_000),
+        ("knows".to_string(), 20_000_000),
+      ]),
+      ..Default::default()
+    }
+  }
+
+  fn order_of(cypher: &str, dp_max_vertices: usize) -> Vec<Vid> {
+    let pattern = parse_query(cypher, QueryFormat::Cypher).unwrap();
+    let (graph, _) = pattern.build().unwrap();
+    JoinOrderEnumerator::new(&graph, &statistics())
+      .with_dp_max_vertices(dp_max_vertices)
+      .enumerate()
+  }
+
+  #[test]
+  fn test_dp_order() {
+    let cypher = "MATCH (co:Country)<-[a:isPartOf]-(ci:City)<-[b:isLocatedIn]-(p:Person), \
+                  (f:Forum)-[c:hasModerator]->(p)-[d:hasInterest]->(t:Tag) \
+                  WHERE co.name = 'China' RETURN p";
+    let order = order_of(cypher, DP_MAX_VERTICES);
+    // from the selective end, and always connected
+    assert_eq!(order, ["co", "ci", "p", "f", "t"]);
+    assert_eq!(order_of(cypher, 1), order);
+
+    // the smaller part first, the other one from its filtered vertex
+    let order = order_of(
+      "MATCH (t:Tag), (p:Person)-[k:knows]->(q:Person) WHERE q.id = 42 RETURN p",
+      DP_MAX_VERTICES,
+    );
+    assert_eq!(order, ["t", "q", "p"]);
+
+    // too many vertices to enumerate, however many are allowed
+    let path = (0..70)
+      .map(|i| format!("(v{i}:Person)"))
+      .join("-[:knows]->");
+    let order = order_of(&format!("MATCH {path} RETURN v0"), usize::MAX);
+    assert_eq!(order.len(), 70);
+  }
+
+  #[test]
+  fn test_dp_plans_of_queries() {
+    let queries = project_root::get_project_root()
+      .unwrap()
+      .join("resources")
+      .join("queries");
+    for entry in std::fs::read_dir(queries).unwrap() {
+      let path = entry.unwrap().path();
+      if !path
+        .file_name()
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .starts_with("ldbc-bi-")
+      {
+        continue;
+      }
+      let plan = generate_plan_with_strategy(&path, OrderStrategy::DynamicProgramming).unwrap();
+      let graph = parse_query_file(&path)
+        .unwrap()
+        .build()
+        .unwrap()
+        .0
+        .required_part();
+
+      // every vertex but the first is adjacent to the matched ones (the queries are connected)
+      let order = &plan.matching_order;
+      assert_eq!(
+        order.iter().sorted().collect_vec(),
+        graph.view_vids().into_iter().sorted().collect_vec()
+      );
+      for (idx, vid) in order.iter().enumerate().skip(1) {
+        let adj_vids = graph.get_adj_vids(vid);
+        assert!(
+          order[..idx].iter().any(|prev| adj_vids.contains(prev)),
+          "{}",
+          path.display()
+        );
+      }
+    }
+  }
+}
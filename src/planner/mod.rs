@@ -1,28 +1,321 @@
-use crate::{parser::PatternParser, schemas::PlanData};
+use crate::{
+  parser::{CypherParser, JsonPatternParser, ParseError, PatternParser},
+  schemas::{CrossPredicate, PatternEdge, PatternVertex, PlanData, Symmetry, SymmetryMode},
+  utils::dyn_graph::DynGraph,
+};
+use explain::{Explain, OrderTerm};
 use itertools::Itertools;
-use order_calc::{OrderCalculator, PlanGenInput};
+use order_calc::{OrderCalculator, OrderStrategy, PlanGenInput};
+use pattern::{Pattern, PatternError};
 use plan_dump::PlanDumper;
 use plan_gen::PlanGenerator;
 use plan_opt::PlanOptimizer;
-use std::{fs, path::Path};
+use std::{fmt::Display, fs, io, path::Path};
 
+pub mod explain;
+pub mod export;
+pub mod join_order;
 pub mod order_calc;
+pub mod pattern;
 pub mod plan_dump;
 pub mod plan_gen;
 pub mod plan_opt;
+pub mod statistics;
+pub mod symmetry;
+pub mod validate;
 
-pub fn generate_optimal_plan(query_path: &Path) -> PlanData {
-  let query_src = fs::read_to_string(query_path).expect("❌  Failed to read query file.");
+/// The language of a query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryFormat {
+  /// the count-prefixed pattern format, see [`PatternParser`]
+  Txt,
+  Cypher,
+  /// see [`JsonPatternParser`]
+  Json,
+}
+
+impl QueryFormat {
+  /// `*.cypher` files are cypher, `*.json` ones are in the JSON pattern format,
+  /// all the others are treated as the count-prefixed format.
+  pub fn of_path(query_path: &Path) -> Self {
+    (query_path.extension().and_then(|ext| ext.to_str()))
+      .and_then(Self::of_extension)
+      .unwrap_or(Self::Txt)
+  }
+
+  /// The format of the query files with `extension`, if it's one of a query file.
+  pub fn of_extension(extension: &str) -> Option<Self> {
+    match extension {
+      "txt" => Some(Self::Txt),
+      "cypher" => Some(Self::Cypher),
+      "json" => Some(Self::Json),
+      _ => None,
+    }
+  }
+}
+
+/// Why a query (file) can't be planned.
+#[derive(Debug)]
+pub enum QueryError {
+  /// the query file can't be read
+  Io(io::Error),
+  Parse(ParseError),
+  /// the parsed pattern is invalid as a whole, e.g. disconnected
+  Pattern(PatternError),
+}
+
+impl Display for QueryError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Io(e) => write!(f, "failed to read the query file: {e}"),
+      Self::Parse(e) => write!(f, "{e}"),
+      Self::Pattern(e) => write!(f, "{e}"),
+    }
+  }
+}
+
+impl std::error::Error for QueryError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Self::Io(e) => Some(e),
+      Self::Parse(e) => Some(e),
+      Self::Pattern(e) => Some(e),
+    }
+  }
+}
+
+impl From<io::Error> for QueryError {
+  fn from(e: io::Error) -> Self {
+    Self::Io(e)
+  }
+}
+
+impl From<ParseError> for QueryError {
+  fn from(e: ParseError) -> Self {
+    Self::Parse(e)
+  }
+}
+
+impl From<PatternError> for QueryError {
+  fn from(e: PatternError) -> Self {
+    Self::Pattern(e)
+  }
+}
+
+/// Parse a query into its pattern.
+pub fn parse_query(query_src: &str, format: QueryFormat) -> Result<Pattern, ParseError> {
+  let (pattern_graph, cross_predicates) = match format {
+    QueryFormat::Cypher => {
+      let mut parser = CypherParser::new(query_src.to_string());
+      parser.parse()?;
+      let cross_predicates = parser.cross_predicates().to_vec();
+      (parser.take_as_pattern_graph(), cross_predicates)
+    }
+    QueryFormat::Txt => {
+      let mut parser = PatternParser::new(query_src.to_string());
+      parser.parse()?;
+      let cross_predicates = parser.cross_predicates().to_vec();
+      (parser.take_as_pattern_graph(), cross_predicates)
+    }
+    QueryFormat::Json => {
+      let mut parser = JsonPatternParser::new(query_src.to_string());
+      parser.parse()?;
+      let cross_predicates = parser.cross_predicates().to_vec();
+      (parser.take_as_pattern_graph(), cross_predicates)
+    }
+  };
+  Ok(Pattern::from_parsed(pattern_graph, cross_predicates))
+}
+
+/// Parse a query file into its pattern, see [`QueryFormat::of_path`].
+pub fn parse_query_file(query_path: &Path) -> Result<Pattern, QueryError> {
+  let query_src = fs::read_to_string(query_path)?;
+  Ok(parse_query(&query_src, QueryFormat::of_path(query_path))?)
+}
+
+pub fn generate_optimal_plan(query_path: &Path) -> Result<PlanData, QueryError> {
+  let pattern = parse_query_file(query_path)?;
+  Ok(generate_optimal_plan_for(pattern)?)
+}
+
+/// [`generate_optimal_plan`] of a query, instead of a query file.
+pub fn generate_optimal_plan_from_query(
+  query_src: &str,
+  format: QueryFormat,
+) -> Result<PlanData, QueryError> {
+  let pattern = parse_query(query_src, format)?;
+  Ok(generate_optimal_plan_for(pattern)?)
+}
+
+/// [`generate_optimal_plan`] of a pattern built in code.
+pub fn generate_optimal_plan_for(pattern: Pattern) -> Result<PlanData, PatternError> {
+  generate_plan_with_strategy_for(pattern, OrderStrategy::default())
+}
+
+/// [`generate_optimal_plan`], with the matching order searched for by `strategy`.
+pub fn generate_plan_with_strategy(
+  query_path: &Path,
+  strategy: OrderStrategy,
+) -> Result<PlanData, QueryError> {
+  let pattern = parse_query_file(query_path)?;
+  Ok(generate_plan_with_strategy_for(pattern, strategy)?)
+}
+
+/// [`generate_plan_with_strategy`] of a pattern built in code.
+pub fn generate_plan_with_strategy_for(
+  pattern: Pattern,
+  strategy: OrderStrategy,
+) -> Result<PlanData, PatternError> {
+  let (pattern_graph, cross_predicates) = pattern.build()?;
+  Ok(optimal_plan(
+    pattern_graph,
+    cross_predicates,
+    strategy,
+    None,
+  ))
+}
+
+/// [`generate_optimal_plan`], where the symmetries of the pattern are broken (see `symmetry`),
+/// and its matches are reported as of `mode`.
+///
+/// It's opt-in, since the vertices the symmetries permute are taken as bound to different
+/// data vertices, while the matches merged by `Report` may bind several of them to the same one,
+/// which are then dropped.
+pub fn generate_plan_with_symmetry(
+  query_path: &Path,
+  mode: SymmetryMode,
+) -> Result<PlanData, QueryError> {
+  let pattern = parse_query_file(query_path)?;
+  Ok(generate_plan_with_symmetry_for(pattern, mode)?)
+}
+
+/// [`generate_plan_with_symmetry`] of a query, instead of a query file.
+pub fn generate_plan_with_symmetry_from_query(
+  query_src: &str,
+  format: QueryFormat,
+  mode: SymmetryMode,
+) -> Result<PlanData, QueryError> {
+  let pattern = parse_query(query_src, format)?;
+  Ok(generate_plan_with_symmetry_for(pattern, mode)?)
+}
 
-  // Parse the query source
-  let mut parser = PatternParser::new(query_src);
-  parser.parse();
-  let pattern_graph = parser.take_as_pattern_graph();
+/// [`generate_plan_with_symmetry`] of a pattern built in code.
+pub fn generate_plan_with_symmetry_for(
+  pattern: Pattern,
+  mode: SymmetryMode,
+) -> Result<PlanData, PatternError> {
+  let (pattern_graph, cross_predicates) = pattern.build()?;
+  Ok(optimal_plan(
+    pattern_graph,
+    cross_predicates,
+    OrderStrategy::default(),
+    Some(mode),
+  ))
+}
+
+pub fn generate_plan_with_given_order(
+  query_path: &Path,
+  given_order: &[&str],
+) -> Result<PlanData, QueryError> {
+  let pattern = parse_query_file(query_path)?;
+  Ok(generate_plan_with_given_order_for(pattern, given_order)?)
+}
+
+/// [`generate_plan_with_given_order`] of a query, instead of a query file.
+pub fn generate_plan_with_given_order_from_query(
+  query_src: &str,
+  format: QueryFormat,
+  given_order: &[&str],
+) -> Result<PlanData, QueryError> {
+  let pattern = parse_query(query_src, format)?;
+  Ok(generate_plan_with_given_order_for(pattern, given_order)?)
+}
+
+/// [`generate_plan_with_given_order`] of a pattern built in code.
+pub fn generate_plan_with_given_order_for(
+  pattern: Pattern,
+  given_order: &[&str],
+) -> Result<PlanData, PatternError> {
+  let (pattern_graph, cross_predicates) = pattern.build_in_order(given_order)?;
+  Ok(plan_with_given_order(
+    pattern_graph,
+    cross_predicates,
+    given_order,
+  ))
+}
 
+/// Explain the plan of [`generate_optimal_plan`], without executing it.
+pub fn explain(query_path: &Path) -> Result<Explain, QueryError> {
+  explain_with_strategy(query_path, OrderStrategy::default())
+}
+
+/// [`explain`] the plan of [`generate_plan_with_strategy`].
+pub fn explain_with_strategy(
+  query_path: &Path,
+  strategy: OrderStrategy,
+) -> Result<Explain, QueryError> {
+  let pattern = parse_query_file(query_path)?;
+  Ok(explain_with_strategy_for(pattern, strategy)?)
+}
+
+/// [`explain`] of a query, instead of a query file.
+pub fn explain_query(query_src: &str, format: QueryFormat) -> Result<Explain, QueryError> {
+  let pattern = parse_query(query_src, format)?;
+  Ok(explain_with_strategy_for(
+    pattern,
+    OrderStrategy::default(),
+  )?)
+}
+
+/// [`explain_with_strategy`] of a pattern built in code.
+pub fn explain_with_strategy_for(
+  pattern: Pattern,
+  strategy: OrderStrategy,
+) -> Result<Explain, PatternError> {
+  let (pattern_graph, cross_predicates) = pattern.build()?;
+  let (plan_data, order_terms) = explained_plan(pattern_graph, cross_predicates, strategy);
+  Ok(Explain::new(&plan_data, strategy, order_terms))
+}
+
+fn optimal_plan(
+  pattern_graph: DynGraph<PatternVertex, PatternEdge>,
+  cross_predicates: Vec<CrossPredicate>,
+  strategy: OrderStrategy,
+  symmetry_mode: Option<SymmetryMode>,
+) -> PlanData {
   // Compute the optimal matching order
-  let order_calc = OrderCalculator::new(pattern_graph);
+  let order_calc = OrderCalculator::new(pattern_graph)
+    .with_cross_predicates(cross_predicates)
+    .with_strategy(strategy);
   let plan_gen_input = order_calc.compute_optimal_order();
 
+  plan_from(plan_gen_input, symmetry_mode)
+}
+
+/// [`optimal_plan`], along with the terms of the cost model behind its matching order.
+fn explained_plan(
+  pattern_graph: DynGraph<PatternVertex, PatternEdge>,
+  cross_predicates: Vec<CrossPredicate>,
+  strategy: OrderStrategy,
+) -> (PlanData, Vec<OrderTerm>) {
+  let order_calc = OrderCalculator::new(pattern_graph)
+    .with_cross_predicates(cross_predicates)
+    .with_strategy(strategy);
+  let (plan_gen_input, order_terms) = order_calc.compute_optimal_order_explained();
+
+  (plan_from(plan_gen_input, None), order_terms)
+}
+
+/// `symmetry_mode` is given only to break the symmetries of the pattern.
+fn plan_from(mut plan_gen_input: PlanGenInput, symmetry_mode: Option<SymmetryMode>) -> PlanData {
+  // Break the symmetries of the pattern
+  let mut symmetry = Symmetry::default();
+  if let Some(symmetry_mode) = symmetry_mode {
+    let vid_lt_predicates;
+    (symmetry, vid_lt_predicates) = symmetry::break_symmetries(&plan_gen_input, symmetry_mode);
+    plan_gen_input.cross_predicates.extend(vid_lt_predicates);
+  }
+
   // Generate the raw plan
   let mut plan_gen = PlanGenerator::from(plan_gen_input);
   plan_gen.generate_raw_plan();
@@ -32,39 +325,21 @@ pub fn generate_optimal_plan(query_path: &Path) -> PlanData {
   plan_optimizer.apply_optimization();
 
   // Dump the plan
-  let plan_dumper = PlanDumper::from(plan_optimizer);
+  let plan_dumper = PlanDumper::from(plan_optimizer).with_symmetry(symmetry);
   plan_dumper.to_plan_data()
 }
 
-pub fn generate_plan_with_given_order(query_path: &Path, given_order: &[&str]) -> PlanData {
-  let query_src = fs::read_to_string(query_path).expect("❌  Failed to read query file.");
-
-  // Parse the query source
-  let mut parser = PatternParser::new(query_src);
-  parser.parse();
-  let pattern_graph = parser.take_as_pattern_graph();
-
-  #[cfg(debug_assertions)]
-  assert!(
-    pattern_graph.has_all_vids(given_order) && pattern_graph.get_v_count() == given_order.len(),
-    "❌  The `given order` != `pattern graph vertices`."
-  );
-
+fn plan_with_given_order(
+  pattern_graph: DynGraph<PatternVertex, PatternEdge>,
+  cross_predicates: Vec<CrossPredicate>,
+  given_order: &[&str],
+) -> PlanData {
   let given_order = given_order.iter().map(|s| s.to_string()).collect_vec();
   let plan_gen_input = PlanGenInput {
     pattern_graph,
     optimal_order: given_order,
+    cross_predicates,
   };
 
-  // Generate the raw plan
-  let mut plan_gen = PlanGenerator::from(plan_gen_input);
-  plan_gen.generate_raw_plan();
-
-  // Optimize the plan
-  let mut plan_optimizer = PlanOptimizer::from(plan_gen);
-  plan_optimizer.apply_optimization();
-
-  // Dump the plan
-  let plan_dumper = PlanDumper::from(plan_optimizer);
-  plan_dumper.to_plan_data()
+  plan_from(plan_gen_input, None)
 }
@@ -1,36 +1,45 @@
+use super::{
+  explain::OrderTerm,
+  join_order::{CardinalityModel, JoinOrderEnumerator},
+  statistics::{STATISTICS, Statistics},
+};
 use crate::{
-  schemas::{Label, Op, PatternEdge, PatternVertex, Vid},
+  schemas::{CrossPredicate, Op, PatternEdge, PatternPredicate, PatternVertex, Vid},
   utils::dyn_graph::DynGraph,
 };
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 use itertools::Itertools;
 use ordered_float::OrderedFloat;
-use project_root::get_project_root;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use serde::{Deserialize, Serialize};
-use std::{fs::File, path::PathBuf, sync::LazyLock};
-
-static STAT_FILEPATH: LazyLock<PathBuf> = LazyLock::new(|| {
-  get_project_root()
-    .unwrap()
-    .join("resources")
-    .join("statistics")
-    .join("label_statistics.json")
-});
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct Statistics {
-  v_cnt: usize,
-  e_cnt: usize,
-  v_label_cnt: HashMap<Label, usize>,
-  e_label_cnt: HashMap<Label, usize>,
+use serde::Serialize;
+
+/// How the matching order is searched for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, strum_macros::Display)]
+pub enum OrderStrategy {
+  /// the vertices bucketed by their most selective predicate, then sorted by heuristic prices,
+  /// as long as each one is adjacent to the matched ones
+  #[default]
+  RuleBased,
+  /// the cheapest order by the estimated matches, see [`JoinOrderEnumerator`]
+  DynamicProgramming,
 }
 
 #[derive(Debug, Clone)]
 pub struct OrderCalculator {
-  statistics: Statistics,
+  statistics: &'static Statistics,
+  strategy: OrderStrategy,
   pattern_graph: DynGraph<PatternVertex, PatternEdge>,
+  /// the optional / negated sub-patterns are left out of the order
+  required_graph: DynGraph<PatternVertex, PatternEdge>,
+  /// left to the plan generator, which attaches them to the instructions
+  cross_predicates: Vec<CrossPredicate>,
   cost_2_vids: HashMap<usize, Vec<Vid>>,
+  /// the estimated candidates of each vertex, by its label and predicates
+  v_cands: HashMap<Vid, usize>,
+  /// the heuristic prices of the vertices, by their edges and neighbors
+  v_prices: HashMap<Vid, f64>,
+  /// the worst-case costs of the vertices, by their candidates and the edges from them
+  v_costs: HashMap<Vid, usize>,
 
   order: Vec<Vid>,
 
@@ -40,20 +49,82 @@ pub struct OrderCalculator {
   plain_vids: Vec<Vid>,
 }
 
+/// Selectivity class of a predicate, from the most selective to the least.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, strum_macros::Display)]
+pub(crate) enum AttrGroup {
+  Eq,
+  Range,
+  Ne,
+  Plain,
+}
+
+impl AttrGroup {
+  /// The group of a vertex / edge, decided by its most selective predicate.
+  pub(crate) fn of_attrs(attrs: &[PatternPredicate]) -> Self {
+    (attrs.iter())
+      .map(|pred| Self::of(&pred.to_nnf()))
+      .min()
+      .unwrap_or(Self::Plain)
+  }
+
+  /// `pred` should be in negation normal form.
+  pub(crate) fn of(pred: &PatternPredicate) -> Self {
+    match pred {
+      PatternPredicate::Cmp(attr) => Self::of_op(attr.op),
+      // whatever it's bound to later
+      PatternPredicate::Param { op, .. } => Self::of_op(*op),
+      // an IN-list is a handful of point lookups
+      PatternPredicate::In { .. } => Self::Eq,
+      PatternPredicate::Between { .. } => Self::Range,
+      // a conjunction is as selective as its most selective part
+      PatternPredicate::And { and: preds } => {
+        preds.iter().map(Self::of).min().unwrap_or(Self::Plain)
+      }
+      // a disjunction is as selective as its least selective part
+      PatternPredicate::Or { or: preds } => preds.iter().map(Self::of).max().unwrap_or(Self::Eq),
+      PatternPredicate::Not { not } => match not.as_ref() {
+        PatternPredicate::Between { .. } => Self::Range,
+        _ => Self::Ne,
+      },
+    }
+  }
+
+  pub(crate) fn of_op(op: Op) -> Self {
+    match op {
+      Op::Eq | Op::EqIgnoreCase => Self::Eq,
+      Op::Ne => Self::Ne,
+      // a prefix is a range of strings
+      Op::StartsWith => Self::Range,
+      // can't narrow down the candidates much
+      Op::EndsWith | Op::Contains | Op::Regex => Self::Ne,
+      _ => Self::Range,
+    }
+  }
+
+  /// System R's defaults, for the attributes without statistics.
+  pub(crate) fn default_selectivity(self) -> f64 {
+    match self {
+      Self::Eq => 0.1,
+      Self::Range => 1.0 / 3.0,
+      Self::Ne => 0.9,
+      Self::Plain => 1.0,
+    }
+  }
+}
+
 #[derive(Debug, Clone)]
 pub struct PlanGenInput {
   pub(crate) pattern_graph: DynGraph<PatternVertex, PatternEdge>,
   pub(crate) optimal_order: Vec<Vid>,
+  pub(crate) cross_predicates: Vec<CrossPredicate>,
 }
 
 impl OrderCalculator {
   pub fn new(pattern_graph: DynGraph<PatternVertex, PatternEdge>) -> Self {
-    let statistics: Statistics = serde_json::from_reader(
-      File::open(STAT_FILEPATH.as_path()).expect("❌  Failed to open statistics file"),
-    )
-    .expect("❌  Failed to parse statistics file");
+    let statistics = &*STATISTICS;
 
-    let raw_order = pattern_graph
+    let required_graph = pattern_graph.required_part();
+    let raw_order = required_graph
       .view_vids()
       .into_iter()
       .map(String::from)
@@ -62,8 +133,14 @@ impl OrderCalculator {
 
     Self {
       statistics,
+      strategy: OrderStrategy::default(),
       pattern_graph,
+      required_graph,
+      cross_predicates: vec![],
       cost_2_vids: HashMap::with_capacity(max_cap),
+      v_cands: HashMap::with_capacity(max_cap),
+      v_prices: HashMap::with_capacity(max_cap),
+      v_costs: HashMap::with_capacity(max_cap),
       order: Vec::with_capacity(max_cap),
       eq_vids: Vec::with_capacity(max_cap),
       range_vids: Vec::with_capacity(max_cap),
@@ -72,42 +149,46 @@ impl OrderCalculator {
     }
   }
 
+  /// The predicates across several pattern vertices / edges, e.g. `a.id <> b.id`.
+  pub fn with_cross_predicates(mut self, cross_predicates: Vec<CrossPredicate>) -> Self {
+    self.cross_predicates = cross_predicates;
+    self
+  }
+
+  pub fn with_strategy(mut self, strategy: OrderStrategy) -> Self {
+    self.strategy = strategy;
+    self
+  }
+
   fn group_vids_by_attr_op(&mut self) {
-    for (vid, v) in self.pattern_graph.v_entities.iter() {
-      if let Some(ref attr) = v.attr {
-        match attr.op {
-          Op::Eq => self.eq_vids.push(vid.clone()),
-          Op::Ne => self.ne_vids.push(vid.clone()),
-          _ => self.range_vids.push(vid.clone()),
-        }
-      } else {
-        self.plain_vids.push(vid.clone());
+    for (vid, v) in self.required_graph.v_entities.iter() {
+      match AttrGroup::of_attrs(&v.attrs) {
+        AttrGroup::Eq => self.eq_vids.push(vid.clone()),
+        AttrGroup::Range => self.range_vids.push(vid.clone()),
+        AttrGroup::Ne => self.ne_vids.push(vid.clone()),
+        AttrGroup::Plain => self.plain_vids.push(vid.clone()),
       }
     }
   }
 
   /// Core logic: Rule-based optimization
   fn rule_based_optimization(&mut self) {
-    let mut v_prices = HashMap::new();
-
-    for vid in self.pattern_graph.v_entities.keys() {
+    for vid in self.required_graph.v_entities.keys() {
       let mut price = 0.0;
 
       // 1. edge connectivity
-      let in_degree = self.pattern_graph.get_in_degree(vid);
-      let out_degree = self.pattern_graph.get_out_degree(vid);
+      let in_degree = self.required_graph.get_in_degree(vid);
+      let out_degree = self.required_graph.get_out_degree(vid);
       // vertex with more edges should be more expensive
       price += (in_degree + out_degree) as f64;
 
       // 2. vertex connectivity
-      let num_of_neighbors = self.pattern_graph.get_adj_vids(vid).len();
+      let num_of_neighbors = self.required_graph.get_adj_vids(vid).len();
       // vertex with more neighbors should be more expensive
       // (and should be much more expensive than edge connectivity)
       price += num_of_neighbors as f64 * 5.0;
 
-      let price = OrderedFloat::from(price);
-
-      v_prices.insert(vid, price);
+      self.v_prices.insert(vid.clone(), price);
     }
 
     // for each bucket
@@ -118,7 +199,7 @@ impl OrderCalculator {
       &mut self.plain_vids,
     ] {
       // Sort by price (ASC)
-      bucket.sort_unstable_by_key(|vid| v_prices[vid]);
+      bucket.sort_unstable_by_key(|vid| OrderedFloat(self.v_prices[vid]));
     }
   }
 
@@ -126,19 +207,19 @@ impl OrderCalculator {
   ///
   /// To keep `worst-case optimal`, we assume that each step is in the worst case.
   fn cost_based_optimization(&mut self) {
-    let vs = self.pattern_graph.view_v_entities();
+    let vs = self.required_graph.view_v_entities();
 
     vs.into_par_iter()
       .map(|v| {
-        // Get current vertex's cost (=> v_label_cnt)
-        let mut v_cost = self
-          .statistics
-          .v_label_cnt
-          .get(&v.label)
-          .copied()
-          .unwrap_or(0);
-
-        let dst_grouped_adj_eids = self.pattern_graph.view_adj_es_grouped_by_target_vid(&v.vid);
+        // Get current vertex's cost (=> v_label_cnt, narrowed down by its predicates)
+        let label_cnt = self.statistics.v_cnt_of(&v.label);
+        let v_cands =
+          (label_cnt as f64 * self.statistics.v_selectivity(&v.label, &v.attrs)).ceil() as usize;
+        let mut v_cost = v_cands;
+
+        let dst_grouped_adj_eids = self
+          .required_graph
+          .view_adj_es_grouped_by_target_vid(&v.vid);
         let mut dst_grouped_e_costs = Vec::with_capacity(dst_grouped_adj_eids.len());
 
         // In the worst case, each vertex could match.
@@ -153,15 +234,22 @@ impl OrderCalculator {
           // the patterns of edges between two vertices will never overlap.
           let curr_group_adj_es_costs: usize = adj_eids
             .into_iter()
-            .map(|eid| {
-              let e_estimation = self
-                .statistics
-                .e_label_cnt
-                .get(&eid.label)
-                .copied()
-                .unwrap_or(0);
-              // notice that, e_cost should be the minimum value of `e_est` and `original_v_cost`
-              e_estimation.min(original_v_cost)
+            .map(|e| {
+              let label_of = |vid: &str| &self.required_graph.v_entities()[vid].label;
+              let e_matches =
+                self
+                  .statistics
+                  .e_matches(e, label_of(&e.src_vid), label_of(&e.dst_vid));
+              // the average degree of a data vertex (of `v`'s label) by `e`
+              let fan_out = e_matches / label_cnt.max(1) as f64;
+              let fan_out = match e.var_length {
+                None => fan_out,
+                Some(var_length) => (var_length.min_hops..=var_length.max_hops)
+                  .map(|hops| fan_out.powi(hops as i32))
+                  .sum(),
+              };
+              // notice that, e_cost is the edges from the candidates of `v`
+              (original_v_cost as f64 * fan_out).ceil() as usize
             })
             .sum();
 
@@ -171,20 +259,20 @@ impl OrderCalculator {
         // Now we have the cost of `v`
         let cost = v_cost + dst_grouped_e_costs.into_iter().sum::<usize>();
 
-        (v.vid.clone(), cost)
+        (v.vid.clone(), v_cands, cost)
       })
       .collect_vec_list()
       .into_iter()
       .flatten()
-      .for_each(|(vid, cost)| {
+      .for_each(|(vid, v_cands, cost)| {
         // Update
+        self.v_cands.insert(vid.clone(), v_cands);
         self.cost_2_vids.entry(cost).or_default().push(vid);
       });
 
-    let mut v_costs = HashMap::with_capacity(self.cost_2_vids.len());
     for (cost, vids) in self.cost_2_vids.drain() {
       for vid in vids {
-        v_costs.insert(vid, cost);
+        self.v_costs.insert(vid, cost);
       }
     }
 
@@ -196,7 +284,7 @@ impl OrderCalculator {
       &mut self.plain_vids,
     ] {
       // Sort by cost (ASC)
-      bucket.sort_by_key(|vid| v_costs[vid]);
+      bucket.sort_by_key(|vid| self.v_costs[vid]);
     }
   }
 
@@ -212,17 +300,148 @@ impl OrderCalculator {
 
     // 4. plain_vids (no attr)
     self.order.append(&mut self.plain_vids);
+
+    // start from the fewest candidates, whichever group it's in
+    if let Some(start) = (self.order.iter()).position_min_by_key(|vid| self.v_cands[*vid]) {
+      let start = self.order.remove(start);
+      self.order.insert(0, start);
+    }
+  }
+
+  /// Reorder the vertices (from the most preferred to the least), so that each one is adjacent
+  /// to the matched ones, or else starts another connected part of the pattern.
+  ///
+  /// A connected part is thus matched as a whole from a single `Init`, before the next one starts,
+  /// and the parts are only joined once, by the final merge of the executor.
+  fn preserve_connectivity(&mut self) {
+    let preferred = std::mem::take(&mut self.order);
+    let mut is_matched = HashSet::with_capacity(preferred.len());
+
+    while self.order.len() < preferred.len() {
+      let mut unmatched = preferred.iter().filter(|vid| !is_matched.contains(*vid));
+      // the most preferred one adjacent to the matched ones (of the current part)
+      let next = (unmatched.clone())
+        .find(|vid| {
+          (self.required_graph.get_adj_vids(vid).iter()).any(|adj| is_matched.contains(adj))
+        })
+        // all of the current part are matched, start the next one
+        .or_else(|| unmatched.next())
+        .unwrap()
+        .clone();
+      is_matched.insert(next.clone());
+      self.order.push(next);
+    }
   }
 
   pub fn compute_optimal_order(mut self) -> PlanGenInput {
-    self.group_vids_by_attr_op();
-    self.rule_based_optimization();
-    self.cost_based_optimization();
-    self.concat_final_optimal_order();
+    self.optimize();
+    self.into_plan_gen_input()
+  }
+
+  /// [`Self::compute_optimal_order`], along with the terms of the cost model behind it.
+  pub(crate) fn compute_optimal_order_explained(mut self) -> (PlanGenInput, Vec<OrderTerm>) {
+    self.optimize();
+    let order_terms = self.order_terms();
+    (self.into_plan_gen_input(), order_terms)
+  }
+
+  fn optimize(&mut self) {
+    match self.strategy {
+      OrderStrategy::RuleBased => {
+        self.group_vids_by_attr_op();
+        self.rule_based_optimization();
+        self.cost_based_optimization();
+        self.concat_final_optimal_order();
+        self.preserve_connectivity();
+      }
+      OrderStrategy::DynamicProgramming => {
+        self.order = JoinOrderEnumerator::new(&self.required_graph, self.statistics).enumerate();
+      }
+    }
+  }
+
+  /// The terms of each vertex in the order, where the prices and the costs are only there
+  /// for the rule-based strategy.
+  fn order_terms(&self) -> Vec<OrderTerm> {
+    let model = CardinalityModel::new(&self.required_graph, self.statistics);
+    (self.order.iter())
+      .zip(model.growths_along(&self.order))
+      .map(|(vid, (part, prev_matches, growth))| {
+        let v = &self.required_graph.v_entities()[vid];
+        let label_cnt = self.statistics.v_cnt_of(&v.label);
+        let selectivity = self.statistics.v_selectivity(&v.label, &v.attrs);
+        OrderTerm {
+          vid: vid.clone(),
+          label: v.label.clone(),
+          part,
+          label_cnt,
+          selectivity,
+          candidates: (label_cnt as f64 * selectivity).ceil() as usize,
+          attr_group: AttrGroup::of_attrs(&v.attrs),
+          price: self.v_prices.get(vid).copied(),
+          cost: self.v_costs.get(vid).copied(),
+          prev_matches,
+          growth,
+          matches: prev_matches * growth,
+        }
+      })
+      .collect()
+  }
 
+  fn into_plan_gen_input(self) -> PlanGenInput {
     PlanGenInput {
       pattern_graph: self.pattern_graph,
       optimal_order: self.order,
+      cross_predicates: self.cross_predicates,
+    }
+  }
+}
+
+#[cfg(test)]
+mod test_order_calc {
+  use crate::{
+    planner::{generate_optimal_plan, parse_query_file},
+    schemas::InstructionType,
+  };
+
+  #[test]
+  fn test_connected_orders_of_queries() {
+    let queries = project_root::get_project_root()
+      .unwrap()
+      .join("resources")
+      .join("queries");
+    for entry in std::fs::read_dir(queries).unwrap() {
+      let path = entry.unwrap().path();
+      let plan = generate_optimal_plan(&path).unwrap();
+      let graph = parse_query_file(&path)
+        .unwrap()
+        .build()
+        .unwrap()
+        .0
+        .required_part();
+
+      // each vertex is adjacent to the matched ones, or starts another connected part
+      let order = &plan.matching_order;
+      let mut parts = 0;
+      for (idx, vid) in order.iter().enumerate() {
+        let adj_vids = graph.get_adj_vids(vid);
+        if order[..idx].iter().any(|prev| adj_vids.contains(prev)) {
+          continue;
+        }
+        let part = graph.reachable_vids(vid);
+        assert!(
+          order[..idx].iter().all(|prev| !part.contains(prev)),
+          "{}",
+          path.display()
+        );
+        parts += 1;
+      }
+
+      // and each part starts from a single `Init`
+      let inits = (plan.instructions.iter())
+        .filter(|instr| instr.type_ == InstructionType::Init)
+        .count();
+      assert_eq!(inits, parts, "{}", path.display());
     }
   }
 }
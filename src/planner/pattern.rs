@@ -0,0 +1,547 @@
+use crate::{
+  parser::ParseErrorKind,
+  schemas::{
+    AttrValue, CrossPredicate, EdgeDirection, Op, PathMode, PatternAttr, PatternEdge,
+    PatternPredicate, PatternVertex, VarLength,
+  },
+  utils::dyn_graph::DynGraph,
+};
+use hashbrown::{HashMap, HashSet};
+use itertools::Itertools;
+use std::fmt::Display;
+
+/// An invalid step of a [`Pattern`], e.g. an edge on an undeclared vertex.
+///
+/// `step` is the (1-based) index of the offending builder call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternError {
+  pub(crate) kind: ParseErrorKind,
+  pub(crate) step: usize,
+  pub(crate) message: String,
+}
+
+impl PatternError {
+  pub fn kind(&self) -> ParseErrorKind {
+    self.kind
+  }
+  pub fn step(&self) -> usize {
+    self.step
+  }
+  pub fn message(&self) -> &str {
+    &self.message
+  }
+}
+
+impl Display for PatternError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "{}: {}\n --> step {}",
+      self.kind, self.message, self.step
+    )
+  }
+}
+
+impl std::error::Error for PatternError {}
+
+/// A pattern built in code, instead of being parsed from a query.
+///
+/// ```
+/// use ember_graph::{planner::pattern::Pattern, schemas::Op};
+///
+/// let pattern = Pattern::new()
+///   .vertex("p", "Person")
+///   .vertex("c", "City")
+///   .edge("e", "p", "c", "isLocatedIn")
+///   .where_v("p", "id", Op::Eq, 42);
+/// assert!(pattern.error().is_none());
+/// ```
+///
+/// Each step is validated as it's taken, the first invalid one is kept (see [`Pattern::error`])
+/// and the ones after it are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct Pattern {
+  graph: DynGraph<PatternVertex, PatternEdge>,
+  cross_predicates: Vec<CrossPredicate>,
+
+  steps: usize,
+  /// var -> the step that declares it
+  decl_steps: HashMap<String, usize>,
+  error: Option<PatternError>,
+}
+
+impl Pattern {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// A pattern parsed from a query, which is valid as a whole.
+  pub(crate) fn from_parsed(
+    graph: DynGraph<PatternVertex, PatternEdge>,
+    cross_predicates: Vec<CrossPredicate>,
+  ) -> Self {
+    Self {
+      graph,
+      cross_predicates,
+      ..Default::default()
+    }
+  }
+
+  /// The first invalid step, if any.
+  pub fn error(&self) -> Option<&PatternError> {
+    self.error.as_ref()
+  }
+
+  /// The pattern graph of the valid steps so far.
+  pub fn graph(&self) -> &DynGraph<PatternVertex, PatternEdge> {
+    &self.graph
+  }
+
+  /// The predicates across vertices / edges of the valid steps so far.
+  pub fn cross_predicates(&self) -> &[CrossPredicate] {
+    &self.cross_predicates
+  }
+
+  /// `(vid: label)`
+  pub fn vertex(self, vid: impl Into<String>, label: impl Into<String>) -> Self {
+    let (vid, label) = (vid.into(), label.into());
+    self.step(|pattern, step| {
+      pattern.check_new_var(&vid, ParseErrorKind::DuplicateVid, step)?;
+      if label.is_empty() {
+        return Err(pattern.error_at(ParseErrorKind::MissingLabel, step, format!("`{vid}`")));
+      }
+      let vertex = PatternVertex {
+        vid: vid.clone(),
+        label,
+        attrs: vec![],
+        optional: false,
+        negated: false,
+      };
+      pattern.graph.update_v(vertex, &vid);
+      pattern.decl_steps.insert(vid, step);
+      Ok(())
+    })
+  }
+
+  /// `(src_vid)-[eid: label]->(dst_vid)`
+  pub fn edge(
+    self,
+    eid: impl Into<String>,
+    src_vid: impl Into<String>,
+    dst_vid: impl Into<String>,
+    label: impl Into<String>,
+  ) -> Self {
+    let (eid, src_vid, dst_vid, label) = (eid.into(), src_vid.into(), dst_vid.into(), label.into());
+    self.step(|pattern, step| {
+      pattern.check_new_var(&eid, ParseErrorKind::DuplicateEid, step)?;
+      if label.is_empty() {
+        return Err(pattern.error_at(ParseErrorKind::MissingLabel, step, format!("`{eid}`")));
+      }
+      for vid in [&src_vid, &dst_vid] {
+        if pattern.graph.view_v_from_vid(vid).is_none() {
+          let message = format!("edge `{eid}` on undeclared `{vid}`");
+          return Err(pattern.error_at(ParseErrorKind::UndeclaredVertex, step, message));
+        }
+      }
+      let edge = PatternEdge {
+        eid: eid.clone(),
+        src_vid,
+        dst_vid,
+        label,
+        attrs: vec![],
+        direction: EdgeDirection::default(),
+        var_length: None,
+        optional: false,
+        negated: false,
+      };
+      pattern.graph.update_e(edge, eid.clone());
+      pattern.decl_steps.insert(eid, step);
+      Ok(())
+    })
+  }
+
+  /// Which way the edge `eid` is matched, `Out` (from `src_vid` to `dst_vid`) by default.
+  pub fn direction(self, eid: &str, direction: EdgeDirection) -> Self {
+    self.step(|pattern, step| {
+      pattern.edge_mut(eid, step)?.direction = direction;
+      Ok(())
+    })
+  }
+
+  /// Match the edge `eid` as a path of `min_hops..=max_hops` edges.
+  pub fn var_length(self, eid: &str, min_hops: usize, max_hops: usize, mode: PathMode) -> Self {
+    self.step(|pattern, step| {
+      let Some(var_length) = VarLength::new(min_hops, max_hops, mode) else {
+        let message = format!("`*{min_hops}..{max_hops}` on `{eid}`, which needs 1 <= min <= max");
+        return Err(pattern.error_at(ParseErrorKind::Unsupported, step, message));
+      };
+      pattern.edge_mut(eid, step)?.var_length = Some(var_length);
+      Ok(())
+    })
+  }
+
+  /// `vid.key <op> value`
+  pub fn where_v(self, vid: &str, key: &str, op: Op, value: impl Into<AttrValue>) -> Self {
+    let value = value.into();
+    self.step(|pattern, step| {
+      let pred = pattern.cmp(key, op, value, step)?;
+      pattern.vertex_mut(vid, step)?.attrs.push(pred);
+      Ok(())
+    })
+  }
+
+  /// `eid.key <op> value`
+  pub fn where_e(self, eid: &str, key: &str, op: Op, value: impl Into<AttrValue>) -> Self {
+    let value = value.into();
+    self.step(|pattern, step| {
+      let pred = pattern.cmp(key, op, value, step)?;
+      pattern.edge_mut(eid, step)?.attrs.push(pred);
+      Ok(())
+    })
+  }
+
+  /// Any predicate on the vertex `vid`, e.g. `IN`, `BETWEEN` or a `$param`.
+  pub fn where_v_pred(self, vid: &str, pred: PatternPredicate) -> Self {
+    self.step(|pattern, step| {
+      pattern.vertex_mut(vid, step)?.attrs.push(pred);
+      Ok(())
+    })
+  }
+
+  /// Any predicate on the edge `eid`, e.g. `IN`, `BETWEEN` or a `$param`.
+  pub fn where_e_pred(self, eid: &str, pred: PatternPredicate) -> Self {
+    self.step(|pattern, step| {
+      pattern.edge_mut(eid, step)?.attrs.push(pred);
+      Ok(())
+    })
+  }
+
+  /// A predicate across vertices / edges, e.g. `a.id <> b.id`
+  /// (one on a single vertex / edge is attached to it instead).
+  pub fn where_cross(self, pred: CrossPredicate) -> Self {
+    self.step(|pattern, step| {
+      for var in pred.vars() {
+        if !pattern.decl_steps.contains_key(var) {
+          let message = format!("predicate on undeclared `{var}`");
+          return Err(pattern.error_at(ParseErrorKind::UndeclaredVertex, step, message));
+        }
+      }
+      if let [var] = pred.vars()[..]
+        && let Some(single) = pred.to_single()
+      {
+        let var = var.to_string();
+        match pattern.graph.v_entities.get_mut(&var) {
+          Some(v) => v.attrs.push(single),
+          None => pattern.edge_mut(&var, step)?.attrs.push(single),
+        }
+        return Ok(());
+      }
+      pattern.cross_predicates.push(pred);
+      Ok(())
+    })
+  }
+
+  /// Mark the vertex / edge `var` as part of an optional sub-pattern (`OPTIONAL MATCH`).
+  pub fn optional(self, var: &str) -> Self {
+    self.mark(var, |optional, _| *optional = true)
+  }
+
+  /// Mark the vertex / edge `var` as part of a negated sub-pattern (`NOT EXISTS`).
+  pub fn negated(self, var: &str) -> Self {
+    self.mark(var, |_, negated| *negated = true)
+  }
+
+  /// The pattern graph, along with the predicates across its vertices / edges,
+  /// or the first invalid step.
+  pub fn build(
+    self,
+  ) -> Result<(DynGraph<PatternVertex, PatternEdge>, Vec<CrossPredicate>), PatternError> {
+    if let Some(error) = self.error {
+      return Err(error);
+    }
+    if let Err((var, reason)) = self.graph.check_sub_patterns() {
+      let step = self.decl_steps.get(&var).copied().unwrap_or(self.steps);
+      return Err(self.error_at(ParseErrorKind::Unsupported, step, reason));
+    }
+    Ok((self.graph, self.cross_predicates))
+  }
+
+  /// [`Self::build`], where `given_order` should list each vertex out of the negated / optional
+  /// sub-patterns exactly once. It's taken as the step after the last one.
+  pub(crate) fn build_in_order(
+    self,
+    given_order: &[&str],
+  ) -> Result<(DynGraph<PatternVertex, PatternEdge>, Vec<CrossPredicate>), PatternError> {
+    let step = self.steps + 1;
+    let order_error = |message| PatternError {
+      kind: ParseErrorKind::InvalidOrder,
+      step,
+      message,
+    };
+
+    let (graph, cross_predicates) = self.build()?;
+    let required = graph.required_part();
+    let mut given = HashSet::new();
+    for &vid in given_order {
+      if !required.has_vid(vid) {
+        let message = match graph.has_vid(vid) {
+          true => format!("`{vid}` is in a negated / optional sub-pattern"),
+          false => format!("`{vid}` is not a vertex of the pattern"),
+        };
+        return Err(order_error(message));
+      }
+      if !given.insert(vid) {
+        return Err(order_error(format!("`{vid}` is given more than once")));
+      }
+    }
+    let missing = (required.view_vids().into_iter())
+      .filter(|vid| !given.contains(vid))
+      .sorted()
+      .map(|vid| format!("`{vid}`"))
+      .join(", ");
+    if !missing.is_empty() {
+      return Err(order_error(format!("{missing} missing")));
+    }
+    Ok((graph, cross_predicates))
+  }
+}
+
+impl Pattern {
+  /// Take a step, unless an earlier one is invalid.
+  fn step(mut self, f: impl FnOnce(&mut Self, usize) -> Result<(), PatternError>) -> Self {
+    self.steps += 1;
+    if self.error.is_none() {
+      let step = self.steps;
+      if let Err(error) = f(&mut self, step) {
+        self.error = Some(error);
+      }
+    }
+    self
+  }
+
+  fn error_at(&self, kind: ParseErrorKind, step: usize, message: String) -> PatternError {
+    PatternError {
+      kind,
+      step,
+      message,
+    }
+  }
+
+  /// vids and eids share the namespace, as the predicates refer to both by name.
+  fn check_new_var(
+    &self,
+    var: &str,
+    kind: ParseErrorKind,
+    step: usize,
+  ) -> Result<(), PatternError> {
+    if self.decl_steps.contains_key(var) {
+      let message = format!("`{var}` is declared more than once");
+      return Err(self.error_at(kind, step, message));
+    }
+    Ok(())
+  }
+
+  fn cmp(
+    &self,
+    key: &str,
+    op: Op,
+    value: AttrValue,
+    step: usize,
+  ) -> Result<PatternPredicate, PatternError> {
+    if let Err((kind, message)) = value.check_operand_of(op) {
+      return Err(self.error_at(kind, step, message));
+    }
+    let _type = value.to_type();
+    Ok(
+      PatternAttr {
+        key: key.to_string(),
+        op,
+        value,
+        _type,
+      }
+      .into(),
+    )
+  }
+
+  fn vertex_mut(&mut self, vid: &str, step: usize) -> Result<&mut PatternVertex, PatternError> {
+    if !self.graph.v_entities.contains_key(vid) {
+      let message = format!("predicate on undeclared `{vid}`");
+      return Err(self.error_at(ParseErrorKind::UndeclaredVertex, step, message));
+    }
+    Ok(self.graph.v_entities.get_mut(vid).unwrap())
+  }
+
+  fn edge_mut(&mut self, eid: &str, step: usize) -> Result<&mut PatternEdge, PatternError> {
+    if !self.graph.e_entities.contains_key(eid) {
+      let message = format!("undeclared edge `{eid}`");
+      return Err(self.error_at(ParseErrorKind::UndeclaredEdge, step, message));
+    }
+    Ok(self.graph.e_entities.get_mut(eid).unwrap())
+  }
+
+  /// Set the `optional` / `negated` flags of the vertex / edge `var`.
+  fn mark(self, var: &str, f: impl FnOnce(&mut bool, &mut bool)) -> Self {
+    self.step(|pattern, step| {
+      if let Some(v) = pattern.graph.v_entities.get_mut(var) {
+        f(&mut v.optional, &mut v.negated);
+      } else {
+        let e = pattern.edge_mut(var, step)?;
+        f(&mut e.optional, &mut e.negated);
+      }
+      Ok(())
+    })
+  }
+}
+
+#[cfg(test)]
+mod test_pattern {
+  use super::*;
+  use crate::{
+    parser::PatternParser,
+    planner::{
+      QueryError, QueryFormat, generate_optimal_plan, generate_optimal_plan_for,
+      generate_optimal_plan_from_query, generate_plan_with_given_order_for,
+    },
+    schemas::{AttrRef, EBase, PlanData, VBase},
+  };
+  use std::path::Path;
+
+  #[test]
+  fn test_same_as_parsed() {
+    let (graph, cross_predicates) = Pattern::new()
+      .vertex("a", "Person")
+      .vertex("b", "Person")
+      .vertex("c", "City")
+      .edge("k", "a", "b", "knows")
+      .direction("k", EdgeDirection::Both)
+      .edge("l", "b", "c", "isLocatedIn")
+      .where_v("c", "name", Op::StartsWith, "Chi")
+      .where_cross(CrossPredicate::Cmp {
+        left: AttrRef::new("a", "age"),
+        op: Op::Lt,
+        right: AttrRef::new("b", "age"),
+      })
+      .build()
+      .unwrap();
+
+    let src = "3 2 1 0 1\na Person\nb Person\nc City\nk a b knows both\nl b c isLocatedIn\n\
+               c name STARTS WITH 'Chi'\na.age < b.age\n";
+    let mut parser = PatternParser::new(src.to_string());
+    parser.parse().unwrap();
+    assert_eq!(cross_predicates, parser.cross_predicates());
+    let parsed = parser.take_as_pattern_graph();
+
+    let vs = |g: &DynGraph<PatternVertex, PatternEdge>| {
+      let vs = g.view_v_entities().into_iter().cloned();
+      vs.map(|v| (v.vid().to_string(), v)).collect::<HashSet<_>>()
+    };
+    let es = |g: &DynGraph<PatternVertex, PatternEdge>| {
+      let es = g.view_e_entities().into_iter().cloned();
+      es.map(|e| (e.eid().to_string(), e)).collect::<HashSet<_>>()
+    };
+    assert_eq!(vs(&graph), vs(&parsed));
+    assert_eq!(es(&graph), es(&parsed));
+  }
+
+  #[test]
+  fn test_first_invalid_step() {
+    let pattern = Pattern::new()
+      .vertex("a", "Person")
+      .edge("e", "a", "b", "knows")
+      // ignored, since the step above is invalid
+      .vertex("a", "Person");
+    let error = pattern.error().unwrap();
+    assert_eq!(error.kind(), ParseErrorKind::UndeclaredVertex);
+    assert_eq!(error.step(), 2);
+
+    let error = |pattern: Pattern| pattern.build().unwrap_err();
+    let err = error(Pattern::new().vertex("a", "A").vertex("a", "B"));
+    assert_eq!((err.kind(), err.step()), (ParseErrorKind::DuplicateVid, 2));
+    let err = error(
+      Pattern::new()
+        .vertex("a", "A")
+        .where_v("a", "id", Op::Contains, 1),
+    );
+    assert_eq!(
+      (err.kind(), err.step()),
+      (ParseErrorKind::InvalidOperator, 2)
+    );
+    let err = error(
+      Pattern::new()
+        .vertex("a", "A")
+        .var_length("e", 1, 2, PathMode::Trail),
+    );
+    assert_eq!(
+      (err.kind(), err.step()),
+      (ParseErrorKind::UndeclaredEdge, 2)
+    );
+    // a positive edge on a negated vertex
+    let err = error(
+      Pattern::new()
+        .vertex("a", "A")
+        .vertex("b", "B")
+        .edge("e", "a", "b", "E")
+        .negated("b"),
+    );
+    assert_eq!((err.kind(), err.step()), (ParseErrorKind::Unsupported, 3));
+  }
+
+  #[test]
+  fn test_plan_without_files() {
+    let pattern = Pattern::new()
+      .vertex("p", "Person")
+      .vertex("c", "City")
+      .edge("e", "p", "c", "isLocatedIn")
+      .where_v("p", "id", Op::Eq, 42);
+    let built = generate_optimal_plan_for(pattern).unwrap();
+    let parsed = generate_optimal_plan_from_query(
+      "MATCH (p: Person)-[e: isLocatedIn]->(c: City) WHERE p.id = 42 RETURN c",
+      QueryFormat::Cypher,
+    )
+    .unwrap();
+
+    let instrs = |plan_data: &PlanData| {
+      let instrs = plan_data.instructions.iter();
+      // the names of the temporary vars may differ
+      instrs
+        .map(|instr| (instr.vid.clone(), instr.type_))
+        .collect_vec()
+    };
+    assert_eq!(built.matching_order, parsed.matching_order);
+    assert_eq!(instrs(&built), instrs(&parsed));
+
+    let plan_data =
+      generate_plan_with_given_order_for(Pattern::new().vertex("p", "Person"), &["p"]).unwrap();
+    assert_eq!(plan_data.matching_order, ["p"]);
+    let pattern = || {
+      Pattern::new()
+        .vertex("p", "Person")
+        .vertex("c", "City")
+        .vertex("q", "Person")
+        .edge("e", "p", "c", "isLocatedIn")
+        .edge("k", "p", "q", "knows")
+        .negated("k")
+        .negated("q")
+    };
+    for (given_order, message) in [
+      (&["p"][..], "`c` missing"),
+      (&["p", "c", "p"], "`p` is given more than once"),
+      (&["p", "c", "x"], "`x` is not a vertex of the pattern"),
+      (
+        &["p", "c", "q"],
+        "`q` is in a negated / optional sub-pattern",
+      ),
+    ] {
+      let err = generate_plan_with_given_order_for(pattern(), given_order).unwrap_err();
+      assert_eq!(err.kind(), ParseErrorKind::InvalidOrder);
+      assert_eq!((err.step(), err.message()), (8, message));
+    }
+    let err = generate_optimal_plan_for(Pattern::new().edge("e", "a", "b", "E")).unwrap_err();
+    assert_eq!(err.kind(), ParseErrorKind::UndeclaredVertex);
+    let err = generate_optimal_plan_from_query("MATCH (p: Person RETURN p", QueryFormat::Cypher)
+      .unwrap_err();
+    assert!(matches!(err, QueryError::Parse(e) if e.kind() == ParseErrorKind::UnexpectedToken));
+    let err = generate_optimal_plan(Path::new("no/such/query.cypher")).unwrap_err();
+    assert!(matches!(err, QueryError::Io(e) if e.kind() == std::io::ErrorKind::NotFound));
+  }
+}
@@ -1,6 +1,6 @@
 use super::{plan_gen::PlanGenerator, plan_opt::PlanOptimizer};
 use crate::{
-  schemas::{Instruction, PatternEdge, PatternVertex, PlanData, Vid},
+  schemas::{AttrCmpMode, Instruction, PatternEdge, PatternVertex, PlanData, Symmetry, Vid},
   utils::dyn_graph::DynGraph,
 };
 use hashbrown::HashMap;
@@ -10,9 +10,15 @@ pub struct PlanDumper {
   matching_order: Vec<Vid>,
   exec_instructions: Vec<Instruction>,
   pattern_graph: DynGraph<PatternVertex, PatternEdge>,
+  symmetry: Symmetry,
 }
 
 impl PlanDumper {
+  pub fn with_symmetry(mut self, symmetry: Symmetry) -> Self {
+    self.symmetry = symmetry;
+    self
+  }
+
   pub fn to_plan_data(self) -> PlanData {
     let matching_order = self.matching_order;
 
@@ -36,6 +42,8 @@ impl PlanDumper {
       pattern_vs,
       pattern_es,
       instructions,
+      symmetry: self.symmetry,
+      attr_cmp_mode: AttrCmpMode::default(),
     }
   }
 
@@ -50,6 +58,7 @@ impl From<PlanGenerator> for PlanDumper {
       matching_order: plan_generator.optimal_order.into_iter().collect(),
       exec_instructions: plan_generator.exec_instructions,
       pattern_graph: plan_generator.pattern_graph,
+      symmetry: Symmetry::default(),
     }
   }
 }
@@ -60,6 +69,7 @@ impl From<PlanOptimizer> for PlanDumper {
       matching_order: plan_optimizer.matching_order,
       exec_instructions: plan_optimizer.exec_instructions,
       pattern_graph: plan_optimizer.pattern_graph,
+      symmetry: Symmetry::default(),
     }
   }
 }
@@ -1,17 +1,19 @@
 use super::order_calc::PlanGenInput;
 use crate::{
   schemas::{
-    Instruction, InstructionBuilder, InstructionType, PatternEdge, PatternVertex, VarPrefix, Vid,
+    CrossPredicate, Eid, Instruction, InstructionBuilder, InstructionType, PatternEdge,
+    PatternVertex, VarPrefix, Vid,
   },
-  utils::dyn_graph::DynGraph,
+  utils::{dyn_graph::DynGraph, sub_pattern::SubPatternKind},
 };
-use hashbrown::HashSet;
+use hashbrown::{HashMap, HashSet};
 use itertools::Itertools;
 
 #[derive(Debug, Clone)]
 pub struct PlanGenerator {
   pub(crate) pattern_graph: DynGraph<PatternVertex, PatternEdge>,
   pub(crate) optimal_order: Vec<Vid>,
+  pub(crate) cross_predicates: Vec<CrossPredicate>,
   pub(crate) exec_instructions: Vec<Instruction>,
 }
 
@@ -20,6 +22,7 @@ impl From<PlanGenInput> for PlanGenerator {
     Self {
       pattern_graph: input.pattern_graph,
       optimal_order: input.optimal_order,
+      cross_predicates: input.cross_predicates,
       exec_instructions: vec![],
     }
   }
@@ -31,6 +34,8 @@ impl PlanGenerator {
       return;
     }
 
+    let required_graph = self.pattern_graph.required_part();
+    let (mut vid_2_predicates, report_predicates) = self.place_cross_predicates(&required_graph);
     let mut instructions = vec![];
     let mut f_set = HashSet::new();
     #[cfg(not(feature = "no_optimizations"))]
@@ -38,16 +43,17 @@ impl PlanGenerator {
 
     // first vertex
     let vid = self.optimal_order[0].clone();
-    let adj_eids = self.pattern_graph.get_adj_eids(&vid);
+    let adj_eids = required_graph.get_adj_eids(&vid);
     // Init -> fx
     instructions.push(
       InstructionBuilder::new(&vid, InstructionType::Init)
         .target_var(VarPrefix::EnumerateTarget.with(&vid))
+        .predicates(vid_2_predicates.remove(&vid).unwrap_or_default())
         .build(),
     );
     // GetAdj(fx) -> Ax
     instructions.push(
-      InstructionBuilder::new(&vid, InstructionType::GetAdj)
+      InstructionBuilder::new(&vid, self.expand_type(&adj_eids))
         .expand_eids(adj_eids.clone())
         .single_op(VarPrefix::EnumerateTarget.with(&vid))
         .target_var(VarPrefix::DbQueryTarget.with(&vid))
@@ -61,9 +67,9 @@ impl PlanGenerator {
     // other vertices
     for vid in self.optimal_order.iter().skip(1).cloned() {
       let mut adj_precursors = f_set.clone();
-      adj_precursors.retain(|v| self.pattern_graph.get_adj_vids(&vid).contains(v));
+      adj_precursors.retain(|v| required_graph.get_adj_vids(&vid).contains(v));
 
-      let mut adj_eids = self.pattern_graph.get_adj_eids(&vid);
+      let mut adj_eids = required_graph.get_adj_eids(&vid);
       // only pick those edges that are not expanded
       #[cfg(not(feature = "no_optimizations"))]
       adj_eids.retain(|eid| !expanded_es.contains(eid));
@@ -73,6 +79,7 @@ impl PlanGenerator {
         instructions.push(
           InstructionBuilder::new(&vid, InstructionType::Init)
             .target_var(VarPrefix::EnumerateTarget.with(&vid))
+            .predicates(vid_2_predicates.remove(&vid).unwrap_or_default())
             .build(),
         );
       }
@@ -115,13 +122,14 @@ impl PlanGenerator {
           InstructionBuilder::new(&vid, InstructionType::Foreach)
             .single_op(VarPrefix::IntersectCandidate.with(&vid))
             .target_var(VarPrefix::EnumerateTarget.with(&vid))
+            .predicates(vid_2_predicates.remove(&vid).unwrap_or_default())
             .build(),
         );
       }
 
       // GetAdj(fx) -> Ax
       instructions.push(
-        InstructionBuilder::new(&vid, InstructionType::GetAdj)
+        InstructionBuilder::new(&vid, self.expand_type(&adj_eids))
           .expand_eids(adj_eids.clone())
           .single_op(VarPrefix::EnumerateTarget.with(&vid))
           .target_var(VarPrefix::DbQueryTarget.with(&vid))
@@ -134,20 +142,103 @@ impl PlanGenerator {
       expanded_es.extend(adj_eids);
     }
 
-    // Report
     let embedding = f_set
       .iter()
       .map(|vid| VarPrefix::EnumerateTarget.with(vid))
       .collect_vec();
+
+    // AntiJoin(fw, ..., fz)~~[negated eids], once the required part is matched
+    // OptionalExpand(fw, ..., fz)~~[optional eids], after the anti-joins cut the matches down
+    let sub_patterns = [SubPatternKind::Negated, SubPatternKind::Optional]
+      .into_iter()
+      .flat_map(|kind| self.pattern_graph.sub_patterns(kind));
+    for (idx, sub_pattern) in sub_patterns.enumerate() {
+      let (type_, mark) = match sub_pattern.kind {
+        SubPatternKind::Negated => (InstructionType::AntiJoin, "!"),
+        SubPatternKind::Optional => (InstructionType::OptionalExpand, "?"),
+      };
+      instructions.push(
+        InstructionBuilder::new("", type_)
+          .expand_eids(sub_pattern.eids)
+          .multi_ops(embedding.clone())
+          .target_var(VarPrefix::EnumerateTarget.with(format!("{mark}{}", idx + 1)))
+          .build(),
+      );
+    }
+
+    // Report
     instructions.push(
       InstructionBuilder::new("", InstructionType::Report)
         .multi_ops(embedding)
         .target_var(VarPrefix::EnumerateTarget.to_string())
+        .predicates(report_predicates)
         .build(),
     );
 
     self.exec_instructions = remove_unused_dbq(instructions);
   }
+
+  /// Attach each cross predicate to the last vertex (in the order) it needs, so that it's checked
+  /// as soon as they're all bound, or leave it to `Report` if they're not connected.
+  fn place_cross_predicates(
+    &self,
+    required_graph: &DynGraph<PatternVertex, PatternEdge>,
+  ) -> (HashMap<Vid, Vec<CrossPredicate>>, Vec<CrossPredicate>) {
+    let pos = |vid: &str| self.optimal_order.iter().position(|v| v == vid);
+    let mut vid_2_predicates = HashMap::<Vid, Vec<CrossPredicate>>::new();
+    let mut report_predicates = vec![];
+
+    for pred in self.cross_predicates.iter().cloned() {
+      // the vertices that bind the vars (an edge is bound along with its later end)
+      let vids = pred
+        .vars()
+        .into_iter()
+        .flat_map(|var| match required_graph.view_e_from_eid(var) {
+          Some(e) => vec![e.src_vid.clone(), e.dst_vid.clone()],
+          None => vec![var.to_string()],
+        })
+        .collect_vec();
+      let last_vid = vids
+        .iter()
+        .map(|vid: &Vid| pos(vid).map(|pos| (pos, vid)))
+        .collect::<Option<Vec<_>>>()
+        .and_then(|positions| positions.into_iter().max())
+        .map(|(_, vid)| vid.clone());
+
+      match last_vid {
+        Some(last_vid)
+          if {
+            let reachable = required_graph.reachable_vids(&last_vid);
+            vids.iter().all(|vid| reachable.contains(vid))
+          } =>
+        {
+          // the vertices of a `vid_lt` may be bound in different parts, so it's checked again
+          if pred.is_vid_lt() {
+            report_predicates.push(pred.clone());
+          }
+          vid_2_predicates.entry(last_vid).or_default().push(pred)
+        }
+        _ => report_predicates.push(pred),
+      }
+    }
+
+    (vid_2_predicates, report_predicates)
+  }
+
+  /// `ExpandPath` once any of the edges to expand is variable-length, `GetAdj` otherwise
+  fn expand_type(&self, eids: &HashSet<Eid>) -> InstructionType {
+    let has_var_length = eids.iter().any(|eid| {
+      self
+        .pattern_graph
+        .view_e_from_eid(eid)
+        .is_some_and(PatternEdge::is_var_length)
+    });
+    if has_var_length {
+      InstructionType::ExpandPath
+    } else {
+      InstructionType::GetAdj
+    }
+  }
 }
 
 #[cfg(not(feature = "no_optimizations"))]
@@ -165,9 +256,7 @@ fn remove_unused_dbq(instructions: Vec<Instruction>) -> Vec<Instruction> {
 
   instructions
     .into_iter()
-    .filter(|instr| {
-      instr.type_ != InstructionType::GetAdj || depend_set.contains(&instr.target_var)
-    })
+    .filter(|instr| !instr.type_.is_expand() || depend_set.contains(&instr.target_var))
     .collect()
 }
 
@@ -1,13 +1,52 @@
 use super::plan_gen::PlanGenerator;
 use crate::{
   schemas::{
-    Instruction, InstructionBuilder, InstructionType, PatternEdge, PatternVertex, VarPrefix, Vid,
+    EdgeShape, Instruction, InstructionBuilder, InstructionType, LabelRef, PatternEdge,
+    PatternPredicate, PatternVertex, STR_TUPLE_SPLITTER, VarPrefix, Vid, VidRef,
   },
   utils::{apriori::AprioriBuilder, dyn_graph::DynGraph},
 };
 use hashbrown::{HashMap, HashSet};
 use itertools::Itertools;
-use std::collections::{BTreeSet, VecDeque};
+use std::{
+  collections::{BTreeSet, VecDeque},
+  hash::Hash,
+};
+
+/// Splits an operand of `eliminate_cse` from the tag of its consumer's shape
+#[cfg(not(feature = "no_optimizations"))]
+const OPERAND_TAG_SPLITTER: char = '|';
+
+/// The operand of a tagged one (see `PlanOptimizer::tag_operand`)
+#[cfg(not(feature = "no_optimizations"))]
+fn untag(item: &str) -> &str {
+  item
+    .split_once(OPERAND_TAG_SPLITTER)
+    .map_or(item, |(op, _)| op)
+}
+
+/// How the vertex of an intersection hangs on one of its operands: its label and attrs,
+/// the (sorted) ids of the shapes of its edges to the operand, and itself unless it's shared
+#[cfg(not(feature = "no_optimizations"))]
+type OperandShape<'a> = (
+  Option<(LabelRef<'a>, &'a [PatternPredicate])>,
+  Vec<usize>,
+  Option<VidRef<'a>>,
+);
+
+/// The ids of the shapes met by `PlanOptimizer::tag_operand`, which tell them apart by value
+#[cfg(not(feature = "no_optimizations"))]
+#[derive(Default)]
+struct ShapeIds<'a> {
+  e_shapes: HashMap<EdgeShape<'a>, usize>,
+  operands: HashMap<OperandShape<'a>, usize>,
+}
+
+#[cfg(not(feature = "no_optimizations"))]
+fn id_of<K: Hash + Eq>(ids: &mut HashMap<K, usize>, key: K) -> usize {
+  let next_id = ids.len();
+  *ids.entry(key).or_insert(next_id)
+}
 
 fn compute_instr_dependencies(instructions: &mut [Instruction]) {
   let mut depend_record: HashMap<Vid, HashSet<Vid>> = HashMap::new();
@@ -40,6 +79,8 @@ pub struct PlanOptimizer {
   pub(crate) pattern_graph: DynGraph<PatternVertex, PatternEdge>,
   pub(crate) exec_instructions: Vec<Instruction>,
   pub(crate) matching_order: Vec<Vid>,
+  /// whether the intersections on different vertices may share a bucket (see `TCache`)
+  pub(crate) share_t_buckets: bool,
 
   ti: usize,
 }
@@ -50,12 +91,20 @@ impl From<PlanGenerator> for PlanOptimizer {
       pattern_graph: plan_generator.pattern_graph,
       exec_instructions: plan_generator.exec_instructions,
       matching_order: plan_generator.optimal_order,
+      share_t_buckets: true,
       ti: 0,
     }
   }
 }
 
 impl PlanOptimizer {
+  /// Set whether the intersections on different vertices may share a bucket (`true` by default),
+  /// otherwise each one intersects its own.
+  pub fn with_shared_t_buckets(mut self, share_t_buckets: bool) -> Self {
+    self.share_t_buckets = share_t_buckets;
+    self
+  }
+
   pub fn apply_optimization(&mut self) {
     if self.exec_instructions.is_empty() {
       return;
@@ -66,6 +115,7 @@ impl PlanOptimizer {
       self.eliminate_cse();
       self.flatten_multi_ops();
       self.reorder();
+      self.cache_shared_t_buckets();
     }
 
     // `compute dependencies` should be done after all optimizations
@@ -172,17 +222,21 @@ impl PlanOptimizer {
       let mut intersect_pos = HashMap::new();
 
       // find `intersect`
+      let mut shape_ids = ShapeIds::default();
       for (idx, instr) in self.exec_instructions.iter().enumerate() {
-        if instr.type_ == InstructionType::GetAdj {
+        if instr.type_.is_expand() {
           intersect_pos.insert(instr.target_var.clone(), idx);
         } else if instr.type_ == InstructionType::Intersect && !instr.is_single_op() {
           intersect_pos.insert(instr.target_var.clone(), idx);
 
-          let operands = instr.multi_ops.iter().cloned().collect::<HashSet<_>>();
+          let operands = (instr.multi_ops.iter())
+            .map(|op| self.tag_operand(&mut shape_ids, &instr.vid, op))
+            .collect::<HashSet<_>>();
           data_list.push(operands);
           instr_idx.push(idx);
         }
       }
+      drop(shape_ids);
 
       // use `apriori` to find all frequent itemset (support >= 2)
       let apriori = AprioriBuilder::new(&data_list).min_support(2).build();
@@ -198,12 +252,12 @@ impl PlanOptimizer {
         } else if support == max_freq_support {
           let l1 = max_freq_set
             .iter()
-            .map(|var| intersect_pos[var])
+            .map(|item| intersect_pos[untag(item)])
             .sorted_unstable()
             .collect_vec();
           let l2 = itemset
             .iter()
-            .map(|var| intersect_pos[var])
+            .map(|item| intersect_pos[untag(item)])
             .sorted_unstable()
             .collect_vec();
           for (e1, e2) in l1.iter().zip(l2.iter()) {
@@ -229,14 +283,16 @@ impl PlanOptimizer {
             continue;
           }
 
-          operands.insert(VarPrefix::IntersectTarget.with(self.ti));
           operands.retain(|item| !max_freq_set.contains(item));
+          let mut operands = (operands.iter())
+            .map(|item| untag(item).to_string())
+            .collect::<HashSet<_>>();
+          operands.insert(VarPrefix::IntersectTarget.with(self.ti));
 
           if flag {
-            let operators = max_freq_set
-              .iter()
-              .cloned()
-              .sorted_unstable_by_key(|s| intersect_pos[s])
+            let operators = (max_freq_set.iter())
+              .map(|item| untag(item).to_string())
+              .sorted_unstable_by_key(|var| intersect_pos[var])
               .collect_vec();
             let old_instr = &self.exec_instructions[instr_idx[i]];
             let new_instr = InstructionBuilder::new(&old_instr.vid, InstructionType::Intersect)
@@ -257,5 +313,109 @@ impl PlanOptimizer {
         break;
       }
     }
+
+    self.forward_bare_t_intersections();
+  }
+
+  /// Tag an operand of the intersection on `vid` with how `vid` hangs on it, so that only the
+  /// consumers of the same shape share the bucket intersected from it (see `TCache`).
+  #[cfg(not(feature = "no_optimizations"))]
+  fn tag_operand<'a>(&'a self, shape_ids: &mut ShapeIds<'a>, vid: VidRef<'a>, op: &str) -> String {
+    let v_shape =
+      (self.pattern_graph.view_v_from_vid(vid)).map(|v| (v.label.as_str(), v.attrs.as_slice()));
+
+    // `Ax` is grouped by the edges from `x`, while `Tx` is mirrored by those of its consumers
+    let (var_prefix, var_name) = op.split_once(STR_TUPLE_SPLITTER).unwrap();
+    let mut e_shapes = vec![];
+    if var_prefix == VarPrefix::DbQueryTarget.as_ref() {
+      e_shapes = (self.pattern_graph.get_adj_eids(vid).iter())
+        .filter_map(|eid| self.pattern_graph.view_e_from_eid(eid))
+        .filter(|e| e.src_vid == var_name || e.dst_vid == var_name)
+        .map(|e| id_of(&mut shape_ids.e_shapes, e.shape_from(var_name)))
+        .sorted_unstable()
+        .collect();
+    }
+
+    let owner = (!self.share_t_buckets).then_some(vid);
+    let shape = id_of(&mut shape_ids.operands, (v_shape, e_shapes, owner));
+    format!("{op}{OPERAND_TAG_SPLITTER}{shape}")
+  }
+
+  /// `Intersect(vx, Ti) -> Tx` is all that's left of an intersection whose operands are all
+  /// shared (by CSE), so drop it and let `Intersect(vx, Tx) -> Cx` read `Ti` instead.
+  #[cfg(not(feature = "no_optimizations"))]
+  fn forward_bare_t_intersections(&mut self) {
+    let t_prefix = VarPrefix::IntersectTarget.with("");
+    let is_t_var = |var: &str| var.starts_with(&t_prefix);
+
+    let mut forwarded = HashMap::new();
+    self.exec_instructions.retain(|instr| {
+      let is_bare = instr.type_ == InstructionType::Intersect
+        && instr.single_op.as_deref().is_some_and(is_t_var)
+        && is_t_var(&instr.target_var);
+      if is_bare {
+        let single_op = instr.single_op.clone().unwrap();
+        forwarded.insert(instr.target_var.clone(), single_op);
+      }
+      !is_bare
+    });
+
+    let resolve = |var: &mut String| {
+      // a shared bucket may itself be forwarded
+      while let Some(forwarded_var) = forwarded.get(var) {
+        *var = forwarded_var.clone();
+      }
+    };
+    for instr in self.exec_instructions.iter_mut() {
+      instr.single_op.iter_mut().for_each(resolve);
+      instr.multi_ops.iter_mut().for_each(resolve);
+    }
+  }
+
+  /// Emit `TCache(Tx) -> T#x` after each `Tx` consumed by several intersections,
+  /// which then share `T#x` instead of popping `Tx` out one after another.
+  #[cfg(not(feature = "no_optimizations"))]
+  fn cache_shared_t_buckets(&mut self) {
+    let t_prefix = VarPrefix::IntersectTarget.with("");
+
+    let mut idx = 0;
+    while idx < self.exec_instructions.len() {
+      let instr = &self.exec_instructions[idx];
+      idx += 1;
+      if instr.type_ == InstructionType::TCache || !instr.target_var.starts_with(&t_prefix) {
+        continue;
+      }
+
+      let (vid, t_var) = (instr.vid.clone(), instr.target_var.clone());
+      let consumers = self.exec_instructions[idx..]
+        .iter()
+        .filter(|consumer| {
+          consumer.single_op.as_ref() == Some(&t_var) || consumer.multi_ops.contains(&t_var)
+        })
+        .count();
+      if consumers < 2 {
+        continue;
+      }
+
+      let (_, t_name) = t_var.split_once(STR_TUPLE_SPLITTER).unwrap();
+      let cached_var = VarPrefix::IntersectTarget.with(format!("#{t_name}"));
+      for consumer in self.exec_instructions[idx..].iter_mut() {
+        let ops = consumer
+          .single_op
+          .iter_mut()
+          .chain(consumer.multi_ops.iter_mut());
+        ops
+          .filter(|op| **op == t_var)
+          .for_each(|op| *op = cached_var.clone());
+      }
+
+      let t_cache = InstructionBuilder::new(&vid, InstructionType::TCache)
+        .single_op(t_var)
+        .target_var(cached_var)
+        .consumers(consumers)
+        .build();
+      self.exec_instructions.insert(idx, t_cache);
+      idx += 1;
+    }
   }
 }
@@ -0,0 +1,560 @@
+use super::order_calc::AttrGroup;
+use crate::schemas::{
+  AttrCmpMode, AttrType, AttrValue, EdgeDirection, Label, LabelRef, Op, PatternEdge,
+  PatternPredicate,
+};
+use hashbrown::HashMap;
+use project_root::get_project_root;
+use serde::{Deserialize, Serialize};
+use std::{
+  fs::{self, File},
+  io,
+  path::{Path, PathBuf},
+  sync::LazyLock,
+};
+
+/// the label counts only
+pub const LABEL_STATISTICS_FILE: &str = "label_statistics.json";
+/// the label counts, along with the attribute and degree statistics
+pub const ADVANCED_STATISTICS_FILE: &str = "advanced_statistics.json";
+
+/// Where the planner reads the statistics from.
+pub static STATISTICS_DIR: LazyLock<PathBuf> = LazyLock::new(|| {
+  get_project_root()
+    .unwrap()
+    .join("resources")
+    .join("statistics")
+});
+
+/// Loaded once, so that planning doesn't read the file over and over.
+pub(crate) static STATISTICS: LazyLock<Statistics> = LazyLock::new(|| {
+  Statistics::from_dir(&STATISTICS_DIR).expect("❌  Failed to load statistics file")
+});
+
+/// What the planner knows about the data graph,
+/// see [`StatisticsCollector`](crate::storage::StatisticsCollector).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Statistics {
+  pub(crate) v_cnt: usize,
+  pub(crate) e_cnt: usize,
+  pub(crate) v_label_cnt: HashMap<Label, usize>,
+  pub(crate) e_label_cnt: HashMap<Label, usize>,
+  /// v_label -> attr key -> stats
+  #[serde(default)]
+  pub(crate) v_attr_stats: HashMap<Label, HashMap<String, AttrStats>>,
+  /// e_label -> attr key -> stats
+  #[serde(default)]
+  pub(crate) e_attr_stats: HashMap<Label, HashMap<String, AttrStats>>,
+  /// e_label -> the edges between each pair of endpoint labels
+  #[serde(default)]
+  pub(crate) e_degree_stats: HashMap<Label, Vec<DegreeStats>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct AttrStats {
+  /// the non-null values
+  pub(crate) count: usize,
+  pub(crate) null_count: usize,
+  pub(crate) distinct_count: usize,
+  /// numbers only (temporal values are stored as epoch millis)
+  pub(crate) min_value: Option<f64>,
+  pub(crate) max_value: Option<f64>,
+  #[serde(default)]
+  pub(crate) histogram: Histogram,
+  #[serde(rename = "type", default)]
+  pub(crate) type_: AttrType,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct Histogram {
+  /// the edges of equal-width bins (one more than `counts`), for numbers
+  #[serde(default)]
+  pub(crate) bins: Vec<f64>,
+  #[serde(default)]
+  pub(crate) counts: Vec<usize>,
+  /// value -> its frequency, for strings
+  #[serde(default)]
+  pub(crate) value_counts: HashMap<String, usize>,
+}
+
+/// `(src_label)-[e_label]->(dst_label)` appears `count` times in the data graph.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct DegreeStats {
+  pub(crate) src_label: Label,
+  pub(crate) dst_label: Label,
+  pub(crate) count: usize,
+  /// the `src_label` vertices with such edges, by their out-degree in `[1], [2, 3], [4, 7], ...`
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub(crate) out_degree_histogram: Vec<usize>,
+  /// the `dst_label` vertices with such edges, by their in-degree in `[1], [2, 3], [4, 7], ...`
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub(crate) in_degree_histogram: Vec<usize>,
+}
+
+/// The parts of the statistics in `label_statistics.json`.
+#[derive(Serialize)]
+struct LabelStatistics<'a> {
+  v_cnt: usize,
+  e_cnt: usize,
+  v_label_cnt: &'a HashMap<Label, usize>,
+  e_label_cnt: &'a HashMap<Label, usize>,
+}
+
+impl Statistics {
+  /// `advanced_statistics.json` in `dir` if it's there, or else `label_statistics.json`
+  /// (which only counts the labels).
+  pub fn from_dir(dir: &Path) -> io::Result<Self> {
+    match dir.join(ADVANCED_STATISTICS_FILE) {
+      advanced if advanced.exists() => Self::from_file(&advanced),
+      _ => Self::from_file(&dir.join(LABEL_STATISTICS_FILE)),
+    }
+  }
+
+  pub fn from_file(path: &Path) -> io::Result<Self> {
+    let file = File::open(path)?;
+    let statistics: Self = serde_json::from_reader(io::BufReader::new(file))?;
+    Ok(statistics.normalized())
+  }
+
+  /// Write both `label_statistics.json` and `advanced_statistics.json` into `dir`,
+  /// with the keys sorted, so that a refresh only differs where the data graph does.
+  pub fn dump_to_dir(&self, dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let label_statistics = LabelStatistics {
+      v_cnt: self.v_cnt,
+      e_cnt: self.e_cnt,
+      v_label_cnt: &self.v_label_cnt,
+      e_label_cnt: &self.e_label_cnt,
+    };
+    let dump = |file: &str, value: serde_json::Value| {
+      fs::write(dir.join(file), serde_json::to_string_pretty(&value)? + "\n")
+    };
+    dump(
+      LABEL_STATISTICS_FILE,
+      serde_json::to_value(label_statistics)?,
+    )?;
+    dump(ADVANCED_STATISTICS_FILE, serde_json::to_value(self)?)
+  }
+
+  pub fn v_label_cnt(&self) -> &HashMap<Label, usize> {
+    &self.v_label_cnt
+  }
+
+  pub fn e_label_cnt(&self) -> &HashMap<Label, usize> {
+    &self.e_label_cnt
+  }
+
+  /// Keys in the statistics keep the suffix of the csv header (e.g. `creationDate:long`).
+  fn normalized(mut self) -> Self {
+    for attr_stats in (self.v_attr_stats.values_mut()).chain(self.e_attr_stats.values_mut()) {
+      *attr_stats = (attr_stats.drain())
+        .map(|(key, stats)| match key.split_once(':') {
+          Some((key, _)) => (key.to_string(), stats),
+          None => (key, stats),
+        })
+        .collect();
+    }
+    self
+  }
+
+  /// The `v_label` vertices, or all of them if `v_label` is empty (an unlabeled pattern vertex).
+  pub(crate) fn v_cnt_of(&self, v_label: LabelRef) -> usize {
+    match v_label.is_empty() {
+      true => self.v_cnt,
+      false => self.v_label_cnt.get(v_label).copied().unwrap_or(0),
+    }
+  }
+
+  /// The fraction of the `v_label` vertices that satisfy all of `preds`.
+  pub(crate) fn v_selectivity(&self, v_label: LabelRef, preds: &[PatternPredicate]) -> f64 {
+    let label_cnt = self.v_cnt_of(v_label);
+    Self::selectivity(self.v_attr_stats.get(v_label), label_cnt, preds)
+  }
+
+  /// The fraction of the `e_label` edges that satisfy all of `preds`.
+  pub(crate) fn e_selectivity(&self, e_label: LabelRef, preds: &[PatternPredicate]) -> f64 {
+    let label_cnt = self.e_label_cnt.get(e_label).copied().unwrap_or(0);
+    Self::selectivity(self.e_attr_stats.get(e_label), label_cnt, preds)
+  }
+
+  fn selectivity(
+    attr_stats: Option<&HashMap<String, AttrStats>>,
+    label_cnt: usize,
+    preds: &[PatternPredicate],
+  ) -> f64 {
+    let selectivity = (preds.iter())
+      .map(|pred| estimate(attr_stats, &pred.to_nnf()))
+      .product::<f64>();
+    // half a match at least, so that an unlikely one still ranks below an impossible one
+    match preds.is_empty() {
+      true => 1.0,
+      false => selectivity.clamp(0.5 / label_cnt.max(1) as f64, 1.0),
+    }
+  }
+
+  /// The `e_label` edges from `src_label` vertices to `dst_label` ones
+  /// (where an empty label matches any), or all the `e_label` edges if their endpoints
+  /// aren't counted.
+  pub(crate) fn e_cnt_between(
+    &self,
+    e_label: LabelRef,
+    src_label: LabelRef,
+    dst_label: LabelRef,
+  ) -> usize {
+    match self.e_degree_stats.get(e_label) {
+      Some(degree_stats) => (degree_stats.iter())
+        .filter(|stats| {
+          (src_label.is_empty() || stats.src_label == src_label)
+            && (dst_label.is_empty() || stats.dst_label == dst_label)
+        })
+        .map(|stats| stats.count)
+        .sum(),
+      None => self.e_label_cnt.get(e_label).copied().unwrap_or(0),
+    }
+  }
+
+  /// The data edges a (single-hop) pattern edge matches,
+  /// given the labels of its source and destination vertices.
+  pub(crate) fn e_matches(&self, e: &PatternEdge, src_label: LabelRef, dst_label: LabelRef) -> f64 {
+    let e_cnt = match e.direction {
+      EdgeDirection::Out => self.e_cnt_between(&e.label, src_label, dst_label),
+      EdgeDirection::In => self.e_cnt_between(&e.label, dst_label, src_label),
+      EdgeDirection::Both => {
+        self.e_cnt_between(&e.label, src_label, dst_label)
+          + self.e_cnt_between(&e.label, dst_label, src_label)
+      }
+    };
+    e_cnt as f64 * self.e_selectivity(&e.label, &e.attrs)
+  }
+}
+
+/// `pred` should be in negation normal form.
+fn estimate(attr_stats: Option<&HashMap<String, AttrStats>>, pred: &PatternPredicate) -> f64 {
+  let stats_of = |key: &str| attr_stats?.get(key);
+  match pred {
+    PatternPredicate::And { and: preds } => preds.iter().map(|p| estimate(attr_stats, p)).product(),
+    PatternPredicate::Or { or: preds } => {
+      1.0
+        - (preds.iter())
+          .map(|p| 1.0 - estimate(attr_stats, p))
+          .product::<f64>()
+    }
+    leaf => match key_of(leaf).and_then(stats_of) {
+      Some(stats) => stats.estimate(leaf),
+      None => AttrGroup::of(leaf).default_selectivity(),
+    },
+  }
+}
+
+fn key_of(leaf: &PatternPredicate) -> Option<&str> {
+  match leaf {
+    PatternPredicate::Cmp(attr) => Some(&attr.key),
+    PatternPredicate::Param { key, .. }
+    | PatternPredicate::In { key, .. }
+    | PatternPredicate::Between { key, .. } => Some(key),
+    PatternPredicate::Not { not } => key_of(not),
+    PatternPredicate::And { .. } | PatternPredicate::Or { .. } => None,
+  }
+}
+
+impl AttrStats {
+  /// The fraction of the non-null values.
+  fn present(&self) -> f64 {
+    self.count as f64 / (self.count + self.null_count).max(1) as f64
+  }
+
+  /// The fraction of the values that equal a (typical) value.
+  fn eq_fraction(&self) -> f64 {
+    1.0 / self.distinct_count.max(1) as f64
+  }
+
+  /// The fraction of the rows that satisfy `leaf` (on this attribute).
+  fn estimate(&self, leaf: &PatternPredicate) -> f64 {
+    let is_param = |pred: &PatternPredicate| matches!(pred, PatternPredicate::Param { .. });
+    let is_param = match leaf {
+      PatternPredicate::Not { not } => is_param(not),
+      leaf => is_param(leaf),
+    };
+    // the frequencies tell it all
+    if !self.histogram.value_counts.is_empty() && !is_param {
+      return self.estimate_by_value_counts(leaf);
+    }
+
+    let fraction = match leaf {
+      PatternPredicate::Cmp(attr) => match attr.value.to_epoch_millis() {
+        Some(value) => self.cmp_fraction(attr.op, value),
+        None => AttrGroup::of(leaf).default_selectivity(),
+      },
+      PatternPredicate::Param { op, .. } => match op {
+        Op::Eq => self.eq_fraction(),
+        Op::Ne => 1.0 - self.eq_fraction(),
+        op => AttrGroup::of_op(*op).default_selectivity(),
+      },
+      PatternPredicate::In { values, .. } => (values.iter())
+        .map(|value| match value.to_epoch_millis() {
+          Some(value) => self.cmp_fraction(Op::Eq, value),
+          None => 0.0,
+        })
+        .sum::<f64>()
+        .min(1.0),
+      PatternPredicate::Between {
+        bounds: (low, high),
+        ..
+      } => match (low.to_epoch_millis(), high.to_epoch_millis()) {
+        (Some(low), Some(high)) => self.cmp_fraction(Op::Le, high) - self.cmp_fraction(Op::Lt, low),
+        _ => AttrGroup::of(leaf).default_selectivity(),
+      },
+      // an `IN` / `BETWEEN` that doesn't hold, on the non-null values
+      PatternPredicate::Not { not } => return self.present() - self.estimate(not),
+      PatternPredicate::And { .. } | PatternPredicate::Or { .. } => unreachable!(),
+    };
+    self.present() * fraction.clamp(0.0, 1.0)
+  }
+
+  /// Out of the non-null values.
+  fn cmp_fraction(&self, op: Op, value: f64) -> f64 {
+    let eq = match (self.min_value, self.max_value) {
+      (Some(min), Some(max)) if value < min || value > max => 0.0,
+      _ => self.eq_fraction(),
+    };
+    let Some(lt) = self.cdf(value) else {
+      return match op {
+        Op::Eq => eq,
+        Op::Ne => 1.0 - eq,
+        op => AttrGroup::of_op(op).default_selectivity(),
+      };
+    };
+    let fraction = match op {
+      Op::Eq => eq,
+      Op::Ne => 1.0 - eq,
+      Op::Lt => lt,
+      Op::Le => lt + eq,
+      Op::Gt => 1.0 - lt - eq,
+      Op::Ge => 1.0 - lt,
+      // never holds on numbers
+      _ => 0.0,
+    };
+    fraction.clamp(0.0, 1.0)
+  }
+
+  /// The fraction of the non-null values below `value`, interpolated linearly within a bin
+  /// of the histogram, or else between the min and max.
+  fn cdf(&self, value: f64) -> Option<f64> {
+    let Histogram { bins, counts, .. } = &self.histogram;
+    let total = counts.iter().sum::<usize>();
+    if bins.len() == counts.len() + 1 && total > 0 {
+      if value <= bins[0] {
+        return Some(0.0);
+      }
+      let mut below = 0.0;
+      for (bin, &count) in bins.windows(2).zip(counts) {
+        if value < bin[1] {
+          below += count as f64 * (value - bin[0]) / (bin[1] - bin[0]);
+          return Some(below / total as f64);
+        }
+        below += count as f64;
+      }
+      return Some(1.0);
+    }
+
+    let (min, max) = (self.min_value?, self.max_value?);
+    Some(match value {
+      _ if value <= min => 0.0,
+      _ if value > max => 1.0,
+      // a single value
+      _ if min == max => 0.0,
+      _ => (value - min) / (max - min),
+    })
+  }
+
+  /// Out of all the rows, by evaluating `leaf` on each of the (string) values.
+  fn estimate_by_value_counts(&self, leaf: &PatternPredicate) -> f64 {
+    let key = key_of(leaf).unwrap_or_default();
+    let mut data_attrs = HashMap::with_capacity(1);
+    let mut satisfied = 0;
+    for (value, &count) in &self.histogram.value_counts {
+      data_attrs.insert(key.to_string(), AttrValue::String(value.clone()));
+      // the values are kept as strings, which only compare with the others leniently
+      if leaf.is_satisfied_by(&data_attrs, AttrCmpMode::Lenient) {
+        satisfied += count;
+      }
+    }
+    satisfied as f64 / (self.count + self.null_count).max(1) as f64
+  }
+}
+
+#[cfg(test)]
+mod test_statistics {
+  use super::*;
+  use crate::{
+    planner::{QueryFormat, parse_query},
+    schemas::PatternAttr,
+  };
+
+  fn cmp(key: &str, op: Op, value: AttrValue) -> PatternPredicate {
+    PatternPredicate::Cmp(PatternAttr {
+      key: key.to_string(),
+      op,
+      _type: value.to_type(),
+      value,
+    })
+  }
+
+  fn statistics() -> Statistics {
+    let gender = AttrStats {
+      count: 100,
+      null_count: 0,
+      distinct_count: 2,
+      histogram: Histogram {
+        value_counts: HashMap::from_iter([("male".to_string(), 40), ("female".to_string(), 60)]),
+        ..Default::default()
+      },
+      ..Default::default()
+    };
+    let birthday = AttrStats {
+      count: 80,
+      null_count: 20,
+      distinct_count: 80,
+      min_value: Some(0.0),
+      max_value: Some(100.0),
+      histogram: Histogram {
+        bins: vec![0.0, 50.0, 100.0],
+        counts: vec![60, 20],
+        ..Default::default()
+      },
+      type_: AttrType::Int,
+    };
+    Statistics {
+      v_label_cnt: HashMap::from_iter([("Person".to_string(), 100), ("City".to_string(), 10)]),
+      e_label_cnt: HashMap::from_iter([("isLocatedIn".to_string(), 300)]),
+      v_attr_stats: HashMap::from_iter([(
+        "Person".to_string(),
+        HashMap::from_iter([
+          ("gender".to_string(), gender),
+          ("birthday".to_string(), birthday),
+        ]),
+      )]),
+      e_degree_stats: HashMap::from_iter([(
+        "isLocatedIn".to_string(),
+        vec![
+          DegreeStats {
+            src_label: "Person".to_string(),
+            dst_label: "City".to_string(),
+            count: 100,
+            ..Default::default()
+          },
+          DegreeStats {
+            src_label: "Post".to_string(),
+            dst_label: "Country".to_string(),
+            count: 200,
+            ..Default::default()
+          },
+        ],
+      )]),
+      ..Default::default()
+    }
+  }
+
+  fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+  }
+
+  #[test]
+  fn test_selectivity() {
+    let stats = statistics();
+    let sel = |pred: PatternPredicate| stats.v_selectivity("Person", &[pred]);
+
+    // by the frequencies
+    assert_close(sel(cmp("gender", Op::Eq, "male".into())), 0.4);
+    assert_close(sel(cmp("gender", Op::Ne, "male".into())), 0.6);
+    assert_close(sel(cmp("gender", Op::StartsWith, "fe".into())), 0.6);
+    // an unknown value, at least half a match
+    assert_close(sel(cmp("gender", Op::Eq, "other".into())), 0.005);
+
+    // by the histogram, out of the non-null values
+    assert_close(
+      sel(cmp("birthday", Op::Lt, AttrValue::Int(25))),
+      0.8 * 0.375,
+    );
+    assert_close(
+      sel(cmp("birthday", Op::Ge, AttrValue::Int(75))),
+      0.8 * 0.125,
+    );
+    assert_close(
+      sel(PatternPredicate::Between {
+        key: "birthday".to_string(),
+        bounds: (AttrValue::Int(25), AttrValue::Int(75)),
+      }),
+      0.8 * (0.5 + 1.0 / 80.0),
+    );
+    assert_close(sel(cmp("birthday", Op::Eq, AttrValue::Int(200))), 0.005);
+    assert_close(sel(cmp("birthday", Op::Eq, AttrValue::Int(42))), 0.8 / 80.0);
+
+    // combined as if independent
+    assert_close(
+      stats.v_selectivity(
+        "Person",
+        &[
+          cmp("gender", Op::Eq, "male".into()),
+          PatternPredicate::not(cmp("birthday", Op::Ge, AttrValue::Int(75))),
+        ],
+      ),
+      0.4 * 0.8 * 0.875,
+    );
+
+    // no statistics, by the defaults
+    assert_close(sel(cmp("firstName", Op::Eq, "Jack".into())), 0.1);
+    assert_close(
+      stats.v_selectivity("City", &[cmp("id", Op::Gt, AttrValue::Int(1))]),
+      1.0 / 3.0,
+    );
+    assert_close(stats.v_selectivity("City", &[]), 1.0);
+  }
+
+  #[test]
+  fn test_e_matches() {
+    let stats = statistics();
+    assert_eq!(stats.e_cnt_between("isLocatedIn", "Person", "City"), 100);
+    assert_eq!(stats.e_cnt_between("isLocatedIn", "City", "Person"), 0);
+    // the endpoints aren't counted
+    assert_eq!(stats.e_cnt_between("knows", "Person", "Person"), 0);
+
+    let pattern = parse_query(
+      "MATCH (p:Person)-[e:isLocatedIn]-(c:City) RETURN p",
+      QueryFormat::Cypher,
+    )
+    .unwrap();
+    let (graph, _) = pattern.build().unwrap();
+    let e = &graph.e_entities()["e"];
+    assert_close(stats.e_matches(e, "Person", "City"), 100.0);
+    assert_close(stats.e_matches(e, "City", "Person"), 100.0);
+    // `e c p isLocatedIn in`, which the text format keeps as `(c)<-[e]-(p)`
+    let e = PatternEdge {
+      src_vid: "c".to_string(),
+      dst_vid: "p".to_string(),
+      direction: EdgeDirection::In,
+      ..e.clone()
+    };
+    assert_close(stats.e_matches(&e, "City", "Person"), 100.0);
+    assert_close(stats.e_matches(&e, "Person", "City"), 0.0);
+
+    let stats = &*STATISTICS;
+    assert_eq!(
+      stats.e_cnt_between("knows", "Person", "Person"),
+      stats.e_label_cnt["knows"]
+    );
+  }
+
+  #[test]
+  fn test_default_statistics() {
+    let stats = &*STATISTICS;
+    let person_cnt = stats.v_label_cnt["Person"] as f64;
+    // the keys are stripped of the csv suffix
+    assert_close(
+      stats.v_selectivity("Person", &[cmp("id", Op::Eq, AttrValue::Int(933))]),
+      1.0 / person_cnt,
+    );
+    let gender = stats.v_selectivity("Person", &[cmp("gender", Op::Eq, "male".into())]);
+    assert!(gender > 0.3 && gender < 0.7);
+  }
+}
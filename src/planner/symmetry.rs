@@ -0,0 +1,312 @@
+use super::order_calc::PlanGenInput;
+use crate::{
+  schemas::{
+    CrossPredicate, EBase, PatternEdge, PatternVertex, Symmetry, SymmetryMode, VBase, VidRef,
+  },
+  utils::{dyn_graph::DynGraph, sub_pattern::SubPatternKind},
+};
+use hashbrown::{HashMap, HashSet};
+use itertools::Itertools;
+
+/// Beyond this many automorphisms (e.g. a star of many alike leaves), the symmetries are left
+/// as they are, since each match would be mapped back through all of them.
+pub const MAX_AUTOMORPHISMS: usize = 720;
+
+/// The vertices an automorphism moves (to their images), where the fixed ones are left out.
+type VMap<'g> = HashMap<VidRef<'g>, VidRef<'g>>;
+
+/// The symmetries of the pattern to plan, along with the `vid_lt` predicates that break them,
+/// so that each subgraph is only matched once.
+///
+/// Each time, the first vertex (in the matching order) that some of the remaining automorphisms
+/// move has to be the smallest in its orbit, and only those that fix it remain,
+/// so that the predicates are checked as early as possible.
+pub fn break_symmetries(
+  input: &PlanGenInput,
+  mode: SymmetryMode,
+) -> (Symmetry, Vec<CrossPredicate>) {
+  let graph = &input.pattern_graph;
+  let v_maps = automorphisms(graph, &input.cross_predicates);
+
+  let mut predicates = vec![];
+  let mut stabilizer = v_maps.iter().collect_vec();
+  for vid in &input.optimal_order {
+    let orbit = (stabilizer.iter())
+      .filter_map(|v_map| v_map.get(vid.as_str()))
+      .unique()
+      .sorted()
+      .collect_vec();
+    if orbit.is_empty() {
+      continue;
+    }
+    predicates.extend(
+      orbit
+        .into_iter()
+        .map(|other| CrossPredicate::vid_lt(vid, *other)),
+    );
+    stabilizer.retain(|v_map| !v_map.contains_key(vid.as_str()));
+  }
+
+  let automorphisms = (v_maps.iter())
+    .filter(|v_map| !v_map.is_empty())
+    .map(|v_map| with_edges(graph, v_map))
+    .collect();
+  (
+    Symmetry {
+      mode,
+      automorphisms,
+    },
+    predicates,
+  )
+}
+
+/// All the automorphisms of `graph` (the identity included),
+/// or only the identity if there are more than [`MAX_AUTOMORPHISMS`].
+///
+/// The vertices of the optional / negated parts, those the cross predicates refer to and the ends
+/// of variable-length edges stay fixed. Each vertex also stays within its connected part,
+/// since those of different parts may be bound to the same data vertex.
+pub(crate) fn automorphisms<'g>(
+  graph: &'g DynGraph<PatternVertex, PatternEdge>,
+  cross_predicates: &[CrossPredicate],
+) -> Vec<VMap<'g>> {
+  let referred = (cross_predicates.iter())
+    .flat_map(CrossPredicate::vars)
+    .flat_map(|var| match graph.view_e_from_eid(var) {
+      Some(e) => vec![e.src_vid(), e.dst_vid()],
+      None => graph
+        .view_v_from_vid(var)
+        .map(VBase::vid)
+        .into_iter()
+        .collect(),
+    });
+  let var_length_ends = (graph.e_entities().values())
+    .filter(|e| e.is_var_length())
+    .flat_map(|e| [e.src_vid(), e.dst_vid()]);
+  let sub_pattern_vids = (graph.v_entities().values())
+    .filter(|v| SubPatternKind::of_v(v).is_some())
+    .map(VBase::vid);
+  let fixed = (referred.chain(var_length_ends).chain(sub_pattern_vids)).collect::<HashSet<_>>();
+
+  let vids = graph.view_vids().into_iter().sorted().collect_vec();
+  let candidates = (vids.iter())
+    .map(|&vid| {
+      if fixed.contains(vid) {
+        return vec![vid];
+      }
+      let reachable = graph.reachable_vids(vid);
+      let signature = v_signature(graph, vid);
+      (vids.iter().copied())
+        .filter(|&other| !fixed.contains(other) && reachable.contains(other))
+        .filter(|&other| v_signature(graph, other) == signature)
+        .collect()
+    })
+    .collect_vec();
+
+  let mut search = AutomorphismSearch {
+    graph,
+    vids,
+    candidates,
+    images: vec![],
+    found: vec![],
+  };
+  if !search.extend() {
+    return vec![VMap::new()];
+  }
+  search.found
+}
+
+struct AutomorphismSearch<'g> {
+  graph: &'g DynGraph<PatternVertex, PatternEdge>,
+  /// sorted
+  vids: Vec<VidRef<'g>>,
+  /// the possible images of each one of `vids`
+  candidates: Vec<Vec<VidRef<'g>>>,
+  /// the images of the first `images.len()` ones of `vids`
+  images: Vec<VidRef<'g>>,
+  found: Vec<VMap<'g>>,
+}
+
+impl AutomorphismSearch<'_> {
+  /// Extend `images` in all the ways that keep the edges between the vertices mapped so far,
+  /// or `false` once more than [`MAX_AUTOMORPHISMS`] are found.
+  fn extend(&mut self) -> bool {
+    let idx = self.images.len();
+    if idx == self.vids.len() {
+      let v_map = (self.vids.iter().copied())
+        .zip(self.images.iter().copied())
+        .filter(|(vid, image)| vid != image)
+        .collect();
+      self.found.push(v_map);
+      return self.found.len() <= MAX_AUTOMORPHISMS;
+    }
+
+    let vid = self.vids[idx];
+    for image in self.candidates[idx].clone() {
+      if self.images.contains(&image) {
+        continue;
+      }
+      let keeps_edges = (self.vids.iter().copied())
+        .zip(self.images.iter().copied().chain([image]))
+        .all(|(other, other_image)| {
+          e_signatures_between(self.graph, vid, other)
+            == e_signatures_between(self.graph, image, other_image)
+        });
+      if !keeps_edges {
+        continue;
+      }
+
+      self.images.push(image);
+      let goes_on = self.extend();
+      self.images.pop();
+      if !goes_on {
+        return false;
+      }
+    }
+    true
+  }
+}
+
+/// The vertices / edges `v_map` moves, where each edge is mapped to one (of the same signature)
+/// between the images of its ends, preferably itself.
+fn with_edges(
+  graph: &DynGraph<PatternVertex, PatternEdge>,
+  v_map: &VMap,
+) -> HashMap<String, String> {
+  let image = |vid: VidRef| v_map.get(vid).copied().unwrap_or(vid).to_string();
+  let mut mapping = (v_map.iter())
+    .map(|(vid, image)| (vid.to_string(), image.to_string()))
+    .collect::<HashMap<_, _>>();
+
+  let mut used = HashSet::new();
+  for e in graph
+    .view_e_entities()
+    .into_iter()
+    .sorted_by_key(|e| &e.eid)
+  {
+    let (src, dst) = (image(&e.src_vid), image(&e.dst_vid));
+    let signature = e_signature(e, &e.src_vid);
+    let targets = (graph.get_adj_eids(&src).into_iter().sorted())
+      .map(|eid| &graph.e_entities()[&eid])
+      .filter(|f| !used.contains(&f.eid) && f.opposite_vid(&src) == dst)
+      .filter(|f| e_signature(f, &src) == signature)
+      .collect_vec();
+    let target = (targets.iter().find(|f| f.eid == e.eid))
+      .or(targets.first())
+      .expect("❌  An automorphism should keep the edges");
+    used.insert(&target.eid);
+    if target.eid != e.eid {
+      mapping.insert(e.eid.clone(), target.eid.clone());
+    }
+  }
+
+  mapping
+}
+
+/// What an automorphism keeps of a vertex, along with the edges it's incident to.
+fn v_signature(graph: &DynGraph<PatternVertex, PatternEdge>, vid: VidRef) -> String {
+  let v = &graph.v_entities()[vid];
+  let e_signatures = (graph.get_adj_eids(vid).iter())
+    .map(|eid| e_signature(&graph.e_entities()[eid], vid))
+    .sorted()
+    .join(", ");
+  format!(
+    "{}{:?}{:?}[{e_signatures}]",
+    v.label,
+    v.attrs,
+    SubPatternKind::of_v(v)
+  )
+}
+
+/// What an automorphism keeps of an edge, as it hangs on `anchor` (one of its ends).
+fn e_signature(e: &PatternEdge, anchor: VidRef) -> String {
+  format!(
+    "{:?}{}{:?}{:?}{:?}",
+    e.direction.load_with_src_or_dst(e.src_vid == anchor),
+    e.label,
+    e.attrs,
+    e.var_length,
+    SubPatternKind::of_e(e)
+  )
+}
+
+/// The (sorted) signatures of the edges between `vid` and `other`, as they hang on `vid`.
+fn e_signatures_between(
+  graph: &DynGraph<PatternVertex, PatternEdge>,
+  vid: VidRef,
+  other: VidRef,
+) -> Vec<String> {
+  (graph.get_adj_eids(vid).iter())
+    .map(|eid| &graph.e_entities()[eid])
+    .filter(|e| e.opposite_vid(vid) == other)
+    .map(|e| e_signature(e, vid))
+    .sorted()
+    .collect()
+}
+
+#[cfg(test)]
+mod test_symmetry {
+  use super::*;
+  use crate::planner::{QueryFormat, parse_query};
+
+  fn broken(cypher: &str, order: &[&str]) -> (Symmetry, Vec<String>) {
+    let pattern = parse_query(cypher, QueryFormat::Cypher).unwrap();
+    let (pattern_graph, cross_predicates) = pattern.build().unwrap();
+    let input = PlanGenInput {
+      pattern_graph,
+      optimal_order: order.iter().map(|vid| vid.to_string()).collect(),
+      cross_predicates,
+    };
+    let (symmetry, predicates) = break_symmetries(&input, SymmetryMode::AllEmbeddings);
+    (
+      symmetry,
+      predicates.iter().map(|pred| pred.to_string()).collect(),
+    )
+  }
+
+  #[test]
+  fn test_break_symmetries() {
+    // an undirected triangle maps onto itself in all 3! ways
+    let (symmetry, predicates) = broken(
+      "MATCH (p:Person)-[a:knows]-(q:Person)-[b:knows]-(r:Person)-[c:knows]-(p) RETURN p",
+      &["q", "p", "r"],
+    );
+    assert_eq!(symmetry.embeddings_per_subgraph(), 6);
+    assert_eq!(
+      predicates,
+      [
+        "elementId(q) < elementId(p)",
+        "elementId(q) < elementId(r)",
+        "elementId(p) < elementId(r)"
+      ]
+    );
+    // the edges are moved along with their ends
+    let swap_p_r = (symmetry.automorphisms().iter())
+      .find(|mapping| mapping.len() == 4 && mapping.get("p").is_some_and(|r| r == "r"))
+      .unwrap();
+    assert_eq!(swap_p_r["a"], "b");
+    assert_eq!(swap_p_r["b"], "a");
+
+    // a directed one only rotates
+    let (symmetry, predicates) = broken(
+      "MATCH (p:Person)-[:knows]->(q:Person)-[:knows]->(r:Person)-[:knows]->(p) RETURN p",
+      &["p", "q", "r"],
+    );
+    assert_eq!(symmetry.embeddings_per_subgraph(), 3);
+    assert_eq!(
+      predicates,
+      ["elementId(p) < elementId(q)", "elementId(p) < elementId(r)"]
+    );
+
+    // the vertices the predicates refer to are fixed
+    let (symmetry, predicates) = broken(
+      "MATCH (p:Person)-[:knows]-(q:Person) WHERE p.id < q.id RETURN p",
+      &["p", "q"],
+    );
+    assert!(symmetry.is_trivial() && predicates.is_empty());
+
+    // and so are the vertices in different parts
+    let (symmetry, _) = broken("MATCH (p:Person), (q:Person) RETURN p", &["p", "q"]);
+    assert!(symmetry.is_trivial());
+  }
+}
@@ -0,0 +1,595 @@
+use super::pattern::Pattern;
+use crate::schemas::{
+  AttrCmpMode, AttrRef, AttrSchema, AttrType, AttrValue, CrossPredicate, EdgeDirection,
+  GraphSchema, Label, Op, PatternPredicate,
+};
+use hashbrown::{HashMap, HashSet};
+use itertools::Itertools;
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::Display)]
+pub enum SchemaIssueKind {
+  #[strum(serialize = "unknown vertex label")]
+  UnknownVertexLabel,
+  #[strum(serialize = "unknown edge label")]
+  UnknownEdgeLabel,
+  #[strum(serialize = "unknown attribute")]
+  UnknownAttr,
+  #[strum(serialize = "impossible endpoints")]
+  ImpossibleEndpoints,
+  #[strum(serialize = "type mismatch")]
+  TypeMismatch,
+}
+
+/// Something in a pattern that can't match anything in the data graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaIssue {
+  kind: SchemaIssueKind,
+  /// the pattern vertex / edge it's about
+  var: String,
+  message: String,
+  /// a known label / attribute key that's spelled alike
+  suggestion: Option<String>,
+}
+
+impl SchemaIssue {
+  pub fn kind(&self) -> SchemaIssueKind {
+    self.kind
+  }
+  pub fn var(&self) -> &str {
+    &self.var
+  }
+  pub fn message(&self) -> &str {
+    &self.message
+  }
+  pub fn suggestion(&self) -> Option<&str> {
+    self.suggestion.as_deref()
+  }
+}
+
+impl Display for SchemaIssue {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{} of `{}`: {}", self.kind, self.var, self.message)?;
+    if let Some(suggestion) = &self.suggestion {
+      write!(f, ", did you mean `{suggestion}`?")?;
+    }
+    Ok(())
+  }
+}
+
+/// All the [`SchemaIssue`]s of a pattern, see [`Pattern::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError {
+  issues: Vec<SchemaIssue>,
+}
+
+impl SchemaError {
+  pub fn issues(&self) -> &[SchemaIssue] {
+    &self.issues
+  }
+}
+
+impl Display for SchemaError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let issues = (self.issues.iter())
+      .map(|issue| format!("  - {issue}"))
+      .join("\n");
+    write!(f, "the pattern doesn't fit the data graph:\n{issues}")
+  }
+}
+
+impl std::error::Error for SchemaError {}
+
+impl Pattern {
+  /// Check the labels, the endpoints of the edges and the literals in the predicates
+  /// against the data graph, so that a typo doesn't silently match nothing.
+  ///
+  /// Whatever the schema doesn't know (e.g. the endpoints, if the statistics have no
+  /// `e_degree_stats`) is taken as valid, and a vertex / edge with an unknown label is reported
+  /// only once.
+  pub fn validate(&self, schema: &GraphSchema) -> Result<(), SchemaError> {
+    self.validate_with(schema, AttrCmpMode::default())
+  }
+
+  /// [`Self::validate`], where the types are taken as comparable as of `mode`.
+  pub fn validate_with(&self, schema: &GraphSchema, mode: AttrCmpMode) -> Result<(), SchemaError> {
+    let mut validator = Validator {
+      schema,
+      mode,
+      var_labels: HashMap::new(),
+      issues: vec![],
+    };
+
+    let graph = self.graph();
+    let vertices = graph.view_v_entities();
+    let edges = graph.view_e_entities();
+    for v in vertices.iter().sorted_by_key(|v| &v.vid) {
+      // an unlabeled vertex matches any label, so there's nothing to check it against
+      let is_known = !v.label.is_empty() && validator.check_label(&v.vid, &v.label, true);
+      validator
+        .var_labels
+        .insert(&v.vid, (true, &v.label, is_known));
+    }
+    for e in edges.iter().sorted_by_key(|e| &e.eid) {
+      let is_known = validator.check_label(&e.eid, &e.label, false);
+      validator
+        .var_labels
+        .insert(&e.eid, (false, &e.label, is_known));
+      if is_known {
+        validator.check_endpoints(
+          e.label.as_str(),
+          &e.eid,
+          &e.src_vid,
+          &e.dst_vid,
+          e.direction,
+          e.var_length.is_some(),
+        );
+      }
+    }
+
+    for v in vertices.iter().sorted_by_key(|v| &v.vid) {
+      v.attrs
+        .iter()
+        .for_each(|pred| validator.check_pred(&v.vid, pred));
+    }
+    for e in edges.iter().sorted_by_key(|e| &e.eid) {
+      e.attrs
+        .iter()
+        .for_each(|pred| validator.check_pred(&e.eid, pred));
+    }
+    for pred in self.cross_predicates() {
+      validator.check_cross(pred);
+    }
+
+    match validator.issues {
+      issues if issues.is_empty() => Ok(()),
+      issues => Err(SchemaError { issues }),
+    }
+  }
+}
+
+struct Validator<'a> {
+  schema: &'a GraphSchema,
+  mode: AttrCmpMode,
+  /// var -> (is_vertex, label, is_label_known)
+  var_labels: HashMap<&'a str, (bool, &'a Label, bool)>,
+  issues: Vec<SchemaIssue>,
+}
+
+impl<'a> Validator<'a> {
+  fn report(
+    &mut self,
+    kind: SchemaIssueKind,
+    var: &str,
+    message: String,
+    suggestion: Option<String>,
+  ) {
+    let issue = SchemaIssue {
+      kind,
+      var: var.to_string(),
+      message,
+      suggestion,
+    };
+    // the same attribute may be in several predicates
+    if !self.issues.contains(&issue) {
+      self.issues.push(issue);
+    }
+  }
+
+  fn check_label(&mut self, var: &str, label: &str, is_vertex: bool) -> bool {
+    let (labels, kind) = match is_vertex {
+      true => (&self.schema.v_labels, SchemaIssueKind::UnknownVertexLabel),
+      false => (&self.schema.e_labels, SchemaIssueKind::UnknownEdgeLabel),
+    };
+    if labels.contains(label) {
+      return true;
+    }
+    let suggestion = suggest(label, labels.iter());
+    self.report(
+      kind,
+      var,
+      format!("`{label}` isn't in the data graph"),
+      suggestion,
+    );
+    false
+  }
+
+  /// Variable-length edges only need the first / last hop to fit.
+  fn check_endpoints(
+    &mut self,
+    e_label: &str,
+    eid: &str,
+    src_vid: &str,
+    dst_vid: &str,
+    direction: EdgeDirection,
+    is_var_length: bool,
+  ) {
+    let Some(endpoints) = self.schema.e_endpoints_of(e_label) else {
+      return;
+    };
+    let (Some(&(_, src_label, true)), Some(&(_, dst_label, true))) =
+      (self.var_labels.get(src_vid), self.var_labels.get(dst_vid))
+    else {
+      return;
+    };
+
+    let fits = |from: &str, to: &str| match is_var_length {
+      true => {
+        endpoints.iter().any(|(src, _)| src == from) && endpoints.iter().any(|(_, dst)| dst == to)
+      }
+      false => endpoints.contains(&(from.to_string(), to.to_string())),
+    };
+    let is_possible = match direction {
+      EdgeDirection::Out => fits(src_label, dst_label),
+      EdgeDirection::In => fits(dst_label, src_label),
+      EdgeDirection::Both => fits(src_label, dst_label) || fits(dst_label, src_label),
+    };
+    if is_possible {
+      return;
+    }
+
+    let arrow = match direction {
+      EdgeDirection::Out => "->",
+      EdgeDirection::In => "<-",
+      EdgeDirection::Both => "-",
+    };
+    let message = format!(
+      "`{src_label}` {arrow} `{dst_label}` never holds, as `{e_label}` is `{}`",
+      format_endpoints(endpoints)
+    );
+    self.report(SchemaIssueKind::ImpossibleEndpoints, eid, message, None);
+  }
+
+  /// The attributes of `var`, unless it or its label is unknown.
+  fn attrs_of(&self, var: &str) -> Option<(&'a Label, &'a AttrSchema)> {
+    let &(is_vertex, label, true) = self.var_labels.get(var)? else {
+      return None;
+    };
+    let attrs = match is_vertex {
+      true => self.schema.v_attrs_of(label)?,
+      false => self.schema.e_attrs_of(label)?,
+    };
+    Some((label, attrs))
+  }
+
+  /// The types of `var.key`, or `None` if there's nothing to check against.
+  fn types_of(&mut self, var: &str, key: &str) -> Option<&'a HashSet<AttrType>> {
+    let (label, attrs) = self.attrs_of(var)?;
+    if let Some(types) = attrs.get(key) {
+      return Some(types);
+    }
+    let suggestion = suggest(key, attrs.keys());
+    let message = format!("`{label}` has no `{key}`");
+    self.report(SchemaIssueKind::UnknownAttr, var, message, suggestion);
+    None
+  }
+
+  fn check_pred(&mut self, var: &str, pred: &PatternPredicate) {
+    match pred {
+      PatternPredicate::Cmp(attr) => self.check_literal(var, &attr.key, attr.op, &attr.value),
+      PatternPredicate::Param { key, op, .. } => {
+        if let Some(types) = self.types_of(var, key)
+          && op.is_string_only()
+          && !accepts_string_op(types)
+        {
+          self.report_string_op(var, key, *op, types);
+        }
+      }
+      PatternPredicate::In { key, values } => {
+        (values.iter()).for_each(|value| self.check_literal(var, key, Op::Eq, value))
+      }
+      PatternPredicate::Between { key, bounds } => {
+        self.check_literal(var, key, Op::Ge, &bounds.0);
+        self.check_literal(var, key, Op::Le, &bounds.1);
+      }
+      PatternPredicate::And { and: preds } | PatternPredicate::Or { or: preds } => {
+        preds.iter().for_each(|pred| self.check_pred(var, pred))
+      }
+      PatternPredicate::Not { not } => self.check_pred(var, not),
+    }
+  }
+
+  fn check_literal(&mut self, var: &str, key: &str, op: Op, value: &AttrValue) {
+    let Some(types) = self.types_of(var, key) else {
+      return;
+    };
+    if op.is_string_only() && !accepts_string_op(types) {
+      self.report_string_op(var, key, op, types);
+      return;
+    }
+    let literal_type = value.to_type();
+    if types.is_empty()
+      || types
+        .iter()
+        .any(|&type_| is_comparable(type_, literal_type, self.mode))
+    {
+      return;
+    }
+    let message = format!(
+      "`{key}` holds {}, but it's compared with the {literal_type} `{}`",
+      format_types(types),
+      value.to_literal()
+    );
+    self.report(SchemaIssueKind::TypeMismatch, var, message, None);
+  }
+
+  fn report_string_op(&mut self, var: &str, key: &str, op: Op, types: &HashSet<AttrType>) {
+    let message = format!(
+      "`{op}` takes strings, but `{key}` holds {}",
+      format_types(types)
+    );
+    self.report(SchemaIssueKind::TypeMismatch, var, message, None);
+  }
+
+  fn check_cross(&mut self, pred: &CrossPredicate) {
+    match pred {
+      CrossPredicate::Cmp { left, op, right } => {
+        let left_types = self.types_of(&left.var, &left.key);
+        let right_types = self.types_of(&right.var, &right.key);
+        let (Some(left_types), Some(right_types)) = (left_types, right_types) else {
+          return;
+        };
+        if left_types.is_empty() || right_types.is_empty() {
+          return;
+        }
+        let is_possible = match op.is_string_only() {
+          true => accepts_string_op(left_types) && accepts_string_op(right_types),
+          false => (left_types.iter())
+            .cartesian_product(right_types)
+            .any(|(&l, &r)| is_comparable(l, r, self.mode)),
+        };
+        if !is_possible {
+          self.report_cross(left, *op, right, left_types, right_types);
+        }
+      }
+      CrossPredicate::On { var, pred } => self.check_pred(var, pred),
+      CrossPredicate::And { and: preds } | CrossPredicate::Or { or: preds } => {
+        preds.iter().for_each(|pred| self.check_cross(pred))
+      }
+      CrossPredicate::Not { not } => self.check_cross(not),
+      CrossPredicate::VidLt { .. } => {}
+    }
+  }
+
+  fn report_cross(
+    &mut self,
+    left: &AttrRef,
+    op: Op,
+    right: &AttrRef,
+    left_types: &HashSet<AttrType>,
+    right_types: &HashSet<AttrType>,
+  ) {
+    let message = format!(
+      "`{left} {op} {right}` compares {} with {}",
+      format_types(left_types),
+      format_types(right_types)
+    );
+    self.report(SchemaIssueKind::TypeMismatch, &left.var, message, None);
+  }
+}
+
+/// Whether comparing the two types may yield `true`.
+///
+/// Temporal values are also stored as epoch millis (e.g. `creationDate` in LDBC),
+/// which compare with dates / datetimes unless it's [`AttrCmpMode::Strict`].
+fn is_comparable(l: AttrType, r: AttrType, mode: AttrCmpMode) -> bool {
+  let is_number = |type_| matches!(type_, AttrType::Int | AttrType::Float);
+  let is_temporal = |type_| matches!(type_, AttrType::Date | AttrType::DateTime);
+  let is_instant = |type_| is_number(type_) || is_temporal(type_);
+  l == r
+    || l == AttrType::Null
+    || r == AttrType::Null
+    || (is_number(l) && is_number(r))
+    || (mode.is_lenient() && is_instant(l) && is_instant(r))
+}
+
+fn accepts_string_op(types: &HashSet<AttrType>) -> bool {
+  types.is_empty() || types.contains(&AttrType::String)
+}
+
+/// e.g. `int|string`
+fn format_types(types: &HashSet<AttrType>) -> String {
+  types.iter().map(AttrType::to_string).sorted().join("|")
+}
+
+/// e.g. `Comment|Post -> Person, Forum -> Post`
+fn format_endpoints(endpoints: &HashSet<(Label, Label)>) -> String {
+  (endpoints.iter())
+    .into_group_map_by(|(_, dst)| dst)
+    .into_iter()
+    .map(|(dst, pairs)| {
+      let srcs = pairs.iter().map(|(src, _)| src).sorted().join("|");
+      format!("{srcs} -> {dst}")
+    })
+    .sorted()
+    .join(", ")
+}
+
+/// A known name that differs only in case, or else in a few chars (at most 2).
+fn suggest<'s>(name: &str, known: impl Iterator<Item = &'s String>) -> Option<String> {
+  let lower = name.to_lowercase();
+  let max_distance = (lower.chars().count() / 3).clamp(1, 2);
+  (known)
+    .map(|candidate| {
+      let distance = match candidate.to_lowercase() == lower {
+        true => 0,
+        false => edit_distance(&lower, &candidate.to_lowercase()),
+      };
+      (distance, candidate)
+    })
+    .filter(|(distance, _)| *distance <= max_distance)
+    .min()
+    .map(|(_, candidate)| candidate.clone())
+}
+
+/// The Levenshtein distance of two strings, in chars.
+fn edit_distance(l: &str, r: &str) -> usize {
+  let r = r.chars().collect_vec();
+  let mut prev = (0..=r.len()).collect_vec();
+  for (i, l_char) in l.chars().enumerate() {
+    let mut curr = vec![i + 1; r.len() + 1];
+    for (j, r_char) in r.iter().enumerate() {
+      let substitution = prev[j] + usize::from(l_char != *r_char);
+      curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+    }
+    prev = curr;
+  }
+  prev[r.len()]
+}
+
+#[cfg(test)]
+mod test_validate {
+  use super::*;
+  use crate::planner::{QueryFormat, parse_query, parse_query_file};
+
+  fn issues_of(cypher: &str, schema: &GraphSchema) -> Vec<SchemaIssue> {
+    issues_with(cypher, schema, AttrCmpMode::default())
+  }
+
+  fn issues_with(cypher: &str, schema: &GraphSchema, mode: AttrCmpMode) -> Vec<SchemaIssue> {
+    let pattern = parse_query(cypher, QueryFormat::Cypher).unwrap();
+    pattern
+      .validate_with(schema, mode)
+      .err()
+      .map(|e| e.issues)
+      .unwrap_or_default()
+  }
+
+  #[test]
+  fn test_validate_with_statistics() {
+    let schema = GraphSchema::from_default_statistics();
+    let queries = project_root::get_project_root()
+      .unwrap()
+      .join("resources")
+      .join("queries");
+    for entry in std::fs::read_dir(queries).unwrap() {
+      let path = entry.unwrap().path();
+      if path
+        .file_name()
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .starts_with("ldbc-")
+      {
+        let pattern = parse_query_file(&path).unwrap();
+        assert_eq!(pattern.validate(&schema), Ok(()), "{}", path.display());
+      }
+    }
+
+    let issues = issues_of(
+      "MATCH (t:TagClass)<-[e:hasType]-(g:Tag)-[f:hasCreater]->(p:Person) \
+       WHERE p.firstname = 'Jan' RETURN p",
+      &schema,
+    );
+    let issues = (issues.iter())
+      .map(|issue| (issue.kind(), issue.var(), issue.suggestion()))
+      .collect_vec();
+    assert_eq!(
+      issues,
+      [
+        (SchemaIssueKind::UnknownVertexLabel, "t", Some("Tagclass")),
+        (SchemaIssueKind::UnknownEdgeLabel, "f", Some("hasCreator")),
+        (SchemaIssueKind::UnknownAttr, "p", Some("firstName")),
+      ]
+    );
+
+    // epoch millis compare with dates, unless it's strict
+    for mode in [AttrCmpMode::Lenient, AttrCmpMode::Strict] {
+      let issues = issues_with(
+        "MATCH (p:Person)-[e:knows]->(q:Person) \
+         WHERE p.id STARTS WITH '1' AND e.creationDate > date('2010-01-01') \
+           AND q.id IN [1, 'two'] AND p.firstName < q.creationDate RETURN p",
+        &schema,
+        mode,
+      );
+      let kinds = (issues.iter())
+        .map(|issue| (issue.var(), issue.kind()))
+        .collect_vec();
+      let mut expected = vec![
+        ("p", SchemaIssueKind::TypeMismatch),
+        ("q", SchemaIssueKind::TypeMismatch),
+        ("p", SchemaIssueKind::TypeMismatch),
+      ];
+      if mode.is_strict() {
+        expected.insert(2, ("e", SchemaIssueKind::TypeMismatch));
+      }
+      assert_eq!(kinds, expected);
+      assert_eq!(
+        issues[0].to_string(),
+        "type mismatch of `p`: `STARTS WITH` takes strings, but `id` holds int"
+      );
+    }
+  }
+
+  #[test]
+  fn test_validate_endpoints() {
+    let mut schema = GraphSchema::default();
+    for (e_label, src, dst) in [
+      ("hasCreator", "Comment", "Person"),
+      ("hasCreator", "Post", "Person"),
+      ("replyOf", "Comment", "Post"),
+    ] {
+      schema.add_e_endpoints(e_label, src, dst);
+    }
+    schema.add_v_label("Forum");
+
+    let issues = issues_of(
+      "MATCH (c:Comment)-[e:hasCreator]->(f:Forum), (p:Person)-[g:hasCreator]->(c) RETURN c",
+      &schema,
+    );
+    assert_eq!(issues.len(), 2);
+    assert_eq!(
+      issues[0].to_string(),
+      "impossible endpoints of `e`: `Comment` -> `Forum` never holds, \
+       as `hasCreator` is `Comment|Post -> Person`"
+    );
+    assert_eq!(issues[1].var(), "g");
+
+    // either way, and only the first / last hop of a path
+    let issues = issues_of(
+      "MATCH (p:Person)-[e:hasCreator]-(c:Comment)<-[r:replyOf*1..3]-(d:Comment) RETURN c",
+      &schema,
+    );
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].var(), "r");
+    assert!(
+      issues_of(
+        "MATCH (c:Comment)-[r:replyOf*1..3]->(p:Post) RETURN c",
+        &schema
+      )
+      .is_empty()
+    );
+
+    // the attributes are unknown
+    assert!(issues_of("MATCH (c:Comment) WHERE c.nothing = 1 RETURN c", &schema).is_empty());
+  }
+
+  #[test]
+  fn test_validate_endpoints_from_statistics() {
+    let path = std::env::temp_dir().join(format!("ember-graph-schema-{}.json", std::process::id()));
+    std::fs::write(
+      &path,
+      r#"{
+        "v_cnt": 3, "e_cnt": 2,
+        "v_label_cnt": { "Person": 1, "Comment": 1, "Forum": 1 },
+        "e_label_cnt": { "hasCreator": 1, "hasMember": 1 },
+        "e_degree_stats": {
+          "hasCreator": [{ "src_label": "Comment", "dst_label": "Person", "count": 1 }]
+        }
+      }"#,
+    )
+    .unwrap();
+    let schema = GraphSchema::from_statistics_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let issues = issues_of(
+      "MATCH (c:Comment)-[e:hasCreator]->(f:Forum), (f)-[m:hasMember]->(p:Person) RETURN c",
+      &schema,
+    );
+    let issues = (issues.iter())
+      .map(|issue| (issue.kind(), issue.var()))
+      .collect_vec();
+    // `hasMember` has no degree statistics, so its endpoints are unknown
+    assert_eq!(issues, [(SchemaIssueKind::ImpossibleEndpoints, "e")]);
+  }
+}
@@ -14,13 +14,7 @@ impl<'plan> EmptyResultDumper<'plan> {
   }
 
   pub fn to_df(self) -> DataFrame {
-    let patterns = self
-      .plan_data
-      .pattern_vs
-      .keys()
-      .chain(self.plan_data.pattern_es.keys());
-
-    let columns = patterns
+    let columns = bound_patterns(self.plan_data)
       .map(|pattern| Column::new(pattern.as_str().into(), Vec::<String>::new()))
       .collect_vec();
 
@@ -28,13 +22,38 @@ impl<'plan> EmptyResultDumper<'plan> {
   }
 }
 
+/// The patterns that may be bound in a result, i.e. all but the negated ones.
+fn bound_patterns(plan_data: &PlanData) -> impl Iterator<Item = &String> {
+  let vids = plan_data
+    .pattern_vs
+    .values()
+    .filter(|v| !v.negated)
+    .map(|v| &v.vid);
+  let eids = plan_data
+    .pattern_es
+    .values()
+    .filter(|e| !e.negated)
+    .map(|e| &e.eid);
+  vids.chain(eids)
+}
+
 pub struct ResultDumper {
   results: Vec<DynGraph>,
+  patterns: Vec<String>,
 }
 
 impl ResultDumper {
   pub fn new(results: Vec<DynGraph>) -> Self {
-    Self { results }
+    Self {
+      results,
+      patterns: vec![],
+    }
+  }
+
+  /// Keep a (null) column for each pattern of the plan, even if no result binds it.
+  pub fn with_plan_data(mut self, plan_data: &PlanData) -> Self {
+    self.patterns = bound_patterns(plan_data).cloned().collect();
+    self
   }
 
   pub fn to_detailed_df(self, colored: bool) -> Option<DataFrame> {
@@ -48,7 +67,7 @@ impl ResultDumper {
       .map(|g| g.pre_dump_detailed(colored))
       .collect_vec();
 
-    to_df(all_pre_dumped)
+    to_df(all_pre_dumped, self.patterns)
   }
 
   pub fn to_simplified_df(self, colored: bool) -> Option<DataFrame> {
@@ -62,19 +81,29 @@ impl ResultDumper {
       .map(|g| g.pre_dump_simplified(colored))
       .collect_vec();
 
-    to_df(all_pre_dumped)
+    to_df(all_pre_dumped, self.patterns)
   }
 }
 
-fn to_df(all_pre_dumped: Vec<HashMap<String, Vec<String>>>) -> Option<DataFrame> {
-  let mut ordered_columns = BTreeMap::new();
-
-  for pre_dump in all_pre_dumped {
-    for (pattern, repr) in pre_dump {
-      ordered_columns
-        .entry(pattern)
-        .or_insert_with(Vec::new)
-        .extend(repr);
+/// Each result is a row, where the unbound (optional) patterns are nulls.
+fn to_df(
+  all_pre_dumped: Vec<HashMap<String, Vec<String>>>,
+  patterns: Vec<String>,
+) -> Option<DataFrame> {
+  let mut ordered_columns = all_pre_dumped
+    .iter()
+    .flat_map(|pre_dump| pre_dump.keys().cloned())
+    .chain(patterns)
+    .map(|pattern| (pattern, Vec::new()))
+    .collect::<BTreeMap<_, _>>();
+
+  for mut pre_dump in all_pre_dumped {
+    let height = pre_dump.values().map(Vec::len).max().unwrap_or(1);
+    for (pattern, column) in ordered_columns.iter_mut() {
+      match pre_dump.remove(pattern) {
+        Some(repr) => column.extend(repr.into_iter().map(Some)),
+        None => column.extend(std::iter::repeat_n(None::<String>, height)),
+      }
     }
   }
 
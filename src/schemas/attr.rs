@@ -1,17 +1,56 @@
 use super::base::Op;
+use crate::parser::{ParseError, ParseErrorKind};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, SecondsFormat, Utc};
 use hashbrown::HashMap;
 use serde::{Deserialize, Serialize};
-use std::{fmt::Display, hash::Hash, str::FromStr};
+use std::{cmp::Ordering, fmt::Display, hash::Hash, str::FromStr};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, strum_macros::Display)]
+/// How the values of different types compare, chosen per plan (see `PlanData::with_attr_cmp_mode`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttrCmpMode {
+  /// Values of different types are compared as strings,
+  /// and temporal values against numbers as epoch millis.
+  #[default]
+  Lenient,
+  /// Comparisons across types (except for ints against floats) yield `null`, as in Neo4j.
+  Strict,
+}
+
+impl AttrCmpMode {
+  #[inline]
+  pub fn is_strict(&self) -> bool {
+    *self == Self::Strict
+  }
+
+  #[inline]
+  pub fn is_lenient(&self) -> bool {
+    *self == Self::Lenient
+  }
+}
+
+#[derive(
+  Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize, strum_macros::Display,
+)]
 #[serde(rename_all = "lowercase")]
 pub enum AttrType {
   #[strum(serialize = "int")]
   Int,
   #[strum(serialize = "float")]
   Float,
+  #[default]
   #[strum(serialize = "string")]
   String,
+  #[strum(serialize = "date")]
+  Date,
+  #[strum(serialize = "datetime")]
+  DateTime,
+  #[strum(serialize = "bool")]
+  Bool,
+  #[strum(serialize = "null")]
+  Null,
+  #[strum(serialize = "list")]
+  List,
 }
 
 #[derive(Debug, Clone)]
@@ -19,6 +58,12 @@ pub enum AttrValue {
   Int(i64),
   Float(f64),
   String(String),
+  Date(NaiveDate),
+  /// always normalized to UTC
+  DateTime(DateTime<Utc>),
+  Bool(bool),
+  Null,
+  List(Vec<AttrValue>),
 }
 
 impl AttrValue {
@@ -27,6 +72,244 @@ impl AttrValue {
       Self::Int(_) => AttrType::Int,
       Self::Float(_) => AttrType::Float,
       Self::String(_) => AttrType::String,
+      Self::Date(_) => AttrType::Date,
+      Self::DateTime(_) => AttrType::DateTime,
+      Self::Bool(_) => AttrType::Bool,
+      Self::Null => AttrType::Null,
+      Self::List(_) => AttrType::List,
+    }
+  }
+
+  pub fn is_temporal(&self) -> bool {
+    matches!(self, Self::Date(_) | Self::DateTime(_))
+  }
+
+  /// Milliseconds since the unix epoch, dates are taken at midnight (UTC).
+  ///
+  /// Numbers are returned as is, since temporal values are also stored as epoch millis
+  /// (e.g. `creationDate` in LDBC).
+  pub fn to_epoch_millis(&self) -> Option<f64> {
+    match self {
+      Self::Int(v) => Some(*v as f64),
+      Self::Float(v) => Some(*v),
+      Self::Date(v) => Some(v.and_time(Default::default()).and_utc().timestamp_millis() as f64),
+      Self::DateTime(v) => Some(v.timestamp_millis() as f64),
+      Self::String(_) | Self::Bool(_) | Self::Null | Self::List(_) => None,
+    }
+  }
+
+  /// Both numbers, or both of the same type.
+  pub fn is_same_kind_as(&self, other: &Self) -> bool {
+    let is_number = |value: &Self| matches!(value, Self::Int(_) | Self::Float(_));
+    (is_number(self) && is_number(other)) || self.to_type() == other.to_type()
+  }
+
+  /// `=` in cypher's three-valued logic, `None` stands for `null`.
+  ///
+  /// It's `null` if either side is `null` (so `null` never equals anything),
+  /// or (in `Strict` mode) if they're of different types.
+  pub fn cypher_eq(&self, other: &Self, mode: AttrCmpMode) -> Option<bool> {
+    match (self, other) {
+      (Self::Null, _) | (_, Self::Null) => None,
+      (Self::List(l0), Self::List(r0)) => {
+        if l0.len() != r0.len() {
+          return Some(false);
+        }
+        let mut result = Some(true);
+        for (l, r) in l0.iter().zip(r0) {
+          match l.cypher_eq(r, mode) {
+            Some(false) => return Some(false),
+            None => result = None,
+            Some(true) => {}
+          }
+        }
+        result
+      }
+      _ if mode.is_strict() && !self.is_same_kind_as(other) => None,
+      _ => Some(self == other),
+    }
+  }
+
+  /// `<`, `>`, ... in cypher's three-valued logic, `None` stands for `null`.
+  pub fn cypher_cmp(&self, other: &Self, mode: AttrCmpMode) -> Option<Ordering> {
+    match (self, other) {
+      (Self::Null, _) | (_, Self::Null) => None,
+      _ if mode.is_strict() && !self.is_same_kind_as(other) => None,
+      _ => self.partial_cmp(other),
+    }
+  }
+
+  /// Parse an ISO-8601 date (`2010-03-24`) or datetime (`2010-03-24T12:00:00.000Z`).
+  ///
+  /// A datetime without an offset is taken as UTC.
+  pub fn parse_temporal(s: &str) -> Option<Self> {
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+      return Some(Self::Date(date));
+    }
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(s) {
+      return Some(Self::DateTime(datetime.to_utc()));
+    }
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f")
+      .ok()
+      .map(|datetime| Self::DateTime(datetime.and_utc()))
+  }
+
+  /// The literal in Neo4j Cypher, strings are double-quoted and escaped.
+  pub fn to_neo4j_repr(&self) -> String {
+    match self {
+      Self::String(v) => format!("\"{}\"", v.replace('\\', "\\\\").replace('"', "\\\"")),
+      Self::Date(_) => format!("date(\"{self}\")"),
+      Self::DateTime(_) => format!("datetime(\"{self}\")"),
+      Self::List(values) => format!(
+        "[{}]",
+        values
+          .iter()
+          .map(Self::to_neo4j_repr)
+          .collect::<Vec<_>>()
+          .join(", ")
+      ),
+      _ => self.to_string(),
+    }
+  }
+
+  /// The literal form in patterns, e.g. `'China'`, `date('2010-03-24')`.
+  pub fn to_literal(&self) -> String {
+    match self {
+      Self::String(v) => format!("'{v}'"),
+      Self::Date(_) => format!("date('{self}')"),
+      Self::DateTime(_) => format!("datetime('{self}')"),
+      Self::List(values) => format!(
+        "[{}]",
+        values
+          .iter()
+          .map(Self::to_literal)
+          .collect::<Vec<_>>()
+          .join(", ")
+      ),
+      _ => self.to_string(),
+    }
+  }
+
+  /// String-only ops (e.g. `STARTS WITH`) need a string operand, and `=~` a valid regex.
+  ///
+  /// The error (its kind and what's wrong) is positioned by the caller.
+  pub(crate) fn check_operand_of(&self, op: Op) -> Result<(), (ParseErrorKind, String)> {
+    match self {
+      Self::String(pattern) if op == Op::Regex => {
+        regex::Regex::new(pattern).map(|_| ()).map_err(|e| {
+          (
+            ParseErrorKind::MalformedLiteral,
+            format!("invalid regex: {e}"),
+          )
+        })
+      }
+      Self::Int(_) | Self::Float(_) if op.is_string_only() => Err((
+        ParseErrorKind::InvalidOperator,
+        format!("`{op}` on a number"),
+      )),
+      Self::String(_) => Ok(()),
+      _ if op.is_string_only() => Err((
+        ParseErrorKind::InvalidOperator,
+        format!("`{op}` on a {}", self.to_type()),
+      )),
+      _ => Ok(()),
+    }
+  }
+
+  /// Parse a literal (a number, a quoted string, or `date('...')` / `datetime('...')`)
+  /// that spans the whole `raw`.
+  ///
+  /// `col` is the (1-based) column of `raw`, errors are reported on line 1.
+  pub(crate) fn parse_literal(raw: &str, col: usize) -> Result<Self, ParseError> {
+    let chars = raw.chars().collect::<Vec<_>>();
+    let error = |kind| ParseError::new(kind, 1, col);
+
+    if let Some((func, arg)) = raw.split_once('(') {
+      let func = func.trim_end();
+      if func.eq_ignore_ascii_case("date") || func.eq_ignore_ascii_case("datetime") {
+        let Some(arg) = arg.strip_suffix(')') else {
+          return Err(
+            error(ParseErrorKind::UnexpectedEnd)
+              .with_token(raw)
+              .with_expected("closing `)`"),
+          );
+        };
+        let arg_col = col + raw.chars().count() - arg.chars().count() - 1;
+        let AttrValue::String(iso) = Self::parse_literal(arg.trim(), arg_col)? else {
+          return Err(
+            error(ParseErrorKind::MalformedLiteral)
+              .with_token(raw)
+              .with_expected(format!("a quoted ISO-8601 string in `{func}(...)`")),
+          );
+        };
+        return Self::parse_temporal_of(&iso, func.eq_ignore_ascii_case("date")).ok_or_else(|| {
+          error(ParseErrorKind::MalformedLiteral)
+            .with_token(raw)
+            .with_expected(format!("an ISO-8601 {}", func.to_lowercase()))
+            .with_found(format!("`{iso}`"))
+        });
+      }
+    }
+
+    for (word, value) in [
+      ("true", Self::Bool(true)),
+      ("false", Self::Bool(false)),
+      ("null", Self::Null),
+    ] {
+      if raw.eq_ignore_ascii_case(word) {
+        return Ok(value);
+      }
+    }
+
+    match chars.first() {
+      None => Err(
+        error(ParseErrorKind::UnexpectedEnd)
+          .with_expected("a literal")
+          .with_found("end of line"),
+      ),
+      Some(&c) if c.is_ascii_digit() || c == '-' || c == '+' => {
+        let malformed = || {
+          error(ParseErrorKind::MalformedLiteral)
+            .with_token(raw)
+            .with_expected("an integer or a float")
+            .with_found(format!("`{raw}`"))
+        };
+        match AttrValue::from_str(raw).map_err(|_| malformed())? {
+          AttrValue::String(_) => Err(malformed()),
+          value => Ok(value),
+        }
+      }
+      Some(&quote) if quote == '\'' || quote == '"' => {
+        if chars.len() < 2 || chars.last() != Some(&quote) {
+          return Err(
+            error(ParseErrorKind::UnexpectedEnd)
+              .with_token(raw)
+              .with_expected(format!("closing quote `{quote}`")),
+          );
+        }
+        Ok(AttrValue::String(
+          chars[1..chars.len() - 1].iter().collect(),
+        ))
+      }
+      Some(&c) => Err(
+        error(ParseErrorKind::UnexpectedToken)
+          .with_token(c.to_string())
+          .with_expected("a number, a quoted string, a boolean or `null`")
+          .with_found(format!("`{c}`")),
+      ),
+    }
+  }
+}
+
+impl AttrValue {
+  /// `date(...)` only takes a date, `datetime(...)` also takes a date (at midnight).
+  pub(crate) fn parse_temporal_of(iso: &str, is_date: bool) -> Option<Self> {
+    match (Self::parse_temporal(iso)?, is_date) {
+      (date @ Self::Date(_), true) | (date @ Self::DateTime(_), false) => Some(date),
+      (Self::Date(date), false) => {
+        Some(Self::DateTime(date.and_time(Default::default()).and_utc()))
+      }
+      _ => None,
     }
   }
 }
@@ -34,7 +317,17 @@ impl AttrValue {
 impl FromStr for AttrValue {
   type Err = String;
 
+  /// Numbers, `date('...')` / `datetime('...')`, or else a plain string.
   fn from_str(s: &str) -> Result<Self, Self::Err> {
+    for (prefix, is_date) in [("date('", true), ("datetime('", false)] {
+      if let Some(value) = s
+        .strip_prefix(prefix)
+        .and_then(|s| s.strip_suffix("')"))
+        .and_then(|iso| Self::parse_temporal_of(iso, is_date))
+      {
+        return Ok(value);
+      }
+    }
     if let Ok(i) = s.parse::<i64>() {
       return Ok(AttrValue::Int(i));
     }
@@ -75,39 +368,54 @@ impl Display for AttrValue {
       Self::Int(v) => write!(f, "{v}"),
       Self::Float(v) => write!(f, "{v}"),
       Self::String(v) => write!(f, "{v}"),
+      Self::Date(v) => write!(f, "{}", v.format("%Y-%m-%d")),
+      // fixed width, so that the lexicographic order is the chronological one
+      Self::DateTime(v) => write!(f, "{}", v.to_rfc3339_opts(SecondsFormat::Millis, true)),
+      Self::Bool(v) => write!(f, "{v}"),
+      Self::Null => write!(f, "null"),
+      // as in JSON, which is how lists are stored
+      Self::List(values) => {
+        let values = values.iter().map(|value| match value {
+          Self::String(_) | Self::Date(_) | Self::DateTime(_) => {
+            serde_json::to_string(&value.to_string()).unwrap()
+          }
+          _ => value.to_string(),
+        });
+        write!(f, "[{}]", values.collect::<Vec<_>>().join(", "))
+      }
     }
   }
 }
 
+/// A total-ish order for values themselves (e.g. `null` equals `null`),
+/// see `cypher_eq` and `cypher_cmp` for the three-valued comparisons.
 impl PartialOrd for AttrValue {
-  fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
     match (self, other) {
       (Self::Int(l0), Self::Int(r0)) => l0.partial_cmp(r0),
       (Self::Int(l0), Self::Float(r0)) => (*l0 as f64).partial_cmp(r0),
       (Self::Float(l0), Self::Float(r0)) => l0.partial_cmp(r0),
       (Self::Float(l0), Self::Int(r0)) => l0.partial_cmp(&(*r0 as f64)),
       (Self::String(l0), Self::String(r0)) => l0.partial_cmp(r0),
-      (Self::String(l0), Self::Int(r0)) => l0.partial_cmp(&r0.to_string()),
-      (Self::String(l0), Self::Float(r0)) => l0.partial_cmp(&r0.to_string()),
-      (Self::Int(l0), Self::String(r0)) => l0.to_string().partial_cmp(r0),
-      (Self::Float(l0), Self::String(r0)) => l0.to_string().partial_cmp(r0),
+      (Self::Date(l0), Self::Date(r0)) => l0.partial_cmp(r0),
+      (Self::DateTime(l0), Self::DateTime(r0)) => l0.partial_cmp(r0),
+      (Self::Bool(l0), Self::Bool(r0)) => l0.partial_cmp(r0),
+      (Self::List(l0), Self::List(r0)) => l0.partial_cmp(r0),
+      (Self::Null, Self::Null) => Some(Ordering::Equal),
+      (Self::Null, _) | (_, Self::Null) => None,
+      // a date against a datetime, or a temporal value against epoch millis
+      (l, r) if l.to_epoch_millis().is_some() && r.to_epoch_millis().is_some() => {
+        l.to_epoch_millis()?.partial_cmp(&r.to_epoch_millis()?)
+      }
+      // otherwise, compare as strings
+      (l, r) => l.to_string().partial_cmp(&r.to_string()),
     }
   }
 }
 
 impl PartialEq for AttrValue {
   fn eq(&self, other: &Self) -> bool {
-    match (self, other) {
-      (Self::Int(l0), Self::Int(r0)) => l0 == r0,
-      (Self::Int(l0), Self::Float(r0)) => *l0 as f64 == *r0,
-      (Self::Float(l0), Self::Float(r0)) => l0 == r0,
-      (Self::Float(l0), Self::Int(r0)) => *l0 == *r0 as f64,
-      (Self::String(l0), Self::String(r0)) => l0 == r0,
-      (Self::String(l0), Self::Int(r0)) => *l0 == r0.to_string(),
-      (Self::String(l0), Self::Float(r0)) => *l0 == r0.to_string(),
-      (Self::Int(l0), Self::String(r0)) => l0.to_string() == *r0,
-      (Self::Float(l0), Self::String(r0)) => l0.to_string() == *r0,
-    }
+    self.partial_cmp(other) == Some(Ordering::Equal)
   }
 }
 
@@ -124,89 +432,135 @@ pub struct PatternAttr {
 }
 
 impl PatternAttr {
-  pub fn parse_from_raw(key: String, raw_pred: String) -> Self {
-    let mut cursor = 0;
-
-    // op
-    let mut raw_op = String::new();
-    for c in raw_pred.chars() {
-      if c.is_alphanumeric() || c == '\'' || c == '\"' {
-        break;
-      }
-      raw_op.push(c);
-      cursor += 1;
-    }
-    let op = Op::from_str(&raw_op)
-      .unwrap_or_else(|_| panic!("⚠️  Invalid operator: `{raw_op}` in `{raw_pred}`."));
-
-    // value
-    let last_char = raw_pred.chars().last().unwrap_or(' ');
-    let value = match raw_pred.chars().nth(cursor) {
-      Some(c) => match c {
-        c if c.is_ascii_digit() => {
-          let raw_value = &raw_pred[cursor..];
-          AttrValue::from_str(raw_value)
-            .unwrap_or_else(|_| panic!("⚠️  Malformed literal: `{raw_value}` in `{raw_pred}`."))
-        }
-        c if c == '\'' && last_char != '\'' => {
-          panic!("⚠️  Missing closing quote `'` in `{raw_pred}`.")
-        }
-        c if c == '\"' && last_char != '\"' => {
-          panic!("⚠️  Missing closing quote `\"` in `{raw_pred}`.")
-        }
-        c if c == '\'' || c == '\"' => {
-          let raw_value = &raw_pred[cursor + 1..raw_pred.len() - 1];
-          AttrValue::String(raw_value.to_string())
-        }
-        _ => panic!("⚠️  Invalid character: {c}."),
-      },
-      None => panic!("⚠️  Missing value in `{raw_pred}`."),
-    };
+  /// Parse `<op><literal>` (e.g. `>=1282431075745`, `= 'China'`, `STARTS WITH 'Chi'`).
+  ///
+  /// Positions in the returned error are relative to `raw_pred` (line 1, 1-based column).
+  pub fn parse_from_raw(key: String, raw_pred: String) -> Result<Self, ParseError> {
+    let chars = raw_pred.chars().collect::<Vec<_>>();
+    let (op, raw_op, value_start) = Self::split_op(&chars)?;
 
-    // Currently, `string` only support `Eq` and `Ne`.
-    if matches!(value, AttrValue::String(_)) && op != Op::Eq && op != Op::Ne {
-      panic!("⚠️  Invalid operator `{op}` for string attribute `{key}`.");
-    }
+    let raw_value = chars[value_start..].iter().collect::<String>();
+    let value = AttrValue::parse_literal(&raw_value, value_start + 1)?;
+
+    value.check_operand_of(op).map_err(|(kind, found)| {
+      let error = match kind {
+        ParseErrorKind::InvalidOperator => ParseError::new(kind, 1, 1).with_token(raw_op),
+        _ => ParseError::new(kind, 1, value_start + 1).with_token(raw_value),
+      };
+      error.with_found(found)
+    })?;
 
     // type
     let _type = value.to_type();
 
-    Self {
+    Ok(Self {
       key,
       op,
       value,
       _type,
-    }
+    })
   }
-}
 
-impl PatternAttr {
-  pub fn to_neo4j_constraint(&self, field: &str) -> String {
-    let value_repr = if self._type == AttrType::String {
-      format!("\"{}\"", self.value)
+  /// Split `<op><rest>` into the op, as it's written, and where the rest starts
+  /// (whitespaces between them are skipped).
+  pub(crate) fn split_op(chars: &[char]) -> Result<(Op, String, usize), ParseError> {
+    let error = |kind, col: usize| ParseError::new(kind, 1, col);
+
+    // op, either symbolic (`>=`, `=~`, ...) or in words (`STARTS WITH`, ...)
+    let symbolic_len = chars
+      .iter()
+      .take_while(|c| matches!(c, '=' | '!' | '<' | '>' | '~'))
+      .count();
+    let op_len = if symbolic_len > 0 {
+      symbolic_len
     } else {
-      format!("{}", self.value)
+      let word_len = |from: usize| {
+        chars[from..]
+          .iter()
+          .take_while(|c| c.is_alphabetic())
+          .count()
+      };
+      let first_len = word_len(0);
+      let first = chars[..first_len].iter().collect::<String>();
+      let gap = chars[first_len..]
+        .iter()
+        .take_while(|c| c.is_whitespace())
+        .count();
+      if ["STARTS", "ENDS"]
+        .iter()
+        .any(|w| first.eq_ignore_ascii_case(w))
+        && gap > 0
+      {
+        first_len + gap + word_len(first_len + gap)
+      } else {
+        first_len
+      }
     };
+    let raw_op = chars[..op_len].iter().collect::<String>();
+    let normalized_op = raw_op
+      .split_whitespace()
+      .collect::<Vec<_>>()
+      .join(" ")
+      .to_uppercase();
+    let op = Op::from_str(&normalized_op).map_err(|_| {
+      let found = chars.first().map(char::to_string).unwrap_or_default();
+      error(ParseErrorKind::InvalidOperator, 1)
+        .with_token(if raw_op.is_empty() {
+          found
+        } else {
+          raw_op.clone()
+        })
+        .with_expected(
+          "one of `=`, `!=`, `>`, `>=`, `<`, `<=`, `~=`, `=~`, `STARTS WITH`, `ENDS WITH`, `CONTAINS`",
+        )
+        .with_found(format!("`{raw_op}`"))
+    })?;
 
-    let left = format!("{field}.{}", &self.key);
-    let mid = self.op.to_neo4j_sqlite_repr();
-    let right = value_repr;
+    // whitespaces between `op` and `value` are allowed
+    let value_start = op_len
+      + chars[op_len..]
+        .iter()
+        .take_while(|c| c.is_whitespace())
+        .count();
 
-    format!("{left} {mid} {right}")
+    Ok((op, raw_op, value_start))
   }
+}
 
-  pub fn is_data_attr_satisfied(&self, data_attr: Option<&AttrValue>) -> bool {
-    match data_attr {
-      Some(data_attr) => self.op.operate_on(data_attr, &self.value),
-      None => false,
+impl PatternAttr {
+  pub fn to_neo4j_constraint(&self, field: &str, mode: AttrCmpMode) -> String {
+    let left = format!("{field}.{}", self.key);
+    let right = self.value.to_neo4j_repr();
+
+    match self.op {
+      // as in `Op::operate_on`
+      op if op.is_string_only() && self._type != AttrType::String => {
+        (if mode.is_strict() { "null" } else { "false" }).to_string()
+      }
+      Op::EqIgnoreCase => format!("toLower({left}) = toLower({right})"),
+      op => match op.to_neo4j_sqlite_repr() {
+        Some(repr) => format!("{left} {repr} {right}"),
+        // `STARTS WITH`, `ENDS WITH`, `CONTAINS` and `=~` are written as they are
+        None => format!("{left} {op} {right}"),
+      },
     }
   }
 
-  pub fn is_data_attrs_satisfied(&self, data_attrs: HashMap<String, AttrValue>) -> bool {
-    match data_attrs.get(&self.key) {
-      Some(data_attr) => self.op.operate_on(data_attr, &self.value),
-      None => false,
-    }
+  /// `None` stands for `null`, which a missing attribute also yields (as in cypher).
+  pub fn evaluate(&self, data_attr: Option<&AttrValue>, mode: AttrCmpMode) -> Option<bool> {
+    self.op.operate_on(data_attr?, &self.value, mode)
+  }
+
+  pub fn is_data_attr_satisfied(&self, data_attr: Option<&AttrValue>, mode: AttrCmpMode) -> bool {
+    self.evaluate(data_attr, mode) == Some(true)
+  }
+
+  pub fn is_data_attrs_satisfied(
+    &self,
+    data_attrs: HashMap<String, AttrValue>,
+    mode: AttrCmpMode,
+  ) -> bool {
+    self.is_data_attr_satisfied(data_attrs.get(&self.key), mode)
   }
 }
 
@@ -215,3 +569,89 @@ impl Hash for PatternAttr {
     self.key.hash(state);
   }
 }
+
+#[cfg(test)]
+mod test_attr {
+  use super::*;
+
+  #[test]
+  fn test_temporal_literals() {
+    let date = AttrValue::parse_literal("date('2010-03-24')", 1).unwrap();
+    assert_eq!(date.to_type(), AttrType::Date);
+    assert_eq!(date.to_string(), "2010-03-24");
+
+    // normalized to UTC, with millis
+    let datetime = AttrValue::parse_literal("datetime('2010-03-24T08:00:00+08:00')", 1).unwrap();
+    assert_eq!(datetime.to_type(), AttrType::DateTime);
+    assert_eq!(datetime.to_string(), "2010-03-24T00:00:00.000Z");
+    assert_eq!(
+      AttrValue::from_str(&datetime.to_literal())
+        .unwrap()
+        .to_type(),
+      AttrType::DateTime
+    );
+
+    for raw in [
+      "date('2010-13-01')",
+      "date('2010-03-24T00:00:00Z')",
+      "date(2010)",
+    ] {
+      let e = AttrValue::parse_literal(raw, 1).unwrap_err();
+      assert_eq!(e.kind(), ParseErrorKind::MalformedLiteral, "{raw}");
+    }
+  }
+
+  #[test]
+  fn test_temporal_ordering() {
+    let date = AttrValue::parse_temporal("2010-03-24").unwrap();
+    let midnight = AttrValue::parse_temporal("2010-03-24T00:00:00Z").unwrap();
+    let noon = AttrValue::parse_temporal("2010-03-24T12:00:00").unwrap();
+    assert!(date < noon && midnight < noon);
+    assert_eq!(date, midnight);
+
+    // against epoch millis, as LDBC stores e.g. `creationDate`
+    let millis = AttrValue::Int(1269388800000);
+    assert_eq!(date, millis);
+    assert!(noon > millis);
+    assert!(AttrValue::parse_temporal("1960-01-01").unwrap() < AttrValue::Int(0));
+  }
+
+  #[test]
+  fn test_three_valued_comparison() {
+    let (one, one_str) = (AttrValue::Int(1), AttrValue::from("1"));
+    let (lenient, strict) = (AttrCmpMode::Lenient, AttrCmpMode::Strict);
+    assert_eq!(Op::Eq.operate_on(&one, &one_str, lenient), Some(true));
+    assert_eq!(Op::Eq.operate_on(&one, &one_str, strict), None);
+    // ints against floats are never across types
+    let one_float = AttrValue::Float(1.0);
+    assert_eq!(Op::Le.operate_on(&one, &one_float, strict), Some(true));
+    // neither are dates against epoch millis, unless it's lenient
+    let (epoch, zero) = (
+      AttrValue::parse_temporal("1970-01-01").unwrap(),
+      AttrValue::Int(0),
+    );
+    assert_eq!(Op::Eq.operate_on(&epoch, &zero, lenient), Some(true));
+    assert_eq!(Op::Eq.operate_on(&epoch, &zero, strict), None);
+
+    let null = AttrValue::Null;
+    assert_eq!(Op::Eq.operate_on(&null, &null, lenient), None);
+    assert_eq!(Op::Gt.operate_on(&one, &null, lenient), None);
+    let (no, yes) = (AttrValue::Bool(false), AttrValue::Bool(true));
+    assert_eq!(Op::Lt.operate_on(&no, &yes, strict), Some(true));
+
+    let list = |values: &[AttrValue]| AttrValue::List(values.to_vec());
+    let (one_null, two_null) = (
+      list(&[one.clone(), AttrValue::Null]),
+      list(&[AttrValue::Int(2), AttrValue::Null]),
+    );
+    assert_eq!(Op::Eq.operate_on(&one_null, &one_null, lenient), None);
+    assert_eq!(
+      Op::Eq.operate_on(&one_null, &two_null, lenient),
+      Some(false)
+    );
+    assert_eq!(
+      Op::Ne.operate_on(&one_null, &list(&[one]), lenient),
+      Some(true)
+    );
+  }
+}
@@ -1,8 +1,13 @@
+use super::{AttrCmpMode, AttrValue};
+use dashmap::DashMap;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::fmt::Display;
+use std::{cmp::Ordering, fmt::Display, sync::LazyLock};
 use strum_macros::{AsRefStr, Display, EnumString};
 
 pub const STR_TUPLE_SPLITTER: &str = "^";
+/// Joins the hop eids of a matched variable-length path into its eid.
+pub const STR_PATH_SPLITTER: &str = "|";
 
 pub type Vid = String;
 pub type VidRef<'a> = &'a str;
@@ -46,35 +51,220 @@ pub enum Op {
   #[serde(rename = "<=")]
   #[strum(serialize = "<=")]
   Le,
+  #[serde(rename = "STARTS WITH")]
+  #[strum(serialize = "STARTS WITH")]
+  StartsWith,
+  #[serde(rename = "ENDS WITH")]
+  #[strum(serialize = "ENDS WITH")]
+  EndsWith,
+  #[serde(rename = "CONTAINS")]
+  #[strum(serialize = "CONTAINS")]
+  Contains,
+  /// case-insensitive equality, as `toLower` on both sides
+  #[serde(rename = "~=")]
+  #[strum(serialize = "~=")]
+  EqIgnoreCase,
+  /// the whole string matches the regex
+  #[serde(rename = "=~")]
+  #[strum(serialize = "=~")]
+  Regex,
+}
+
+/// Which data edges a pattern edge `(src_vid)-[e]-(dst_vid)` matches.
+#[derive(
+  Debug,
+  Clone,
+  Copy,
+  Default,
+  PartialEq,
+  Eq,
+  Hash,
+  Deserialize,
+  Serialize,
+  strum_macros::EnumString,
+  strum_macros::Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum EdgeDirection {
+  /// `(src_vid)-[e]->(dst_vid)`
+  #[default]
+  Out,
+  /// `(src_vid)<-[e]-(dst_vid)`
+  In,
+  /// `(src_vid)-[e]-(dst_vid)`, either way
+  Both,
+}
+
+impl EdgeDirection {
+  #[inline]
+  pub fn is_out(&self) -> bool {
+    *self == Self::Out
+  }
+
+  /// Whether the data edges are loaded by their (`src_vid`, `dst_vid`),
+  /// when expanding from the pattern edge's `src_vid` (or else `dst_vid`).
+  #[inline]
+  pub fn load_with_src_or_dst(&self, is_src_curr_pat: bool) -> (bool, bool) {
+    match (self, is_src_curr_pat) {
+      (Self::Out, true) | (Self::In, false) => (true, false),
+      (Self::Out, false) | (Self::In, true) => (false, true),
+      (Self::Both, _) => (true, true),
+    }
+  }
+}
+
+/// Which repetitions a variable-length path may contain.
+#[derive(
+  Debug,
+  Clone,
+  Copy,
+  Default,
+  PartialEq,
+  Eq,
+  Hash,
+  Deserialize,
+  Serialize,
+  strum_macros::EnumString,
+  strum_macros::Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum PathMode {
+  /// no repeated edges (as Cypher does)
+  #[default]
+  Trail,
+  /// repeated edges and vertices are both allowed
+  Walk,
+  /// no repeated vertices
+  Acyclic,
+}
+
+impl PathMode {
+  #[inline]
+  pub fn is_trail(&self) -> bool {
+    *self == Self::Trail
+  }
+}
+
+/// The bounds of a variable-length pattern edge, e.g. `knows*1..3`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct VarLength {
+  pub min_hops: usize,
+  pub max_hops: usize,
+  #[serde(default, skip_serializing_if = "PathMode::is_trail")]
+  pub mode: PathMode,
+}
+
+impl VarLength {
+  /// `None` unless `1 <= min_hops <= max_hops`.
+  pub fn new(min_hops: usize, max_hops: usize, mode: PathMode) -> Option<Self> {
+    (1 <= min_hops && min_hops <= max_hops).then_some(Self {
+      min_hops,
+      max_hops,
+      mode,
+    })
+  }
+}
+
+impl Display for VarLength {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    if self.min_hops == self.max_hops {
+      write!(f, "*{}", self.min_hops)?;
+    } else {
+      write!(f, "*{}..{}", self.min_hops, self.max_hops)?;
+    }
+    if !self.mode.is_trail() {
+      write!(f, " {}", self.mode)?;
+    }
+    Ok(())
+  }
 }
 
 impl Op {
+  /// The infix operator of both Neo4j and SQLite, `None` for the string-only ops.
   #[inline]
-  pub fn to_neo4j_sqlite_repr(&self) -> &str {
+  pub fn to_neo4j_sqlite_repr(&self) -> Option<&'static str> {
     match self {
-      Op::Eq => "=",
-      Op::Ne => "<>",
-      Op::Gt => ">",
-      Op::Ge => ">=",
-      Op::Lt => "<",
-      Op::Le => "<=",
+      Op::Eq => Some("="),
+      Op::Ne => Some("<>"),
+      Op::Gt => Some(">"),
+      Op::Ge => Some(">="),
+      Op::Lt => Some("<"),
+      Op::Le => Some("<="),
+      Op::StartsWith | Op::EndsWith | Op::Contains | Op::EqIgnoreCase | Op::Regex => None,
     }
   }
+
+  /// `STARTS WITH`, `ENDS WITH`, `CONTAINS`, `~=` and `=~` only apply to strings.
+  #[inline]
+  pub fn is_string_only(&self) -> bool {
+    matches!(
+      self,
+      Op::StartsWith | Op::EndsWith | Op::Contains | Op::EqIgnoreCase | Op::Regex
+    )
+  }
 }
 
 impl Op {
-  pub fn operate_on<V: PartialEq + PartialOrd>(&self, left: &V, right: &V) -> bool {
+  /// `left <op> right`, where `left` is the data attribute and `right` the pattern one.
+  ///
+  /// It follows cypher's three-valued logic, where `None` stands for `null`.
+  /// String-only ops never hold on non-string operands (they're `null` if it's strict).
+  pub fn operate_on(&self, left: &AttrValue, right: &AttrValue, mode: AttrCmpMode) -> Option<bool> {
     match self {
-      Op::Eq => left == right,
-      Op::Ne => left != right,
-      Op::Gt => left > right,
-      Op::Ge => left >= right,
-      Op::Lt => left < right,
-      Op::Le => left <= right,
+      Op::Eq => left.cypher_eq(right, mode),
+      Op::Ne => left.cypher_eq(right, mode).map(|eq| !eq),
+      Op::Gt => left.cypher_cmp(right, mode).map(Ordering::is_gt),
+      Op::Ge => left.cypher_cmp(right, mode).map(Ordering::is_ge),
+      Op::Lt => left.cypher_cmp(right, mode).map(Ordering::is_lt),
+      Op::Le => left.cypher_cmp(right, mode).map(Ordering::is_le),
+      _ => {
+        let (AttrValue::String(left), AttrValue::String(right)) = (left, right) else {
+          // never holds on non-strings, unless it's strict (or on `null`)
+          let is_null = matches!(left, AttrValue::Null) || matches!(right, AttrValue::Null);
+          return if mode.is_strict() || is_null {
+            None
+          } else {
+            Some(false)
+          };
+        };
+        Some(match self {
+          Op::StartsWith => left.starts_with(right.as_str()),
+          Op::EndsWith => left.ends_with(right.as_str()),
+          Op::Contains => left.contains(right.as_str()),
+          // as Neo4j's `toLower` on both sides
+          Op::EqIgnoreCase => left.to_lowercase() == right.to_lowercase(),
+          Op::Regex => regex_full_match(right, left).unwrap_or(false),
+          _ => unreachable!(),
+        })
+      }
     }
   }
 }
 
+/// compiled regexes, shared by all the in-memory matches and the SQLite `REGEXP` function
+static REGEX_CACHE: LazyLock<DashMap<String, Regex>> = LazyLock::new(DashMap::new);
+/// at most so many regexes are kept, since the patterns may come from query params
+const REGEX_CACHE_CAP: usize = 1024;
+
+/// Whether the whole `haystack` matches `pattern` (as Cypher's `=~` does).
+///
+/// Returns `None` if `pattern` is not a valid regex.
+pub fn regex_full_match(pattern: &str, haystack: &str) -> Option<bool> {
+  if let Some(regex) = REGEX_CACHE.get(pattern) {
+    return Some(regex.is_match(haystack));
+  }
+  let regex = Regex::new(&format!("^(?:{pattern})$")).ok()?;
+  let is_match = regex.is_match(haystack);
+  // a query only uses a few, so starting over is cheaper than tracking the least recent ones
+  if REGEX_CACHE.len() >= REGEX_CACHE_CAP {
+    REGEX_CACHE.clear();
+  }
+  REGEX_CACHE.insert(pattern.to_string(), regex);
+  Some(is_match)
+}
+
 #[derive(
   Debug, Clone, Copy, Display, EnumString, PartialEq, Eq, Deserialize, Serialize, AsRefStr,
 )]
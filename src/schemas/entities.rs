@@ -1,27 +1,51 @@
-use super::{AttrValue, Eid, Label, LabelRef, PatternAttr, Vid, VidRef};
+use super::{
+  AttrCmpMode, AttrValue, EdgeDirection, Eid, Label, LabelRef, PatternPredicate, STR_PATH_SPLITTER,
+  VarLength, Vid, VidRef,
+};
 use hashbrown::HashMap;
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use std::hash::Hash;
 
 pub trait VBase<T = Self>: Clone + AsRef<T> + Hash + PartialEq + Eq {
-  fn vid(&self) -> VidRef;
-  fn label(&self) -> LabelRef;
+  fn vid(&self) -> VidRef<'_>;
+  fn label(&self) -> LabelRef<'_>;
 }
 pub trait EBase<T = Self>: Clone + AsRef<T> + Hash + PartialEq + Eq {
-  fn eid(&self) -> VidRef;
-  fn src_vid(&self) -> VidRef;
-  fn dst_vid(&self) -> VidRef;
-  fn label(&self) -> LabelRef;
+  fn eid(&self) -> VidRef<'_>;
+  fn src_vid(&self) -> VidRef<'_>;
+  fn dst_vid(&self) -> VidRef<'_>;
+  fn label(&self) -> LabelRef<'_>;
   fn contains(&self, vid: VidRef) -> bool {
     self.src_vid() == vid || self.dst_vid() == vid
   }
+  /// The other end of the edge, which is `vid` itself on a self-loop.
+  fn opposite_vid(&self, vid: VidRef) -> VidRef<'_> {
+    if self.src_vid() == vid {
+      self.dst_vid()
+    } else {
+      self.src_vid()
+    }
+  }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct PatternVertex {
   pub(crate) vid: Vid,
+  /// empty for an unlabeled vertex, which matches any label
   pub(crate) label: Label,
-  pub(crate) attr: Option<PatternAttr>,
+  #[serde(
+    alias = "attr",
+    default,
+    deserialize_with = "super::serde::deserialize_pattern_attrs"
+  )]
+  pub(crate) attrs: Vec<PatternPredicate>,
+  /// part of an `OPTIONAL MATCH` sub-pattern
+  #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+  pub(crate) optional: bool,
+  /// part of a `NOT EXISTS` sub-pattern
+  #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+  pub(crate) negated: bool,
 }
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct DataVertex {
@@ -35,8 +59,35 @@ pub struct PatternEdge {
   pub(crate) src_vid: Vid,
   pub(crate) dst_vid: Vid,
   pub(crate) label: Label,
-  pub(crate) attr: Option<PatternAttr>,
+  #[serde(
+    alias = "attr",
+    default,
+    deserialize_with = "super::serde::deserialize_pattern_attrs"
+  )]
+  pub(crate) attrs: Vec<PatternPredicate>,
+  #[serde(default, skip_serializing_if = "EdgeDirection::is_out")]
+  pub(crate) direction: EdgeDirection,
+  /// matches a path of several data edges instead of a single one
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub(crate) var_length: Option<VarLength>,
+  /// part of an `OPTIONAL MATCH` sub-pattern
+  #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+  pub(crate) optional: bool,
+  /// part of a `NOT EXISTS` sub-pattern
+  #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+  pub(crate) negated: bool,
 }
+
+/// How a pattern edge hangs on one of its ends (see [`PatternEdge::shape_from`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EdgeShape<'a> {
+  pub(crate) from_src: bool,
+  pub(crate) direction: EdgeDirection,
+  pub(crate) label: LabelRef<'a>,
+  pub(crate) attrs: &'a [PatternPredicate],
+  pub(crate) var_length: Option<VarLength>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct DataEdge {
   pub(crate) eid: Eid,
@@ -44,6 +95,9 @@ pub struct DataEdge {
   pub(crate) dst_vid: Vid,
   pub(crate) label: Label,
   pub(crate) attrs: HashMap<String, AttrValue>,
+  /// the data edges (from `src_vid` to `dst_vid`) of a matched variable-length path
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub(crate) hops: Vec<DataEdge>,
 }
 
 impl AsRef<Self> for PatternVertex {
@@ -135,38 +189,62 @@ impl EBase for DataEdge {
 }
 
 impl DataVertex {
-  pub fn satisfy_attr(&self, attr: Option<impl AsRef<PatternAttr>>) -> bool {
-    if attr.is_none() {
-      return true;
+  /// Whether all the given predicates (conjunction) hold on this vertex.
+  pub fn satisfy_attrs(&self, attrs: &[PatternPredicate], mode: AttrCmpMode) -> bool {
+    attrs
+      .iter()
+      .all(|pred| pred.is_satisfied_by(&self.attrs, mode))
+  }
+}
+
+impl PatternEdge {
+  #[inline]
+  pub fn is_var_length(&self) -> bool {
+    self.var_length.is_some()
+  }
+
+  /// How it hangs on `anchor` (one of its ends), where the edges of the same shape
+  /// load the same data edges from the same data vertex.
+  pub fn shape_from(&self, anchor: VidRef) -> EdgeShape<'_> {
+    EdgeShape {
+      from_src: self.src_vid == anchor,
+      direction: self.direction,
+      label: &self.label,
+      attrs: &self.attrs,
+      var_length: self.var_length,
     }
+  }
+}
 
-    let attr = attr.unwrap();
+impl DataEdge {
+  /// Whether all the given predicates (conjunction) hold on this edge.
+  pub fn satisfy_attrs(&self, attrs: &[PatternPredicate], mode: AttrCmpMode) -> bool {
+    attrs
+      .iter()
+      .all(|pred| pred.is_satisfied_by(&self.attrs, mode))
+  }
 
-    match attr.as_ref().op {
-      super::Op::Eq => self
-        .attrs
-        .get(&attr.as_ref().key)
-        .is_some_and(|v| v == &attr.as_ref().value),
-      super::Op::Ne => self
-        .attrs
-        .get(&attr.as_ref().key)
-        .is_some_and(|v| v != &attr.as_ref().value),
-      super::Op::Gt => self
-        .attrs
-        .get(&attr.as_ref().key)
-        .is_some_and(|v| v > &attr.as_ref().value),
-      super::Op::Ge => self
-        .attrs
-        .get(&attr.as_ref().key)
-        .is_some_and(|v| v >= &attr.as_ref().value),
-      super::Op::Lt => self
-        .attrs
-        .get(&attr.as_ref().key)
-        .is_some_and(|v| v < &attr.as_ref().value),
-      super::Op::Le => self
-        .attrs
-        .get(&attr.as_ref().key)
-        .is_some_and(|v| v <= &attr.as_ref().value),
+  /// Bind a matched path (`hops` go from `src_vid` to `dst_vid`) as a single edge,
+  /// whose eid joins those of the hops.
+  pub fn from_hops(label: Label, src_vid: Vid, dst_vid: Vid, hops: Vec<DataEdge>) -> Self {
+    Self {
+      eid: hops.iter().map(|e| e.eid.as_str()).join(STR_PATH_SPLITTER),
+      src_vid,
+      dst_vid,
+      label,
+      attrs: HashMap::new(),
+      hops,
     }
   }
+
+  /// The hops of a matched variable-length path, empty for a plain edge.
+  #[inline]
+  pub fn hops(&self) -> &[DataEdge] {
+    &self.hops
+  }
+
+  #[inline]
+  pub fn is_path(&self) -> bool {
+    !self.hops.is_empty()
+  }
 }
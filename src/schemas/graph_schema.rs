@@ -0,0 +1,193 @@
+use super::{AttrType, Label, LabelRef};
+use hashbrown::{HashMap, HashSet};
+use project_root::get_project_root;
+use serde::Deserialize;
+use std::{fs::File, io, path::Path};
+
+/// attr key -> the types of its (non-null) values
+pub type AttrSchema = HashMap<String, HashSet<AttrType>>;
+
+/// The labels, attribute keys / types and edge endpoints of a data graph,
+/// which patterns are validated against before planning (see `Pattern::validate`).
+///
+/// Attributes and endpoints are optional, since not every source knows about them
+/// (e.g. `label_statistics.json` only counts the labels).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GraphSchema {
+  pub(crate) v_labels: HashSet<Label>,
+  pub(crate) e_labels: HashSet<Label>,
+  /// v_label -> its attributes
+  pub(crate) v_attrs: Option<HashMap<Label, AttrSchema>>,
+  /// e_label -> its attributes
+  pub(crate) e_attrs: Option<HashMap<Label, AttrSchema>>,
+  /// e_label -> (src_v_label, dst_v_label)
+  pub(crate) e_endpoints: Option<HashMap<Label, HashSet<(Label, Label)>>>,
+}
+
+/// The parts of `label_statistics.json` / `advanced_statistics.json` a schema is made of.
+#[derive(Debug, Deserialize)]
+struct StatisticsSchema {
+  v_label_cnt: HashMap<Label, usize>,
+  e_label_cnt: HashMap<Label, usize>,
+  #[serde(default)]
+  v_attr_stats: Option<HashMap<Label, HashMap<String, AttrStatistics>>>,
+  #[serde(default)]
+  e_attr_stats: Option<HashMap<Label, HashMap<String, AttrStatistics>>>,
+  #[serde(default)]
+  e_degree_stats: Option<HashMap<Label, Vec<EndpointStatistics>>>,
+}
+
+/// The endpoint labels of an entry of `e_degree_stats`.
+#[derive(Debug, Deserialize)]
+struct EndpointStatistics {
+  src_label: Label,
+  dst_label: Label,
+}
+
+#[derive(Debug, Deserialize)]
+struct AttrStatistics {
+  #[serde(rename = "type", default)]
+  type_: AttrType,
+}
+
+/// Keys in the statistics keep the suffix of the csv header (e.g. `creationDate:long`).
+fn attr_schema_of(attr_stats: HashMap<String, AttrStatistics>) -> AttrSchema {
+  let mut attrs = AttrSchema::new();
+  for (key, stats) in attr_stats {
+    let key = key.split_once(':').map_or(key.as_str(), |(key, _)| key);
+    attrs
+      .entry(key.to_string())
+      .or_default()
+      .insert(stats.type_);
+  }
+  attrs
+}
+
+impl GraphSchema {
+  /// The schema in `resources/statistics/advanced_statistics.json`.
+  pub fn from_default_statistics() -> Self {
+    let path = get_project_root()
+      .unwrap()
+      .join("resources")
+      .join("statistics")
+      .join("advanced_statistics.json");
+    Self::from_statistics_file(&path).expect("❌  Failed to load the schema from statistics file")
+  }
+
+  /// The labels (and the attributes / endpoints, if there're `v_attr_stats` / `e_attr_stats` /
+  /// `e_degree_stats`) of a statistics file.
+  pub fn from_statistics_file(path: &Path) -> io::Result<Self> {
+    let stats: StatisticsSchema = serde_json::from_reader(io::BufReader::new(File::open(path)?))?;
+    let attrs_of = |attr_stats: HashMap<Label, HashMap<String, AttrStatistics>>| {
+      (attr_stats.into_iter())
+        .map(|(label, attr_stats)| (label, attr_schema_of(attr_stats)))
+        .collect()
+    };
+    Ok(Self {
+      v_labels: stats.v_label_cnt.into_keys().collect(),
+      e_labels: stats.e_label_cnt.into_keys().collect(),
+      v_attrs: stats.v_attr_stats.map(attrs_of),
+      e_attrs: stats.e_attr_stats.map(attrs_of),
+      e_endpoints: stats.e_degree_stats.map(|e_degree_stats| {
+        (e_degree_stats.into_iter())
+          .map(|(label, degree_stats)| {
+            let endpoints = (degree_stats.into_iter())
+              .map(|stats| (stats.src_label, stats.dst_label))
+              .collect();
+            (label, endpoints)
+          })
+          .collect()
+      }),
+    })
+  }
+
+  pub fn add_v_label(&mut self, v_label: LabelRef) {
+    self.v_labels.insert(v_label.to_string());
+    if let Some(v_attrs) = &mut self.v_attrs {
+      v_attrs.entry_ref(v_label).or_default();
+    }
+  }
+
+  pub fn add_e_label(&mut self, e_label: LabelRef) {
+    self.e_labels.insert(e_label.to_string());
+    if let Some(e_attrs) = &mut self.e_attrs {
+      e_attrs.entry_ref(e_label).or_default();
+    }
+  }
+
+  /// Once added, the attributes of the other labels are known (as none).
+  ///
+  /// `null` values only make the key known.
+  pub fn add_v_attr(&mut self, v_label: LabelRef, key: &str, type_: AttrType) {
+    let v_attrs = self.v_attrs.get_or_insert_with(|| {
+      (self.v_labels.iter())
+        .map(|label| (label.clone(), AttrSchema::new()))
+        .collect()
+    });
+    Self::add_attr(v_attrs, v_label, key, type_);
+    self.v_labels.insert(v_label.to_string());
+  }
+
+  /// Once added, the attributes of the other labels are known (as none).
+  ///
+  /// `null` values only make the key known.
+  pub fn add_e_attr(&mut self, e_label: LabelRef, key: &str, type_: AttrType) {
+    let e_attrs = self.e_attrs.get_or_insert_with(|| {
+      (self.e_labels.iter())
+        .map(|label| (label.clone(), AttrSchema::new()))
+        .collect()
+    });
+    Self::add_attr(e_attrs, e_label, key, type_);
+    self.e_labels.insert(e_label.to_string());
+  }
+
+  fn add_attr(attrs: &mut HashMap<Label, AttrSchema>, label: LabelRef, key: &str, type_: AttrType) {
+    let types = attrs
+      .entry_ref(label)
+      .or_default()
+      .entry_ref(key)
+      .or_default();
+    if type_ != AttrType::Null {
+      types.insert(type_);
+    }
+  }
+
+  /// `(src_v_label)-[e_label]->(dst_v_label)` exists in the data graph.
+  pub fn add_e_endpoints(
+    &mut self,
+    e_label: LabelRef,
+    src_v_label: LabelRef,
+    dst_v_label: LabelRef,
+  ) {
+    (self.e_endpoints.get_or_insert_default())
+      .entry_ref(e_label)
+      .or_default()
+      .insert((src_v_label.to_string(), dst_v_label.to_string()));
+    self.e_labels.insert(e_label.to_string());
+    self.v_labels.insert(src_v_label.to_string());
+    self.v_labels.insert(dst_v_label.to_string());
+  }
+
+  pub fn v_labels(&self) -> &HashSet<Label> {
+    &self.v_labels
+  }
+
+  pub fn e_labels(&self) -> &HashSet<Label> {
+    &self.e_labels
+  }
+
+  /// `None` if the attributes are unknown.
+  pub fn v_attrs_of(&self, v_label: LabelRef) -> Option<&AttrSchema> {
+    self.v_attrs.as_ref()?.get(v_label)
+  }
+
+  /// `None` if the attributes are unknown.
+  pub fn e_attrs_of(&self, e_label: LabelRef) -> Option<&AttrSchema> {
+    self.e_attrs.as_ref()?.get(e_label)
+  }
+
+  /// `None` if the endpoints are unknown.
+  pub fn e_endpoints_of(&self, e_label: LabelRef) -> Option<&HashSet<(Label, Label)>> {
+    self.e_endpoints.as_ref()?.get(e_label)
+  }
+}
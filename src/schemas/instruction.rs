@@ -1,5 +1,6 @@
-use super::{VidRef, base::Vid};
+use super::{CrossPredicate, VidRef, base::Vid};
 use colored::Colorize;
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use std::fmt::{Debug, Display};
 
@@ -24,12 +25,29 @@ pub enum InstructionType {
   #[serde(rename = "report")]
   #[strum(serialize = "Report")]
   Report = 5,
+  /// `GetAdj`, where some of the `expand_eids` are variable-length
+  #[serde(rename = "expand_path")]
+  #[strum(serialize = "ExpandPath")]
+  ExpandPath = 6,
+  /// drops the partial matches in `multi_ops` where the negated `expand_eids` can be matched
+  #[serde(rename = "anti_join")]
+  #[strum(serialize = "AntiJoin")]
+  AntiJoin = 7,
+  /// extends the partial matches in `multi_ops` with the optional `expand_eids` (if any)
+  #[serde(rename = "optional_expand")]
+  #[strum(serialize = "OptionalExpand")]
+  OptionalExpand = 8,
 }
 
 impl InstructionType {
   pub fn compare(self, other: Self) -> i8 {
     self as u8 as i8 - other as u8 as i8
   }
+
+  /// `GetAdj` or `ExpandPath`, which both turn `fx` into `Ax`
+  pub fn is_expand(self) -> bool {
+    matches!(self, Self::GetAdj | Self::ExpandPath)
+  }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,13 +61,19 @@ pub struct Instruction {
   pub(crate) multi_ops: Vec<String>,
   pub(crate) target_var: String,
   pub(crate) depend_on: Vec<String>,
+  /// the cross predicates to check on the matches it yields (`Init`, `Foreach` and `Report`)
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub(crate) predicates: Vec<CrossPredicate>,
+  /// the intersections sharing the bucket it caches (`TCache`)
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub(crate) consumers: Option<usize>,
 }
 
 impl Instruction {
   pub fn to_string_uncolored(&self) -> String {
-    match self.type_ {
+    let instr = match self.type_ {
       InstructionType::Init => format!("{} ({}) -> {}", self.type_, self.vid, self.target_var),
-      InstructionType::GetAdj => format!(
+      InstructionType::GetAdj | InstructionType::ExpandPath => format!(
         "{} ({})~~{:?} -> {}",
         self.type_,
         self.single_op.as_ref().unwrap_or(&"".to_string()),
@@ -66,20 +90,33 @@ impl Instruction {
           self.type_, self.vid, self.multi_ops, self.target_var
         ),
       },
-      InstructionType::Foreach => format!(
+      InstructionType::Foreach | InstructionType::TCache => format!(
         "{} ({}) -> {}",
         self.type_,
         self.single_op.as_ref().unwrap_or(&"".to_string()),
         self.target_var
       ),
       InstructionType::Report => format!("{} {:?}", self.type_, self.multi_ops),
-      InstructionType::TCache => unimplemented!(),
+      InstructionType::AntiJoin | InstructionType::OptionalExpand => format!(
+        "{} {:?}~~{:?} -> {}",
+        self.type_, self.multi_ops, self.expand_eids, self.target_var
+      ),
+    };
+    format!("{instr}{}", self.predicates_suffix())
+  }
+
+  /// ` WHERE <pred> AND ...`, or nothing if there's no predicate
+  fn predicates_suffix(&self) -> String {
+    if self.predicates.is_empty() {
+      return String::new();
     }
+    format!(" WHERE {}", self.predicates.iter().join(" AND "))
   }
 }
 
 impl Display for Instruction {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let predicates_suffix = self.predicates_suffix();
     match self.type_ {
       InstructionType::Init => write!(
         f,
@@ -90,7 +127,7 @@ impl Display for Instruction {
         ")".purple(),
         self.target_var.green()
       ),
-      InstructionType::GetAdj => write!(
+      InstructionType::GetAdj | InstructionType::ExpandPath => write!(
         f,
         "{} {}{}{}~~{:?} -> {}",
         self.type_.to_string().purple().bold(),
@@ -124,7 +161,7 @@ impl Display for Instruction {
           self.target_var.green()
         ),
       },
-      InstructionType::Foreach => write!(
+      InstructionType::Foreach | InstructionType::TCache => write!(
         f,
         "{} {}{}{} -> {}",
         self.type_.to_string().purple().bold(),
@@ -139,8 +176,16 @@ impl Display for Instruction {
         self.type_.to_string().purple().bold(),
         format!("{:?}", self.multi_ops).yellow()
       ),
-      InstructionType::TCache => unimplemented!(),
-    }
+      InstructionType::AntiJoin | InstructionType::OptionalExpand => write!(
+        f,
+        "{} {}~~{:?} -> {}",
+        self.type_.to_string().purple().bold(),
+        format!("{:?}", self.multi_ops).yellow(),
+        self.expand_eids,
+        self.target_var.green()
+      ),
+    }?;
+    write!(f, "{}", predicates_suffix.blue())
   }
 }
 
@@ -158,6 +203,8 @@ pub struct InstructionBuilder {
   pub(crate) single_op: Option<String>,
   pub(crate) multi_ops: Vec<String>,
   pub(crate) depend_on: Vec<String>,
+  pub(crate) predicates: Vec<CrossPredicate>,
+  pub(crate) consumers: Option<usize>,
 }
 
 impl InstructionBuilder {
@@ -170,6 +217,8 @@ impl InstructionBuilder {
       single_op: None,
       multi_ops: vec![],
       depend_on: vec![],
+      predicates: vec![],
+      consumers: None,
     }
   }
 
@@ -198,6 +247,16 @@ impl InstructionBuilder {
     self
   }
 
+  pub fn predicates(mut self, predicates: impl IntoIterator<Item = CrossPredicate>) -> Self {
+    self.predicates = predicates.into_iter().collect();
+    self
+  }
+
+  pub fn consumers(mut self, consumers: usize) -> Self {
+    self.consumers = Some(consumers);
+    self
+  }
+
   pub fn build(self) -> Instruction {
     Instruction {
       vid: self.vid,
@@ -207,6 +266,8 @@ impl InstructionBuilder {
       multi_ops: self.multi_ops,
       target_var: self.target_var,
       depend_on: self.depend_on,
+      predicates: self.predicates,
+      consumers: self.consumers,
     }
   }
 }
@@ -1,14 +1,22 @@
 pub mod attr;
 pub mod base;
 pub mod entities;
+pub mod graph_schema;
 pub mod instruction;
+pub mod params;
+pub mod predicate;
 pub mod serde;
+pub mod symmetry;
 
 use ::serde::{Deserialize, Serialize};
 use hashbrown::HashMap;
+use std::collections::VecDeque;
 
 #[allow(unused_imports)]
-pub use {attr::*, base::*, entities::*, instruction::*, serde::*};
+pub use {
+  attr::*, base::*, entities::*, graph_schema::*, instruction::*, params::*, predicate::*,
+  serde::*, symmetry::*,
+};
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PlanData {
@@ -18,6 +26,10 @@ pub struct PlanData {
   #[serde(rename = "edges")]
   pub(crate) pattern_es: HashMap<Eid, PatternEdge>,
   pub(crate) instructions: Vec<Instruction>,
+  #[serde(default, skip_serializing_if = "Symmetry::is_trivial")]
+  pub(crate) symmetry: Symmetry,
+  #[serde(default, skip_serializing_if = "AttrCmpMode::is_lenient")]
+  pub(crate) attr_cmp_mode: AttrCmpMode,
 }
 
 impl PlanData {
@@ -27,4 +39,87 @@ impl PlanData {
   pub fn pattern_es(&self) -> &HashMap<Vid, PatternEdge> {
     &self.pattern_es
   }
+  pub fn symmetry(&self) -> &Symmetry {
+    &self.symmetry
+  }
+  pub fn attr_cmp_mode(&self) -> AttrCmpMode {
+    self.attr_cmp_mode
+  }
+
+  /// Compare the values of different types as of `mode`, both in memory and in the storage.
+  pub fn with_attr_cmp_mode(mut self, mode: AttrCmpMode) -> Self {
+    self.attr_cmp_mode = mode;
+    self
+  }
+
+  /// The instructions (by index) grouped into layers, where each one only depends on
+  /// those in the previous layers, or `None` if the dependencies contain a cycle.
+  pub fn dependency_layers(&self) -> Option<Vec<Vec<usize>>> {
+    let instructions = &self.instructions;
+    let n = instructions.len();
+
+    // build dependency graph
+    let mut incoming_edges = vec![0; n];
+    let mut graph = vec![vec![]; n];
+
+    // build `target_var -> index` map
+    let mut var_to_idx = HashMap::new();
+    for (idx, instr) in instructions.iter().enumerate() {
+      var_to_idx.insert(instr.target_var.clone(), idx);
+    }
+
+    // fill the dependency graph
+    for (idx, instr) in instructions.iter().enumerate() {
+      for dep_var in &instr.depend_on {
+        if let Some(&dep_idx) = var_to_idx.get(dep_var) {
+          graph[dep_idx].push(idx); // `dep_idx` is dependent on `idx`
+          incoming_edges[idx] += 1; // increase the incoming edge count for `idx`
+        }
+      }
+    }
+
+    // Kahn's algorithm for topological sorting
+    let mut layers = vec![];
+    let mut queue = VecDeque::new();
+
+    // init the queue with all nodes on which incoming edges = 0
+    for (i, &incoming_edge) in incoming_edges.iter().enumerate().take(n) {
+      if incoming_edge == 0 {
+        queue.push_back(i);
+      }
+    }
+
+    while !queue.is_empty() {
+      let mut current_layer = vec![];
+      let layer_size = queue.len();
+
+      // iterate over each node of current_layer
+      for _ in 0..layer_size {
+        let node = queue.pop_front().unwrap();
+        current_layer.push(node);
+
+        // decrease the incoming edge count for all dependent nodes
+        for &dependent in &graph[node] {
+          incoming_edges[dependent] -= 1;
+
+          // if incoming_edges[dependent] == 0, then add to queue
+          if incoming_edges[dependent] == 0 {
+            queue.push_back(dependent);
+          }
+        }
+      }
+
+      // add current_layer to layers
+      if !current_layer.is_empty() {
+        layers.push(current_layer);
+      }
+    }
+
+    // check for cycles
+    if layers.iter().map(|layer| layer.len()).sum::<usize>() < n {
+      return None;
+    }
+
+    Some(layers)
+  }
 }
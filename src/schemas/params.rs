@@ -0,0 +1,256 @@
+use super::{AttrValue, CrossPredicate, PatternAttr, PatternPredicate, PlanData};
+use hashbrown::HashMap;
+use itertools::Itertools;
+use std::fmt::Display;
+
+/// The values of the `$param`s, keyed by their names (without `$`).
+pub type Params = HashMap<String, AttrValue>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamError {
+  /// no value is given for these `$param`s
+  Unbound(Vec<String>),
+  /// the value doesn't fit the op, e.g. a number for `STARTS WITH`
+  InvalidValue { param: String, reason: String },
+}
+
+impl Display for ParamError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Unbound(params) => {
+        let plural = if params.len() > 1 { "s" } else { "" };
+        let params = params.iter().map(|param| format!("`${param}`")).join(", ");
+        write!(f, "unbound parameter{plural} {params}")
+      }
+      Self::InvalidValue { param, reason } => {
+        write!(f, "invalid value for parameter `${param}`: {reason}")
+      }
+    }
+  }
+}
+
+impl std::error::Error for ParamError {}
+
+impl PatternPredicate {
+  /// The (sorted, unique) `$param`s it refers to.
+  pub fn params(&self) -> Vec<&str> {
+    let mut params = vec![];
+    self.collect_params(&mut params);
+    params.sort_unstable();
+    params.dedup();
+    params
+  }
+
+  fn collect_params<'a>(&'a self, params: &mut Vec<&'a str>) {
+    match self {
+      Self::Param { param, .. } => params.push(param),
+      Self::And { and: preds } | Self::Or { or: preds } => {
+        preds.iter().for_each(|pred| pred.collect_params(params))
+      }
+      Self::Not { not } => not.collect_params(params),
+      Self::Cmp(_) | Self::In { .. } | Self::Between { .. } => {}
+    }
+  }
+
+  /// A copy with each `$param` replaced by its value in `params`.
+  pub fn bind_params(&self, params: &Params) -> Result<Self, ParamError> {
+    let bind_all = |preds: &[Self]| {
+      preds
+        .iter()
+        .map(|pred| pred.bind_params(params))
+        .collect::<Result<Vec<_>, _>>()
+    };
+
+    match self {
+      Self::Param { key, op, param } => {
+        let value = params
+          .get(param)
+          .ok_or_else(|| ParamError::Unbound(vec![param.clone()]))?;
+        value
+          .check_operand_of(*op)
+          .map_err(|(_, reason)| ParamError::InvalidValue {
+            param: param.clone(),
+            reason,
+          })?;
+        Ok(Self::Cmp(PatternAttr {
+          key: key.clone(),
+          op: *op,
+          value: value.clone(),
+          _type: value.to_type(),
+        }))
+      }
+      Self::And { and: preds } => bind_all(preds).map(Self::and),
+      Self::Or { or: preds } => bind_all(preds).map(Self::or),
+      Self::Not { not } => not.bind_params(params).map(Self::not),
+      Self::Cmp(_) | Self::In { .. } | Self::Between { .. } => Ok(self.clone()),
+    }
+  }
+}
+
+impl CrossPredicate {
+  fn collect_params<'a>(&'a self, params: &mut Vec<&'a str>) {
+    match self {
+      Self::On { pred, .. } => pred.collect_params(params),
+      Self::And { and: preds } | Self::Or { or: preds } => {
+        preds.iter().for_each(|pred| pred.collect_params(params))
+      }
+      Self::Not { not } => not.collect_params(params),
+      Self::Cmp { .. } | Self::VidLt { .. } => {}
+    }
+  }
+
+  /// A copy with each `$param` replaced by its value in `params`.
+  pub fn bind_params(&self, params: &Params) -> Result<Self, ParamError> {
+    let bind_all = |preds: &[Self]| {
+      preds
+        .iter()
+        .map(|pred| pred.bind_params(params))
+        .collect::<Result<Vec<_>, _>>()
+    };
+
+    match self {
+      Self::On { var, pred } => Ok(Self::On {
+        var: var.clone(),
+        pred: pred.bind_params(params)?,
+      }),
+      Self::And { and: preds } => bind_all(preds).map(Self::and),
+      Self::Or { or: preds } => bind_all(preds).map(Self::or),
+      Self::Not { not } => not.bind_params(params).map(Self::not),
+      Self::Cmp { .. } | Self::VidLt { .. } => Ok(self.clone()),
+    }
+  }
+}
+
+impl PlanData {
+  /// The (sorted, unique) `$param`s to bind before execution.
+  pub fn params(&self) -> Vec<&str> {
+    let mut params = vec![];
+    let pattern_attrs = (self.pattern_vs.values().flat_map(|v| &v.attrs))
+      .chain(self.pattern_es.values().flat_map(|e| &e.attrs));
+    for pred in pattern_attrs {
+      pred.collect_params(&mut params);
+    }
+    for pred in self.instructions.iter().flat_map(|instr| &instr.predicates) {
+      pred.collect_params(&mut params);
+    }
+    params.sort_unstable();
+    params.dedup();
+    params
+  }
+
+  /// `Unbound` with all of its `$param`s, unless there're none.
+  pub fn check_params_bound(&self) -> Result<(), ParamError> {
+    self.check_params_bound_by(&Params::new())
+  }
+
+  fn check_params_bound_by(&self, params: &Params) -> Result<(), ParamError> {
+    let unbound = (self.params().into_iter())
+      .filter(|param| !params.contains_key(*param))
+      .map(String::from)
+      .collect_vec();
+    match unbound.is_empty() {
+      true => Ok(()),
+      false => Err(ParamError::Unbound(unbound)),
+    }
+  }
+
+  /// A copy with each `$param` replaced by its value in `params`, so that the plan is generated
+  /// once and executed with different bindings. Values of unknown `$param`s are ignored.
+  pub fn bind_params(&self, params: &Params) -> Result<Self, ParamError> {
+    self.check_params_bound_by(params)?;
+    let bind_all = |preds: &[PatternPredicate]| {
+      preds
+        .iter()
+        .map(|pred| pred.bind_params(params))
+        .collect::<Result<Vec<_>, _>>()
+    };
+
+    let mut plan_data = self.clone();
+    for v in plan_data.pattern_vs.values_mut() {
+      v.attrs = bind_all(&v.attrs)?;
+    }
+    for e in plan_data.pattern_es.values_mut() {
+      e.attrs = bind_all(&e.attrs)?;
+    }
+    for instr in plan_data.instructions.iter_mut() {
+      instr.predicates = (instr.predicates.iter())
+        .map(|pred| pred.bind_params(params))
+        .collect::<Result<_, _>>()?;
+    }
+    Ok(plan_data)
+  }
+}
+
+#[cfg(test)]
+mod test_params {
+  use super::*;
+  use crate::schemas::{AttrCmpMode, AttrRef, Op};
+
+  fn param(key: &str, op: Op, param: &str) -> PatternPredicate {
+    PatternPredicate::Param {
+      key: key.to_string(),
+      op,
+      param: param.to_string(),
+    }
+  }
+
+  #[test]
+  fn test_bind_params() {
+    let pred = PatternPredicate::or(vec![
+      param("id", Op::Eq, "personId"),
+      PatternPredicate::not(param("name", Op::StartsWith, "prefix")),
+    ]);
+    assert_eq!(pred.params(), vec!["personId", "prefix"]);
+    assert_eq!(
+      pred.to_neo4j_constraint("v", AttrCmpMode::default()),
+      "(v.id = $personId OR NOT (v.name STARTS WITH $prefix))"
+    );
+    // unbound, so it's `null`
+    assert_eq!(pred.evaluate(&HashMap::new(), AttrCmpMode::default()), None);
+
+    let params = Params::from([
+      ("personId".to_string(), AttrValue::Int(1)),
+      ("prefix".to_string(), AttrValue::from("Ch")),
+    ]);
+    let bound = pred.bind_params(&params).unwrap();
+    assert!(bound.params().is_empty());
+    let data_attrs = HashMap::from([("id".to_string(), AttrValue::Int(1))]);
+    assert!(bound.is_satisfied_by(&data_attrs, AttrCmpMode::default()));
+
+    let err = pred.bind_params(&Params::new()).unwrap_err();
+    assert_eq!(err, ParamError::Unbound(vec!["personId".to_string()]));
+    let params = Params::from([
+      ("personId".to_string(), AttrValue::Int(1)),
+      ("prefix".to_string(), AttrValue::Int(1)),
+    ]);
+    let err = pred.bind_params(&params).unwrap_err();
+    assert!(matches!(err, ParamError::InvalidValue { param, .. } if param == "prefix"));
+  }
+
+  #[test]
+  fn test_cross_and_serde() {
+    let pred = CrossPredicate::or(vec![
+      CrossPredicate::Cmp {
+        left: AttrRef::new("a", "id"),
+        op: Op::Lt,
+        right: AttrRef::new("b", "id"),
+      },
+      CrossPredicate::On {
+        var: "a".to_string(),
+        pred: param("id", Op::Gt, "minId"),
+      },
+    ]);
+    let json = serde_json::to_string(&pred).unwrap();
+    assert!(json.contains(r#"{"attr":"id","op":">","param":"minId"}"#));
+    assert_eq!(serde_json::from_str::<CrossPredicate>(&json).unwrap(), pred);
+
+    let params = Params::from([("minId".to_string(), AttrValue::Int(3))]);
+    let CrossPredicate::Or { or: preds } = pred.bind_params(&params).unwrap() else {
+      panic!("expected `or`");
+    };
+    let CrossPredicate::On { pred, .. } = &preds[1] else {
+      panic!("expected `on`");
+    };
+    assert!(matches!(pred, PatternPredicate::Cmp(attr) if attr.value == AttrValue::Int(3)));
+  }
+}
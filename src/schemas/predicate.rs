@@ -0,0 +1,792 @@
+use super::{AttrCmpMode, AttrValue, Op, PatternAttr};
+use crate::parser::{ParseError, ParseErrorKind};
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, hash::Hash};
+
+/// A predicate expression on the attributes of a single pattern vertex / edge.
+///
+/// Plain comparisons keep their original (flat) json layout, so that
+/// existing plans are still valid:
+///
+/// - `{"attr": "id", "op": "=", "value": 1, "type": "int"}`
+/// - `{"attr": "name", "in": ["China", "India"]}`
+/// - `{"attr": "id", "between": [1, 9]}` (both bounds inclusive)
+/// - `{"and": [...]}`, `{"or": [...]}`, `{"not": {...}}`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum PatternPredicate {
+  Cmp(PatternAttr),
+  /// `<attr> <op> $param`, which has to be bound before execution (see `PlanData::bind_params`)
+  Param {
+    #[serde(rename = "attr")]
+    key: String,
+    op: Op,
+    param: String,
+  },
+  In {
+    #[serde(rename = "attr")]
+    key: String,
+    #[serde(rename = "in")]
+    values: Vec<AttrValue>,
+  },
+  Between {
+    #[serde(rename = "attr")]
+    key: String,
+    #[serde(rename = "between")]
+    bounds: (AttrValue, AttrValue),
+  },
+  And {
+    and: Vec<PatternPredicate>,
+  },
+  Or {
+    or: Vec<PatternPredicate>,
+  },
+  Not {
+    not: Box<PatternPredicate>,
+  },
+}
+
+impl From<PatternAttr> for PatternPredicate {
+  fn from(attr: PatternAttr) -> Self {
+    Self::Cmp(attr)
+  }
+}
+
+impl Hash for PatternPredicate {
+  fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+    std::mem::discriminant(self).hash(state);
+    match self {
+      Self::Cmp(attr) => attr.hash(state),
+      Self::Param { key, .. } | Self::In { key, .. } | Self::Between { key, .. } => key.hash(state),
+      Self::And { and: preds } | Self::Or { or: preds } => preds.hash(state),
+      Self::Not { not } => not.hash(state),
+    }
+  }
+}
+
+impl PatternPredicate {
+  pub fn and(preds: Vec<PatternPredicate>) -> Self {
+    Self::And { and: preds }
+  }
+
+  pub fn or(preds: Vec<PatternPredicate>) -> Self {
+    Self::Or { or: preds }
+  }
+
+  #[allow(clippy::should_implement_trait)]
+  pub fn not(pred: PatternPredicate) -> Self {
+    Self::Not {
+      not: Box::new(pred),
+    }
+  }
+
+  /// The plain comparison, if this predicate is one.
+  pub fn as_cmp(&self) -> Option<&PatternAttr> {
+    match self {
+      Self::Cmp(attr) => Some(attr),
+      _ => None,
+    }
+  }
+
+  /// The predicate with `NOT` pushed down to the leaves (negation normal form).
+  ///
+  /// Only `In`, `Between` and string-only comparisons may still be wrapped in a `Not` afterwards.
+  /// Meant for selectivity estimation: unlike `Not`, a flipped comparison
+  /// doesn't hold on a missing attribute.
+  pub fn to_nnf(&self) -> Self {
+    match self {
+      Self::And { and: preds } => Self::and(preds.iter().map(Self::to_nnf).collect()),
+      Self::Or { or: preds } => Self::or(preds.iter().map(Self::to_nnf).collect()),
+      Self::Not { not } => not.negated_nnf(),
+      leaf => leaf.clone(),
+    }
+  }
+
+  fn negated_nnf(&self) -> Self {
+    // no negated counterpart for the string-only ops
+    let negated_op = |op| match op {
+      Op::Eq => Some(Op::Ne),
+      Op::Ne => Some(Op::Eq),
+      Op::Gt => Some(Op::Le),
+      Op::Ge => Some(Op::Lt),
+      Op::Lt => Some(Op::Ge),
+      Op::Le => Some(Op::Gt),
+      _ => None,
+    };
+    match self {
+      Self::Cmp(attr) => match negated_op(attr.op) {
+        Some(op) => Self::Cmp(PatternAttr { op, ..attr.clone() }),
+        None => Self::not(self.clone()),
+      },
+      Self::Param { key, op, param } => match negated_op(*op) {
+        Some(op) => Self::Param {
+          key: key.clone(),
+          op,
+          param: param.clone(),
+        },
+        None => Self::not(self.clone()),
+      },
+      Self::And { and: preds } => Self::or(preds.iter().map(Self::negated_nnf).collect()),
+      Self::Or { or: preds } => Self::and(preds.iter().map(Self::negated_nnf).collect()),
+      Self::Not { not } => not.to_nnf(),
+      leaf => Self::not(leaf.clone()),
+    }
+  }
+}
+
+impl PatternPredicate {
+  /// Parse `<op><literal>`, `<op>$param`, `IN [<literal>, ...]` or
+  /// `BETWEEN <literal> AND <literal>` (keywords are case-insensitive).
+  ///
+  /// Positions in the returned error are relative to `raw_pred` (line 1, 1-based column).
+  pub fn parse_from_raw(key: String, raw_pred: String) -> Result<Self, ParseError> {
+    let chars = raw_pred.chars().collect::<Vec<_>>();
+    let keyword_len = chars.iter().take_while(|c| c.is_alphabetic()).count();
+    let keyword = chars[..keyword_len]
+      .iter()
+      .collect::<String>()
+      .to_uppercase();
+
+    match keyword.as_str() {
+      "IN" => {
+        let (start, end) = bracketed(&chars, keyword_len)?;
+        let mut values = vec![];
+        if chars[start..end].iter().any(|c| !c.is_whitespace()) {
+          let is_comma = |chars: &[char], idx: usize| chars[idx] == ',';
+          for (col, raw) in split_outside_quotes(&chars, (start, end), 1, is_comma) {
+            values.push(AttrValue::parse_literal(&raw, col)?);
+          }
+        }
+        Ok(Self::In { key, values })
+      }
+      "BETWEEN" => {
+        let is_and = |chars: &[char], idx: usize| {
+          let word = chars[idx..].iter().take(3).collect::<String>();
+          word.eq_ignore_ascii_case("and")
+            && chars[idx - 1].is_whitespace()
+            && chars.get(idx + 3).is_none_or(|c| c.is_whitespace())
+        };
+        let bounds = split_outside_quotes(&chars, (keyword_len, chars.len()), 3, is_and);
+        let [(low_col, low), (high_col, high)] = bounds.as_slice() else {
+          return Err(
+            ParseError::new(ParseErrorKind::UnexpectedToken, 1, keyword_len + 1)
+              .with_token(raw_pred.as_str())
+              .with_expected("`BETWEEN <literal> AND <literal>`")
+              .with_found(format!("`{raw_pred}`")),
+          );
+        };
+        let low = AttrValue::parse_literal(low, *low_col)?;
+        let high = AttrValue::parse_literal(high, *high_col)?;
+        Ok(Self::Between {
+          key,
+          bounds: (low, high),
+        })
+      }
+      _ => {
+        let (op, _, value_start) = PatternAttr::split_op(&chars)?;
+        if chars.get(value_start) != Some(&'$') {
+          return PatternAttr::parse_from_raw(key, raw_pred).map(Into::into);
+        }
+        let param = chars[value_start + 1..].iter().collect::<String>();
+        if !is_param_name(&param) {
+          return Err(
+            ParseError::new(ParseErrorKind::MalformedLiteral, 1, value_start + 1)
+              .with_token(format!("${param}"))
+              .with_expected("a parameter name, e.g. `$personId`")
+              .with_found(format!("`${param}`")),
+          );
+        }
+        Ok(Self::Param { key, op, param })
+      }
+    }
+  }
+}
+
+/// `[A-Za-z_][A-Za-z0-9_]*`
+pub(crate) fn is_param_name(name: &str) -> bool {
+  let mut chars = name.chars();
+  chars
+    .next()
+    .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+    && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// The (char-wise) range within `[...]` that follows `chars[from..]`.
+fn bracketed(chars: &[char], from: usize) -> Result<(usize, usize), ParseError> {
+  let open = from
+    + chars[from..]
+      .iter()
+      .take_while(|c| c.is_whitespace())
+      .count();
+  if chars.get(open) != Some(&'[') {
+    let found = chars.get(open).map(char::to_string).unwrap_or_default();
+    return Err(
+      ParseError::new(ParseErrorKind::UnexpectedToken, 1, open + 1)
+        .with_token(found.as_str())
+        .with_expected("`[`")
+        .with_found(format!("`{found}`")),
+    );
+  }
+  let close = chars.len() - 1 - chars.iter().rev().take_while(|c| c.is_whitespace()).count();
+  if close <= open || chars[close] != ']' {
+    return Err(
+      ParseError::new(ParseErrorKind::UnexpectedEnd, 1, chars.len() + 1)
+        .with_expected("closing `]`")
+        .with_found("end of line"),
+    );
+  }
+  Ok((open + 1, close))
+}
+
+/// Split `chars[start..end]` at every `sep_len`-char separator outside quoted strings.
+///
+/// Each piece is trimmed and comes with its (1-based) column.
+fn split_outside_quotes(
+  chars: &[char],
+  (start, end): (usize, usize),
+  sep_len: usize,
+  is_sep: impl Fn(&[char], usize) -> bool,
+) -> Vec<(usize, String)> {
+  let mut ranges = vec![];
+  let mut piece_start = start;
+  let mut quote = None;
+  let mut idx = start;
+  while idx < end {
+    match (quote, chars[idx]) {
+      (None, c @ ('\'' | '"')) => quote = Some(c),
+      (Some(q), c) if q == c => quote = None,
+      (None, _) if is_sep(chars, idx) => {
+        ranges.push((piece_start, idx));
+        idx += sep_len;
+        piece_start = idx;
+        continue;
+      }
+      _ => {}
+    }
+    idx += 1;
+  }
+  ranges.push((piece_start, end));
+
+  ranges
+    .into_iter()
+    .map(|(start, end)| {
+      let lead = chars[start..end]
+        .iter()
+        .take_while(|c| c.is_whitespace())
+        .count();
+      let piece = chars[start + lead..end].iter().collect::<String>();
+      (start + lead + 1, piece.trim_end().to_string())
+    })
+    .collect()
+}
+
+impl PatternPredicate {
+  /// Evaluate the predicate on the attributes of a data vertex / edge,
+  /// in cypher's three-valued logic, where `None` stands for `null`.
+  ///
+  /// A missing attribute yields `null`, so does `NOT null`.
+  pub fn evaluate(
+    &self,
+    data_attrs: &HashMap<String, AttrValue>,
+    mode: AttrCmpMode,
+  ) -> Option<bool> {
+    match self {
+      Self::Cmp(attr) => attr.evaluate(data_attrs.get(&attr.key), mode),
+      // unbound, so it's `null`
+      Self::Param { .. } => None,
+      // `x IN [a, b]` is `x = a OR x = b`, so `x IN []` is `false` even if `x` is `null`
+      Self::In { key, values } => {
+        let data_attr = data_attrs.get(key);
+        kleene_or(
+          values
+            .iter()
+            .map(|value| Op::Eq.operate_on(data_attr?, value, mode)),
+        )
+      }
+      Self::Between {
+        key,
+        bounds: (low, high),
+      } => {
+        let data_attr = data_attrs.get(key)?;
+        kleene_and(
+          [
+            Op::Ge.operate_on(data_attr, low, mode),
+            Op::Le.operate_on(data_attr, high, mode),
+          ]
+          .into_iter(),
+        )
+      }
+      Self::And { and: preds } => {
+        kleene_and(preds.iter().map(|pred| pred.evaluate(data_attrs, mode)))
+      }
+      Self::Or { or: preds } => kleene_or(preds.iter().map(|pred| pred.evaluate(data_attrs, mode))),
+      Self::Not { not } => not.evaluate(data_attrs, mode).map(|b| !b),
+    }
+  }
+
+  /// Whether it evaluates to `true` (neither `false` nor `null`).
+  pub fn is_satisfied_by(
+    &self,
+    data_attrs: &HashMap<String, AttrValue>,
+    mode: AttrCmpMode,
+  ) -> bool {
+    self.evaluate(data_attrs, mode) == Some(true)
+  }
+
+  pub fn to_neo4j_constraint(&self, field: &str, mode: AttrCmpMode) -> String {
+    match self {
+      Self::Cmp(attr) => attr.to_neo4j_constraint(field, mode),
+      // as a parameter of Neo4j
+      Self::Param { key, op, param } => match op {
+        Op::EqIgnoreCase => format!("toLower({field}.{key}) = toLower(${param})"),
+        op => match op.to_neo4j_sqlite_repr() {
+          Some(repr) => format!("{field}.{key} {repr} ${param}"),
+          None => format!("{field}.{key} {op} ${param}"),
+        },
+      },
+      Self::In { key, values } => {
+        let values = values
+          .iter()
+          .map(AttrValue::to_neo4j_repr)
+          .collect::<Vec<_>>();
+        format!("{field}.{key} IN [{}]", values.join(", "))
+      }
+      Self::Between {
+        key,
+        bounds: (low, high),
+      } => format!(
+        "({field}.{key} >= {} AND {field}.{key} <= {})",
+        low.to_neo4j_repr(),
+        high.to_neo4j_repr()
+      ),
+      Self::And { and: preds } if preds.is_empty() => "true".to_string(),
+      Self::Or { or: preds } if preds.is_empty() => "false".to_string(),
+      Self::And { and: preds } => Self::join_neo4j_constraints(preds, field, mode, " AND "),
+      Self::Or { or: preds } => Self::join_neo4j_constraints(preds, field, mode, " OR "),
+      Self::Not { not } => format!("NOT ({})", not.to_neo4j_constraint(field, mode)),
+    }
+  }
+
+  fn join_neo4j_constraints(
+    preds: &[PatternPredicate],
+    field: &str,
+    mode: AttrCmpMode,
+    sep: &str,
+  ) -> String {
+    let constraints = preds
+      .iter()
+      .map(|pred| pred.to_neo4j_constraint(field, mode))
+      .collect::<Vec<_>>();
+    format!("({})", constraints.join(sep))
+  }
+
+  /// `AND`-joined constraints of all `preds` on `field`, or `None` if there's no predicate.
+  pub fn to_neo4j_constraints(
+    preds: &[PatternPredicate],
+    field: &str,
+    mode: AttrCmpMode,
+  ) -> Option<String> {
+    if preds.is_empty() {
+      return None;
+    }
+    let constraints = preds
+      .iter()
+      .map(|pred| pred.to_neo4j_constraint(field, mode))
+      .collect::<Vec<_>>();
+    Some(constraints.join(" AND "))
+  }
+}
+
+/// `var.attr`, i.e. an attribute of the data vertex / edge bound to a pattern one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct AttrRef {
+  pub(crate) var: String,
+  #[serde(rename = "attr")]
+  pub(crate) key: String,
+}
+
+impl AttrRef {
+  pub fn new(var: impl Into<String>, key: impl Into<String>) -> Self {
+    Self {
+      var: var.into(),
+      key: key.into(),
+    }
+  }
+}
+
+impl Display for AttrRef {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}.{}", self.var, self.key)
+  }
+}
+
+/// A predicate across the attributes of several pattern vertices / edges,
+/// which is evaluated on the partial matches once all of them are bound.
+///
+/// - `{"left": {"var": "c1", "attr": "creationDate"}, "op": "<", "right": {"var": "c2", "attr": "creationDate"}}`
+/// - `{"var": "c1", "pred": {...}}`, a predicate on a single pattern vertex / edge
+/// - `{"and": [...]}`, `{"or": [...]}`, `{"not": {...}}`
+/// - `{"vid_lt": ["a", "b"]}`, the vid bound to `a` is smaller than the one bound to `b`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum CrossPredicate {
+  Cmp {
+    left: AttrRef,
+    op: Op,
+    right: AttrRef,
+  },
+  On {
+    var: String,
+    pred: PatternPredicate,
+  },
+  And {
+    and: Vec<CrossPredicate>,
+  },
+  Or {
+    or: Vec<CrossPredicate>,
+  },
+  Not {
+    not: Box<CrossPredicate>,
+  },
+  /// injected by the planner to break the symmetries of the pattern (see `planner::symmetry`)
+  VidLt {
+    vid_lt: [String; 2],
+  },
+}
+
+impl CrossPredicate {
+  pub fn and(preds: Vec<CrossPredicate>) -> Self {
+    Self::And { and: preds }
+  }
+
+  pub fn or(preds: Vec<CrossPredicate>) -> Self {
+    Self::Or { or: preds }
+  }
+
+  #[allow(clippy::should_implement_trait)]
+  pub fn not(pred: CrossPredicate) -> Self {
+    Self::Not {
+      not: Box::new(pred),
+    }
+  }
+
+  pub fn vid_lt(left: impl Into<String>, right: impl Into<String>) -> Self {
+    Self::VidLt {
+      vid_lt: [left.into(), right.into()],
+    }
+  }
+
+  /// Whether it's a symmetry-breaking `vid(a) < vid(b)`.
+  pub fn is_vid_lt(&self) -> bool {
+    matches!(self, Self::VidLt { .. })
+  }
+
+  /// The (sorted, unique) pattern vertices / edges it refers to.
+  pub fn vars(&self) -> Vec<&str> {
+    let mut vars = vec![];
+    self.collect_vars(&mut vars);
+    vars.sort_unstable();
+    vars.dedup();
+    vars
+  }
+
+  fn collect_vars<'a>(&'a self, vars: &mut Vec<&'a str>) {
+    match self {
+      Self::Cmp { left, right, .. } => vars.extend([left.var.as_str(), right.var.as_str()]),
+      Self::On { var, .. } => vars.push(var),
+      Self::And { and: preds } | Self::Or { or: preds } => {
+        preds.iter().for_each(|pred| pred.collect_vars(vars))
+      }
+      Self::Not { not } => not.collect_vars(vars),
+      Self::VidLt { vid_lt } => vars.extend(vid_lt.iter().map(String::as_str)),
+    }
+  }
+
+  /// The equivalent predicate on a single pattern vertex / edge,
+  /// unless it compares two attributes.
+  pub fn to_single(&self) -> Option<PatternPredicate> {
+    match self {
+      Self::Cmp { .. } | Self::VidLt { .. } => None,
+      Self::On { pred, .. } => Some(pred.clone()),
+      Self::And { and: preds } => preds
+        .iter()
+        .map(Self::to_single)
+        .collect::<Option<_>>()
+        .map(PatternPredicate::and),
+      Self::Or { or: preds } => preds
+        .iter()
+        .map(Self::to_single)
+        .collect::<Option<_>>()
+        .map(PatternPredicate::or),
+      Self::Not { not } => not.to_single().map(PatternPredicate::not),
+    }
+  }
+
+  /// Evaluate in cypher's three-valued logic, where `attrs_of` yields the attributes
+  /// of the data vertex / edge bound to a pattern one.
+  ///
+  /// An unbound pattern vertex / edge yields `null`, as a missing attribute does.
+  pub fn evaluate<'a>(
+    &self,
+    attrs_of: &impl Fn(&str) -> Option<&'a HashMap<String, AttrValue>>,
+    mode: AttrCmpMode,
+  ) -> Option<bool> {
+    self.evaluate_with(attrs_of, &|_| None, mode)
+  }
+
+  /// Same as `evaluate`, where `vid_of` also yields the vid of the data vertex
+  /// bound to a pattern one, as `vid_lt` requires.
+  pub fn evaluate_with<'a, 'v>(
+    &self,
+    attrs_of: &impl Fn(&str) -> Option<&'a HashMap<String, AttrValue>>,
+    vid_of: &impl Fn(&str) -> Option<&'v str>,
+    mode: AttrCmpMode,
+  ) -> Option<bool> {
+    let evaluate = |pred: &Self| pred.evaluate_with(attrs_of, vid_of, mode);
+    match self {
+      Self::Cmp { left, op, right } => {
+        let left = attrs_of(&left.var)?.get(&left.key)?;
+        let right = attrs_of(&right.var)?.get(&right.key)?;
+        op.operate_on(left, right, mode)
+      }
+      Self::On { var, pred } => pred.evaluate(attrs_of(var)?, mode),
+      Self::And { and: preds } => kleene_and(preds.iter().map(evaluate)),
+      Self::Or { or: preds } => kleene_or(preds.iter().map(evaluate)),
+      Self::Not { not } => evaluate(not).map(|b| !b),
+      // it holds until both are bound, since they may be bound in different parts,
+      // which are only merged by `Report` (where it's checked again)
+      Self::VidLt {
+        vid_lt: [left, right],
+      } => match (vid_of(left), vid_of(right)) {
+        (Some(left), Some(right)) => Some(left < right),
+        _ => Some(true),
+      },
+    }
+  }
+
+  /// Whether it evaluates to `true` (neither `false` nor `null`).
+  pub fn is_satisfied_by<'a>(
+    &self,
+    attrs_of: &impl Fn(&str) -> Option<&'a HashMap<String, AttrValue>>,
+    mode: AttrCmpMode,
+  ) -> bool {
+    self.evaluate(attrs_of, mode) == Some(true)
+  }
+
+  /// The constraint in cypher, where each pattern vertex / edge is named after its var.
+  pub fn to_neo4j_constraint(&self, mode: AttrCmpMode) -> String {
+    match self {
+      Self::Cmp { left, op, right } => match op {
+        Op::EqIgnoreCase => format!("toLower({left}) = toLower({right})"),
+        op => match op.to_neo4j_sqlite_repr() {
+          Some(repr) => format!("{left} {repr} {right}"),
+          None => format!("{left} {op} {right}"),
+        },
+      },
+      Self::On { var, pred } => pred.to_neo4j_constraint(var, mode),
+      Self::And { and: preds } if preds.is_empty() => "true".to_string(),
+      Self::Or { or: preds } if preds.is_empty() => "false".to_string(),
+      Self::And { and: preds } => Self::join_neo4j_constraints(preds, mode, " AND "),
+      Self::Or { or: preds } => Self::join_neo4j_constraints(preds, mode, " OR "),
+      Self::Not { not } => format!("NOT ({})", not.to_neo4j_constraint(mode)),
+      Self::VidLt {
+        vid_lt: [left, right],
+      } => format!("elementId({left}) < elementId({right})"),
+    }
+  }
+
+  fn join_neo4j_constraints(preds: &[CrossPredicate], mode: AttrCmpMode, sep: &str) -> String {
+    let constraints = preds
+      .iter()
+      .map(|pred| pred.to_neo4j_constraint(mode))
+      .collect::<Vec<_>>();
+    format!("({})", constraints.join(sep))
+  }
+}
+
+impl Display for CrossPredicate {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.to_neo4j_constraint(AttrCmpMode::default()))
+  }
+}
+
+/// `AND` in the three-valued logic: `false` wins over `null`, which wins over `true`.
+fn kleene_and(values: impl Iterator<Item = Option<bool>>) -> Option<bool> {
+  let mut result = Some(true);
+  for value in values {
+    match value {
+      Some(false) => return Some(false),
+      None => result = None,
+      Some(true) => {}
+    }
+  }
+  result
+}
+
+/// `OR` in the three-valued logic: `true` wins over `null`, which wins over `false`.
+fn kleene_or(values: impl Iterator<Item = Option<bool>>) -> Option<bool> {
+  let mut result = Some(false);
+  for value in values {
+    match value {
+      Some(true) => return Some(true),
+      None => result = None,
+      Some(false) => {}
+    }
+  }
+  result
+}
+
+#[cfg(test)]
+mod test_predicate {
+  use super::*;
+
+  fn cmp(key: &str, op: Op, value: AttrValue) -> PatternPredicate {
+    let _type = value.to_type();
+    PatternAttr {
+      key: key.to_string(),
+      op,
+      value,
+      _type,
+    }
+    .into()
+  }
+
+  #[test]
+  fn test_evaluate() {
+    let data_attrs = HashMap::from([
+      ("name".to_string(), AttrValue::from("China")),
+      ("id".to_string(), AttrValue::Int(5)),
+    ]);
+
+    let name_in = PatternPredicate::In {
+      key: "name".to_string(),
+      values: vec!["India".into(), "China".into()],
+    };
+    let id_between = PatternPredicate::Between {
+      key: "id".to_string(),
+      bounds: (AttrValue::Int(1), AttrValue::Int(5)),
+    };
+    let mode = AttrCmpMode::Lenient;
+    assert!(name_in.is_satisfied_by(&data_attrs, mode));
+    assert!(id_between.is_satisfied_by(&data_attrs, mode));
+
+    // `age` is missing, so `NOT age = 1` is `null` (as is `false OR null`)
+    let age_ne = PatternPredicate::not(cmp("age", Op::Eq, AttrValue::Int(1)));
+    let pred = PatternPredicate::or(vec![cmp("id", Op::Gt, AttrValue::Int(9)), age_ne.clone()]);
+    assert_eq!(pred.evaluate(&data_attrs, mode), None);
+    assert!(!PatternPredicate::not(pred).is_satisfied_by(&data_attrs, mode));
+
+    // while `true OR null` is `true`, and `false AND null` is `false`
+    let id_eq = cmp("id", Op::Eq, AttrValue::Int(5));
+    let pred = PatternPredicate::or(vec![id_eq.clone(), age_ne.clone()]);
+    assert_eq!(pred.evaluate(&data_attrs, mode), Some(true));
+    let pred = PatternPredicate::and(vec![PatternPredicate::not(id_eq), age_ne]);
+    assert_eq!(pred.evaluate(&data_attrs, mode), Some(false));
+
+    // `null` never equals anything, not even `null`
+    let data_attrs = HashMap::from([("id".to_string(), AttrValue::Null)]);
+    for value in [AttrValue::Null, AttrValue::Int(5)] {
+      assert_eq!(
+        cmp("id", Op::Eq, value.clone()).evaluate(&data_attrs, mode),
+        None
+      );
+      assert_eq!(cmp("id", Op::Ne, value).evaluate(&data_attrs, mode), None);
+    }
+
+    // case folded beyond ASCII, as Neo4j's `toLower`
+    let data_attrs = HashMap::from([("name".to_string(), AttrValue::from("Österreich"))]);
+    let name_like = cmp("name", Op::EqIgnoreCase, "ÖSTERREICH".into());
+    assert!(name_like.is_satisfied_by(&data_attrs, mode));
+  }
+
+  #[test]
+  fn test_nnf_and_neo4j() {
+    let pred = PatternPredicate::not(PatternPredicate::and(vec![
+      cmp("id", Op::Lt, AttrValue::Int(3)),
+      PatternPredicate::In {
+        key: "name".to_string(),
+        values: vec!["a".into()],
+      },
+    ]));
+
+    let (lenient, strict) = (AttrCmpMode::Lenient, AttrCmpMode::Strict);
+    assert_eq!(
+      pred.to_nnf().to_neo4j_constraint("v", lenient),
+      r#"(v.id >= 3 OR NOT (v.name IN ["a"]))"#
+    );
+
+    let pred = PatternPredicate::or(vec![
+      cmp("name", Op::StartsWith, r#"a"\"#.into()),
+      cmp("name", Op::EqIgnoreCase, "b".into()),
+      cmp("id", Op::Contains, AttrValue::Int(1)),
+    ]);
+    for (mode, never) in [(lenient, "false"), (strict, "null")] {
+      assert_eq!(
+        pred.to_neo4j_constraint("v", mode),
+        format!(r#"(v.name STARTS WITH "a\"\\" OR toLower(v.name) = toLower("b") OR {never})"#)
+      );
+    }
+  }
+
+  #[test]
+  fn test_serde_layout() {
+    let json = r#"{"or": [
+      {"attr": "id", "op": "=", "value": 1, "type": "int"},
+      {"attr": "name", "in": ["a", "b"]},
+      {"not": {"attr": "id", "between": [3, 5]}}
+    ]}"#;
+    let pred: PatternPredicate = serde_json::from_str(json).unwrap();
+    let PatternPredicate::Or { or: preds } = &pred else {
+      panic!("expected `or`, got {pred:?}");
+    };
+    assert!(matches!(preds[0], PatternPredicate::Cmp(_)));
+    assert!(matches!(preds[1], PatternPredicate::In { .. }));
+    assert!(matches!(preds[2], PatternPredicate::Not { .. }));
+
+    let round_trip = serde_json::to_string(&pred).unwrap();
+    assert_eq!(
+      serde_json::from_str::<PatternPredicate>(&round_trip).unwrap(),
+      pred
+    );
+  }
+
+  #[test]
+  fn test_cross_predicate() {
+    let c1 = HashMap::from([("creationDate".to_string(), AttrValue::Int(1))]);
+    let c2 = HashMap::from([("creationDate".to_string(), AttrValue::Int(2))]);
+    let attrs_of = |var: &str| match var {
+      "c1" => Some(&c1),
+      "c2" => Some(&c2),
+      _ => None,
+    };
+
+    let json = r#"{"and": [
+      {"left": {"var": "c1", "attr": "creationDate"}, "op": "<",
+       "right": {"var": "c2", "attr": "creationDate"}},
+      {"var": "c1", "pred": {"attr": "creationDate", "op": "=", "value": 1, "type": "int"}}
+    ]}"#;
+    let pred: CrossPredicate = serde_json::from_str(json).unwrap();
+    assert_eq!(pred.vars(), vec!["c1", "c2"]);
+    assert!(pred.to_single().is_none());
+    assert!(pred.is_satisfied_by(&attrs_of, AttrCmpMode::Lenient));
+    assert_eq!(
+      pred.to_string(),
+      "(c1.creationDate < c2.creationDate AND c1.creationDate = 1)"
+    );
+
+    // an unbound var is `null`, which `NOT` keeps
+    let pred = CrossPredicate::not(CrossPredicate::Cmp {
+      left: AttrRef::new("c1", "creationDate"),
+      op: Op::Ne,
+      right: AttrRef::new("c3", "creationDate"),
+    });
+    assert_eq!(pred.evaluate(&attrs_of, AttrCmpMode::Lenient), None);
+
+    let round_trip = serde_json::to_string(&pred).unwrap();
+    assert_eq!(
+      serde_json::from_str::<CrossPredicate>(&round_trip).unwrap(),
+      pred
+    );
+  }
+}
@@ -4,7 +4,7 @@ use serde::{
 };
 use std::str::FromStr;
 
-pub use super::{attr::*, base::*, entities::*, instruction::*};
+pub use super::{attr::*, base::*, entities::*, instruction::*, predicate::*};
 
 impl Serialize for AttrValue {
   fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -15,6 +15,11 @@ impl Serialize for AttrValue {
       AttrValue::Int(v) => serializer.serialize_i64(*v),
       AttrValue::Float(v) => serializer.serialize_f64(*v),
       AttrValue::String(v) => serializer.serialize_str(v),
+      AttrValue::Bool(v) => serializer.serialize_bool(*v),
+      AttrValue::Null => serializer.serialize_unit(),
+      AttrValue::List(v) => serializer.collect_seq(v),
+      // as `date('...')` / `datetime('...')`, which `FromStr` recognizes
+      AttrValue::Date(_) | AttrValue::DateTime(_) => serializer.serialize_str(&self.to_literal()),
     }
   }
 }
@@ -30,7 +35,7 @@ impl<'de> Deserialize<'de> for AttrValue {
       type Value = AttrValue;
 
       fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-        formatter.write_str("Integer / Float / String")
+        formatter.write_str("Integer / Float / String / Date / DateTime / Boolean / Null / List")
       }
 
       fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
@@ -68,12 +73,62 @@ impl<'de> Deserialize<'de> for AttrValue {
       {
         Ok(AttrValue::Float(v))
       }
+
+      fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+      where
+        E: de::Error,
+      {
+        Ok(AttrValue::Bool(v))
+      }
+      fn visit_unit<E>(self) -> Result<Self::Value, E>
+      where
+        E: de::Error,
+      {
+        Ok(AttrValue::Null)
+      }
+      fn visit_none<E>(self) -> Result<Self::Value, E>
+      where
+        E: de::Error,
+      {
+        Ok(AttrValue::Null)
+      }
+      fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+      where
+        A: de::SeqAccess<'de>,
+      {
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(value) = seq.next_element()? {
+          values.push(value);
+        }
+        Ok(AttrValue::List(values))
+      }
     }
 
     deserializer.deserialize_any(AttrValueVisitor)
   }
 }
 
+/// Accepts `null`, a single predicate object (legacy `"attr"` field) or a list of predicates.
+pub(crate) fn deserialize_pattern_attrs<'de, D>(
+  deserializer: D,
+) -> Result<Vec<PatternPredicate>, D::Error>
+where
+  D: serde::Deserializer<'de>,
+{
+  #[derive(Deserialize)]
+  #[serde(untagged)]
+  enum OneOrMany {
+    One(PatternPredicate),
+    Many(Vec<PatternPredicate>),
+  }
+
+  Ok(match Option::<OneOrMany>::deserialize(deserializer)? {
+    None => vec![],
+    Some(OneOrMany::One(attr)) => vec![attr],
+    Some(OneOrMany::Many(attrs)) => attrs,
+  })
+}
+
 #[cfg(test)]
 mod test_deserializer {
   use super::*;
@@ -101,4 +156,24 @@ mod test_deserializer {
     assert_eq!(_int_attr_value, AttrValue::Int(int));
     assert_eq!(_float_attr_value, AttrValue::Float(float));
   }
+
+  #[test]
+  fn test_pattern_attrs_deserializer() {
+    let legacy_null = r#"{"vid": "a", "label": "A", "attr": null}"#;
+    let legacy_one =
+      r#"{"vid": "a", "label": "A", "attr": {"attr": "id", "op": "=", "value": 1, "type": "int"}}"#;
+    let many = r#"{"vid": "a", "label": "A", "attrs": [
+      {"attr": "id", "op": ">", "value": 1, "type": "int"},
+      {"attr": "id", "op": "<", "value": 9, "type": "int"}
+    ]}"#;
+
+    let legacy_null: PatternVertex = serde_json::from_str(legacy_null).unwrap();
+    let legacy_one: PatternVertex = serde_json::from_str(legacy_one).unwrap();
+    let many: PatternVertex = serde_json::from_str(many).unwrap();
+
+    assert!(legacy_null.attrs.is_empty());
+    assert_eq!(legacy_one.attrs.len(), 1);
+    assert_eq!(many.attrs.len(), 2);
+    assert_eq!(many.attrs[1].as_cmp().unwrap().op, Op::Lt);
+  }
 }
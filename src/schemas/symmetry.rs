@@ -0,0 +1,46 @@
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+
+/// What's reported for a pattern with symmetries, i.e. whose subgraphs are matched once
+/// per automorphism of it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymmetryMode {
+  /// each subgraph once per automorphism, as if no symmetry was broken
+  #[default]
+  AllEmbeddings,
+  /// each subgraph once
+  DistinctSubgraphs,
+}
+
+/// The automorphisms of a pattern, i.e. the permutations of its vertices / edges
+/// that map it onto itself.
+///
+/// The planner breaks them with `vid_lt` predicates, so that each subgraph is only matched once,
+/// and the executor maps the matches back through them in [`SymmetryMode::AllEmbeddings`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Symmetry {
+  pub(crate) mode: SymmetryMode,
+  /// the pattern vertices / edges each one moves, without the identity
+  pub(crate) automorphisms: Vec<HashMap<String, String>>,
+}
+
+impl Symmetry {
+  pub fn mode(&self) -> SymmetryMode {
+    self.mode
+  }
+
+  pub fn automorphisms(&self) -> &[HashMap<String, String>] {
+    &self.automorphisms
+  }
+
+  /// Whether the pattern has no automorphisms but the identity.
+  pub fn is_trivial(&self) -> bool {
+    self.automorphisms.is_empty()
+  }
+
+  /// The number of embeddings of each subgraph, i.e. the order of the automorphism group.
+  pub fn embeddings_per_subgraph(&self) -> usize {
+    self.automorphisms.len() + 1
+  }
+}
@@ -1,11 +1,18 @@
-use super::{AdvancedStorageAdapter, AsyncDefault, StorageAdapter};
-use crate::schemas::{DataEdge, DataVertex, LabelRef, PatternAttr, VidRef};
+use super::{AdvancedStorageAdapter, AsyncDefault, SchemaStorageAdapter, StorageAdapter};
+use crate::schemas::{
+  AttrCmpMode, AttrValue, DataEdge, DataVertex, Eid, GraphSchema, Label, LabelRef, PatternAttr,
+  PatternPredicate, VidRef,
+};
 use colored::Colorize;
+use hashbrown::HashMap;
 use moka::future::Cache;
 use std::{
   hash::{DefaultHasher, Hash, Hasher},
   num::NonZeroUsize,
-  sync::Arc,
+  sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+  },
 };
 use tokio::sync::Semaphore;
 
@@ -19,46 +26,54 @@ enum CacheKey {
     String, // vid
   ),
 
-  /// Vertices by label and optional attribute.
+  /// Vertices by label and attributes.
   VerticesByLabel(
-    String,                    // v_label
-    Option<CachedPatternAttr>, // v_attr
+    String,                  // v_label
+    CachedPatternPredicates, // v_attrs
+    AttrCmpMode,             // mode
   ),
 
-  /// Edges by source vertex, label, and optional attribute.
+  /// Edges by source vertex, label, and attributes.
   EdgesBySrc(
-    String,                    // src_vid
-    String,                    // e_label
-    Option<CachedPatternAttr>, // e_attr
+    String,                  // src_vid
+    String,                  // e_label
+    CachedPatternPredicates, // e_attrs
+    AttrCmpMode,             // mode
   ),
 
-  /// Edges by destination vertex, label, and optional attribute.
+  /// Edges by destination vertex, label, and attributes.
   EdgesByDst(
-    String,                    // dst_vid
-    String,                    // e_label
-    Option<CachedPatternAttr>, // e_attr
+    String,                  // dst_vid
+    String,                  // e_label
+    CachedPatternPredicates, // e_attrs
+    AttrCmpMode,             // mode
   ),
 
-  /// Edges by source vertex with destination filter, label, and optional attribute.
+  /// Edges by source vertex with destination filter, label, and attributes.
   EdgesBySrcWithDstFilter(
-    String,                    // src_vid
-    String,                    // e_label
-    Option<CachedPatternAttr>, // e_attr
-    String,                    // dst_v_label
-    Option<CachedPatternAttr>, // dst_v_attr
+    String,                  // src_vid
+    String,                  // e_label
+    CachedPatternPredicates, // e_attrs
+    String,                  // dst_v_label
+    CachedPatternPredicates, // dst_v_attrs
+    AttrCmpMode,             // mode
   ),
 
-  /// Edges by destination vertex with source filter, label, and optional attribute.
+  /// Edges by destination vertex with source filter, label, and attributes.
   EdgesByDstWithSrcFilter(
-    String,                    // dst_vid
-    String,                    // e_label
-    Option<CachedPatternAttr>, // e_attr
-    String,                    // src_v_label
-    Option<CachedPatternAttr>, // src_v_attr
+    String,                  // dst_vid
+    String,                  // e_label
+    CachedPatternPredicates, // e_attrs
+    String,                  // src_v_label
+    CachedPatternPredicates, // src_v_attrs
+    AttrCmpMode,             // mode
   ),
 }
 
-#[derive(Clone, Eq, PartialEq, Hash)]
+/// Sorted, so that the order of predicates doesn't affect the key.
+type CachedPatternPredicates = Vec<CachedPatternPredicate>;
+
+#[derive(Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
 struct CachedPatternAttr {
   key: String,
   op_repr: String,
@@ -67,21 +82,67 @@ struct CachedPatternAttr {
 
 impl From<&PatternAttr> for CachedPatternAttr {
   fn from(attr: &PatternAttr) -> Self {
-    let mut hasher = DefaultHasher::new();
-    format!("{:?}", attr.value).hash(&mut hasher);
-
     Self {
       key: attr.key.clone(),
-      op_repr: format!("{:?}", attr.op.to_neo4j_sqlite_repr()),
-      value_hash: hasher.finish(),
+      op_repr: attr.op.to_string(),
+      value_hash: hash_attr_value(&attr.value),
+    }
+  }
+}
+
+#[derive(Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+enum CachedPatternPredicate {
+  Cmp(CachedPatternAttr),
+  Param(String, String, String),
+  In(String, Vec<u64>),
+  Between(String, u64, u64),
+  And(Vec<CachedPatternPredicate>),
+  Or(Vec<CachedPatternPredicate>),
+  Not(Box<CachedPatternPredicate>),
+}
+
+impl CachedPatternPredicate {
+  fn from_preds(preds: &[PatternPredicate]) -> CachedPatternPredicates {
+    let mut res = preds.iter().map(Self::from).collect::<Vec<_>>();
+    res.sort_unstable();
+    res
+  }
+}
+
+impl From<&PatternPredicate> for CachedPatternPredicate {
+  fn from(pred: &PatternPredicate) -> Self {
+    match pred {
+      PatternPredicate::Cmp(attr) => Self::Cmp(attr.into()),
+      PatternPredicate::Param { key, op, param } => {
+        Self::Param(key.clone(), op.to_string(), param.clone())
+      }
+      PatternPredicate::In { key, values } => {
+        let mut value_hashes = values.iter().map(hash_attr_value).collect::<Vec<_>>();
+        value_hashes.sort_unstable();
+        Self::In(key.clone(), value_hashes)
+      }
+      PatternPredicate::Between {
+        key,
+        bounds: (low, high),
+      } => Self::Between(key.clone(), hash_attr_value(low), hash_attr_value(high)),
+      PatternPredicate::And { and: preds } => Self::And(Self::from_preds(preds)),
+      PatternPredicate::Or { or: preds } => Self::Or(Self::from_preds(preds)),
+      PatternPredicate::Not { not } => Self::Not(Box::new(not.as_ref().into())),
     }
   }
 }
 
+fn hash_attr_value(value: &AttrValue) -> u64 {
+  let mut hasher = DefaultHasher::new();
+  format!("{value:?}").hash(&mut hasher);
+  hasher.finish()
+}
+
 struct StorageCache {
   vertex_cache: Cache<CacheKey, Option<DataVertex>>,
   vertices_cache: Cache<CacheKey, Vec<DataVertex>>,
   edges_cache: Cache<CacheKey, Vec<DataEdge>>,
+  hits: AtomicUsize,
 
   background_tasks_sem: Arc<Semaphore>,
 }
@@ -95,6 +156,7 @@ impl StorageCache {
       vertex_cache: Cache::new(cache_size.get() as u64),
       vertices_cache: Cache::new(cache_size.get() as u64),
       edges_cache: Cache::new(cache_size.get() as u64),
+      hits: AtomicUsize::new(0),
 
       background_tasks_sem: Arc::new(Semaphore::new(MAX_BACKGROUND_WRITE_TASKS)),
     }
@@ -118,6 +180,13 @@ impl<S: StorageAdapter> CachedStorageAdapter<S> {
     self.cache.vertex_cache.invalidate_all();
     self.cache.vertices_cache.invalidate_all();
     self.cache.edges_cache.invalidate_all();
+    self.cache.hits.store(0, Ordering::Relaxed);
+  }
+
+  #[inline]
+  fn hit<V>(&self, result: V) -> V {
+    self.cache.hits.fetch_add(1, Ordering::Relaxed);
+    result
   }
 
   #[allow(dead_code)]
@@ -185,12 +254,16 @@ impl<S: StorageAdapter> AsyncDefault for CachedStorageAdapter<S> {
 }
 
 impl<S: StorageAdapter> StorageAdapter for CachedStorageAdapter<S> {
+  fn cache_hits(&self) -> usize {
+    self.cache.hits.load(Ordering::Relaxed)
+  }
+
   async fn get_v(&self, vid: VidRef<'_>) -> Option<DataVertex> {
     let key = CacheKey::Vertex(vid.to_string());
 
     // try to get cache
     if let Some(vertex) = self.cache.vertex_cache.get(&key).await {
-      return vertex.clone();
+      return self.hit(vertex.clone());
     }
 
     // not found in cache, fetch from inner storage
@@ -202,41 +275,55 @@ impl<S: StorageAdapter> StorageAdapter for CachedStorageAdapter<S> {
     result
   }
 
-  async fn load_v(&self, v_label: LabelRef<'_>, v_attr: Option<&PatternAttr>) -> Vec<DataVertex> {
-    let attr_cache = v_attr.map(CachedPatternAttr::from);
-    let key = CacheKey::VerticesByLabel(v_label.to_string(), attr_cache);
+  async fn load_v(
+    &self,
+    v_label: LabelRef<'_>,
+    v_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
+  ) -> Vec<DataVertex> {
+    let attr_cache = CachedPatternPredicate::from_preds(v_attrs);
+    let key = CacheKey::VerticesByLabel(v_label.to_string(), attr_cache, mode);
 
     if let Some(result) = self.cache.vertices_cache.get(&key).await {
-      return result;
+      return self.hit(result);
     }
 
-    let result = self.inner.load_v(v_label, v_attr).await;
+    let result = self.inner.load_v(v_label, v_attrs, mode).await;
 
     self.background_update(&self.cache.vertices_cache, &result, key);
 
     result
   }
 
-  async fn load_e(&self, e_label: LabelRef<'_>, e_attr: Option<&PatternAttr>) -> Vec<DataEdge> {
+  async fn load_e(
+    &self,
+    e_label: LabelRef<'_>,
+    e_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
+  ) -> Vec<DataEdge> {
     // This is a broad query that might return a lot of data.
     // We'll skip caching for this case to avoid memory pressure.
-    self.inner.load_e(e_label, e_attr).await
+    self.inner.load_e(e_label, e_attrs, mode).await
   }
 
   async fn load_e_with_src(
     &self,
     src_vid: VidRef<'_>,
     e_label: LabelRef<'_>,
-    e_attr: Option<&PatternAttr>,
+    e_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
   ) -> Vec<DataEdge> {
-    let attr_cache = e_attr.map(CachedPatternAttr::from);
-    let key = CacheKey::EdgesBySrc(src_vid.to_string(), e_label.to_string(), attr_cache);
+    let attr_cache = CachedPatternPredicate::from_preds(e_attrs);
+    let key = CacheKey::EdgesBySrc(src_vid.to_string(), e_label.to_string(), attr_cache, mode);
 
     if let Some(result) = self.cache.edges_cache.get(&key).await {
-      return result;
+      return self.hit(result);
     }
 
-    let result = self.inner.load_e_with_src(src_vid, e_label, e_attr).await;
+    let result = self
+      .inner
+      .load_e_with_src(src_vid, e_label, e_attrs, mode)
+      .await;
 
     self.background_update(&self.cache.edges_cache, &result, key);
 
@@ -247,16 +334,20 @@ impl<S: StorageAdapter> StorageAdapter for CachedStorageAdapter<S> {
     &self,
     dst_vid: VidRef<'_>,
     e_label: LabelRef<'_>,
-    e_attr: Option<&PatternAttr>,
+    e_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
   ) -> Vec<DataEdge> {
-    let attr_cache = e_attr.map(CachedPatternAttr::from);
-    let key = CacheKey::EdgesByDst(dst_vid.to_string(), e_label.to_string(), attr_cache);
+    let attr_cache = CachedPatternPredicate::from_preds(e_attrs);
+    let key = CacheKey::EdgesByDst(dst_vid.to_string(), e_label.to_string(), attr_cache, mode);
 
     if let Some(result) = self.cache.edges_cache.get(&key).await {
-      return result;
+      return self.hit(result);
     }
 
-    let result = self.inner.load_e_with_dst(dst_vid, e_label, e_attr).await;
+    let result = self
+      .inner
+      .load_e_with_dst(dst_vid, e_label, e_attrs, mode)
+      .await;
 
     self.background_update(&self.cache.edges_cache, &result, key);
 
@@ -264,32 +355,56 @@ impl<S: StorageAdapter> StorageAdapter for CachedStorageAdapter<S> {
   }
 }
 
+/// The schema isn't cached, since it's loaded once before planning.
+impl<S: SchemaStorageAdapter> SchemaStorageAdapter for CachedStorageAdapter<S> {
+  async fn load_schema(&self) -> Option<GraphSchema> {
+    self.inner.load_schema().await
+  }
+
+  async fn count_v(&self, v_label: LabelRef<'_>) -> Option<usize> {
+    self.inner.count_v(v_label).await
+  }
+
+  async fn count_e(&self, e_label: LabelRef<'_>) -> Option<usize> {
+    self.inner.count_e(e_label).await
+  }
+
+  async fn load_endpoint_labels(
+    &self,
+    e_label: LabelRef<'_>,
+  ) -> Option<HashMap<Eid, (Label, Label)>> {
+    self.inner.load_endpoint_labels(e_label).await
+  }
+}
+
 impl<S: AdvancedStorageAdapter> AdvancedStorageAdapter for CachedStorageAdapter<S> {
   async fn load_e_with_src_and_dst_filter(
     &self,
     src_vid: VidRef<'_>,
     e_label: LabelRef<'_>,
-    e_attr: Option<&PatternAttr>,
+    e_attrs: &[PatternPredicate],
     dst_v_label: LabelRef<'_>,
-    dst_v_attr: Option<&PatternAttr>,
+    dst_v_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
   ) -> Vec<DataEdge> {
-    let e_attr_cache = e_attr.map(CachedPatternAttr::from);
-    let dst_v_attr_cache = dst_v_attr.map(CachedPatternAttr::from);
+    let e_attr_cache = CachedPatternPredicate::from_preds(e_attrs);
+    let dst_v_attr_cache = CachedPatternPredicate::from_preds(dst_v_attrs);
     let key = CacheKey::EdgesBySrcWithDstFilter(
       src_vid.to_string(),
       e_label.to_string(),
       e_attr_cache,
       dst_v_label.to_string(),
       dst_v_attr_cache,
+      mode,
     );
 
     if let Some(result) = self.cache.edges_cache.get(&key).await {
-      return result;
+      return self.hit(result);
     }
 
     let result = self
       .inner
-      .load_e_with_src_and_dst_filter(src_vid, e_label, e_attr, dst_v_label, dst_v_attr)
+      .load_e_with_src_and_dst_filter(src_vid, e_label, e_attrs, dst_v_label, dst_v_attrs, mode)
       .await;
 
     self.background_update(&self.cache.edges_cache, &result, key);
@@ -301,27 +416,29 @@ impl<S: AdvancedStorageAdapter> AdvancedStorageAdapter for CachedStorageAdapter<
     &self,
     dst_vid: VidRef<'_>,
     e_label: LabelRef<'_>,
-    e_attr: Option<&PatternAttr>,
+    e_attrs: &[PatternPredicate],
     src_v_label: LabelRef<'_>,
-    src_v_attr: Option<&PatternAttr>,
+    src_v_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
   ) -> Vec<DataEdge> {
-    let e_attr_cache = e_attr.map(CachedPatternAttr::from);
-    let src_v_attr_cache = src_v_attr.map(CachedPatternAttr::from);
+    let e_attr_cache = CachedPatternPredicate::from_preds(e_attrs);
+    let src_v_attr_cache = CachedPatternPredicate::from_preds(src_v_attrs);
     let key = CacheKey::EdgesByDstWithSrcFilter(
       dst_vid.to_string(),
       e_label.to_string(),
       e_attr_cache,
       src_v_label.to_string(),
       src_v_attr_cache,
+      mode,
     );
 
     if let Some(result) = self.cache.edges_cache.get(&key).await {
-      return result;
+      return self.hit(result);
     }
 
     let result = self
       .inner
-      .load_e_with_dst_and_src_filter(dst_vid, e_label, e_attr, src_v_label, src_v_attr)
+      .load_e_with_dst_and_src_filter(dst_vid, e_label, e_attrs, src_v_label, src_v_attrs, mode)
       .await;
 
     self.background_update(&self.cache.edges_cache, &result, key);
@@ -0,0 +1,364 @@
+use super::SchemaStorageAdapter;
+use crate::{
+  planner::statistics::{AttrStats, DegreeStats, Histogram, Statistics},
+  schemas::{AttrCmpMode, AttrType, AttrValue, DataEdge, Eid, Label},
+};
+use colored::Colorize;
+use hashbrown::{HashMap, HashSet};
+use itertools::Itertools;
+
+/// equal-width bins of the histograms of numbers
+const HISTOGRAM_BINS: usize = 10;
+
+/// Scans a storage for the statistics the planner reads (see [`Statistics::dump_to_dir`]):
+/// the label counts, the distinct count / min / max / histogram of each attribute,
+/// and the edges (with their degree distributions) between each pair of endpoint labels.
+///
+/// Given the previous statistics, it refreshes them incrementally:
+/// only the labels new to them, whose counts in the storage changed since
+/// (or picked by [`with_labels`](Self::with_labels)) are scanned,
+/// the others are kept as they were, and the vanished ones are dropped.
+#[derive(Debug, Clone)]
+pub struct StatisticsCollector<S: SchemaStorageAdapter> {
+  storage: S,
+  previous: Option<Statistics>,
+  labels: HashSet<Label>,
+}
+
+impl<S: SchemaStorageAdapter> StatisticsCollector<S> {
+  pub fn new(storage: S) -> Self {
+    Self {
+      storage,
+      previous: None,
+      labels: HashSet::new(),
+    }
+  }
+
+  /// Refresh `previous` instead of scanning everything.
+  pub fn with_previous(mut self, previous: Statistics) -> Self {
+    self.previous = Some(previous);
+    self
+  }
+
+  /// Vertex / edge labels to scan again, even if their counts are the same as the previous ones.
+  pub fn with_labels(mut self, labels: impl IntoIterator<Item = impl Into<Label>>) -> Self {
+    self.labels = labels.into_iter().map(Into::into).collect();
+    self
+  }
+
+  /// Whether `label` is to be scanned, where `cnt` is its count in the storage,
+  /// `None` if it can't be counted.
+  fn is_stale(&self, label: &Label, cnt: Option<usize>, label_cnt: &HashMap<Label, usize>) -> bool {
+    self.previous.is_none()
+      || self.labels.contains(label)
+      || cnt.is_none()
+      || label_cnt.get(label).copied() != cnt
+  }
+
+  /// `None` if the labels in the storage (or the endpoints of an edge label) can't be loaded.
+  pub async fn collect(&self) -> Option<Statistics> {
+    let schema = self.storage.load_schema().await?;
+    let previous = self.previous.clone().unwrap_or_default();
+    let mut statistics = Statistics::default();
+
+    for v_label in schema.v_labels().iter().sorted() {
+      let cnt = match self.previous {
+        Some(_) => self.storage.count_v(v_label).await,
+        None => None,
+      };
+      if !self.is_stale(v_label, cnt, &previous.v_label_cnt) {
+        statistics
+          .v_label_cnt
+          .insert(v_label.clone(), previous.v_label_cnt[v_label]);
+        if let Some(attr_stats) = previous.v_attr_stats.get(v_label) {
+          statistics
+            .v_attr_stats
+            .insert(v_label.clone(), attr_stats.clone());
+        }
+        continue;
+      }
+
+      let vs = self
+        .storage
+        .load_v(v_label, &[], AttrCmpMode::default())
+        .await;
+      statistics.v_label_cnt.insert(v_label.clone(), vs.len());
+      let attr_stats = attr_stats_of(vs.iter().map(|v| &v.attrs));
+      statistics.v_attr_stats.insert(v_label.clone(), attr_stats);
+      println!(
+        "✅  '{}' => {} vertices",
+        v_label.green(),
+        vs.len().to_string().yellow()
+      );
+    }
+
+    for e_label in schema.e_labels().iter().sorted() {
+      let cnt = match self.previous {
+        Some(_) => self.storage.count_e(e_label).await,
+        None => None,
+      };
+      if !self.is_stale(e_label, cnt, &previous.e_label_cnt) {
+        statistics
+          .e_label_cnt
+          .insert(e_label.clone(), previous.e_label_cnt[e_label]);
+        if let Some(attr_stats) = previous.e_attr_stats.get(e_label) {
+          statistics
+            .e_attr_stats
+            .insert(e_label.clone(), attr_stats.clone());
+        }
+        if let Some(degree_stats) = previous.e_degree_stats.get(e_label) {
+          statistics
+            .e_degree_stats
+            .insert(e_label.clone(), degree_stats.clone());
+        }
+        continue;
+      }
+
+      let es = self
+        .storage
+        .load_e(e_label, &[], AttrCmpMode::default())
+        .await;
+      let endpoint_labels = self.storage.load_endpoint_labels(e_label).await?;
+
+      statistics.e_label_cnt.insert(e_label.clone(), es.len());
+      let attr_stats = attr_stats_of(es.iter().map(|e| &e.attrs));
+      statistics.e_attr_stats.insert(e_label.clone(), attr_stats);
+      let degree_stats = degree_stats_of(&es, &endpoint_labels);
+      statistics
+        .e_degree_stats
+        .insert(e_label.clone(), degree_stats);
+      println!(
+        "✅  '{}' => {} edges",
+        e_label.green(),
+        es.len().to_string().yellow()
+      );
+    }
+
+    statistics.v_cnt = statistics.v_label_cnt.values().sum();
+    statistics.e_cnt = statistics.e_label_cnt.values().sum();
+    Some(statistics)
+  }
+}
+
+/// The statistics of each attribute among `rows`, where a missing attribute counts as `null`.
+fn attr_stats_of<'a>(
+  rows: impl Iterator<Item = &'a HashMap<String, AttrValue>>,
+) -> HashMap<String, AttrStats> {
+  let mut rows_cnt = 0;
+  let mut key_values = HashMap::<&str, Vec<&AttrValue>>::new();
+  for attrs in rows {
+    rows_cnt += 1;
+    for (key, value) in attrs {
+      if !matches!(value, AttrValue::Null) {
+        key_values.entry(key.as_str()).or_default().push(value);
+      }
+    }
+  }
+
+  (key_values.into_iter())
+    .map(|(key, values)| {
+      let mut stats = AttrStats {
+        count: values.len(),
+        null_count: rows_cnt - values.len(),
+        distinct_count: (values.iter())
+          .map(|value| (value.to_type(), value.to_string()))
+          .unique()
+          .count(),
+        // the most common one, if the values are of several types
+        type_: (values.iter().map(|value| value.to_type()).counts())
+          .into_iter()
+          .max_by_key(|&(type_, cnt)| (cnt, type_ == AttrType::String))
+          .map(|(type_, _)| type_)
+          .unwrap_or_default(),
+        ..Default::default()
+      };
+
+      match stats.type_ {
+        AttrType::String => {
+          for value in &values {
+            if let AttrValue::String(value) = value {
+              *stats.histogram.value_counts.entry_ref(value).or_default() += 1;
+            }
+          }
+        }
+        // temporal values are kept as epoch millis, as the planner compares them so
+        AttrType::Int | AttrType::Float | AttrType::Date | AttrType::DateTime => {
+          let numbers = values.iter().filter_map(|value| value.to_epoch_millis());
+          let numbers = numbers.collect_vec();
+          stats.min_value = numbers.iter().copied().reduce(f64::min);
+          stats.max_value = numbers.iter().copied().reduce(f64::max);
+          if let (Some(min), Some(max)) = (stats.min_value, stats.max_value) {
+            stats.histogram = histogram_of(&numbers, min, max, stats.distinct_count);
+          }
+        }
+        AttrType::Bool | AttrType::Null | AttrType::List => {}
+      }
+      (key.to_string(), stats)
+    })
+    .collect()
+}
+
+/// Equal-width bins from `min` to `max`, where the last one includes `max`.
+fn histogram_of(numbers: &[f64], min: f64, max: f64, distinct_count: usize) -> Histogram {
+  let bins_cnt = HISTOGRAM_BINS.min(distinct_count);
+  if bins_cnt <= 1 || min >= max {
+    return Histogram::default();
+  }
+  let width = (max - min) / bins_cnt as f64;
+  let mut counts = vec![0; bins_cnt];
+  for number in numbers {
+    let bin = ((number - min) / width) as usize;
+    counts[bin.min(bins_cnt - 1)] += 1;
+  }
+  Histogram {
+    bins: (0..=bins_cnt).map(|i| min + width * i as f64).collect(),
+    counts,
+    ..Default::default()
+  }
+}
+
+/// The edges between each pair of endpoint labels, with their degree distributions.
+fn degree_stats_of(
+  es: &[DataEdge],
+  endpoint_labels: &HashMap<Eid, (Label, Label)>,
+) -> Vec<DegreeStats> {
+  // (src_label, dst_label) -> (count, out-degrees, in-degrees)
+  let mut groups =
+    HashMap::<(&str, &str), (usize, HashMap<&str, usize>, HashMap<&str, usize>)>::new();
+  for e in es {
+    let Some((src_label, dst_label)) = endpoint_labels.get(&e.eid) else {
+      continue;
+    };
+    let (count, out_degrees, in_degrees) = groups
+      .entry((src_label.as_str(), dst_label.as_str()))
+      .or_default();
+    *count += 1;
+    *out_degrees.entry(&e.src_vid).or_default() += 1;
+    *in_degrees.entry(&e.dst_vid).or_default() += 1;
+  }
+
+  // vertices by degree in `[1], [2, 3], [4, 7], ...`
+  let histogram_of = |degrees: HashMap<&str, usize>| {
+    let mut histogram = vec![];
+    for degree in degrees.into_values() {
+      let bucket = degree.ilog2() as usize;
+      if histogram.len() <= bucket {
+        histogram.resize(bucket + 1, 0);
+      }
+      histogram[bucket] += 1;
+    }
+    histogram
+  };
+  (groups.into_iter())
+    .sorted_by_key(|(labels, _)| *labels)
+    .map(
+      |((src_label, dst_label), (count, out_degrees, in_degrees))| DegreeStats {
+        src_label: src_label.to_string(),
+        dst_label: dst_label.to_string(),
+        count,
+        out_degree_histogram: histogram_of(out_degrees),
+        in_degree_histogram: histogram_of(in_degrees),
+      },
+    )
+    .collect()
+}
+
+#[cfg(test)]
+mod test_statistics_collector {
+  use super::*;
+  use crate::storage::SqliteStorageAdapter;
+
+  const INIT_SQL: &str = r#"
+    INSERT INTO db_vertex VALUES
+      ('u1', 'Person'), ('u2', 'Person'), ('u3', 'Person'), ('c1', 'City'), ('c2', 'City');
+    INSERT INTO db_edge VALUES
+      ('e1', 'isLocatedIn', 'u1', 'c1'), ('e2', 'isLocatedIn', 'u2', 'c1'),
+      ('e3', 'isLocatedIn', 'u3', 'c2'), ('e4', 'knows', 'u1', 'u2'), ('e5', 'knows', 'u1', 'u3');
+    INSERT INTO vertex_attribute (vid, key, value, type) VALUES
+      ('u1', 'gender', 'male', 'string'), ('u2', 'gender', 'female', 'string'),
+      ('u3', 'gender', 'male', 'string'),
+      ('u1', 'birthday', '1989-12-03', 'date'), ('u2', 'birthday', '1990-01-01', 'date'),
+      ('c1', 'name', 'Beijing', 'string'), ('c2', 'name', 'Shanghai', 'string');
+    INSERT INTO edge_attribute (eid, key, value, type) VALUES
+      ('e4', 'creationDate', '10', 'int'), ('e5', 'creationDate', '30', 'int');
+  "#;
+
+  #[tokio::test]
+  async fn test_collect() {
+    let adapter = SqliteStorageAdapter::in_memory_test_only(INIT_SQL);
+    let statistics = StatisticsCollector::new(adapter).collect().await.unwrap();
+
+    assert_eq!((statistics.v_cnt, statistics.e_cnt), (5, 5));
+    assert_eq!(statistics.v_label_cnt["Person"], 3);
+
+    let gender = &statistics.v_attr_stats["Person"]["gender"];
+    assert_eq!((gender.count, gender.distinct_count), (3, 2));
+    assert_eq!(gender.histogram.value_counts["male"], 2);
+    let birthday = &statistics.v_attr_stats["Person"]["birthday"];
+    assert_eq!((birthday.count, birthday.null_count), (2, 1));
+    assert_eq!(birthday.type_, AttrType::Date);
+    assert!(birthday.min_value < birthday.max_value);
+
+    let creation_date = &statistics.e_attr_stats["knows"]["creationDate"];
+    assert_eq!(creation_date.histogram.bins, [10.0, 20.0, 30.0]);
+    assert_eq!(creation_date.histogram.counts, [1, 1]);
+
+    let located_in = &statistics.e_degree_stats["isLocatedIn"];
+    assert_eq!(located_in.len(), 1);
+    assert_eq!(located_in[0].count, 3);
+    assert_eq!(located_in[0].out_degree_histogram, [3]);
+    // `c1` has two of them, `c2` has one
+    assert_eq!(located_in[0].in_degree_histogram, [1, 1]);
+    let knows = &statistics.e_degree_stats["knows"][0];
+    assert_eq!(
+      (knows.count, knows.out_degree_histogram.as_slice()),
+      (2, &[0, 1][..])
+    );
+
+    // as the planner reads them
+    let dir = std::env::temp_dir().join(format!("ember-graph-statistics-{}", std::process::id()));
+    statistics.dump_to_dir(&dir).unwrap();
+    let loaded = Statistics::from_dir(&dir).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(loaded.v_label_cnt, statistics.v_label_cnt);
+    assert_eq!(loaded.v_selectivity("Person", &[]), 1.0);
+  }
+
+  #[tokio::test]
+  async fn test_refresh() {
+    let adapter = SqliteStorageAdapter::in_memory_test_only(INIT_SQL);
+    let mut previous = StatisticsCollector::new(adapter.clone())
+      .collect()
+      .await
+      .unwrap();
+    // unchanged (with its attributes dropped, to tell it's not scanned), a stale count,
+    // a picked one, a new one and a vanished one
+    previous.v_attr_stats.remove("Person");
+    previous.v_label_cnt.insert("City".to_string(), 42);
+    previous.e_attr_stats.remove("knows");
+    previous.e_label_cnt.remove("isLocatedIn");
+    previous.v_label_cnt.insert("Forum".to_string(), 42);
+
+    let refreshed = StatisticsCollector::new(adapter)
+      .with_previous(previous)
+      .with_labels(["knows"])
+      .collect()
+      .await
+      .unwrap();
+    // kept as it was
+    assert_eq!(refreshed.v_label_cnt["Person"], 3);
+    assert!(!refreshed.v_attr_stats.contains_key("Person"));
+    // counted differently, picked, new, or vanished
+    assert_eq!(refreshed.v_label_cnt["City"], 2);
+    assert!(refreshed.e_attr_stats["knows"].contains_key("creationDate"));
+    assert_eq!(refreshed.e_label_cnt["isLocatedIn"], 3);
+    assert!(!refreshed.v_label_cnt.contains_key("Forum"));
+    assert_eq!(refreshed.v_cnt, 3 + 2);
+    // the endpoints of the scanned edges are loaded along with them
+    let located_in = &refreshed.e_degree_stats["isLocatedIn"][0];
+    assert_eq!(
+      (located_in.src_label.as_str(), located_in.dst_label.as_str()),
+      ("Person", "City")
+    );
+  }
+}
@@ -1,67 +1,103 @@
 use crate::schemas::*;
 
 pub mod cached;
+pub mod collector;
 pub mod neo4j;
+pub mod profiled;
 pub mod sqlite;
 
 pub use cached::*;
+pub use collector::*;
 pub use neo4j::*;
+pub use profiled::*;
 pub use sqlite::*;
 
 pub trait AsyncDefault: Send + Sync {
   fn async_default() -> impl Future<Output = Self> + Send;
 }
 
+/// The predicates of each load are evaluated as of `mode` (see [`AttrCmpMode`]).
 pub trait StorageAdapter: Clone + AsyncDefault {
   fn get_v(&self, vid: VidRef<'_>) -> impl Future<Output = Option<DataVertex>> + Send;
 
+  /// An empty `v_label` (of an unlabeled pattern vertex) matches any label.
   fn load_v(
     &self,
     v_label: LabelRef<'_>,
-    v_attr: Option<&PatternAttr>,
+    v_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
   ) -> impl Future<Output = Vec<DataVertex>> + Send;
 
   fn load_e(
     &self,
     e_label: LabelRef<'_>,
-    e_attr: Option<&PatternAttr>,
+    e_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
   ) -> impl Future<Output = Vec<DataEdge>> + Send;
 
   fn load_e_with_src(
     &self,
     src_vid: VidRef<'_>,
     e_label: LabelRef<'_>,
-    e_attr: Option<&PatternAttr>,
+    e_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
   ) -> impl Future<Output = Vec<DataEdge>> + Send;
 
   fn load_e_with_dst(
     &self,
     dst_vid: VidRef<'_>,
     e_label: LabelRef<'_>,
-    e_attr: Option<&PatternAttr>,
+    e_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
   ) -> impl Future<Output = Vec<DataEdge>> + Send;
+
+  /// The loads answered by its cache so far, `0` if it isn't cached.
+  fn cache_hits(&self) -> usize {
+    0
+  }
 }
 
+/// An empty `dst_v_label` / `src_v_label` (of an unlabeled pattern vertex) matches any label.
 pub trait AdvancedStorageAdapter: StorageAdapter {
   fn load_e_with_src_and_dst_filter(
     &self,
     src_vid: VidRef<'_>,
     e_label: LabelRef<'_>,
-    e_attr: Option<&PatternAttr>,
+    e_attrs: &[PatternPredicate],
     dst_v_label: LabelRef<'_>,
-    dst_v_attr: Option<&PatternAttr>,
+    dst_v_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
   ) -> impl Future<Output = Vec<DataEdge>> + Send;
 
   fn load_e_with_dst_and_src_filter(
     &self,
     dst_vid: VidRef<'_>,
     e_label: LabelRef<'_>,
-    e_attr: Option<&PatternAttr>,
+    e_attrs: &[PatternPredicate],
     src_v_label: LabelRef<'_>,
-    src_v_attr: Option<&PatternAttr>,
+    src_v_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
   ) -> impl Future<Output = Vec<DataEdge>> + Send;
 }
 
+pub trait SchemaStorageAdapter: StorageAdapter {
+  /// The labels, attribute keys / types and edge endpoints in the storage,
+  /// or `None` if they can't be loaded.
+  fn load_schema(&self) -> impl Future<Output = Option<GraphSchema>> + Send;
+
+  /// How many vertices of `v_label` there are, without loading them.
+  fn count_v(&self, v_label: LabelRef<'_>) -> impl Future<Output = Option<usize>> + Send;
+
+  /// How many edges of `e_label` there are, without loading them.
+  fn count_e(&self, e_label: LabelRef<'_>) -> impl Future<Output = Option<usize>> + Send;
+
+  /// The (src_label, dst_label) of each edge of `e_label`, by its eid, in a single query.
+  fn load_endpoint_labels(
+    &self,
+    e_label: LabelRef<'_>,
+  ) -> impl Future<Output = Option<hashbrown::HashMap<Eid, (Label, Label)>>> + Send;
+}
+
 pub trait WritableStorageAdapter: AdvancedStorageAdapter {
   fn add_v(
     &self,
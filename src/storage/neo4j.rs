@@ -1,5 +1,6 @@
-use super::{AdvancedStorageAdapter, AsyncDefault, StorageAdapter};
+use super::{AdvancedStorageAdapter, AsyncDefault, SchemaStorageAdapter, StorageAdapter};
 use crate::{schemas::*, utils::time_async_with_desc};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime};
 use neo4rs::*;
 use std::env;
 
@@ -35,13 +36,62 @@ impl AsyncDefault for Neo4jStorageAdapter {
   }
 }
 
+/// `: Label` of a node pattern, or nothing for an unlabeled pattern vertex (which matches any).
+fn label_part(v_label: LabelRef) -> String {
+  match v_label.is_empty() {
+    true => String::new(),
+    false => format!(": {v_label}"),
+  }
+}
+
+/// The first of `labels(v)`, or an empty label if the node has none.
+fn first_label(row: &Row) -> Label {
+  let labels: Vec<String> = row.get("v_label").unwrap();
+  labels.into_iter().next().unwrap_or_default()
+}
+
+/// Read `props`, converting Neo4j temporal values into dates / datetimes (in UTC).
+fn get_props(row: &Row) -> hashbrown::HashMap<String, AttrValue> {
+  let props: std::collections::HashMap<String, BoltType> = row.get("props").unwrap();
+  props
+    .into_iter()
+    .map(|(key, value)| (key, to_attr_value(&value)))
+    .collect()
+}
+
+fn to_attr_value(value: &BoltType) -> AttrValue {
+  let fallback = || AttrValue::String(value.to_string());
+  match value {
+    BoltType::Integer(v) => AttrValue::Int(v.value),
+    BoltType::Float(v) => AttrValue::Float(v.value),
+    BoltType::String(v) => AttrValue::String(v.value.clone()),
+    BoltType::Boolean(v) => AttrValue::Bool(v.value),
+    BoltType::Null(_) => AttrValue::Null,
+    BoltType::List(v) => AttrValue::List(v.value.iter().map(to_attr_value).collect()),
+    BoltType::Date(v) => NaiveDate::try_from(v)
+      .map(AttrValue::Date)
+      .unwrap_or_else(|_| fallback()),
+    BoltType::DateTime(v) => DateTime::<FixedOffset>::try_from(v)
+      .map(|v| AttrValue::DateTime(v.to_utc()))
+      .unwrap_or_else(|_| fallback()),
+    BoltType::DateTimeZoneId(v) => DateTime::<FixedOffset>::try_from(v)
+      .map(|v| AttrValue::DateTime(v.to_utc()))
+      .unwrap_or_else(|_| fallback()),
+    // local datetimes are taken as UTC
+    BoltType::LocalDateTime(v) => NaiveDateTime::try_from(v)
+      .map(|v| AttrValue::DateTime(v.and_utc()))
+      .unwrap_or_else(|_| fallback()),
+    _ => fallback(),
+  }
+}
+
 impl From<(Row, LabelRef<'_>)> for DataEdge {
   fn from((row, e_label): (Row, LabelRef)) -> Self {
     let eid = row.get("eid").unwrap();
     let src_vid = row.get("src_vid").unwrap();
     let dst_vid = row.get("dst_vid").unwrap();
     let label = e_label.to_string();
-    let attrs = row.get("props").unwrap();
+    let attrs = get_props(&row);
 
     DataEdge {
       eid,
@@ -49,6 +99,7 @@ impl From<(Row, LabelRef<'_>)> for DataEdge {
       dst_vid,
       label,
       attrs,
+      hops: vec![],
     }
   }
 }
@@ -70,23 +121,27 @@ impl StorageAdapter for Neo4jStorageAdapter {
     let row = result.next().await.unwrap()?;
 
     let vid = vid.to_string();
-    let labels: Vec<String> = row.get("v_label").unwrap();
-    let label = labels[0].clone();
-    let attrs = row.get("props").unwrap();
+    let label = first_label(&row);
+    let attrs = get_props(&row);
 
     Some(DataVertex { vid, label, attrs })
   }
 
-  async fn load_v(&self, v_label: LabelRef<'_>, v_attr: Option<&PatternAttr>) -> Vec<DataVertex> {
-    let mut query_str = format!("\n\t\tMATCH (v: {v_label})\n");
-    if let Some(attr) = v_attr {
-      let constraint = attr.to_neo4j_constraint("v");
+  async fn load_v(
+    &self,
+    v_label: LabelRef<'_>,
+    v_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
+  ) -> Vec<DataVertex> {
+    let mut query_str = format!("\n\t\tMATCH (v{})\n", label_part(v_label));
+    if let Some(constraint) = PatternPredicate::to_neo4j_constraints(v_attrs, "v", mode) {
       query_str += &format!("\t\tWHERE {constraint}\n");
     }
     query_str += "
       RETURN
         properties(v) as props,
-        elementId(v) as vid"
+        elementId(v) as vid,
+        labels(v) as v_label"
       .trim_start_matches('\n');
 
     let mut result = time_async_with_desc(self.graph.execute(query(&query_str)), query_str)
@@ -97,8 +152,12 @@ impl StorageAdapter for Neo4jStorageAdapter {
 
     while let Some(row) = result.next().await.unwrap() {
       let vid = row.get("vid").unwrap();
-      let label = v_label.to_string();
-      let attrs = row.get("props").unwrap();
+      // a labeled pattern vertex takes its own label, even if the node has several
+      let label = match v_label.is_empty() {
+        true => first_label(&row),
+        false => v_label.to_string(),
+      };
+      let attrs = get_props(&row);
 
       ret.push(DataVertex { vid, label, attrs });
     }
@@ -106,10 +165,14 @@ impl StorageAdapter for Neo4jStorageAdapter {
     ret
   }
 
-  async fn load_e(&self, e_label: LabelRef<'_>, e_attr: Option<&PatternAttr>) -> Vec<DataEdge> {
+  async fn load_e(
+    &self,
+    e_label: LabelRef<'_>,
+    e_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
+  ) -> Vec<DataEdge> {
     let mut query_str = format!("\n\t\tMATCH (src)-[e: {e_label}]->(dst)\n");
-    if let Some(attr) = e_attr {
-      let constraint = attr.to_neo4j_constraint("e");
+    if let Some(constraint) = PatternPredicate::to_neo4j_constraints(e_attrs, "e", mode) {
       query_str += &format!("\t\tWHERE {constraint}\n");
     }
     query_str += "
@@ -137,13 +200,12 @@ impl StorageAdapter for Neo4jStorageAdapter {
     &self,
     src_vid: VidRef<'_>,
     e_label: LabelRef<'_>,
-    e_attr: Option<&PatternAttr>,
+    e_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
   ) -> Vec<DataEdge> {
     let mut query_str = format!("\n\t\tMATCH (src)-[e: {e_label}]->(dst)\n");
     let mut constraint_parts = vec![format!("elementId(src) = '{src_vid}'")];
-    if let Some(attr) = e_attr {
-      constraint_parts.push(attr.to_neo4j_constraint("e"));
-    }
+    constraint_parts.extend(PatternPredicate::to_neo4j_constraints(e_attrs, "e", mode));
     if !constraint_parts.is_empty() {
       query_str += &format!("\t\tWHERE {}\n", constraint_parts.join(" AND "));
     }
@@ -172,13 +234,12 @@ impl StorageAdapter for Neo4jStorageAdapter {
     &self,
     dst_vid: VidRef<'_>,
     e_label: LabelRef<'_>,
-    e_attr: Option<&PatternAttr>,
+    e_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
   ) -> Vec<DataEdge> {
     let mut query_str = format!("\n\t\tMATCH (src)-[e: {e_label}]->(dst)\n");
     let mut constraint_parts = vec![format!("elementId(dst) = '{dst_vid}'")];
-    if let Some(attr) = e_attr {
-      constraint_parts.push(attr.to_neo4j_constraint("e"));
-    }
+    constraint_parts.extend(PatternPredicate::to_neo4j_constraints(e_attrs, "e", mode));
     if !constraint_parts.is_empty() {
       query_str += &format!("\t\tWHERE {}\n", constraint_parts.join(" AND "));
     }
@@ -209,18 +270,22 @@ impl AdvancedStorageAdapter for Neo4jStorageAdapter {
     &self,
     src_vid: VidRef<'_>,
     e_label: LabelRef<'_>,
-    e_attr: Option<&PatternAttr>,
+    e_attrs: &[PatternPredicate],
     dst_v_label: LabelRef<'_>,
-    dst_v_attr: Option<&PatternAttr>,
+    dst_v_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
   ) -> Vec<DataEdge> {
-    let mut query_str = format!("\n\t\tMATCH (src)-[e: {e_label}]->(dst: {dst_v_label})\n");
+    let mut query_str = format!(
+      "\n\t\tMATCH (src)-[e: {e_label}]->(dst{})\n",
+      label_part(dst_v_label)
+    );
     let mut constraint_parts = vec![format!("elementId(src) = '{src_vid}'")];
-    if let Some(attr) = dst_v_attr {
-      constraint_parts.push(attr.to_neo4j_constraint("dst"));
-    }
-    if let Some(attr) = e_attr {
-      constraint_parts.push(attr.to_neo4j_constraint("e"));
-    }
+    constraint_parts.extend(PatternPredicate::to_neo4j_constraints(
+      dst_v_attrs,
+      "dst",
+      mode,
+    ));
+    constraint_parts.extend(PatternPredicate::to_neo4j_constraints(e_attrs, "e", mode));
     if !constraint_parts.is_empty() {
       query_str += &format!("\t\tWHERE {}\n", constraint_parts.join(" AND "));
     }
@@ -248,18 +313,22 @@ impl AdvancedStorageAdapter for Neo4jStorageAdapter {
     &self,
     dst_vid: VidRef<'_>,
     e_label: LabelRef<'_>,
-    e_attr: Option<&PatternAttr>,
+    e_attrs: &[PatternPredicate],
     src_v_label: LabelRef<'_>,
-    src_v_attr: Option<&PatternAttr>,
+    src_v_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
   ) -> Vec<DataEdge> {
-    let mut query_str = format!("\n\t\tMATCH (src: {src_v_label})-[e: {e_label}]->(dst)\n");
+    let mut query_str = format!(
+      "\n\t\tMATCH (src{})-[e: {e_label}]->(dst)\n",
+      label_part(src_v_label)
+    );
     let mut constraint_parts = vec![format!("elementId(dst) = '{dst_vid}'")];
-    if let Some(attr) = src_v_attr {
-      constraint_parts.push(attr.to_neo4j_constraint("src"));
-    }
-    if let Some(attr) = e_attr {
-      constraint_parts.push(attr.to_neo4j_constraint("e"));
-    }
+    constraint_parts.extend(PatternPredicate::to_neo4j_constraints(
+      src_v_attrs,
+      "src",
+      mode,
+    ));
+    constraint_parts.extend(PatternPredicate::to_neo4j_constraints(e_attrs, "e", mode));
     if !constraint_parts.is_empty() {
       query_str += &format!("\t\tWHERE {}\n", constraint_parts.join(" AND "));
     }
@@ -283,3 +352,152 @@ impl AdvancedStorageAdapter for Neo4jStorageAdapter {
     ret
   }
 }
+
+/// The property types reported by `db.schema.nodeTypeProperties()` / `relTypeProperties()`.
+fn attr_type_of(neo4j_type: &str) -> AttrType {
+  match neo4j_type {
+    _ if neo4j_type.ends_with("Array") => AttrType::List,
+    "Long" | "Integer" => AttrType::Int,
+    "Double" | "Float" => AttrType::Float,
+    "Boolean" => AttrType::Bool,
+    "Date" => AttrType::Date,
+    "DateTime" | "LocalDateTime" => AttrType::DateTime,
+    _ => AttrType::String,
+  }
+}
+
+impl Neo4jStorageAdapter {
+  /// All the rows of a query, or `None` if it fails.
+  async fn collect_rows(&self, query_str: String) -> Option<Vec<Row>> {
+    let mut result =
+      match time_async_with_desc(self.graph.execute(query(&query_str)), query_str).await {
+        Ok(result) => result,
+        Err(e) => {
+          eprintln!("❌  Error executing query: {e}");
+          return None;
+        }
+      };
+
+    let mut rows = vec![];
+    loop {
+      match result.next().await {
+        Ok(Some(row)) => rows.push(row),
+        Ok(None) => return Some(rows),
+        Err(e) => {
+          eprintln!("❌  Error fetching rows: {e}");
+          return None;
+        }
+      }
+    }
+  }
+}
+
+impl SchemaStorageAdapter for Neo4jStorageAdapter {
+  async fn load_schema(&self) -> Option<GraphSchema> {
+    // all known, even if there're no properties / relationships at all
+    let mut schema = GraphSchema {
+      v_attrs: Some(Default::default()),
+      e_attrs: Some(Default::default()),
+      e_endpoints: Some(Default::default()),
+      ..Default::default()
+    };
+
+    let query_str = "CALL db.labels() YIELD label RETURN label".to_string();
+    for row in self.collect_rows(query_str).await? {
+      schema.add_v_label(&row.get::<String>("label").ok()?);
+    }
+    let query_str = "CALL db.relationshipTypes() YIELD relationshipType AS label RETURN label";
+    for row in self.collect_rows(query_str.to_string()).await? {
+      schema.add_e_label(&row.get::<String>("label").ok()?);
+    }
+
+    // properties without any value are reported with a `null` name
+    let query_str = "
+      CALL db.schema.nodeTypeProperties()
+      YIELD nodeLabels, propertyName, propertyTypes
+      RETURN nodeLabels, propertyName, propertyTypes";
+    for row in self.collect_rows(query_str.to_string()).await? {
+      let Ok(Some(key)) = row.get::<Option<String>>("propertyName") else {
+        continue;
+      };
+      let types: Vec<String> = row.get("propertyTypes").unwrap_or_default();
+      for v_label in row.get::<Vec<String>>("nodeLabels").ok()? {
+        for type_ in &types {
+          schema.add_v_attr(&v_label, &key, attr_type_of(type_));
+        }
+      }
+    }
+    let query_str = "
+      CALL db.schema.relTypeProperties()
+      YIELD relType, propertyName, propertyTypes
+      RETURN relType, propertyName, propertyTypes";
+    for row in self.collect_rows(query_str.to_string()).await? {
+      let Ok(Some(key)) = row.get::<Option<String>>("propertyName") else {
+        continue;
+      };
+      let types: Vec<String> = row.get("propertyTypes").unwrap_or_default();
+      // e.g. ":`knows`"
+      let rel_type = row.get::<String>("relType").ok()?;
+      let e_label = rel_type.trim_start_matches(":`").trim_end_matches('`');
+      for type_ in &types {
+        schema.add_e_attr(e_label, &key, attr_type_of(type_));
+      }
+    }
+
+    // an unlabeled endpoint has no label to be recorded by
+    let query_str = "
+      MATCH (src)-[e]->(dst)
+      RETURN DISTINCT
+        type(e) AS e_label,
+        coalesce(head(labels(src)), '') AS src_v_label,
+        coalesce(head(labels(dst)), '') AS dst_v_label";
+    for row in self.collect_rows(query_str.to_string()).await? {
+      let e_label = row.get::<String>("e_label").ok()?;
+      let src_v_label = row.get::<String>("src_v_label").ok()?;
+      let dst_v_label = row.get::<String>("dst_v_label").ok()?;
+      if src_v_label.is_empty() || dst_v_label.is_empty() {
+        continue;
+      }
+      schema.add_e_endpoints(&e_label, &src_v_label, &dst_v_label);
+    }
+
+    Some(schema)
+  }
+
+  async fn count_v(&self, v_label: LabelRef<'_>) -> Option<usize> {
+    let query_str = format!("MATCH (v: {v_label}) RETURN count(v) AS cnt");
+    let rows = self.collect_rows(query_str).await?;
+    let cnt = rows.first()?.get::<i64>("cnt").ok()?;
+    Some(cnt as usize)
+  }
+
+  async fn count_e(&self, e_label: LabelRef<'_>) -> Option<usize> {
+    let query_str = format!("MATCH ()-[e: {e_label}]->() RETURN count(e) AS cnt");
+    let rows = self.collect_rows(query_str).await?;
+    let cnt = rows.first()?.get::<i64>("cnt").ok()?;
+    Some(cnt as usize)
+  }
+
+  async fn load_endpoint_labels(
+    &self,
+    e_label: LabelRef<'_>,
+  ) -> Option<hashbrown::HashMap<Eid, (Label, Label)>> {
+    // a node without labels is taken as unlabeled
+    let query_str = format!(
+      "
+      MATCH (src)-[e: {e_label}]->(dst)
+      RETURN
+        elementId(e) AS eid,
+        coalesce(head(labels(src)), '') AS src_v_label,
+        coalesce(head(labels(dst)), '') AS dst_v_label"
+    );
+    (self.collect_rows(query_str).await?.into_iter())
+      .map(|row| {
+        let eid = row.get::<String>("eid").ok()?;
+        let src_v_label = row.get::<String>("src_v_label").ok()?;
+        let dst_v_label = row.get::<String>("dst_v_label").ok()?;
+        Some((eid, (src_v_label, dst_v_label)))
+      })
+      .collect()
+  }
+}
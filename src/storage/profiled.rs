@@ -0,0 +1,167 @@
+use super::{AdvancedStorageAdapter, AsyncDefault, StorageAdapter};
+use crate::schemas::{AttrCmpMode, DataEdge, DataVertex, LabelRef, PatternPredicate, VidRef};
+use serde::Serialize;
+use std::{
+  ops::Sub,
+  sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+  },
+};
+
+/// What a storage adapter has been asked for so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct StorageStats {
+  /// the loads of vertices / edges
+  pub calls: usize,
+  /// the vertices / edges they returned
+  pub rows: usize,
+  /// the loads answered by the cache (if any), which are also counted in `calls`
+  pub cache_hits: usize,
+}
+
+impl Sub for StorageStats {
+  type Output = Self;
+
+  fn sub(self, rhs: Self) -> Self::Output {
+    Self {
+      calls: self.calls - rhs.calls,
+      rows: self.rows - rhs.rows,
+      cache_hits: self.cache_hits - rhs.cache_hits,
+    }
+  }
+}
+
+/// Counts the loads of the inner storage adapter, and the rows they return (see [`StorageStats`]).
+#[derive(Clone)]
+pub struct ProfiledStorageAdapter<S: StorageAdapter> {
+  inner: Arc<S>,
+  calls: Arc<AtomicUsize>,
+  rows: Arc<AtomicUsize>,
+}
+
+impl<S: StorageAdapter> ProfiledStorageAdapter<S> {
+  pub fn new(inner: Arc<S>) -> Self {
+    Self {
+      inner,
+      calls: Arc::new(AtomicUsize::new(0)),
+      rows: Arc::new(AtomicUsize::new(0)),
+    }
+  }
+
+  pub fn stats(&self) -> StorageStats {
+    StorageStats {
+      calls: self.calls.load(Ordering::Relaxed),
+      rows: self.rows.load(Ordering::Relaxed),
+      cache_hits: self.inner.cache_hits(),
+    }
+  }
+
+  #[inline]
+  fn count<T>(&self, rows: Vec<T>) -> Vec<T> {
+    self.calls.fetch_add(1, Ordering::Relaxed);
+    self.rows.fetch_add(rows.len(), Ordering::Relaxed);
+    rows
+  }
+}
+
+impl<S: StorageAdapter> AsyncDefault for ProfiledStorageAdapter<S> {
+  async fn async_default() -> Self {
+    Self::new(Arc::new(S::async_default().await))
+  }
+}
+
+impl<S: StorageAdapter> StorageAdapter for ProfiledStorageAdapter<S> {
+  async fn get_v(&self, vid: VidRef<'_>) -> Option<DataVertex> {
+    let v = self.inner.get_v(vid).await;
+    self.calls.fetch_add(1, Ordering::Relaxed);
+    self.rows.fetch_add(v.is_some() as usize, Ordering::Relaxed);
+    v
+  }
+
+  async fn load_v(
+    &self,
+    v_label: LabelRef<'_>,
+    v_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
+  ) -> Vec<DataVertex> {
+    self.count(self.inner.load_v(v_label, v_attrs, mode).await)
+  }
+
+  async fn load_e(
+    &self,
+    e_label: LabelRef<'_>,
+    e_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
+  ) -> Vec<DataEdge> {
+    self.count(self.inner.load_e(e_label, e_attrs, mode).await)
+  }
+
+  async fn load_e_with_src(
+    &self,
+    src_vid: VidRef<'_>,
+    e_label: LabelRef<'_>,
+    e_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
+  ) -> Vec<DataEdge> {
+    self.count(
+      self
+        .inner
+        .load_e_with_src(src_vid, e_label, e_attrs, mode)
+        .await,
+    )
+  }
+
+  async fn load_e_with_dst(
+    &self,
+    dst_vid: VidRef<'_>,
+    e_label: LabelRef<'_>,
+    e_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
+  ) -> Vec<DataEdge> {
+    self.count(
+      self
+        .inner
+        .load_e_with_dst(dst_vid, e_label, e_attrs, mode)
+        .await,
+    )
+  }
+
+  fn cache_hits(&self) -> usize {
+    self.inner.cache_hits()
+  }
+}
+
+impl<S: AdvancedStorageAdapter> AdvancedStorageAdapter for ProfiledStorageAdapter<S> {
+  async fn load_e_with_src_and_dst_filter(
+    &self,
+    src_vid: VidRef<'_>,
+    e_label: LabelRef<'_>,
+    e_attrs: &[PatternPredicate],
+    dst_v_label: LabelRef<'_>,
+    dst_v_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
+  ) -> Vec<DataEdge> {
+    let es = self
+      .inner
+      .load_e_with_src_and_dst_filter(src_vid, e_label, e_attrs, dst_v_label, dst_v_attrs, mode)
+      .await;
+    self.count(es)
+  }
+
+  async fn load_e_with_dst_and_src_filter(
+    &self,
+    dst_vid: VidRef<'_>,
+    e_label: LabelRef<'_>,
+    e_attrs: &[PatternPredicate],
+    src_v_label: LabelRef<'_>,
+    src_v_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
+  ) -> Vec<DataEdge> {
+    let es = self
+      .inner
+      .load_e_with_dst_and_src_filter(dst_vid, e_label, e_attrs, src_v_label, src_v_attrs, mode)
+      .await;
+    self.count(es)
+  }
+}
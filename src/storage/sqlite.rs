@@ -1,10 +1,14 @@
-use super::{AdvancedStorageAdapter, AsyncDefault, StorageAdapter};
-use crate::schemas::{AttrType, AttrValue, DataEdge, DataVertex, LabelRef, PatternAttr, VidRef};
+use super::{AdvancedStorageAdapter, AsyncDefault, SchemaStorageAdapter, StorageAdapter};
+use crate::schemas::{
+  AttrCmpMode, AttrType, AttrValue, DataEdge, DataVertex, Eid, GraphSchema, Label, LabelRef, Op,
+  PatternAttr, PatternPredicate, VidRef, regex_full_match,
+};
+use chrono::DateTime;
 use hashbrown::HashMap;
 use project_root::get_project_root;
 use r2d2::{Pool, PooledConnection};
 use r2d2_sqlite::SqliteConnectionManager;
-use rusqlite::{params, params_from_iter};
+use rusqlite::{functions::FunctionFlags, params, params_from_iter};
 use std::{env, sync::Arc};
 use tokio::task;
 
@@ -23,9 +27,9 @@ impl AsyncDefault for SqliteStorageAdapter {
     let db_path = root.join(db_name);
 
     // create connection manager and connection pool
-    let manager = SqliteConnectionManager::file(&db_path).with_flags(
-      rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_URI,
-    );
+    let manager = SqliteConnectionManager::file(&db_path)
+      .with_flags(rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_URI)
+      .with_init(Self::register_functions);
 
     let pool = task::spawn_blocking(move || {
       let pool = Pool::builder()
@@ -90,6 +94,34 @@ impl AsyncDefault for SqliteStorageAdapter {
 }
 
 impl SqliteStorageAdapter {
+  /// `X REGEXP Y` calls `regexp(Y, X)`, which SQLite doesn't provide by default,
+  /// and `to_lower(X)` lowercases non-ASCII chars as well (as Neo4j's `toLower`), unlike `lower`.
+  fn register_functions(conn: &mut rusqlite::Connection) -> rusqlite::Result<()> {
+    conn.create_scalar_function(
+      "to_lower",
+      1,
+      FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+      |ctx| {
+        let text = ctx.get::<Option<String>>(0)?;
+        Ok(text.map(|text| text.to_lowercase()))
+      },
+    )?;
+    conn.create_scalar_function(
+      "regexp",
+      2,
+      FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+      |ctx| {
+        let pattern = ctx.get::<String>(0)?;
+        let Some(text) = ctx.get::<Option<String>>(1)? else {
+          return Ok(false);
+        };
+        regex_full_match(&pattern, &text).ok_or_else(|| {
+          rusqlite::Error::UserFunctionError(format!("invalid regex: {pattern}").into())
+        })
+      },
+    )
+  }
+
   #[allow(dead_code)]
   fn clear_tables(conn: &SqliteConnection) {
     let queries = vec![
@@ -185,12 +217,13 @@ impl SqliteStorageAdapter {
 
   async fn query_edge_with_attr_then_collect(
     &self,
-    e_attr: Option<&PatternAttr>,
+    e_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
     mut query_str: String,
     params: Vec<String>,
   ) -> Vec<DataEdge> {
     let pool = self.pool.clone();
-    let e_attr_cloned = e_attr.cloned();
+    let e_attrs_cloned = e_attrs.to_vec();
 
     task::spawn_blocking(move || {
       let conn = match pool.get() {
@@ -203,17 +236,7 @@ impl SqliteStorageAdapter {
 
       // add attr filter
       let mut all_params = params.clone();
-      if let Some(attr) = e_attr_cloned.as_ref() {
-        query_str.push_str(
-          r#"
-        AND EXISTS (
-          SELECT * FROM edge_attribute 
-          WHERE eid = e.eid AND key = ?
-        "#,
-        );
-        all_params.push(attr.key.clone());
-        add_attr_filter(attr, &mut query_str, &mut all_params);
-      }
+      add_e_attrs_filter(&e_attrs_cloned, mode, &mut query_str, &mut all_params);
 
       let mut stmt = match conn.prepare_cached(&query_str) {
         Ok(stmt) => stmt,
@@ -243,12 +266,13 @@ impl SqliteStorageAdapter {
 
   async fn query_vertex_with_attr_then_collect(
     &self,
-    v_attr: Option<&PatternAttr>,
+    v_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
     mut query_str: String,
     params: Vec<String>,
   ) -> Vec<DataVertex> {
     let pool = self.pool.clone();
-    let v_attr_cloned = v_attr.cloned();
+    let v_attrs_cloned = v_attrs.to_vec();
 
     task::spawn_blocking(move || {
       let conn = match pool.get() {
@@ -261,17 +285,7 @@ impl SqliteStorageAdapter {
 
       // add attr filter
       let mut all_params = params.clone();
-      if let Some(attr) = v_attr_cloned.as_ref() {
-        query_str.push_str(
-          r#"
-        AND EXISTS (
-          SELECT * FROM vertex_attribute 
-          WHERE vid = v.vid AND key = ?
-        "#,
-        );
-        all_params.push(attr.key.clone());
-        add_attr_filter(attr, &mut query_str, &mut all_params);
-      }
+      add_v_attrs_filter(&v_attrs_cloned, mode, &mut query_str, &mut all_params);
 
       let mut stmt = match conn.prepare_cached(&query_str) {
         Ok(stmt) => stmt,
@@ -300,45 +314,272 @@ impl SqliteStorageAdapter {
   }
 }
 
-fn add_attr_filter(attr: &PatternAttr, query_str: &mut String, params: &mut Vec<String>) {
-  match &attr.value {
-    AttrValue::Int(val) => {
-      query_str.push_str(&format!(
-        "  AND type = '{}' AND CAST(value AS INTEGER) ",
-        AttrType::Int
+/// One boolean condition per predicate, so that all of them must hold.
+fn add_v_attrs_filter(
+  preds: &[PatternPredicate],
+  mode: AttrCmpMode,
+  query_str: &mut String,
+  params: &mut Vec<String>,
+) {
+  for pred in preds {
+    query_str.push_str("\n        AND ");
+    add_pred_filter(
+      pred,
+      mode,
+      "vertex_attribute",
+      "vid = v.vid",
+      query_str,
+      params,
+    );
+  }
+}
+
+/// An unlabeled pattern vertex (with an empty label) matches any label.
+fn add_v_label_filter(v_label: LabelRef, query_str: &mut String, params: &mut Vec<String>) {
+  if !v_label.is_empty() {
+    query_str.push_str(" AND v.label = ?");
+    params.push(v_label.to_string());
+  }
+}
+
+/// One boolean condition per predicate, so that all of them must hold.
+fn add_e_attrs_filter(
+  preds: &[PatternPredicate],
+  mode: AttrCmpMode,
+  query_str: &mut String,
+  params: &mut Vec<String>,
+) {
+  for pred in preds {
+    query_str.push_str("\n        AND ");
+    add_pred_filter(
+      pred,
+      mode,
+      "edge_attribute",
+      "eid = e.eid",
+      query_str,
+      params,
+    );
+  }
+}
+
+/// Translate the predicate tree into a three-valued (`1` / `0` / `NULL`) expression,
+/// of scalar sub-queries on `attr_table` combined with `AND` / `OR` / `NOT`.
+///
+/// These operators already follow the three-valued logic in SQLite, and a missing attribute
+/// yields `NULL` (as in cypher), so only the owners whose expression is `1` are kept.
+fn add_pred_filter(
+  pred: &PatternPredicate,
+  mode: AttrCmpMode,
+  attr_table: &str,
+  owner_cond: &str,
+  query_str: &mut String,
+  params: &mut Vec<String>,
+) {
+  let cmp = |key: &String, op, value: &AttrValue| {
+    PatternPredicate::Cmp(PatternAttr {
+      key: key.clone(),
+      op,
+      value: value.clone(),
+      _type: value.to_type(),
+    })
+  };
+
+  match pred {
+    PatternPredicate::Cmp(attr) => {
+      add_attr_filter(attr, mode, attr_table, owner_cond, query_str, params)
+    }
+    // unbound, so it's `null` (as in `PatternPredicate::evaluate`)
+    PatternPredicate::Param { .. } => query_str.push_str("NULL"),
+    // `x IN [a, b]` is `x = a OR x = b`, as in `PatternPredicate::evaluate`
+    PatternPredicate::In { key, values } => {
+      let pred = PatternPredicate::or(values.iter().map(|value| cmp(key, Op::Eq, value)).collect());
+      add_pred_filter(&pred, mode, attr_table, owner_cond, query_str, params);
+    }
+    PatternPredicate::Between {
+      key,
+      bounds: (low, high),
+    } => {
+      let pred = PatternPredicate::and(vec![cmp(key, Op::Ge, low), cmp(key, Op::Le, high)]);
+      add_pred_filter(&pred, mode, attr_table, owner_cond, query_str, params);
+    }
+    PatternPredicate::And { and: preds } if preds.is_empty() => query_str.push('1'),
+    PatternPredicate::Or { or: preds } if preds.is_empty() => query_str.push('0'),
+    PatternPredicate::And { and: preds } | PatternPredicate::Or { or: preds } => {
+      let sep = if matches!(pred, PatternPredicate::And { .. }) {
+        " AND "
+      } else {
+        " OR "
+      };
+      query_str.push('(');
+      for (idx, pred) in preds.iter().enumerate() {
+        if idx > 0 {
+          query_str.push_str(sep);
+        }
+        add_pred_filter(pred, mode, attr_table, owner_cond, query_str, params);
+      }
+      query_str.push(')');
+    }
+    PatternPredicate::Not { not } => {
+      query_str.push_str("NOT (");
+      add_pred_filter(not, mode, attr_table, owner_cond, query_str, params);
+      query_str.push(')');
+    }
+  }
+}
+
+/// dates are compared as datetimes, whose (normalized) ISO text sorts chronologically
+const DATETIME_TEXT: &str = "(CASE type WHEN 'date' THEN value || 'T00:00:00.000Z' ELSE value END)";
+
+/// `(SELECT CASE type WHEN ... END FROM attr_table WHERE ...)`, which mirrors `Op::operate_on`
+/// (one arm per type that compares with `attr.value`), and is `NULL` if there's no such attribute.
+fn add_attr_filter(
+  attr: &PatternAttr,
+  mode: AttrCmpMode,
+  attr_table: &str,
+  owner_cond: &str,
+  query_str: &mut String,
+  params: &mut Vec<String>,
+) {
+  // `null` never compares
+  if attr.value == AttrValue::Null {
+    query_str.push_str("NULL");
+    return;
+  }
+
+  let op = attr.op;
+  // `None` for the string-only ops
+  let repr = op.to_neo4j_sqlite_repr();
+  let (int, float, string) = (AttrType::Int, AttrType::Float, AttrType::String);
+  let (date, datetime, bool_) = (AttrType::Date, AttrType::DateTime, AttrType::Bool);
+
+  // `(cond, result, params)` of each `WHEN cond THEN result`
+  let mut arms: Vec<(String, String, Vec<String>)> = vec![];
+  match (repr, &attr.value) {
+    (None, AttrValue::String(val)) => {
+      let (cond, param) = match op {
+        // `GLOB` is case-sensitive, unlike `LIKE`
+        Op::StartsWith => ("value GLOB ?".to_string(), format!("{}*", escape_glob(val))),
+        Op::EndsWith => ("value GLOB ?".to_string(), format!("*{}", escape_glob(val))),
+        Op::Contains => (
+          "value GLOB ?".to_string(),
+          format!("*{}*", escape_glob(val)),
+        ),
+        Op::EqIgnoreCase => ("to_lower(value) = ?".to_string(), val.to_lowercase()),
+        Op::Regex => ("value REGEXP ?".to_string(), val.clone()),
+        Op::Eq | Op::Ne | Op::Gt | Op::Ge | Op::Lt | Op::Le => {
+          unreachable!("❌  `{op}` has an infix form")
+        }
+      };
+      arms.push((format!("type = '{string}'"), cond, vec![param]));
+    }
+    // string-only ops never hold on the other values
+    (None, _) => {}
+    (Some(repr), AttrValue::Int(val)) => {
+      arms.push((
+        format!("type = '{int}'"),
+        format!("CAST(value AS INTEGER) {repr} ?"),
+        vec![val.to_string()],
+      ));
+      arms.push((
+        format!("type = '{float}'"),
+        format!("CAST(value AS REAL) {repr} ?"),
+        vec![val.to_string()],
       ));
-      query_str.push_str(attr.op.to_neo4j_sqlite_repr());
-      query_str.push_str(" ?");
-      query_str.push_str(
-        "
-      )",
-      );
-      params.push(val.to_string());
     }
-    AttrValue::Float(val) => {
-      query_str.push_str(&format!(
-        "  AND type = '{}' AND CAST(value AS REAL) ",
-        AttrType::Float
+    (Some(repr), AttrValue::Float(val)) => arms.push((
+      format!("type IN ('{int}', '{float}')"),
+      format!("CAST(value AS REAL) {repr} ?"),
+      vec![val.to_string()],
+    )),
+    (Some(repr), AttrValue::String(val)) => arms.push((
+      format!("type = '{string}'"),
+      format!("value {repr} ?"),
+      vec![val.clone()],
+    )),
+    (Some(repr), AttrValue::Bool(val)) => arms.push((
+      format!("type = '{bool_}'"),
+      format!("value {repr} ?"),
+      vec![val.to_string()],
+    )),
+    (Some(repr), AttrValue::Date(_) | AttrValue::DateTime(_)) if mode.is_strict() => arms.push((
+      format!("type = '{}'", attr.value.to_type()),
+      format!("value {repr} ?"),
+      vec![attr.value.to_string()],
+    )),
+    (Some(repr), AttrValue::Date(_) | AttrValue::DateTime(_)) => {
+      let millis = attr.value.to_epoch_millis().unwrap_or_default();
+      let as_datetime = DateTime::from_timestamp_millis(millis as i64).unwrap_or_default();
+      arms.push((
+        format!("type IN ('{date}', '{datetime}')"),
+        format!("{DATETIME_TEXT} {repr} ?"),
+        vec![AttrValue::DateTime(as_datetime).to_string()],
+      ));
+      // as epoch millis
+      arms.push((
+        format!("type IN ('{int}', '{float}')"),
+        format!("CAST(value AS REAL) {repr} ?"),
+        vec![millis.to_string()],
       ));
-      query_str.push_str(attr.op.to_neo4j_sqlite_repr());
-      query_str.push_str(" ?");
-      query_str.push_str(
-        "
-      )",
-      );
-      params.push(val.to_string());
     }
-    AttrValue::String(val) => {
-      query_str.push_str(&format!("  AND type = '{}' AND value ", AttrType::String));
-      query_str.push_str(attr.op.to_neo4j_sqlite_repr());
-      query_str.push_str(" ?");
-      query_str.push_str(
-        "
-      )",
-      );
-      params.push(val.clone());
+    (Some(_), AttrValue::Null | AttrValue::List(_)) => {}
+  }
+
+  // numbers against temporal values, as epoch millis
+  if let (false, Some(repr), AttrValue::Int(_) | AttrValue::Float(_)) =
+    (mode.is_strict(), repr, &attr.value)
+  {
+    let millis = attr.value.to_epoch_millis().unwrap_or_default() as i64;
+    if let Some(as_datetime) = DateTime::from_timestamp_millis(millis) {
+      arms.push((
+        format!("type IN ('{date}', '{datetime}')"),
+        format!("{DATETIME_TEXT} {repr} ?"),
+        vec![AttrValue::DateTime(as_datetime).to_string()],
+      ));
+    }
+  }
+
+  // values of the other types are compared as strings, unless it's strict
+  let (fallback, fallback_params) = match repr {
+    _ if mode.is_strict() => ("NULL".to_string(), vec![]),
+    None => ("0".to_string(), vec![]),
+    Some(repr) => (format!("value {repr} ?"), vec![attr.value.to_string()]),
+  };
+
+  query_str.push_str(&format!(
+    "(
+          SELECT CASE
+            WHEN type = '{}' THEN NULL",
+    AttrType::Null
+  ));
+  for (cond, result, arm_params) in arms {
+    query_str.push_str(&format!(
+      "
+            WHEN {cond} THEN {result}"
+    ));
+    params.extend(arm_params);
+  }
+  query_str.push_str(&format!(
+    "
+            ELSE {fallback}
+          END
+          FROM {attr_table}
+          WHERE {owner_cond} AND key = ?
+        )"
+  ));
+  params.extend(fallback_params);
+  params.push(attr.key.clone());
+}
+
+/// `*`, `?` and `[` are wildcards in `GLOB`.
+fn escape_glob(val: &str) -> String {
+  let mut escaped = String::with_capacity(val.len());
+  for c in val.chars() {
+    match c {
+      '*' | '?' | '[' => escaped.push_str(&format!("[{c}]")),
+      c => escaped.push(c),
     }
   }
+  escaped
 }
 
 fn collect_vertices(mut rows: rusqlite::Rows) -> HashMap<String, DataVertex> {
@@ -399,6 +640,7 @@ fn collect_edges(mut rows: rusqlite::Rows) -> HashMap<String, DataEdge> {
           src_vid,
           dst_vid,
           attrs: HashMap::new(),
+          hops: vec![],
         },
       );
     }
@@ -422,6 +664,11 @@ fn get_typed_value(type_: &str, value: String) -> AttrValue {
   match type_ {
     "int" => AttrValue::Int(value.parse().unwrap_or(0)),
     "float" => AttrValue::Float(value.parse().unwrap_or(0.0)),
+    "date" | "datetime" => AttrValue::parse_temporal(&value).unwrap_or(AttrValue::String(value)),
+    "bool" => AttrValue::Bool(value == "true"),
+    "null" => AttrValue::Null,
+    // lists are stored as JSON
+    "list" => serde_json::from_str(&value).unwrap_or(AttrValue::String(value)),
     _ => AttrValue::String(value),
   }
 }
@@ -485,11 +732,7 @@ impl StorageAdapter for SqliteStorageAdapter {
       // collect all attrs
       for row in rows_result {
         if let (Some(key), Some(value), Some(type_)) = (row.2, row.3, row.4) {
-          let typed_value = match type_.as_str() {
-            "int" => AttrValue::Int(value.parse().unwrap_or(0)),
-            "float" => AttrValue::Float(value.parse().unwrap_or(0.0)),
-            _ => AttrValue::String(value),
-          };
+          let typed_value = get_typed_value(&type_, value);
 
           attrs.insert(key, typed_value);
         }
@@ -504,22 +747,33 @@ impl StorageAdapter for SqliteStorageAdapter {
     })
   }
 
-  async fn load_v(&self, v_label: LabelRef<'_>, v_attr: Option<&PatternAttr>) -> Vec<DataVertex> {
-    let query_str = String::from(
+  async fn load_v(
+    &self,
+    v_label: LabelRef<'_>,
+    v_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
+  ) -> Vec<DataVertex> {
+    let mut query_str = String::from(
       r#"
       SELECT v.vid, v.label, a.key, a.value, a.type
       FROM db_vertex v
       LEFT JOIN vertex_attribute a ON v.vid = a.vid
-      WHERE v.label = ?"#,
+      WHERE 1"#,
     );
-    let params = vec![v_label.to_string()];
+    let mut params = vec![];
+    add_v_label_filter(v_label, &mut query_str, &mut params);
 
     self
-      .query_vertex_with_attr_then_collect(v_attr, query_str, params)
+      .query_vertex_with_attr_then_collect(v_attrs, mode, query_str, params)
       .await
   }
 
-  async fn load_e(&self, e_label: LabelRef<'_>, e_attr: Option<&PatternAttr>) -> Vec<DataEdge> {
+  async fn load_e(
+    &self,
+    e_label: LabelRef<'_>,
+    e_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
+  ) -> Vec<DataEdge> {
     let query_str = String::from(
       r#"
       SELECT e.eid, e.label, e.src_vid, e.dst_vid, a.key, a.value, a.type
@@ -530,7 +784,7 @@ impl StorageAdapter for SqliteStorageAdapter {
     let params = vec![e_label.to_string()];
 
     self
-      .query_edge_with_attr_then_collect(e_attr, query_str, params)
+      .query_edge_with_attr_then_collect(e_attrs, mode, query_str, params)
       .await
   }
 
@@ -538,7 +792,8 @@ impl StorageAdapter for SqliteStorageAdapter {
     &self,
     src_vid: VidRef<'_>,
     e_label: LabelRef<'_>,
-    e_attr: Option<&PatternAttr>,
+    e_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
   ) -> Vec<DataEdge> {
     let query_str = String::from(
       r#"
@@ -550,7 +805,7 @@ impl StorageAdapter for SqliteStorageAdapter {
     let params = vec![src_vid.to_string(), e_label.to_string()];
 
     self
-      .query_edge_with_attr_then_collect(e_attr, query_str, params)
+      .query_edge_with_attr_then_collect(e_attrs, mode, query_str, params)
       .await
   }
 
@@ -558,7 +813,8 @@ impl StorageAdapter for SqliteStorageAdapter {
     &self,
     dst_vid: VidRef<'_>,
     e_label: LabelRef<'_>,
-    e_attr: Option<&PatternAttr>,
+    e_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
   ) -> Vec<DataEdge> {
     let query_str = String::from(
       r#"
@@ -570,7 +826,7 @@ impl StorageAdapter for SqliteStorageAdapter {
     let params = vec![dst_vid.to_string(), e_label.to_string()];
 
     self
-      .query_edge_with_attr_then_collect(e_attr, query_str, params)
+      .query_edge_with_attr_then_collect(e_attrs, mode, query_str, params)
       .await
   }
 }
@@ -578,14 +834,15 @@ impl StorageAdapter for SqliteStorageAdapter {
 impl SqliteStorageAdapter {
   async fn query_edge_with_attr_and_next_v_attr_then_collect(
     &self,
-    e_attr: Option<&PatternAttr>,
-    next_v_attr: Option<&PatternAttr>,
+    e_attrs: &[PatternPredicate],
+    next_v_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
     mut query_str: String,
     params: Vec<String>,
   ) -> Vec<DataEdge> {
     let pool = self.pool.clone();
-    let e_attr_cloned = e_attr.cloned();
-    let next_v_attr_cloned = next_v_attr.cloned();
+    let e_attrs_cloned = e_attrs.to_vec();
+    let next_v_attrs_cloned = next_v_attrs.to_vec();
 
     task::spawn_blocking(move || {
       let conn = match pool.get() {
@@ -596,32 +853,12 @@ impl SqliteStorageAdapter {
         }
       };
 
-      // add e_attr filter
+      // add e_attrs filter
       let mut all_params = params.clone();
-      if let Some(e_attr) = e_attr_cloned.as_ref() {
-        query_str.push_str(
-          r#"
-        AND EXISTS (
-          SELECT * FROM edge_attribute 
-          WHERE eid = e.eid AND key = ?
-        "#,
-        );
-        all_params.push(e_attr.key.clone());
-        add_attr_filter(e_attr, &mut query_str, &mut all_params);
-      }
+      add_e_attrs_filter(&e_attrs_cloned, mode, &mut query_str, &mut all_params);
 
-      // add next_v_attr filter
-      if let Some(v_attr) = next_v_attr_cloned.as_ref() {
-        query_str.push_str(
-          r#"
-        AND EXISTS (
-          SELECT * FROM vertex_attribute 
-          WHERE vid = v.vid AND key = ?
-        "#,
-        );
-        all_params.push(v_attr.key.clone());
-        add_attr_filter(v_attr, &mut query_str, &mut all_params);
-      }
+      // add next_v_attrs filter
+      add_v_attrs_filter(&next_v_attrs_cloned, mode, &mut query_str, &mut all_params);
 
       let mut stmt = match conn.prepare_cached(&query_str) {
         Ok(stmt) => stmt,
@@ -655,26 +892,30 @@ impl AdvancedStorageAdapter for SqliteStorageAdapter {
     &self,
     src_vid: VidRef<'_>,
     e_label: LabelRef<'_>,
-    e_attr: Option<&PatternAttr>,
+    e_attrs: &[PatternPredicate],
     dst_v_label: LabelRef<'_>,
-    dst_v_attr: Option<&PatternAttr>,
+    dst_v_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
   ) -> Vec<DataEdge> {
-    let query_str = String::from(
+    let mut query_str = String::from(
       r#"
       SELECT e.eid, e.label, e.src_vid, e.dst_vid, ea.key, ea.value, ea.type
       FROM db_edge e
       LEFT JOIN edge_attribute ea ON e.eid = ea.eid
       JOIN db_vertex v ON e.dst_vid = v.vid
-      WHERE e.src_vid = ? AND e.label = ? AND v.label = ?"#,
+      WHERE e.src_vid = ? AND e.label = ?"#,
     );
-    let params = vec![
-      src_vid.to_string(),
-      e_label.to_string(),
-      dst_v_label.to_string(),
-    ];
+    let mut params = vec![src_vid.to_string(), e_label.to_string()];
+    add_v_label_filter(dst_v_label, &mut query_str, &mut params);
 
     self
-      .query_edge_with_attr_and_next_v_attr_then_collect(e_attr, dst_v_attr, query_str, params)
+      .query_edge_with_attr_and_next_v_attr_then_collect(
+        e_attrs,
+        dst_v_attrs,
+        mode,
+        query_str,
+        params,
+      )
       .await
   }
 
@@ -682,26 +923,376 @@ impl AdvancedStorageAdapter for SqliteStorageAdapter {
     &self,
     dst_vid: VidRef<'_>,
     e_label: LabelRef<'_>,
-    e_attr: Option<&PatternAttr>,
+    e_attrs: &[PatternPredicate],
     src_v_label: LabelRef<'_>,
-    src_v_attr: Option<&PatternAttr>,
+    src_v_attrs: &[PatternPredicate],
+    mode: AttrCmpMode,
   ) -> Vec<DataEdge> {
-    let query_str = String::from(
+    let mut query_str = String::from(
       r#"
       SELECT e.eid, e.label, e.src_vid, e.dst_vid, ea.key, ea.value, ea.type
       FROM db_edge e
       LEFT JOIN edge_attribute ea ON e.eid = ea.eid
       JOIN db_vertex v ON e.src_vid = v.vid
-      WHERE e.dst_vid = ? AND e.label = ? AND v.label = ?"#,
+      WHERE e.dst_vid = ? AND e.label = ?"#,
     );
-    let params = vec![
-      dst_vid.to_string(),
-      e_label.to_string(),
-      src_v_label.to_string(),
-    ];
+    let mut params = vec![dst_vid.to_string(), e_label.to_string()];
+    add_v_label_filter(src_v_label, &mut query_str, &mut params);
+
+    self
+      .query_edge_with_attr_and_next_v_attr_then_collect(
+        e_attrs,
+        src_v_attrs,
+        mode,
+        query_str,
+        params,
+      )
+      .await
+  }
+}
+
+/// The `type` column of the attribute tables, see `get_typed_value`.
+fn attr_type_of(type_: &str) -> AttrType {
+  match type_ {
+    "int" => AttrType::Int,
+    "float" => AttrType::Float,
+    "date" => AttrType::Date,
+    "datetime" => AttrType::DateTime,
+    "bool" => AttrType::Bool,
+    "null" => AttrType::Null,
+    "list" => AttrType::List,
+    _ => AttrType::String,
+  }
+}
+
+/// The distinct rows of `N` strings.
+fn query_distinct<const N: usize>(
+  conn: &SqliteConnection,
+  query: &str,
+) -> rusqlite::Result<Vec<[String; N]>> {
+  let mut stmt = conn.prepare(query)?;
+  stmt
+    .query_map([], |row| {
+      let mut cols: [String; N] = std::array::from_fn(|_| String::new());
+      for (idx, col) in cols.iter_mut().enumerate() {
+        *col = row.get(idx)?;
+      }
+      Ok(cols)
+    })?
+    .collect()
+}
+
+impl SqliteStorageAdapter {
+  /// Run `query` on a connection of the pool, off the async runtime, where `desc` names what's
+  /// queried in the errors.
+  async fn query_on_conn<T: Send + 'static>(
+    &self,
+    desc: &'static str,
+    query: impl FnOnce(&SqliteConnection) -> rusqlite::Result<T> + Send + 'static,
+  ) -> Option<T> {
+    let pool = self.pool.clone();
+
+    task::spawn_blocking(move || {
+      let conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+          eprintln!("❌  Error getting connection from pool: {e}");
+          return None;
+        }
+      };
+
+      match query(&conn) {
+        Ok(result) => Some(result),
+        Err(e) => {
+          eprintln!("❌  Error loading {desc}: {e}");
+          None
+        }
+      }
+    })
+    .await
+    .unwrap_or_else(|e| {
+      eprintln!("❌  Task failed: {e}");
+      None
+    })
+  }
+}
+
+impl SchemaStorageAdapter for SqliteStorageAdapter {
+  async fn load_schema(&self) -> Option<GraphSchema> {
+    self
+      .query_on_conn("schema", |conn| {
+        // all known, even if there're no attributes / edges at all
+        let mut schema = GraphSchema {
+          v_attrs: Some(Default::default()),
+          e_attrs: Some(Default::default()),
+          e_endpoints: Some(Default::default()),
+          ..Default::default()
+        };
+
+        for [v_label] in query_distinct(conn, "SELECT DISTINCT label FROM db_vertex")? {
+          schema.add_v_label(&v_label);
+        }
+        for [e_label] in query_distinct(conn, "SELECT DISTINCT label FROM db_edge")? {
+          schema.add_e_label(&e_label);
+        }
+        for [v_label, key, type_] in query_distinct(
+          conn,
+          r#"
+          SELECT DISTINCT v.label, a.key, a.type
+          FROM vertex_attribute a
+          JOIN db_vertex v ON a.vid = v.vid
+          "#,
+        )? {
+          schema.add_v_attr(&v_label, &key, attr_type_of(&type_));
+        }
+        for [e_label, key, type_] in query_distinct(
+          conn,
+          r#"
+          SELECT DISTINCT e.label, a.key, a.type
+          FROM edge_attribute a
+          JOIN db_edge e ON a.eid = e.eid
+          "#,
+        )? {
+          schema.add_e_attr(&e_label, &key, attr_type_of(&type_));
+        }
+        for [e_label, src_v_label, dst_v_label] in query_distinct(
+          conn,
+          r#"
+          SELECT DISTINCT e.label, src.label, dst.label
+          FROM db_edge e
+          JOIN db_vertex src ON e.src_vid = src.vid
+          JOIN db_vertex dst ON e.dst_vid = dst.vid
+          "#,
+        )? {
+          schema.add_e_endpoints(&e_label, &src_v_label, &dst_v_label);
+        }
+
+        Ok(schema)
+      })
+      .await
+  }
+
+  async fn count_v(&self, v_label: LabelRef<'_>) -> Option<usize> {
+    let v_label = v_label.to_string();
+    self
+      .query_on_conn("vertex count", move |conn| {
+        conn.query_row(
+          "SELECT COUNT(*) FROM db_vertex WHERE label = ?1",
+          params![v_label],
+          |row| row.get(0),
+        )
+      })
+      .await
+  }
+
+  async fn count_e(&self, e_label: LabelRef<'_>) -> Option<usize> {
+    let e_label = e_label.to_string();
+    self
+      .query_on_conn("edge count", move |conn| {
+        conn.query_row(
+          "SELECT COUNT(*) FROM db_edge WHERE label = ?1",
+          params![e_label],
+          |row| row.get(0),
+        )
+      })
+      .await
+  }
 
+  async fn load_endpoint_labels(
+    &self,
+    e_label: LabelRef<'_>,
+  ) -> Option<HashMap<Eid, (Label, Label)>> {
+    let e_label = e_label.to_string();
     self
-      .query_edge_with_attr_and_next_v_attr_then_collect(e_attr, src_v_attr, query_str, params)
+      .query_on_conn("endpoint labels", move |conn| {
+        let mut stmt = conn.prepare(
+          r#"
+          SELECT e.eid, src.label, dst.label
+          FROM db_edge e
+          JOIN db_vertex src ON e.src_vid = src.vid
+          JOIN db_vertex dst ON e.dst_vid = dst.vid
+          WHERE e.label = ?1
+          "#,
+        )?;
+        stmt
+          .query_map(params![e_label], |row| {
+            Ok((row.get(0)?, (row.get(1)?, row.get(2)?)))
+          })?
+          .collect()
+      })
       .await
   }
 }
+
+#[cfg(test)]
+impl SqliteStorageAdapter {
+  /// A single-connection in-memory database, filled by `init_sql`.
+  pub(crate) fn in_memory_test_only(init_sql: &str) -> Self {
+    let pool = Pool::builder()
+      .max_size(1)
+      .build(SqliteConnectionManager::memory().with_init(Self::register_functions))
+      .unwrap();
+    let conn = pool.get().unwrap();
+    Self::init_schema(&conn);
+    conn.execute_batch(init_sql).unwrap();
+    drop(conn);
+    Self {
+      pool: Arc::new(pool),
+    }
+  }
+}
+
+#[cfg(test)]
+mod test_sqlite_filter {
+  use super::*;
+  use crate::parser::CypherParser;
+
+  fn in_memory_adapter() -> SqliteStorageAdapter {
+    SqliteStorageAdapter::in_memory_test_only(
+      r#"
+      INSERT INTO db_vertex VALUES ('p1', 'Place'), ('p2', 'Place'), ('p3', 'Place');
+      INSERT INTO vertex_attribute (vid, key, value, type) VALUES
+        ('p1', 'name', 'China', 'string'), ('p1', 'id', '1', 'int'),
+        ('p2', 'name', 'India', 'string'), ('p2', 'id', '5', 'int'),
+        ('p3', 'name', 'Chile', 'string'),
+        ('p1', 'founded', '1949-10-01', 'date'),
+        ('p2', 'founded', '1947-08-15T00:00:00.000Z', 'datetime'),
+        ('p3', 'founded', '-4793040000000', 'int'),
+        ('p1', 'landlocked', 'false', 'bool'), ('p2', 'landlocked', '', 'null'),
+        ('p3', 'langs', '["es", "en"]', 'list'), ('p2', 'alias', 'BHĀRAT', 'string');
+      "#,
+    )
+  }
+
+  fn preds_of(where_clause: &str) -> Vec<PatternPredicate> {
+    let mut parser = CypherParser::new(format!("MATCH (p: Place) WHERE {where_clause} RETURN p"));
+    parser.parse().unwrap();
+    let graph = parser.take_as_pattern_graph();
+    graph.view_v_from_vid("p").unwrap().attrs.clone()
+  }
+
+  #[tokio::test]
+  async fn test_predicate_pushdown() {
+    let adapter = in_memory_adapter();
+    for mode in [AttrCmpMode::Lenient, AttrCmpMode::Strict] {
+      // dates, datetimes and epoch millis only compare with each other if it's lenient
+      let lenient = |expected| if mode.is_strict() { vec![] } else { expected };
+
+      for (where_clause, expected) in [
+        ("p.name IN ['China', 'Chile']", vec!["p1", "p3"]),
+        ("p.id BETWEEN 2 AND 9 OR p.name = 'Chile'", vec!["p2", "p3"]),
+        // `p3.id` is missing, i.e. `null`, and so is `NOT null`
+        ("NOT p.id = 1", vec!["p2"]),
+        ("NOT (p.id > 3 OR p.name IN [])", vec!["p1"]),
+        ("p.id IN [1, 5] AND p.name <> 'China'", vec!["p2"]),
+        ("p.name STARTS WITH 'Ch'", vec!["p1", "p3"]),
+        ("p.name ENDS WITH 'a' AND p.name CONTAINS 'hi'", vec!["p1"]),
+        (
+          "toLower(p.name) = 'CHILE' OR p.name =~ 'I.d.*'",
+          vec!["p2", "p3"],
+        ),
+        // beyond ASCII, as Neo4j's `toLower` does
+        ("toLower(p.alias) = 'bhārat'", vec!["p2"]),
+        ("p.name >= 'Chj' AND p.name < 'J'", vec!["p2"]),
+        ("p.name STARTS WITH 'C*' OR p.name CONTAINS '_'", vec![]),
+        ("p.founded >= date('1949-01-01')", vec!["p1"]),
+        ("p.founded < date('1948-01-01')", lenient(vec!["p2", "p3"])),
+        (
+          "p.founded = datetime('1949-10-01T00:00:00Z')",
+          lenient(vec!["p1"]),
+        ),
+        (
+          "p.founded IN [date('1947-08-15'), date('1818-02-12')]",
+          lenient(vec!["p2", "p3"]),
+        ),
+        (
+          "p.founded BETWEEN date('1900-01-01') AND datetime('1949-10-01T08:00:00+08:00')",
+          lenient(vec!["p1", "p2"]),
+        ),
+        ("p.landlocked = false", vec!["p1"]),
+        ("NOT p.landlocked = false", vec![]),
+        ("p.landlocked = null OR p.landlocked <> true", vec!["p1"]),
+        (
+          "p.landlocked IN [true, null] OR p.name = 'Chile'",
+          vec!["p3"],
+        ),
+      ] {
+        let preds = preds_of(where_clause);
+        let mut vids = adapter
+          .load_v("Place", &preds, mode)
+          .await
+          .into_iter()
+          .map(|v| v.vid)
+          .collect::<Vec<_>>();
+        vids.sort();
+        assert_eq!(vids, expected, "{where_clause} ({mode:?})");
+
+        // the in-memory evaluation should agree with the pushed down one
+        let all = adapter.load_v("Place", &[], mode).await;
+        let mut vids = all
+          .into_iter()
+          .filter(|v| v.satisfy_attrs(&preds, mode))
+          .map(|v| v.vid)
+          .collect::<Vec<_>>();
+        vids.sort();
+        assert_eq!(vids, expected, "{where_clause} ({mode:?}, in-memory)");
+      }
+    }
+
+    let p3 = adapter.get_v("p3").await.unwrap();
+    assert_eq!(
+      p3.attrs["langs"],
+      AttrValue::List(vec!["es".into(), "en".into()])
+    );
+  }
+}
+
+#[cfg(test)]
+mod test_sqlite_schema {
+  use super::*;
+  use crate::planner::{QueryFormat, parse_query};
+
+  #[tokio::test]
+  async fn test_load_schema() {
+    let adapter = SqliteStorageAdapter::in_memory_test_only(
+      r#"
+      INSERT INTO db_vertex VALUES
+        ('c1', 'Comment'), ('p1', 'Post'), ('u1', 'Person'), ('u2', 'Person'), ('f1', 'Forum');
+      INSERT INTO db_edge VALUES
+        ('e1', 'hasCreator', 'c1', 'u1'), ('e2', 'hasCreator', 'p1', 'u2'),
+        ('e3', 'knows', 'u1', 'u2');
+      INSERT INTO vertex_attribute (vid, key, value, type) VALUES
+        ('u1', 'firstName', 'Jan', 'string'), ('u1', 'birthday', '1989-12-03', 'date'),
+        ('u2', 'firstName', '', 'null'), ('c1', 'length', '42', 'int');
+      INSERT INTO edge_attribute (eid, key, value, type) VALUES
+        ('e3', 'creationDate', '1262304000000', 'int');
+      "#,
+    );
+    let schema = adapter.load_schema().await.unwrap();
+
+    assert_eq!(schema.v_labels().len(), 4);
+    assert!(schema.e_labels().contains("knows"));
+    let person = schema.v_attrs_of("Person").unwrap();
+    assert_eq!(
+      person["firstName"],
+      [AttrType::String].into_iter().collect()
+    );
+    assert!(schema.v_attrs_of("Forum").unwrap().is_empty());
+    assert!(schema.e_attrs_of("hasCreator").unwrap().is_empty());
+    assert_eq!(schema.e_endpoints_of("hasCreator").unwrap().len(), 2);
+
+    let pattern = parse_query(
+      "MATCH (f:Forum)-[e:hasCreator]->(u:Person)-[k:knows]->(v:Person) \
+       WHERE u.birthday < date('1990-01-01') AND k.creationDate = 'today' RETURN u",
+      QueryFormat::Cypher,
+    )
+    .unwrap();
+    let issues = pattern.validate(&schema).unwrap_err().to_string();
+    assert_eq!(
+      issues,
+      "the pattern doesn't fit the data graph:
+  - impossible endpoints of `e`: `Forum` -> `Person` never holds, as `hasCreator` is `Comment|Post -> Person`
+  - type mismatch of `k`: `creationDate` holds int, but it's compared with the string `'today'`"
+    );
+  }
+}
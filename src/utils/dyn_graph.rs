@@ -406,3 +406,92 @@ impl<VType: VBase, EType: EBase> DynGraph<VType, EType> {
     }
   }
 }
+
+impl DynGraph<DataVertex, DataEdge> {
+  /// The attributes of the data vertex / edge bound to `pattern`, unless it's bound to several.
+  ///
+  /// `frontier` is the candidate (`data_vid`) of a pattern vertex (`pat_vid`),
+  /// which is picked out of its other candidates, along with the edges it's incident to.
+  pub fn view_bound_attrs(
+    &self,
+    pattern: &str,
+    frontier: Option<(&str, VidRef)>,
+  ) -> Option<&HashMap<String, AttrValue>> {
+    if let Some((pat_vid, data_vid)) = frontier
+      && pattern == pat_vid
+    {
+      return self.v_entities.get(data_vid).map(|v| &v.attrs);
+    }
+    if let Some(vids) = self.pattern_2_vids.get(pattern) {
+      let [vid] = vids.iter().collect::<Vec<_>>()[..] else {
+        return None;
+      };
+      return self.v_entities.get(vid).map(|v| &v.attrs);
+    }
+
+    // the edges incident to the other candidates of the frontier don't count
+    let is_picked_out = |vid: VidRef| {
+      frontier.is_some_and(|(pat_vid, data_vid)| {
+        vid != data_vid
+          && self
+            .pattern_2_vids
+            .get(pat_vid)
+            .is_some_and(|vids| vids.contains(vid))
+      })
+    };
+    let eids = self
+      .pattern_2_eids
+      .get(pattern)?
+      .iter()
+      .filter_map(|eid| self.e_entities.get(eid))
+      .filter(|e| !is_picked_out(e.src_vid()) && !is_picked_out(e.dst_vid()))
+      .collect::<Vec<_>>();
+    let [e] = eids[..] else {
+      return None;
+    };
+    Some(&e.attrs)
+  }
+
+  /// The vid of the data vertex bound to `pattern`, unless it's bound to several.
+  pub fn view_bound_vid<'a>(
+    &'a self,
+    pattern: &str,
+    frontier: Option<(&str, VidRef<'a>)>,
+  ) -> Option<VidRef<'a>> {
+    if let Some((pat_vid, data_vid)) = frontier
+      && pattern == pat_vid
+    {
+      return Some(data_vid);
+    }
+    let [vid] = self.pattern_2_vids.get(pattern)?.iter().collect::<Vec<_>>()[..] else {
+      return None;
+    };
+    Some(vid)
+  }
+
+  /// Whether all the `preds` hold (see [`DynGraph::view_bound_attrs`] for `frontier`).
+  pub fn satisfies(
+    &self,
+    preds: &[CrossPredicate],
+    frontier: Option<(&str, VidRef)>,
+    mode: AttrCmpMode,
+  ) -> bool {
+    let attrs_of = |pattern: &str| self.view_bound_attrs(pattern, frontier);
+    let vid_of = |pattern: &str| self.view_bound_vid(pattern, frontier);
+    (preds.iter()).all(|pred| pred.evaluate_with(&attrs_of, &vid_of, mode) == Some(true))
+  }
+
+  /// A copy where each pattern vertex / edge in `mapping` is bound to
+  /// what the one it's mapped to was bound to.
+  pub fn with_patterns_renamed(&self, mapping: &HashMap<String, String>) -> Self {
+    let rename = |pattern: &String| mapping.get(pattern).unwrap_or(pattern).clone();
+    let mut renamed = self.clone();
+    renamed.pattern_2_vids = (self.pattern_2_vids.iter())
+      .map(|(pattern, vids)| (rename(pattern), vids.clone()))
+      .collect();
+    renamed.pattern_2_eids = (self.pattern_2_eids.iter())
+      .map(|(pattern, eids)| (rename(pattern), eids.clone()))
+      .collect();
+    renamed
+  }
+}
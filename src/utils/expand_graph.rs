@@ -78,8 +78,9 @@ impl ExpandGraph<DataVertex, DataEdge> {
     &mut self,
     pattern_str: impl AsRef<str>,
     expected_label: Arc<str>,
-    expected_attr: Option<Arc<PatternAttr>>,
+    expected_attrs: Arc<[PatternPredicate]>,
     storage_adapter: Arc<impl StorageAdapter + 'static>,
+    mode: AttrCmpMode,
   ) -> Vec<String> {
     if self.pending_v_grouped_dangling_eids.is_empty() {
       return vec![];
@@ -101,14 +102,15 @@ impl ExpandGraph<DataVertex, DataEdge> {
       let pending_vid = pending_vid.clone();
       let storage_adapter = storage_adapter.clone();
       let expected_label = expected_label.clone();
-      let expected_attr = expected_attr.clone();
+      let expected_attrs = expected_attrs.clone();
 
       tokio::spawn(async move {
         let pending_v = storage_adapter.get_v(&pending_vid).await;
         if let Some(pending_v) = pending_v {
-          if pending_v.label() != expected_label.as_ref()
-            || !pending_v.satisfy_attr(expected_attr.as_ref())
-          {
+          // an unlabeled pattern vertex (with an empty label) matches any label
+          let is_label_ok =
+            expected_label.is_empty() || pending_v.label() == expected_label.as_ref();
+          if !is_label_ok || !pending_v.satisfy_attrs(&expected_attrs, mode) {
             return;
           }
 
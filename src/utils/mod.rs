@@ -6,6 +6,7 @@ pub mod expand_graph;
 pub mod parallel;
 pub mod pretty_dump;
 pub mod simd_utils;
+pub mod sub_pattern;
 
 pub async fn time_async<F: Future<Output = O>, O>(future: F) -> (O, f64) {
   let start = Instant::now();
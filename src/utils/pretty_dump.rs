@@ -29,6 +29,10 @@ impl PrettyDump for DataVertex {
           AttrValue::Int(v) => v.to_string().purple(),
           AttrValue::Float(v) => v.to_string().yellow(),
           AttrValue::String(v) => format!(r#""{v}""#).green(),
+          AttrValue::Date(_) | AttrValue::DateTime(_) => value.to_literal().cyan(),
+          AttrValue::Bool(v) => v.to_string().blue(),
+          AttrValue::Null => "null".dimmed(),
+          AttrValue::List(_) => value.to_string().normal(),
         }
         .to_string()
       } else {
@@ -36,6 +40,8 @@ impl PrettyDump for DataVertex {
           AttrValue::Int(v) => v.to_string(),
           AttrValue::Float(v) => v.to_string(),
           AttrValue::String(v) => format!(r#""{v}""#),
+          AttrValue::Date(_) | AttrValue::DateTime(_) => value.to_literal(),
+          AttrValue::Bool(_) | AttrValue::Null | AttrValue::List(_) => value.to_string(),
         }
       };
       // remember to use `to_string` or the color will not be applied
@@ -71,7 +77,7 @@ impl PrettyDump for DataVertex {
 
 impl PrettyDump for DataEdge {
   fn pretty_dump_detailed(&self, colored: bool) -> String {
-    let label = format!(":{}", self.label()).red();
+    let label = edge_label(self).red();
 
     let sorted_kv_pairs = self
       .attrs
@@ -89,6 +95,10 @@ impl PrettyDump for DataEdge {
           AttrValue::Int(v) => v.to_string().purple(),
           AttrValue::Float(v) => v.to_string().yellow(),
           AttrValue::String(v) => format!(r#""{v}""#).green(),
+          AttrValue::Date(_) | AttrValue::DateTime(_) => value.to_literal().cyan(),
+          AttrValue::Bool(v) => v.to_string().blue(),
+          AttrValue::Null => "null".dimmed(),
+          AttrValue::List(_) => value.to_string().normal(),
         }
         .to_string()
       } else {
@@ -96,6 +106,8 @@ impl PrettyDump for DataEdge {
           AttrValue::Int(v) => v.to_string(),
           AttrValue::Float(v) => v.to_string(),
           AttrValue::String(v) => format!(r#""{v}""#),
+          AttrValue::Date(_) | AttrValue::DateTime(_) => value.to_literal(),
+          AttrValue::Bool(_) | AttrValue::Null | AttrValue::List(_) => value.to_string(),
         }
       };
       // remember to use `to_string` or the color will not be applied
@@ -115,15 +127,24 @@ impl PrettyDump for DataEdge {
 
   fn pretty_dump_simplified(&self, colored: bool) -> String {
     let label = if colored {
-      format!(":{}", self.label()).red().to_string()
+      edge_label(self).red().to_string()
     } else {
-      format!(":{}", self.label())
+      edge_label(self)
     };
 
     format!("[{label}]")
   }
 }
 
+/// `:label`, or `:label*hops` for a matched path
+fn edge_label(edge: &DataEdge) -> String {
+  if edge.is_path() {
+    format!(":{}*{}", edge.label(), edge.hops().len())
+  } else {
+    format!(":{}", edge.label())
+  }
+}
+
 impl<VType: VBase + PrettyDump, EType: EBase + PrettyDump> DynGraph<VType, EType> {
   pub fn pre_dump_detailed(&self, colored: bool) -> HashMap<String, Vec<String>> {
     self
@@ -0,0 +1,211 @@
+use crate::{
+  schemas::{EBase, Eid, PatternEdge, PatternVertex, VBase, Vid, VidRef},
+  utils::dyn_graph::DynGraph,
+};
+use hashbrown::HashSet;
+use itertools::Itertools;
+
+/// How a sub-pattern is joined to the matches of the required part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::Display)]
+pub enum SubPatternKind {
+  /// left-outer join (i.e. an `OPTIONAL MATCH`)
+  #[strum(serialize = "optional")]
+  Optional,
+  /// anti-join (i.e. a `NOT EXISTS { ... }`)
+  #[strum(serialize = "negated")]
+  Negated,
+}
+
+impl SubPatternKind {
+  pub fn of_v(v: &PatternVertex) -> Option<Self> {
+    Self::of(v.optional, v.negated)
+  }
+
+  pub fn of_e(e: &PatternEdge) -> Option<Self> {
+    Self::of(e.optional, e.negated)
+  }
+
+  fn of(optional: bool, negated: bool) -> Option<Self> {
+    match (optional, negated) {
+      (_, true) => Some(Self::Negated),
+      (true, false) => Some(Self::Optional),
+      (false, false) => None,
+    }
+  }
+}
+
+/// Optional (or negated) edges that are connected through optional (or negated) vertices,
+/// which are joined as a whole after the required part.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubPattern {
+  pub(crate) kind: SubPatternKind,
+  pub(crate) eids: Vec<Eid>,
+  /// the required vertices it hangs on
+  pub(crate) anchors: Vec<Vid>,
+}
+
+impl DynGraph<PatternVertex, PatternEdge> {
+  /// The pattern graph without its optional / negated vertices and edges.
+  pub fn required_part(&self) -> Self {
+    let mut graph = Self::default();
+    graph.update_v_batch(
+      self
+        .get_v_pattern_pairs_cloned()
+        .into_iter()
+        .filter(|(v, _)| SubPatternKind::of_v(v).is_none()),
+    );
+    graph.update_e_batch(
+      self
+        .get_e_pattern_pairs_cloned()
+        .into_iter()
+        .filter(|(e, _)| SubPatternKind::of_e(e).is_none()),
+    );
+    graph
+  }
+
+  /// All the sub-patterns of `kind`, sorted by their (sorted) eids.
+  pub fn sub_patterns(&self, kind: SubPatternKind) -> Vec<SubPattern> {
+    let is_of_kind_v =
+      |vid: VidRef| self.view_v_from_vid(vid).and_then(SubPatternKind::of_v) == Some(kind);
+    let is_of_kind_e = |eid: &Eid| SubPatternKind::of_e(&self.e_entities[eid]) == Some(kind);
+
+    let mut visited = HashSet::new();
+    let mut sub_patterns = vec![];
+
+    let all_eids = self
+      .e_entities
+      .keys()
+      .filter(|eid| is_of_kind_e(eid))
+      .sorted();
+    for eid in all_eids {
+      if !visited.insert(eid.clone()) {
+        continue;
+      }
+
+      let mut eids = vec![];
+      let mut anchors = HashSet::new();
+      let mut stack = vec![eid.clone()];
+      while let Some(eid) = stack.pop() {
+        let e = &self.e_entities[&eid];
+        for vid in [e.src_vid(), e.dst_vid()] {
+          if !is_of_kind_v(vid) {
+            anchors.insert(vid.to_string());
+            continue;
+          }
+          // spread through the vertices of the same kind only
+          for adj_eid in self.get_adj_eids(vid) {
+            if is_of_kind_e(&adj_eid) && visited.insert(adj_eid.clone()) {
+              stack.push(adj_eid);
+            }
+          }
+        }
+        eids.push(eid);
+      }
+
+      eids.sort();
+      sub_patterns.push(SubPattern {
+        kind,
+        eids,
+        anchors: anchors.into_iter().sorted().collect(),
+      });
+    }
+
+    sub_patterns
+  }
+
+  /// Whether the sub-patterns can be joined after the required part,
+  /// otherwise the offending vid / eid along with the reason.
+  pub fn check_sub_patterns(&self) -> Result<(), (String, String)> {
+    let marked_vs = self
+      .v_entities
+      .values()
+      .filter(|v| v.optional || v.negated)
+      .sorted_unstable_by_key(|v| v.vid());
+    for v in marked_vs {
+      let vid = v.vid();
+      if v.optional && v.negated {
+        let reason = format!("vertex `{vid}` is both optional and negated");
+        return Err((vid.to_string(), reason));
+      }
+      let kind = SubPatternKind::of_v(v).unwrap();
+      let adj_eids = self.get_adj_eids(vid);
+      if adj_eids.is_empty() {
+        let reason = format!("{kind} vertex `{vid}` is not connected to the required part");
+        return Err((vid.to_string(), reason));
+      }
+      let other_kind_eid = adj_eids
+        .iter()
+        .sorted()
+        .find(|eid| SubPatternKind::of_e(&self.e_entities[*eid]) != Some(kind));
+      if let Some(eid) = other_kind_eid {
+        let reason = format!("edge `{eid}` is not {kind}, but ends at {kind} vertex `{vid}`");
+        return Err((eid.clone(), reason));
+      }
+    }
+
+    let marked_es = self
+      .e_entities
+      .values()
+      .filter(|e| e.optional || e.negated)
+      .sorted_unstable_by_key(|e| e.eid());
+    for e in marked_es {
+      let eid = e.eid();
+      if e.optional && e.negated {
+        let reason = format!("edge `{eid}` is both optional and negated");
+        return Err((eid.to_string(), reason));
+      }
+      if e.is_var_length() {
+        let kind = SubPatternKind::of_e(e).unwrap();
+        let reason = format!("variable-length edge `{eid}` in a {kind} sub-pattern");
+        return Err((eid.to_string(), reason));
+      }
+    }
+
+    let required_graph = self.required_part();
+    let sub_patterns = [SubPatternKind::Optional, SubPatternKind::Negated]
+      .into_iter()
+      .flat_map(|kind| self.sub_patterns(kind));
+    for sub_pattern in sub_patterns {
+      let eid = sub_pattern.eids[0].clone();
+      let kind = sub_pattern.kind;
+      if let Some(vid) = sub_pattern
+        .anchors
+        .iter()
+        .find(|vid| !required_graph.has_vid(vid))
+      {
+        let reason = format!("{kind} edge `{eid}` hangs on the non-required vertex `{vid}`");
+        return Err((eid, reason));
+      }
+      let Some(first_anchor) = sub_pattern.anchors.first() else {
+        let reason = format!("{kind} edge `{eid}` is not connected to the required part");
+        return Err((eid, reason));
+      };
+      // the anchors should end up in the same (final) partial match
+      let reachable = required_graph.reachable_vids(first_anchor);
+      if !sub_pattern
+        .anchors
+        .iter()
+        .all(|vid| reachable.contains(vid))
+      {
+        let reason = format!("{kind} edge `{eid}` joins disconnected parts of the pattern");
+        return Err((eid, reason));
+      }
+    }
+
+    Ok(())
+  }
+
+  /// The vertices in the same connected component as `from`.
+  pub(crate) fn reachable_vids(&self, from: VidRef) -> HashSet<Vid> {
+    let mut reachable = HashSet::from([from.to_string()]);
+    let mut stack = vec![from.to_string()];
+    while let Some(vid) = stack.pop() {
+      for adj_vid in self.get_adj_vids(&vid) {
+        if reachable.insert(adj_vid.clone()) {
+          stack.push(adj_vid);
+        }
+      }
+    }
+    reachable
+  }
+}